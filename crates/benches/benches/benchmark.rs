@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use serverx_benches::{bvh, ecs, misc};
+use serverx_benches::{bvh, ecs, misc, network};
 
 pub fn registry_push(c: &mut Criterion) {
     let count: usize = 100000;
@@ -129,9 +129,24 @@ pub fn hyperion_bvh_build(c: &mut Criterion) {
     c.bench_function(id_str.as_str(), |b| bench.iter(b));
 }
 
+pub fn join_burst_write(c: &mut Criterion) {
+    let view_distance = 6;
+    let mut bench = network::write::Benchmark::new(view_distance, false);
+    let id_str = format!("join burst write {} chunks", bench.packets.len());
+    c.bench_function(id_str.as_str(), |b| bench.iter(b));
+}
+
+pub fn join_burst_write_batched(c: &mut Criterion) {
+    let view_distance = 6;
+    let mut bench = network::write::Benchmark::new(view_distance, true);
+    let id_str = format!("join burst batched write {} chunks", bench.packets.len());
+    c.bench_function(id_str.as_str(), |b| bench.iter(b));
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default().measurement_time(Duration::from_secs(10));
+    // targets = join_burst_write, join_burst_write_batched
     // targets = hyperion_bvh_build, bvh_build
     // targets = btree_insert, sorted
     targets = registry_pipeline, registry_no_pipeline, registry_par_pipeline, registry_par_no_pipeline
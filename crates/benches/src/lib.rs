@@ -3,3 +3,4 @@
 pub mod bvh;
 pub mod ecs;
 pub mod misc;
+pub mod network;
@@ -0,0 +1,107 @@
+use criterion::{black_box, Bencher};
+use serverx_block::blocks::Block;
+use serverx_common::collections::bit_vec::BitVec;
+use serverx_game::chunk::generators::{flat::FlatGeneratorBuilder, ChunkGenerator};
+use serverx_protocol::{
+    chunk::encode_chunk,
+    io::{encode_versioned, AsyncPacketWriter, EncodedPacket},
+    packet::PacketDirection::ClientBound,
+    v765::{clientbound::ChunkDataAndLight, types::ChunkLighting},
+    version::ProtocolVersion,
+};
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpListener, TcpStream},
+    runtime::Runtime,
+};
+
+/// Writes the chunks a client receives when joining with a view distance of 6, 13 by 13 chunks.
+pub struct Benchmark {
+    pub packets: Vec<EncodedPacket>,
+    pub batched: bool,
+    runtime: Runtime,
+    socket: TcpStream,
+}
+
+impl Benchmark {
+    pub fn new(view_distance: i32, batched: bool) -> Self {
+        let generator = FlatGeneratorBuilder::new(384)
+            .layer(Block::IronBlock, 64)
+            .build();
+        let chunk = generator.generate((0, 0).into());
+        let encoded = encode_chunk(&chunk).unwrap();
+        let sections = chunk.sections().len();
+        let mut packets = Vec::new();
+        for x in -view_distance..=view_distance {
+            for z in -view_distance..=view_distance {
+                let packet = ChunkDataAndLight {
+                    x,
+                    z,
+                    heightmaps: chunk.heightmaps_tag(),
+                    chunk_data: encoded.clone(),
+                    block_entities: vec![],
+                    chunk_lighting: ChunkLighting {
+                        sky_light_mask: BitVec::zeros(sections),
+                        block_light_mask: BitVec::zeros(sections),
+                        empty_sky_light_mask: BitVec::ones(sections),
+                        empty_block_light_mask: BitVec::ones(sections),
+                        sky_light_sections: vec![],
+                        block_light_sections: vec![],
+                    },
+                };
+                let mut body = Vec::new();
+                encode_versioned(&mut body, ProtocolVersion::V765, ClientBound, &packet).unwrap();
+                packets.push(EncodedPacket::Body(body));
+            }
+        }
+        let runtime = Runtime::new().unwrap();
+        let socket = runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move {
+                // the client side only drains the socket
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 1 << 16];
+                while let Ok(n) = socket.read(buf.as_mut_slice()).await {
+                    if n == 0 {
+                        break;
+                    }
+                }
+            });
+            let socket = TcpStream::connect(addr).await.unwrap();
+            socket.set_nodelay(true).unwrap();
+            socket
+        });
+        Self {
+            packets,
+            batched,
+            runtime,
+            socket,
+        }
+    }
+
+    pub async fn run(&mut self, writer: &mut AsyncPacketWriter) {
+        if self.batched {
+            writer
+                .write_encoded_batch(&mut self.socket, self.packets.as_slice())
+                .await
+                .unwrap();
+        } else {
+            for packet in self.packets.iter() {
+                if let EncodedPacket::Body(body) = packet {
+                    writer
+                        .write_encoded(&mut self.socket, body.as_slice())
+                        .await
+                        .unwrap();
+                }
+            }
+        }
+    }
+
+    pub fn iter(&mut self, b: &mut Bencher) {
+        // uncompressed, so the benchmark measures the writes rather than zlib
+        let mut writer = AsyncPacketWriter::new();
+        let handle = self.runtime.handle().clone();
+        b.iter(|| handle.block_on(Self::run(black_box(self), black_box(&mut writer))));
+    }
+}
@@ -5,6 +5,7 @@ mod ecs_tuple;
 mod identifier;
 mod nbt;
 mod packet;
+mod packet_sets;
 mod proto;
 
 use proc_macro::TokenStream;
@@ -15,6 +16,7 @@ use crate::{
     identifier::identifier_macro_impl,
     nbt::nbt_macro_impl,
     packet::packet_macro_impl,
+    packet_sets::packet_sets_impl,
     proto::{proto_decode_macro_impl, proto_encode_macro_impl},
 };
 
@@ -23,6 +25,13 @@ pub fn packet_macro(input: TokenStream) -> TokenStream {
     packet_macro_impl(input.into()).into()
 }
 
+/// Declares a packet set for every connection state from the `#[packet(..)]` attributes of the
+/// given modules, paths are relative to the manifest of the calling crate.
+#[proc_macro]
+pub fn packet_sets(input: TokenStream) -> TokenStream {
+    packet_sets_impl(input.into()).into()
+}
+
 #[proc_macro_derive(ProtoEncode, attributes(proto))]
 pub fn proto_encode_macro(input: TokenStream) -> TokenStream {
     proto_encode_macro_impl(input.into()).into()
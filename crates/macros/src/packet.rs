@@ -5,10 +5,10 @@ use syn::{
     parse_macro_input, DeriveInput, LitInt, Token,
 };
 
-struct PacketOpts {
-    id: LitInt,
-    direction: Ident,
-    state: Ident,
+pub(crate) struct PacketOpts {
+    pub(crate) id: LitInt,
+    pub(crate) direction: Ident,
+    pub(crate) state: Ident,
 }
 
 impl Parse for PacketOpts {
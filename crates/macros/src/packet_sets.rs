@@ -0,0 +1,113 @@
+use std::{env, fs, path::Path};
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{parse::Parser, punctuated::Punctuated, Attribute, Item, LitStr, Token};
+
+use crate::packet::PacketOpts;
+
+struct SetPacket {
+    variant: Ident,
+    module: Vec<Ident>,
+    name: Ident,
+}
+
+struct PacketSet {
+    direction: Ident,
+    state: Ident,
+    packets: Vec<SetPacket>,
+}
+
+fn packet_opts(attrs: &[Attribute]) -> Option<PacketOpts> {
+    attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("packet"))
+        .map(|attr| attr.parse_args().expect("invalid \"packet\" attribute"))
+}
+
+pub fn packet_sets_impl(input: TokenStream) -> TokenStream {
+    let paths = Punctuated::<LitStr, Token![,]>::parse_terminated
+        .parse2(input)
+        .expect("expected a list of module paths");
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").expect("missing CARGO_MANIFEST_DIR");
+    let mut sets: Vec<PacketSet> = Vec::new();
+    let mut sources = Vec::new();
+    for path in paths.iter() {
+        let path = path.value();
+        let file_path = Path::new(&manifest_dir).join(&path);
+        let source = fs::read_to_string(&file_path)
+            .unwrap_or_else(|err| panic!("unable to read {}: {}", file_path.display(), err));
+        let file = syn::parse_file(&source)
+            .unwrap_or_else(|err| panic!("unable to parse {}: {}", file_path.display(), err));
+        // src/v765/serverbound.rs is the module v765::serverbound
+        let module: Vec<Ident> = path
+            .trim_start_matches("src/")
+            .trim_end_matches(".rs")
+            .split('/')
+            .map(|name| Ident::new(name, Span::call_site()))
+            .collect();
+        let version = module[0].to_string().to_uppercase();
+        for item in file.items.iter() {
+            let (name, opts) = match item {
+                Item::Struct(item) => (&item.ident, packet_opts(&item.attrs)),
+                Item::Enum(item) => (&item.ident, packet_opts(&item.attrs)),
+                _ => continue,
+            };
+            let Some(opts) = opts else {
+                continue;
+            };
+            let set = match sets
+                .iter()
+                .position(|set| set.direction == opts.direction && set.state == opts.state)
+            {
+                Some(index) => &mut sets[index],
+                None => {
+                    sets.push(PacketSet {
+                        direction: opts.direction,
+                        state: opts.state,
+                        packets: Vec::new(),
+                    });
+                    sets.last_mut().unwrap()
+                }
+            };
+            // packets a later version replaced with a new layout carry that version as a suffix
+            let variant = if set.packets.iter().any(|packet| packet.variant == *name) {
+                Ident::new(&format!("{}{}", name, version), name.span())
+            } else {
+                name.clone()
+            };
+            set.packets.push(SetPacket {
+                variant,
+                module: module.clone(),
+                name: name.clone(),
+            });
+        }
+        sources.push(file_path.display().to_string());
+    }
+    let sets = sets.iter().map(|set| {
+        let direction = &set.direction;
+        let state = &set.state;
+        let name = Ident::new(
+            &format!(
+                "{}{}",
+                direction.to_string().replace("Bound", "bound"),
+                state
+            ),
+            Span::call_site(),
+        );
+        let variants = set.packets.iter().map(|packet| {
+            let variant = &packet.variant;
+            let module = &packet.module;
+            let name = &packet.name;
+            quote! { #variant(crate::#(#module::)*#name) }
+        });
+        quote! {
+            protocol::packet::packet_set!(#name, #direction, #state, #(#variants),*);
+        }
+    });
+    quote! {
+        // rebuilds the sets whenever one of the modules changes
+        #(const _: &str = include_str!(#sources);)*
+        #(#sets)*
+    }
+}
@@ -6,6 +6,8 @@ use std::{
 
 use serverx_nbt::decode::NbtDecodeErr;
 
+use crate::text::TextErr;
+
 pub trait AllocTracker {
     fn alloc(&mut self, count: usize) -> Result<(), ProtoDecodeErr>;
     fn dealloc(&mut self, count: usize);
@@ -58,8 +60,10 @@ pub enum ProtoDecodeErr {
     InvalidEnumTag,
     InvalidEither,
     UnknownPacketId(i32),
+    UnexpectedPacket(i32),
     MalformedPacket,
     MalformedJson(String),
+    InvalidTextComponent(TextErr),
     InvalidIdentifier,
     NbtDecodeErr(NbtDecodeErr),
     InvalidBlock,
@@ -98,8 +102,12 @@ impl Display for ProtoDecodeErr {
             ProtoDecodeErr::InvalidEnumTag => write!(f, "invalid enum tag encountered"),
             ProtoDecodeErr::InvalidEither => write!(f, "unable to decode either type"),
             ProtoDecodeErr::UnknownPacketId(id) => write!(f, "unknown packet id {}", id),
+            ProtoDecodeErr::UnexpectedPacket(id) => write!(f, "unexpected packet id {}", id),
             ProtoDecodeErr::MalformedPacket => write!(f, "malformed packet"),
             ProtoDecodeErr::MalformedJson(json_str) => write!(f, "malformed json: {}", json_str),
+            ProtoDecodeErr::InvalidTextComponent(err) => {
+                write!(f, "invalid text component: {}", err)
+            }
             ProtoDecodeErr::InvalidIdentifier => write!(f, "invalid identifier"),
             ProtoDecodeErr::NbtDecodeErr(err) => write!(f, "nbt decode error: {}", err),
             ProtoDecodeErr::InvalidBlock => write!(f, "invalid block"),
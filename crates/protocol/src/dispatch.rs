@@ -0,0 +1,119 @@
+use serverx_macros::packet_sets;
+
+use crate as protocol;
+
+// Every packet of a connection state across all supported versions, packets a later version
+// replaced with a new layout carry that version as a suffix.
+packet_sets!(
+    "src/v765/serverbound.rs",
+    "src/v765/clientbound.rs",
+    "src/v766/serverbound.rs",
+    "src/v766/clientbound.rs"
+);
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::{
+        decode::{BasicAllocTracker, ProtoDecodeErr},
+        dispatch::{ClientboundPlay, ServerboundLogin, ServerboundPlay},
+        packet::{
+            ConnectionState::{Login, Play},
+            Packet, PacketDecoder,
+            PacketDirection::ServerBound,
+            PacketEncoder, PacketSet,
+        },
+        v765, v766,
+    };
+
+    fn decode_as<D: PacketDecoder, P: PacketSet>(
+        id: i32,
+        body: &[u8],
+    ) -> Result<P, ProtoDecodeErr> {
+        let mut reader = Cursor::new(body);
+        let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+        D::decode_as::<P, _, _>(id, P::DIRECTION, P::STATE, &mut reader, &mut alloc_tracker)
+    }
+
+    fn encode<E: PacketEncoder>(packet: &dyn Packet) -> (i32, Vec<u8>) {
+        let id = E::packet_id(packet, packet.direction(), packet.state()).unwrap();
+        let mut cursor = Cursor::new(Vec::new());
+        E::encode_packet(packet, id, packet.direction(), packet.state(), &mut cursor).unwrap();
+        (id, cursor.into_inner())
+    }
+
+    #[test]
+    fn test_decode_play() {
+        let keep_alive = v765::serverbound::KeepAlive { id: 42 };
+        let (id, body) = encode::<v765::PacketEncoderImpl>(&keep_alive);
+        let v765 = decode_as::<v765::PacketDecoderImpl, ServerboundPlay>(id, &body).unwrap();
+        let (id, body) = encode::<v766::PacketEncoderImpl>(&keep_alive);
+        let v766 = decode_as::<v766::PacketDecoderImpl, ServerboundPlay>(id, &body).unwrap();
+        for packet in [v765, v766] {
+            let ServerboundPlay::KeepAlive(keep_alive) = packet else {
+                panic!("unexpected packet {:?}", packet);
+            };
+            assert_eq!(keep_alive.id, 42);
+        }
+    }
+
+    #[test]
+    fn test_decode_renumbered() {
+        let packet =
+            decode_as::<v766::PacketDecoderImpl, ServerboundPlay>(0x04, &[0x01, b'a']).unwrap();
+        let ServerboundPlay::ChatCommandV766(chat_command) = packet else {
+            panic!("unexpected packet {:?}", packet);
+        };
+        assert_eq!(chat_command.command, "a");
+        let packet = ServerboundPlay::ChatCommandV766(chat_command);
+        assert_eq!(packet.as_packet().id(), 0x04);
+        assert!(packet
+            .into_packet()
+            .as_any()
+            .is::<v766::serverbound::ChatCommand>());
+    }
+
+    #[test]
+    fn test_decode_every_packet() {
+        let commands = v765::clientbound::Commands {
+            nodes: vec![],
+            root_index: 0,
+        };
+        let (id, body) = encode::<v765::PacketEncoderImpl>(&commands);
+        assert!(matches!(
+            decode_as::<v765::PacketDecoderImpl, ClientboundPlay>(id, &body),
+            Ok(ClientboundPlay::Commands(_))
+        ));
+        let commands = v766::clientbound::Commands::from(commands);
+        let (id, body) = encode::<v766::PacketEncoderImpl>(&commands);
+        assert!(matches!(
+            decode_as::<v766::PacketDecoderImpl, ClientboundPlay>(id, &body),
+            Ok(ClientboundPlay::CommandsV766(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_wrong_state() {
+        let login_ack = v765::serverbound::LoginAck;
+        let (id, body) = encode::<v765::PacketEncoderImpl>(&login_ack);
+        assert!(matches!(
+            decode_as::<v765::PacketDecoderImpl, ServerboundLogin>(id, &body),
+            Ok(ServerboundLogin::LoginAck(_))
+        ));
+        let mut reader = Cursor::new(body.as_slice());
+        let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+        let result = v765::PacketDecoderImpl::decode_as::<ServerboundPlay, _, _>(
+            id,
+            ServerBound,
+            Login,
+            &mut reader,
+            &mut alloc_tracker,
+        );
+        assert!(matches!(
+            result,
+            Err(ProtoDecodeErr::UnexpectedPacket(0x03))
+        ));
+        assert_eq!(ServerboundPlay::STATE, Play);
+    }
+}
@@ -2,23 +2,57 @@ use std::{
     cmp,
     fmt::{Debug, Display, Formatter},
     io,
-    io::{Cursor, IoSliceMut, Read, Seek, SeekFrom, Write},
+    io::{Cursor, IoSliceMut, Seek, SeekFrom, Write},
     marker::PhantomData,
+    sync::Arc,
 };
 
-use aes::{cipher::BlockDecryptMut, Aes128};
-use cfb8::{cipher::AsyncStreamCipher, Decryptor, Encryptor};
+use aes::Aes128;
+use cfb8::{
+    cipher::{inout::InOutBuf, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    Decryptor, Encryptor,
+};
+use flate2::{write::ZlibEncoder, Compression, Decompress, FlushDecompress, Status};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 
 use crate::{
-    decode::{BasicAllocTracker, ProtoDecode, ProtoDecodeErr},
+    decode::{AllocTracker, BasicAllocTracker, ProtoDecode, ProtoDecodeErr},
     encode::{ProtoEncode, ProtoEncodeErr},
-    packet::{ConnectionState, Packet, PacketDecoder, PacketDirection, PacketEncoder},
+    packet::{
+        ConnectionState, DecodedPacket, Packet, PacketDecoder, PacketDirection, PacketEncoder,
+        PacketSet,
+    },
     types::VarInt,
+    v765, v766,
+    version::ProtocolVersion,
 };
 
 pub const DEFAULT_PACKET_LIMIT: usize = 1 << 21 - 1;
 pub const DEFAULT_ALLOC_LIMIT: usize = 1 << 23;
+pub const CRYPT_KEY_LEN: usize = 16;
+
+fn encrypt_in_place(encryptor: &mut Encryptor<Aes128>, buf: &mut [u8]) {
+    let (blocks, _) = InOutBuf::from(buf).into_chunks();
+    encryptor.encrypt_blocks_inout_mut(blocks);
+}
+
+fn prepend_var_int(buf: &mut [u8], end: usize, value: usize) -> Result<usize, PacketWriteErr> {
+    let mut encoded = [0u8; VarInt::MAX_BYTES];
+    let value = i32::try_from(value).map_err(|_| PacketWriteErr::Unknown)?;
+    let len = {
+        let mut cursor = Cursor::new(encoded.as_mut_slice());
+        VarInt::encode(&value, &mut cursor).map_err(|err| PacketWriteErr::EncodeErr(err))?;
+        cursor.position() as usize
+    };
+    let start = end.checked_sub(len).ok_or(PacketWriteErr::Unknown)?;
+    buf[start..end].copy_from_slice(&encoded[..len]);
+    Ok(start)
+}
+
+fn decrypt_in_place(decryptor: &mut Decryptor<Aes128>, buf: &mut [u8]) {
+    let (blocks, _) = InOutBuf::from(buf).into_chunks();
+    decryptor.decrypt_blocks_inout_mut(blocks);
+}
 
 struct VecWriter<'a> {
     vec: &'a mut Vec<u8>,
@@ -68,44 +102,96 @@ impl<'a> Seek for VecWriter<'a> {
     }
 }
 
-struct VecReader<'a> {
-    vec: &'a Vec<u8>,
-    offset: usize,
-    end: usize,
+fn encode_body<S: PacketEncoder>(
+    buf: &mut Vec<u8>,
+    direction: PacketDirection,
+    packet: &dyn Packet,
+) -> Result<(), ProtoEncodeErr> {
+    let state = packet.state();
+    let offset = buf.len();
+    let mut vec_writer = VecWriter::new(buf, offset, offset + DEFAULT_PACKET_LIMIT);
+    let packet_id = S::packet_id(packet, direction, state)
+        .ok_or_else(|| ProtoEncodeErr::UnknownPacketId(packet.id()))?;
+    VarInt::encode(&packet_id, &mut vec_writer)?;
+    S::encode_packet(packet, packet_id, direction, state, &mut vec_writer)?;
+    let end = vec_writer.offset;
+    buf.truncate(end);
+    Ok(())
 }
 
-impl<'a> VecReader<'a> {
-    pub fn new(vec: &'a Vec<u8>, offset: usize, end: usize) -> Self {
-        Self { vec, offset, end }
+/// Appends the id and fields of a packet to `buf`, encoded for the state the packet belongs to.
+/// The body can later be framed with [`AsyncPacketWriter::write_encoded`].
+pub fn encode_versioned(
+    buf: &mut Vec<u8>,
+    version: ProtocolVersion,
+    direction: PacketDirection,
+    packet: &dyn Packet,
+) -> Result<(), ProtoEncodeErr> {
+    match version {
+        ProtocolVersion::V765 => encode_body::<v765::PacketEncoderImpl>(buf, direction, packet),
+        ProtocolVersion::V766 => encode_body::<v766::PacketEncoderImpl>(buf, direction, packet),
     }
 }
 
-impl<'a> Read for VecReader<'a> {
-    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-        if self.offset + buf.len() <= self.end {
-            let slice = &self.vec[(self.offset)..(self.offset + buf.len())];
-            buf.copy_from_slice(slice);
-            self.offset += buf.len();
-            Ok(buf.len())
-        } else {
-            Err(io::Error::from(io::ErrorKind::UnexpectedEof))
-        }
+/// A packet framed once and written verbatim to every connection that negotiated the same
+/// protocol version and compression threshold, only encryption is applied per connection.
+#[derive(Debug, Clone)]
+pub struct PreEncodedPacket {
+    frame: Arc<[u8]>,
+    version: ProtocolVersion,
+    compression: Option<usize>,
+}
+
+impl PreEncodedPacket {
+    pub fn new(
+        version: ProtocolVersion,
+        direction: PacketDirection,
+        packet: &dyn Packet,
+        compression: Option<usize>,
+    ) -> Result<Self, PacketWriteErr> {
+        let mut body = Vec::new();
+        encode_versioned(&mut body, version, direction, packet)
+            .map_err(PacketWriteErr::EncodeErr)?;
+        let mut writer = AsyncPacketWriter::new();
+        writer.set_compression(compression);
+        writer.copy_body(body.as_slice())?;
+        let frame = Arc::from(writer.encode_frame(body.len())?);
+        Ok(Self {
+            frame,
+            version,
+            compression,
+        })
+    }
+
+    pub fn frame(&self) -> &[u8] {
+        self.frame.as_ref()
+    }
+
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    pub fn compression(&self) -> Option<usize> {
+        self.compression
     }
 }
 
-impl<'a> Seek for VecReader<'a> {
-    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
-        let new_offset: usize = match pos {
-            SeekFrom::Start(offset) => {
-                usize::try_from(offset).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?
-            }
-            SeekFrom::End(offset) => usize::try_from(self.end as i64 + offset)
-                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?,
-            SeekFrom::Current(offset) => usize::try_from(self.offset as i64 + offset)
-                .map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?,
-        };
-        self.offset = new_offset;
-        Ok(self.offset as u64)
+/// A packet ready to be written by an [`AsyncPacketWriter`].
+#[derive(Debug, Clone)]
+pub enum EncodedPacket {
+    /// The id and fields produced by [`encode_versioned`], framed when written.
+    Body(Vec<u8>),
+    PreEncoded(PreEncodedPacket),
+}
+
+impl EncodedPacket {
+    /// The number of bytes held, before compression for bodies and after it for pre-encoded
+    /// packets.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            EncodedPacket::Body(body) => body.len(),
+            EncodedPacket::PreEncoded(packet) => packet.frame().len(),
+        }
     }
 }
 
@@ -113,6 +199,8 @@ pub enum PacketWriteErr {
     IoErr(io::Error),
     EncodeErr(ProtoEncodeErr),
     EncryptionErr,
+    CompressionErr,
+    CompressionMismatch(Option<usize>, Option<usize>),
     PacketTooLong(usize, usize),
     Unknown,
 }
@@ -123,6 +211,12 @@ impl Debug for PacketWriteErr {
             PacketWriteErr::IoErr(err) => write!(f, "io error: {}", err),
             PacketWriteErr::EncodeErr(err) => write!(f, "encode error: {}", err),
             PacketWriteErr::EncryptionErr => write!(f, "encryption err"),
+            PacketWriteErr::CompressionErr => write!(f, "compression err"),
+            PacketWriteErr::CompressionMismatch(packet, writer) => write!(
+                f,
+                "packet compressed with threshold {:?} written with threshold {:?}",
+                packet, writer
+            ),
             PacketWriteErr::PacketTooLong(len, max_len) => write!(
                 f,
                 "packet with length {} is more than the maximum length {}",
@@ -143,7 +237,9 @@ pub enum PacketReadErr {
     IoErr(io::Error),
     DecodeErr(ProtoDecodeErr),
     DecryptionErr,
+    DecompressionErr,
     PacketTooLong(usize, usize),
+    BelowCompressionThreshold(usize, usize),
     MalformedPacketHeader,
 }
 
@@ -153,11 +249,17 @@ impl Debug for PacketReadErr {
             PacketReadErr::IoErr(err) => write!(f, "io error: {}", err),
             PacketReadErr::DecodeErr(err) => write!(f, "decode error: {}", err),
             PacketReadErr::DecryptionErr => write!(f, "encryption err"),
+            PacketReadErr::DecompressionErr => write!(f, "decompression err"),
             PacketReadErr::PacketTooLong(len, max_len) => write!(
                 f,
                 "packet with length {} is more than the maximum length {}",
                 len, max_len
             ),
+            PacketReadErr::BelowCompressionThreshold(len, threshold) => write!(
+                f,
+                "compressed packet with length {} is less than the compression threshold {}",
+                len, threshold
+            ),
             PacketReadErr::MalformedPacketHeader => write!(f, "malformed packet header"),
         }
     }
@@ -172,8 +274,9 @@ impl Display for PacketReadErr {
 pub struct AsyncPacketWriter {
     packet_buf: Vec<u8>,
     deflate_buf: Vec<u8>,
+    batch_buf: Vec<u8>,
     encryptor: Option<Encryptor<Aes128>>,
-    crypt_key: [u8; 16],
+    crypt_key: [u8; CRYPT_KEY_LEN],
     compression: Option<usize>,
     packet_limit: usize,
 }
@@ -183,46 +286,96 @@ impl AsyncPacketWriter {
         Self {
             packet_buf: Vec::<u8>::new(),
             deflate_buf: Vec::<u8>::new(),
+            batch_buf: Vec::<u8>::new(),
             encryptor: None,
-            crypt_key: [0u8; 16],
+            crypt_key: [0u8; CRYPT_KEY_LEN],
             compression: None,
             packet_limit: DEFAULT_PACKET_LIMIT,
         }
     }
 
+    /// Enables AES-128/CFB8 encryption using the shared secret as both the key and the IV. Every
+    /// byte written after this call (including frame headers) is encrypted.
+    pub fn enable_encryption(&mut self, key: &[u8; CRYPT_KEY_LEN]) {
+        self.crypt_key = *key;
+        self.encryptor = Some(Encryptor::<Aes128>::new(key.into(), key.into()));
+    }
+
+    pub fn encryption_enabled(&self) -> bool {
+        self.encryptor.is_some()
+    }
+
+    /// Sets the compression threshold. Packets whose uncompressed length is at least the threshold
+    /// are zlib compressed; passing `None` disables compression entirely.
+    pub fn set_compression(&mut self, threshold: Option<usize>) {
+        self.compression = threshold;
+    }
+
+    pub fn compression(&self) -> Option<usize> {
+        self.compression
+    }
+
+    /// Frames the packet of `len` bytes in the packet buffer, compressing and encrypting it as
+    /// negotiated, and returns the bytes to send.
+    fn encode_frame(&mut self, len: usize) -> Result<&[u8], PacketWriteErr> {
+        if len > self.packet_limit {
+            return Err(PacketWriteErr::PacketTooLong(len, self.packet_limit));
+        } else if VarInt::MAX_BYTES + len > self.packet_buf.len() {
+            return Err(PacketWriteErr::Unknown);
+        }
+        let data_end = VarInt::MAX_BYTES + len;
+        let (buf, start, end) = match self.compression {
+            Some(threshold) if len >= threshold => {
+                let header_len = 2 * VarInt::MAX_BYTES;
+                self.deflate_buf.clear();
+                self.deflate_buf.resize(header_len, 0u8);
+                let mut encoder = ZlibEncoder::new(&mut self.deflate_buf, Compression::default());
+                encoder
+                    .write_all(&self.packet_buf[VarInt::MAX_BYTES..data_end])
+                    .map_err(|_| PacketWriteErr::CompressionErr)?;
+                encoder
+                    .finish()
+                    .map_err(|_| PacketWriteErr::CompressionErr)?;
+                let end = self.deflate_buf.len();
+                let data_len_start = prepend_var_int(&mut self.deflate_buf, header_len, len)?;
+                let frame_len = end - data_len_start;
+                if frame_len > self.packet_limit {
+                    return Err(PacketWriteErr::PacketTooLong(frame_len, self.packet_limit));
+                }
+                let start = prepend_var_int(&mut self.deflate_buf, data_len_start, frame_len)?;
+                (&mut self.deflate_buf, start, end)
+            }
+            Some(_) => {
+                // uncompressed packets are prefixed with a data length of zero
+                self.packet_buf[VarInt::MAX_BYTES - 1] = 0u8;
+                let start = prepend_var_int(&mut self.packet_buf, VarInt::MAX_BYTES - 1, len + 1)?;
+                (&mut self.packet_buf, start, data_end)
+            }
+            None => {
+                let start = prepend_var_int(&mut self.packet_buf, VarInt::MAX_BYTES, len)?;
+                (&mut self.packet_buf, start, data_end)
+            }
+        };
+        let data_slice = buf
+            .as_mut_slice()
+            .get_mut(start..end)
+            .ok_or(PacketWriteErr::Unknown)?;
+        if let Some(encryptor) = self.encryptor.as_mut() {
+            encrypt_in_place(encryptor, data_slice);
+        }
+        Ok(data_slice)
+    }
+
     pub async fn write_frame<W: AsyncWrite + Unpin>(
         &mut self,
         writer: &mut W,
         len: usize,
     ) -> Result<(), PacketWriteErr> {
-        if len > self.packet_limit {
-            Err(PacketWriteErr::PacketTooLong(len, self.packet_limit))
-        } else if len > self.packet_buf.len() {
-            Err(PacketWriteErr::Unknown)
-        } else {
-            let mut encoded_len = [0u8; VarInt::MAX_BYTES];
-            let p = {
-                let mut cursor = Cursor::new(encoded_len.as_mut_slice());
-                VarInt::encode(&(len as i32), &mut cursor)
-                    .map_err(|err| PacketWriteErr::EncodeErr(err))?;
-                cursor.position() as usize
-            };
-            let buf_offset = VarInt::MAX_BYTES - p;
-            (&mut self.packet_buf[buf_offset..VarInt::MAX_BYTES])
-                .copy_from_slice(&encoded_len[..p]);
-            let data_slice = self
-                .packet_buf
-                .as_mut_slice()
-                .get_mut(buf_offset..(VarInt::MAX_BYTES + len))
-                .ok_or(PacketWriteErr::Unknown)?;
-            if let Some(encryptor) = self.encryptor.as_mut() {
-                panic!("encryption not supported");
-            }
-            writer
-                .write_all(data_slice)
-                .await
-                .map_err(|err| PacketWriteErr::IoErr(err))
-        }
+        let data = self.encode_frame(len)?;
+        writer
+            .write_all(data)
+            .await
+            .map_err(|err| PacketWriteErr::IoErr(err))
     }
 
     pub async fn write<W: AsyncWrite + Unpin, S: PacketEncoder>(
@@ -234,7 +387,9 @@ impl AsyncPacketWriter {
     ) -> Result<(), PacketWriteErr> {
         let mut vec_writer =
             VecWriter::new(&mut self.packet_buf, VarInt::MAX_BYTES, self.packet_limit);
-        let packet_id = packet.id();
+        let packet_id = S::packet_id(packet, direction, state).ok_or_else(|| {
+            PacketWriteErr::EncodeErr(ProtoEncodeErr::UnknownPacketId(packet.id()))
+        })?;
         VarInt::encode(&packet_id, &mut vec_writer)
             .map_err(|err| PacketWriteErr::EncodeErr(err))?;
         S::encode_packet(packet, packet_id, direction, state, &mut vec_writer)
@@ -242,13 +397,94 @@ impl AsyncPacketWriter {
         let packet_len = vec_writer.offset - VarInt::MAX_BYTES;
         self.write_frame(writer, packet_len).await
     }
+
+    /// Writes a packet with the encoder of a protocol version negotiated at runtime.
+    pub async fn write_versioned<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        version: ProtocolVersion,
+        direction: PacketDirection,
+        state: ConnectionState,
+        packet: &dyn Packet,
+    ) -> Result<(), PacketWriteErr> {
+        match version {
+            ProtocolVersion::V765 => {
+                self.write::<W, v765::PacketEncoderImpl>(writer, direction, state, packet)
+                    .await
+            }
+            ProtocolVersion::V766 => {
+                self.write::<W, v766::PacketEncoderImpl>(writer, direction, state, packet)
+                    .await
+            }
+        }
+    }
+
+    fn copy_body(&mut self, body: &[u8]) -> Result<(), PacketWriteErr> {
+        if body.len() > self.packet_limit {
+            return Err(PacketWriteErr::PacketTooLong(body.len(), self.packet_limit));
+        }
+        let data_end = VarInt::MAX_BYTES + body.len();
+        if self.packet_buf.len() < data_end {
+            self.packet_buf.resize(data_end, 0u8);
+        }
+        self.packet_buf[VarInt::MAX_BYTES..data_end].copy_from_slice(body);
+        Ok(())
+    }
+
+    /// Frames and writes a packet body produced by [`encode_versioned`].
+    pub async fn write_encoded<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        body: &[u8],
+    ) -> Result<(), PacketWriteErr> {
+        self.copy_body(body)?;
+        self.write_frame(writer, body.len()).await
+    }
+
+    /// Frames every packet into one contiguous buffer and writes it with a single `write_all`,
+    /// instead of one write per packet.
+    pub async fn write_encoded_batch<W: AsyncWrite + Unpin>(
+        &mut self,
+        writer: &mut W,
+        packets: &[EncodedPacket],
+    ) -> Result<(), PacketWriteErr> {
+        let mut batch_buf = std::mem::take(&mut self.batch_buf);
+        batch_buf.clear();
+        for packet in packets {
+            match packet {
+                EncodedPacket::Body(body) => {
+                    self.copy_body(body.as_slice())?;
+                    batch_buf.extend_from_slice(self.encode_frame(body.len())?);
+                }
+                EncodedPacket::PreEncoded(packet) => {
+                    if packet.compression() != self.compression {
+                        return Err(PacketWriteErr::CompressionMismatch(
+                            packet.compression(),
+                            self.compression,
+                        ));
+                    }
+                    let start = batch_buf.len();
+                    batch_buf.extend_from_slice(packet.frame());
+                    if let Some(encryptor) = self.encryptor.as_mut() {
+                        encrypt_in_place(encryptor, &mut batch_buf[start..]);
+                    }
+                }
+            }
+        }
+        let result = writer
+            .write_all(batch_buf.as_slice())
+            .await
+            .map_err(PacketWriteErr::IoErr);
+        self.batch_buf = batch_buf;
+        result
+    }
 }
 
 pub struct AsyncPacketReader {
     packet_buf: Vec<u8>,
     inflate_buf: Vec<u8>,
     decryptor: Option<Decryptor<Aes128>>,
-    crypt_key: [u8; 16],
+    crypt_key: [u8; CRYPT_KEY_LEN],
     compression: Option<usize>,
     packet_limit: usize,
 }
@@ -259,12 +495,23 @@ impl AsyncPacketReader {
             packet_buf: Vec::<u8>::new(),
             inflate_buf: Vec::<u8>::new(),
             decryptor: None,
-            crypt_key: [0u8; 16],
+            crypt_key: [0u8; CRYPT_KEY_LEN],
             compression: None,
             packet_limit: DEFAULT_PACKET_LIMIT,
         }
     }
 
+    /// Enables AES-128/CFB8 decryption using the shared secret as both the key and the IV. Every
+    /// byte read after this call (including frame headers) is decrypted.
+    pub fn enable_encryption(&mut self, key: &[u8; CRYPT_KEY_LEN]) {
+        self.crypt_key = *key;
+        self.decryptor = Some(Decryptor::<Aes128>::new(key.into(), key.into()));
+    }
+
+    pub fn encryption_enabled(&self) -> bool {
+        self.decryptor.is_some()
+    }
+
     pub async fn read_frame_size<R: AsyncRead + Unpin>(
         &mut self,
         reader: &mut R,
@@ -277,7 +524,7 @@ impl AsyncPacketReader {
                 .await
                 .map_err(|err| PacketReadErr::IoErr(err))?;
             if let Some(decryptor) = self.decryptor.as_mut() {
-                panic!("encryption not supported");
+                decrypt_in_place(decryptor, bytes.as_mut_slice());
             }
             let b: i32 = i32::from(bytes[0]);
             size |= (b & 0x7f) << (i * 7);
@@ -288,30 +535,75 @@ impl AsyncPacketReader {
         Err(PacketReadErr::MalformedPacketHeader)
     }
 
-    pub async fn read_frame<R: AsyncRead + Unpin>(
+    /// Sets the compression threshold. Frames are expected to carry a data length header once a
+    /// threshold is set, and compressed payloads smaller than the threshold are rejected.
+    pub fn set_compression(&mut self, threshold: Option<usize>) {
+        self.compression = threshold;
+    }
+
+    pub fn compression(&self) -> Option<usize> {
+        self.compression
+    }
+
+    pub async fn read_frame<R: AsyncRead + Unpin, A: AllocTracker>(
         &mut self,
         reader: &mut R,
-    ) -> Result<usize, PacketReadErr> {
-        if self.compression.is_some() {
-            panic!("compression is not supported");
-        } else {
-            let frame_size = self.read_frame_size(reader).await?;
-            if frame_size > self.packet_limit {
-                return Err(PacketReadErr::PacketTooLong(frame_size, self.packet_limit));
-            } else if self.packet_buf.len() < frame_size {
-                self.packet_buf.resize(frame_size, 0u8);
-            }
-            let slice = self
-                .packet_buf
-                .as_mut_slice()
-                .get_mut(0..frame_size)
-                .ok_or_else(|| PacketReadErr::MalformedPacketHeader)?;
-            reader
-                .read_exact(slice)
-                .await
-                .map_err(|err| PacketReadErr::IoErr(err))?;
-            Ok(frame_size)
+        alloc_tracker: &mut A,
+    ) -> Result<&[u8], PacketReadErr> {
+        let frame_size = self.read_frame_size(reader).await?;
+        if frame_size > self.packet_limit {
+            return Err(PacketReadErr::PacketTooLong(frame_size, self.packet_limit));
+        } else if self.packet_buf.len() < frame_size {
+            self.packet_buf.resize(frame_size, 0u8);
+        }
+        let slice = self
+            .packet_buf
+            .as_mut_slice()
+            .get_mut(0..frame_size)
+            .ok_or_else(|| PacketReadErr::MalformedPacketHeader)?;
+        reader
+            .read_exact(slice)
+            .await
+            .map_err(|err| PacketReadErr::IoErr(err))?;
+        if let Some(decryptor) = self.decryptor.as_mut() {
+            decrypt_in_place(decryptor, slice);
+        }
+        let Some(threshold) = self.compression else {
+            return Ok(&self.packet_buf[..frame_size]);
+        };
+        let mut cursor = Cursor::new(&self.packet_buf[..frame_size]);
+        let data_len = VarInt::decode(&mut cursor, alloc_tracker)
+            .map_err(|err| PacketReadErr::DecodeErr(err))?;
+        let data_len =
+            usize::try_from(data_len).map_err(|_| PacketReadErr::MalformedPacketHeader)?;
+        let data_start = cursor.position() as usize;
+        if data_len == 0 {
+            return Ok(&self.packet_buf[data_start..frame_size]);
+        } else if data_len < threshold {
+            return Err(PacketReadErr::BelowCompressionThreshold(
+                data_len, threshold,
+            ));
+        } else if data_len > self.packet_limit {
+            return Err(PacketReadErr::PacketTooLong(data_len, self.packet_limit));
         }
+        alloc_tracker
+            .alloc(data_len)
+            .map_err(|err| PacketReadErr::DecodeErr(err))?;
+        if self.inflate_buf.len() < data_len {
+            self.inflate_buf.resize(data_len, 0u8);
+        }
+        let mut decompress = Decompress::new(true);
+        let status = decompress
+            .decompress(
+                &self.packet_buf[data_start..frame_size],
+                &mut self.inflate_buf[..data_len],
+                FlushDecompress::Finish,
+            )
+            .map_err(|_| PacketReadErr::DecompressionErr)?;
+        if status != Status::StreamEnd || decompress.total_out() as usize != data_len {
+            return Err(PacketReadErr::DecompressionErr);
+        }
+        Ok(&self.inflate_buf[..data_len])
     }
 
     pub async fn read<R: AsyncRead + Unpin, D: PacketDecoder>(
@@ -320,18 +612,459 @@ impl AsyncPacketReader {
         direction: PacketDirection,
         state: ConnectionState,
     ) -> Result<Box<dyn Packet>, PacketReadErr> {
-        let frame_size = self.read_frame(reader).await?;
-        let mut vec_reader = VecReader::new(&mut self.packet_buf, 0, frame_size);
+        self.read_as::<R, D, Box<dyn Packet>>(reader, direction, state)
+            .await
+    }
+
+    /// Reads a packet straight into the enum of packets expected in its connection state.
+    pub async fn read_typed<R: AsyncRead + Unpin, D: PacketDecoder, P: PacketSet>(
+        &mut self,
+        reader: &mut R,
+    ) -> Result<P, PacketReadErr> {
+        self.read_as::<R, D, P>(reader, P::DIRECTION, P::STATE)
+            .await
+    }
+
+    async fn read_as<R: AsyncRead + Unpin, D: PacketDecoder, P: DecodedPacket>(
+        &mut self,
+        reader: &mut R,
+        direction: PacketDirection,
+        state: ConnectionState,
+    ) -> Result<P, PacketReadErr> {
         let mut alloc_tracker = BasicAllocTracker::new(DEFAULT_ALLOC_LIMIT);
-        let packet_id = VarInt::decode(&mut vec_reader, &mut alloc_tracker)
+        let frame = self.read_frame(reader, &mut alloc_tracker).await?;
+        let mut frame_reader = Cursor::new(frame);
+        let packet_id = VarInt::decode(&mut frame_reader, &mut alloc_tracker)
             .map_err(|err| PacketReadErr::DecodeErr(err))?;
-        D::decode_packet(
+        D::decode_as::<P, _, _>(
             packet_id,
             direction,
             state,
-            &mut vec_reader,
+            &mut frame_reader,
             &mut alloc_tracker,
         )
         .map_err(|err| PacketReadErr::DecodeErr(err))
     }
+
+    /// Reads a packet with the decoder of a protocol version negotiated at runtime.
+    pub async fn read_versioned<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        version: ProtocolVersion,
+        direction: PacketDirection,
+        state: ConnectionState,
+    ) -> Result<Box<dyn Packet>, PacketReadErr> {
+        match version {
+            ProtocolVersion::V765 => {
+                self.read::<R, v765::PacketDecoderImpl>(reader, direction, state)
+                    .await
+            }
+            ProtocolVersion::V766 => {
+                self.read::<R, v766::PacketDecoderImpl>(reader, direction, state)
+                    .await
+            }
+        }
+    }
+
+    /// Reads a packet set with the decoder of a protocol version negotiated at runtime.
+    pub async fn read_versioned_typed<R: AsyncRead + Unpin, P: PacketSet>(
+        &mut self,
+        reader: &mut R,
+        version: ProtocolVersion,
+    ) -> Result<P, PacketReadErr> {
+        match version {
+            ProtocolVersion::V765 => {
+                self.read_typed::<R, v765::PacketDecoderImpl, P>(reader)
+                    .await
+            }
+            ProtocolVersion::V766 => {
+                self.read_typed::<R, v766::PacketDecoderImpl, P>(reader)
+                    .await
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use aes::Aes128;
+    use cfb8::{cipher::KeyIvInit, Decryptor};
+    use flate2::{write::ZlibEncoder, Compression};
+    use uuid::Uuid;
+
+    use crate::{
+        decode::{BasicAllocTracker, ProtoDecode, ProtoDecodeErr},
+        encode::ProtoEncode,
+        io::{
+            decrypt_in_place, encode_versioned, AsyncPacketReader, AsyncPacketWriter,
+            EncodedPacket, PacketReadErr, PacketWriteErr, PreEncodedPacket, CRYPT_KEY_LEN,
+            DEFAULT_ALLOC_LIMIT, DEFAULT_PACKET_LIMIT,
+        },
+        packet::{
+            ConnectionState::{Login, Status},
+            PacketDirection::ServerBound,
+        },
+        types::VarInt,
+        v765::{
+            serverbound::{LoginPluginResponse, LoginStart, StatusPingRequest},
+            PacketDecoderImpl, PacketEncoderImpl,
+        },
+        version::ProtocolVersion,
+    };
+
+    const KEY: [u8; CRYPT_KEY_LEN] = [
+        0x3c, 0x11, 0xe5, 0x7a, 0x90, 0x02, 0x4f, 0xb8, 0x6d, 0x21, 0xc3, 0x55, 0x08, 0xfe, 0x9a,
+        0x47,
+    ];
+
+    #[tokio::test]
+    async fn test_encrypted_round_trip() {
+        let (mut client, mut server) = tokio::io::duplex(4096);
+        let mut writer = AsyncPacketWriter::new();
+        let mut reader = AsyncPacketReader::new();
+        writer.enable_encryption(&KEY);
+        reader.enable_encryption(&KEY);
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5),
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut client, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+        for payload in [0i64, -1, i64::MAX] {
+            writer
+                .write::<_, PacketEncoderImpl>(
+                    &mut client,
+                    ServerBound,
+                    Status,
+                    &StatusPingRequest { payload },
+                )
+                .await
+                .unwrap();
+        }
+        let decoded = reader
+            .read::<_, PacketDecoderImpl>(&mut server, ServerBound, Login)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<LoginStart>()
+            .unwrap();
+        assert_eq!(decoded.name, login_start.name);
+        assert_eq!(decoded.uuid, login_start.uuid);
+        for payload in [0i64, -1, i64::MAX] {
+            let decoded = reader
+                .read::<_, PacketDecoderImpl>(&mut server, ServerBound, Status)
+                .await
+                .unwrap()
+                .into_any()
+                .downcast::<StatusPingRequest>()
+                .unwrap();
+            assert_eq!(decoded.payload, payload);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encryption_enabled_mid_stream() {
+        let (mut client, mut server) = tokio::io::duplex(4096);
+        let mut writer = AsyncPacketWriter::new();
+        let mut reader = AsyncPacketReader::new();
+        writer
+            .write::<_, PacketEncoderImpl>(&mut client, ServerBound, Status, &StatusPingRequest {
+                payload: 1,
+            })
+            .await
+            .unwrap();
+        writer.enable_encryption(&KEY);
+        writer
+            .write::<_, PacketEncoderImpl>(&mut client, ServerBound, Status, &StatusPingRequest {
+                payload: 2,
+            })
+            .await
+            .unwrap();
+        let first = reader
+            .read::<_, PacketDecoderImpl>(&mut server, ServerBound, Status)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<StatusPingRequest>()
+            .unwrap();
+        assert_eq!(first.payload, 1);
+        reader.enable_encryption(&KEY);
+        let second = reader
+            .read::<_, PacketDecoderImpl>(&mut server, ServerBound, Status)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<StatusPingRequest>()
+            .unwrap();
+        assert_eq!(second.payload, 2);
+    }
+
+    #[tokio::test]
+    async fn test_write_encoded() {
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5),
+        };
+        let mut expected: Vec<u8> = Vec::new();
+        let mut writer = AsyncPacketWriter::new();
+        writer.set_compression(Some(8));
+        writer
+            .write::<_, PacketEncoderImpl>(&mut expected, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+        let mut body = Vec::new();
+        encode_versioned(&mut body, ProtocolVersion::V765, ServerBound, &login_start).unwrap();
+        let mut written: Vec<u8> = Vec::new();
+        writer
+            .write_encoded(&mut written, body.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[tokio::test]
+    async fn test_write_encoded_batch() {
+        let mut bodies = Vec::new();
+        for payload in [0i64, 1, i64::MAX] {
+            let mut body = Vec::new();
+            encode_versioned(
+                &mut body,
+                ProtocolVersion::V765,
+                ServerBound,
+                &StatusPingRequest { payload },
+            )
+            .unwrap();
+            bodies.push(EncodedPacket::Body(body));
+        }
+        let mut writer = AsyncPacketWriter::new();
+        writer.enable_encryption(&KEY);
+        writer.set_compression(Some(4));
+        let mut expected: Vec<u8> = Vec::new();
+        for payload in [0i64, 1, i64::MAX] {
+            writer
+                .write::<_, PacketEncoderImpl>(
+                    &mut expected,
+                    ServerBound,
+                    Status,
+                    &StatusPingRequest { payload },
+                )
+                .await
+                .unwrap();
+        }
+        let mut writer = AsyncPacketWriter::new();
+        writer.enable_encryption(&KEY);
+        writer.set_compression(Some(4));
+        let mut written: Vec<u8> = Vec::new();
+        writer
+            .write_encoded_batch(&mut written, bodies.as_slice())
+            .await
+            .unwrap();
+        assert_eq!(written, expected);
+    }
+
+    #[tokio::test]
+    async fn test_pre_encoded_packet() {
+        let packet = StatusPingRequest {
+            payload: 0x0102030405060708,
+        };
+        for compression in [None, Some(4), Some(256)] {
+            let pre_encoded =
+                PreEncodedPacket::new(ProtocolVersion::V765, ServerBound, &packet, compression)
+                    .unwrap();
+            let packets = [
+                EncodedPacket::PreEncoded(pre_encoded.clone()),
+                EncodedPacket::PreEncoded(pre_encoded),
+            ];
+            let mut writer = AsyncPacketWriter::new();
+            writer.enable_encryption(&KEY);
+            writer.set_compression(compression);
+            let mut expected: Vec<u8> = Vec::new();
+            for _ in 0..2 {
+                writer
+                    .write::<_, PacketEncoderImpl>(&mut expected, ServerBound, Status, &packet)
+                    .await
+                    .unwrap();
+            }
+            let mut writer = AsyncPacketWriter::new();
+            writer.enable_encryption(&KEY);
+            writer.set_compression(compression);
+            let mut written: Vec<u8> = Vec::new();
+            writer
+                .write_encoded_batch(&mut written, packets.as_slice())
+                .await
+                .unwrap();
+            assert_eq!(written, expected);
+
+            writer.set_compression(Some(1));
+            assert!(matches!(
+                writer
+                    .write_encoded_batch(&mut written, packets.as_slice())
+                    .await,
+                Err(PacketWriteErr::CompressionMismatch(_, Some(1)))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encryption_covers_frame() {
+        let packet = StatusPingRequest {
+            payload: 0x0102030405060708,
+        };
+        let mut plain: Vec<u8> = Vec::new();
+        AsyncPacketWriter::new()
+            .write::<_, PacketEncoderImpl>(&mut plain, ServerBound, Status, &packet)
+            .await
+            .unwrap();
+        let mut encrypted: Vec<u8> = Vec::new();
+        let mut writer = AsyncPacketWriter::new();
+        writer.enable_encryption(&KEY);
+        writer
+            .write::<_, PacketEncoderImpl>(&mut encrypted, ServerBound, Status, &packet)
+            .await
+            .unwrap();
+        assert_eq!(plain.len(), encrypted.len());
+        assert_ne!(plain[0], encrypted[0]);
+        let mut decryptor = Decryptor::<Aes128>::new((&KEY).into(), (&KEY).into());
+        decrypt_in_place(&mut decryptor, encrypted.as_mut_slice());
+        assert_eq!(plain, encrypted);
+    }
+
+    const THRESHOLD: usize = 64;
+
+    fn var_int_bytes(value: usize) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        VarInt::encode(&(value as i32), &mut cursor).unwrap();
+        cursor.into_inner()
+    }
+
+    fn compressed_frame(data_len: usize, payload: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(payload).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut body = var_int_bytes(data_len);
+        body.extend_from_slice(compressed.as_slice());
+        let mut frame = var_int_bytes(body.len());
+        frame.extend_from_slice(body.as_slice());
+        frame
+    }
+
+    fn plugin_response(len: usize) -> LoginPluginResponse {
+        LoginPluginResponse {
+            message_id: 7,
+            successful: true,
+            data: (0..len).map(|i| (i % 13) as u8).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compression_framing() {
+        let mut writer = AsyncPacketWriter::new();
+        writer.set_compression(Some(THRESHOLD));
+
+        let mut small: Vec<u8> = Vec::new();
+        writer
+            .write::<_, PacketEncoderImpl>(&mut small, ServerBound, Status, &StatusPingRequest {
+                payload: 5,
+            })
+            .await
+            .unwrap();
+        // frame length, zero data length, packet id, i64 payload
+        assert_eq!(small.len(), 1 + 1 + 1 + 8);
+        assert_eq!(small[0] as usize, small.len() - 1);
+        assert_eq!(small[1], 0);
+
+        let packet = plugin_response(4096);
+        let mut large: Vec<u8> = Vec::new();
+        writer
+            .write::<_, PacketEncoderImpl>(&mut large, ServerBound, Login, &packet)
+            .await
+            .unwrap();
+        let mut uncompressed = Cursor::new(Vec::new());
+        VarInt::encode(&LoginPluginResponse::ID, &mut uncompressed).unwrap();
+        LoginPluginResponse::encode(&packet, &mut uncompressed).unwrap();
+        let uncompressed = uncompressed.into_inner();
+        let mut alloc_tracker = BasicAllocTracker::new(DEFAULT_ALLOC_LIMIT);
+        let mut cursor = Cursor::new(large.as_slice());
+        let frame_len = VarInt::decode(&mut cursor, &mut alloc_tracker).unwrap();
+        assert_eq!(frame_len as usize, large.len() - cursor.position() as usize);
+        let data_len = VarInt::decode(&mut cursor, &mut alloc_tracker).unwrap();
+        assert_eq!(data_len as usize, uncompressed.len());
+        assert!(large.len() < uncompressed.len());
+    }
+
+    #[tokio::test]
+    async fn test_compressed_round_trip() {
+        let (mut client, mut server) = tokio::io::duplex(1 << 16);
+        let mut writer = AsyncPacketWriter::new();
+        let mut reader = AsyncPacketReader::new();
+        writer.set_compression(Some(THRESHOLD));
+        reader.set_compression(Some(THRESHOLD));
+        writer.enable_encryption(&KEY);
+        reader.enable_encryption(&KEY);
+        for len in [0, THRESHOLD - 1, THRESHOLD, 20000] {
+            writer
+                .write::<_, PacketEncoderImpl>(
+                    &mut client,
+                    ServerBound,
+                    Login,
+                    &plugin_response(len),
+                )
+                .await
+                .unwrap();
+            let decoded = reader
+                .read::<_, PacketDecoderImpl>(&mut server, ServerBound, Login)
+                .await
+                .unwrap()
+                .into_any()
+                .downcast::<LoginPluginResponse>()
+                .unwrap();
+            assert_eq!(decoded.message_id, 7);
+            assert_eq!(decoded.data, plugin_response(len).data);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compressed_size_limits() {
+        let mut reader = AsyncPacketReader::new();
+        reader.set_compression(Some(THRESHOLD));
+        let mut alloc_tracker = BasicAllocTracker::new(DEFAULT_ALLOC_LIMIT);
+
+        let frame = compressed_frame(THRESHOLD - 1, &[0u8; THRESHOLD - 1]);
+        let result = reader
+            .read_frame(&mut frame.as_slice(), &mut alloc_tracker)
+            .await;
+        assert!(matches!(
+            result,
+            Err(PacketReadErr::BelowCompressionThreshold(_, THRESHOLD))
+        ));
+
+        let bomb = vec![0u8; DEFAULT_PACKET_LIMIT + 1];
+        let frame = compressed_frame(bomb.len(), bomb.as_slice());
+        let result = reader
+            .read_frame(&mut frame.as_slice(), &mut alloc_tracker)
+            .await;
+        assert!(matches!(result, Err(PacketReadErr::PacketTooLong(_, _))));
+
+        let frame = compressed_frame(THRESHOLD, bomb.as_slice());
+        let result = reader
+            .read_frame(&mut frame.as_slice(), &mut alloc_tracker)
+            .await;
+        assert!(matches!(result, Err(PacketReadErr::DecompressionErr)));
+
+        let mut alloc_tracker = BasicAllocTracker::new(THRESHOLD);
+        let frame = compressed_frame(THRESHOLD * 2, &[1u8; THRESHOLD * 2]);
+        let result = reader
+            .read_frame(&mut frame.as_slice(), &mut alloc_tracker)
+            .await;
+        assert!(matches!(
+            result,
+            Err(PacketReadErr::DecodeErr(
+                ProtoDecodeErr::MemoryLimitExceeded(_)
+            ))
+        ));
+    }
 }
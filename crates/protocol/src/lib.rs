@@ -1,10 +1,14 @@
 pub mod chunk;
 pub mod decode;
+pub mod dispatch;
 pub mod encode;
 pub mod io;
 pub mod packet;
+pub mod text;
 pub mod types;
 pub mod v765;
+pub mod v766;
+pub mod version;
 
 #[cfg(test)]
 mod tests {
@@ -34,6 +34,16 @@ pub trait Packet: Send + Sync + Debug {
 }
 
 pub trait PacketEncoder {
+    /// Returns the id a packet is sent with. Packet structs shared between protocol versions carry
+    /// the id of the version that defines them, so versions that renumber packets override this.
+    fn packet_id(
+        packet: &dyn Packet,
+        _direction: PacketDirection,
+        _state: ConnectionState,
+    ) -> Option<i32> {
+        Some(packet.id())
+    }
+
     fn encode_packet<W: Write + Seek>(
         packet: &dyn Packet,
         id: i32,
@@ -43,14 +53,52 @@ pub trait PacketEncoder {
     ) -> Result<(), ProtoEncodeErr>;
 }
 
+/// The type a decoder hands decoded packets to, either a boxed packet or one of the per-state
+/// packet enums.
+pub trait DecodedPacket: Sized {
+    fn from_packet<P: PacketSetMember>(packet: P) -> Option<Self>;
+}
+
+impl DecodedPacket for Box<dyn Packet> {
+    fn from_packet<P: PacketSetMember>(packet: P) -> Option<Self> {
+        Some(Box::new(packet))
+    }
+}
+
+/// A packet that is a variant of the packet set of its connection state.
+pub trait PacketSetMember: Packet + Sized + 'static {
+    type Set: PacketSet + 'static;
+
+    fn into_set(self) -> Self::Set;
+}
+
+/// An enum of every packet that can be received in a connection state, decoded without boxing.
+pub trait PacketSet: DecodedPacket + Debug + Send + Sync {
+    const DIRECTION: PacketDirection;
+    const STATE: ConnectionState;
+
+    fn as_packet(&self) -> &dyn Packet;
+    fn into_packet(self) -> Box<dyn Packet>;
+}
+
 pub trait PacketDecoder {
+    fn decode_as<P: DecodedPacket, R: Read + Seek, T: AllocTracker>(
+        id: i32,
+        direction: PacketDirection,
+        state: ConnectionState,
+        reader: &mut R,
+        alloc_tracker: &mut T,
+    ) -> Result<P, ProtoDecodeErr>;
+
     fn decode_packet<R: Read + Seek, T: AllocTracker>(
         id: i32,
         direction: PacketDirection,
         state: ConnectionState,
         reader: &mut R,
         alloc_tracker: &mut T,
-    ) -> Result<Box<dyn Packet>, ProtoDecodeErr>;
+    ) -> Result<Box<dyn Packet>, ProtoDecodeErr> {
+        Self::decode_as::<Box<dyn Packet>, R, T>(id, direction, state, reader, alloc_tracker)
+    }
 }
 
 macro_rules! encode_packet_impl {
@@ -70,7 +118,8 @@ macro_rules! decode_packet_impl {
     ($id: ident, $reader:ident, $alloc_tracker:ident,$($packet_ty:ty),*) => {
         match $id {
             $(<$packet_ty>::ID => {
-                Ok(Box::new(<$packet_ty as protocol::decode::ProtoDecode>::decode($reader, $alloc_tracker)?))
+                protocol::packet::DecodedPacket::from_packet(<$packet_ty as protocol::decode::ProtoDecode>::decode($reader, $alloc_tracker)?)
+                    .ok_or(protocol::decode::ProtoDecodeErr::UnexpectedPacket($id))
             })*
             _ => Err(protocol::decode::ProtoDecodeErr::UnknownPacketId($id))
         }
@@ -78,3 +127,86 @@ macro_rules! decode_packet_impl {
 }
 
 pub(crate) use decode_packet_impl;
+
+macro_rules! mapped_packet_id_impl {
+    ($packet:ident,$($packet_ty:ty = $packet_id:expr),*) => {{
+        $(if $packet.as_any().is::<$packet_ty>() {
+            return Some($packet_id);
+        })*
+        None
+    }}
+}
+
+pub(crate) use mapped_packet_id_impl;
+
+macro_rules! encode_mapped_packet_impl {
+    ($packet:ident,$id: ident,$writer:ident,$($packet_ty:ty = $packet_id:expr),*) => {{
+        $(if $id == $packet_id {
+            return <$packet_ty as protocol::encode::ProtoEncode>::encode($packet.as_any().downcast_ref::<$packet_ty>().ok_or_else(|| protocol::encode::ProtoEncodeErr::MalformedPacket)?, $writer);
+        })*
+        Err(protocol::encode::ProtoEncodeErr::UnknownPacketId($id))
+    }}
+}
+
+pub(crate) use encode_mapped_packet_impl;
+
+macro_rules! decode_mapped_packet_impl {
+    ($id: ident, $reader:ident, $alloc_tracker:ident,$($packet_ty:ty = $packet_id:expr),*) => {{
+        $(if $id == $packet_id {
+            return protocol::packet::DecodedPacket::from_packet(<$packet_ty as protocol::decode::ProtoDecode>::decode($reader, $alloc_tracker)?)
+                .ok_or(protocol::decode::ProtoDecodeErr::UnexpectedPacket($id));
+        })*
+        Err(protocol::decode::ProtoDecodeErr::UnknownPacketId($id))
+    }}
+}
+
+pub(crate) use decode_mapped_packet_impl;
+
+macro_rules! packet_set {
+    ($(#[$attr:meta])* $name:ident, $direction:ident, $state:ident, $($variant:ident($packet_ty:ty)),*) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        #[allow(clippy::large_enum_variant)]
+        pub enum $name {
+            $($variant($packet_ty)),*
+        }
+
+        impl protocol::packet::DecodedPacket for $name {
+            fn from_packet<P: protocol::packet::PacketSetMember>(packet: P) -> Option<Self> {
+                // packets of other states convert into their own set, moved out through an option
+                // instead of boxing it to downcast
+                let mut set = Some(packet.into_set());
+                (&mut set as &mut dyn std::any::Any)
+                    .downcast_mut::<Option<Self>>()
+                    .and_then(Option::take)
+            }
+        }
+
+        $(impl protocol::packet::PacketSetMember for $packet_ty {
+            type Set = $name;
+
+            fn into_set(self) -> $name {
+                $name::$variant(self)
+            }
+        })*
+
+        impl protocol::packet::PacketSet for $name {
+            const DIRECTION: protocol::packet::PacketDirection = protocol::packet::PacketDirection::$direction;
+            const STATE: protocol::packet::ConnectionState = protocol::packet::ConnectionState::$state;
+
+            fn as_packet(&self) -> &dyn protocol::packet::Packet {
+                match self {
+                    $($name::$variant(packet) => packet),*
+                }
+            }
+
+            fn into_packet(self) -> Box<dyn protocol::packet::Packet> {
+                match self {
+                    $($name::$variant(packet) => Box::new(packet)),*
+                }
+            }
+        }
+    }
+}
+
+pub(crate) use packet_set;
@@ -0,0 +1,998 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    io::{Read, Seek, Write},
+};
+
+use serde_json::{Map, Number, Value};
+use serverx_nbt::{encode::NbtEncode, tag::TagType, NamedTag, Tag};
+use uuid::Uuid;
+
+use crate::{
+    decode::{AllocTracker, ProtoDecode, ProtoDecodeErr},
+    encode::{ProtoEncode, ProtoEncodeErr},
+    types::{decode_root_tag, decode_root_tag_type},
+};
+
+/// A chat component. Sent as JSON during Status and Login and as network NBT in Configuration
+/// and Play.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TextComponent {
+    pub content: TextContent,
+    pub style: Style,
+    pub extra: Vec<TextComponent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextContent {
+    Text(String),
+    Translate {
+        key: String,
+        fallback: Option<String>,
+        with: Vec<TextComponent>,
+    },
+    Score {
+        name: String,
+        objective: String,
+    },
+    Selector {
+        selector: String,
+        separator: Option<Box<TextComponent>>,
+    },
+    Keybind(String),
+}
+
+impl Default for TextContent {
+    fn default() -> Self {
+        TextContent::Text(String::new())
+    }
+}
+
+/// Formatting inherited by the children of a component, unset fields take the parent's value.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Style {
+    pub color: Option<TextColor>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    pub underlined: Option<bool>,
+    pub strikethrough: Option<bool>,
+    pub obfuscated: Option<bool>,
+    pub font: Option<String>,
+    pub insertion: Option<String>,
+    pub click_event: Option<ClickEvent>,
+    pub hover_event: Option<HoverEvent>,
+}
+
+impl Style {
+    pub fn is_empty(&self) -> bool {
+        *self == Style::default()
+    }
+
+    /// This style with every unset field taken from `parent`.
+    pub fn inherit(&self, parent: &Style) -> Style {
+        Style {
+            color: self.color.or(parent.color),
+            bold: self.bold.or(parent.bold),
+            italic: self.italic.or(parent.italic),
+            underlined: self.underlined.or(parent.underlined),
+            strikethrough: self.strikethrough.or(parent.strikethrough),
+            obfuscated: self.obfuscated.or(parent.obfuscated),
+            font: self.font.clone().or_else(|| parent.font.clone()),
+            insertion: self.insertion.clone().or_else(|| parent.insertion.clone()),
+            click_event: self
+                .click_event
+                .clone()
+                .or_else(|| parent.click_event.clone()),
+            hover_event: self
+                .hover_event
+                .clone()
+                .or_else(|| parent.hover_event.clone()),
+        }
+    }
+
+    fn legacy_codes(&self) -> String {
+        let mut codes = String::new();
+        if let Some(code) = self.color.and_then(|color| color.legacy_code()) {
+            codes.push('§');
+            codes.push(code);
+        }
+        for (enabled, code) in [
+            (self.obfuscated, 'k'),
+            (self.bold, 'l'),
+            (self.strikethrough, 'm'),
+            (self.underlined, 'n'),
+            (self.italic, 'o'),
+        ] {
+            if enabled == Some(true) {
+                codes.push('§');
+                codes.push(code);
+            }
+        }
+        codes
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextColor {
+    Black,
+    DarkBlue,
+    DarkGreen,
+    DarkAqua,
+    DarkRed,
+    DarkPurple,
+    Gold,
+    Gray,
+    DarkGray,
+    Blue,
+    Green,
+    Aqua,
+    Red,
+    LightPurple,
+    Yellow,
+    White,
+    Rgb(u32),
+}
+
+impl TextColor {
+    const NAMED: [(TextColor, &'static str, char); 16] = [
+        (TextColor::Black, "black", '0'),
+        (TextColor::DarkBlue, "dark_blue", '1'),
+        (TextColor::DarkGreen, "dark_green", '2'),
+        (TextColor::DarkAqua, "dark_aqua", '3'),
+        (TextColor::DarkRed, "dark_red", '4'),
+        (TextColor::DarkPurple, "dark_purple", '5'),
+        (TextColor::Gold, "gold", '6'),
+        (TextColor::Gray, "gray", '7'),
+        (TextColor::DarkGray, "dark_gray", '8'),
+        (TextColor::Blue, "blue", '9'),
+        (TextColor::Green, "green", 'a'),
+        (TextColor::Aqua, "aqua", 'b'),
+        (TextColor::Red, "red", 'c'),
+        (TextColor::LightPurple, "light_purple", 'd'),
+        (TextColor::Yellow, "yellow", 'e'),
+        (TextColor::White, "white", 'f'),
+    ];
+
+    /// The name used in serialized components, `#rrggbb` for RGB colors.
+    pub fn name(&self) -> String {
+        match self {
+            TextColor::Rgb(rgb) => format!("#{:06x}", rgb & 0xffffff),
+            color => Self::NAMED
+                .iter()
+                .find(|(named, ..)| named == color)
+                .map(|(_, name, _)| name.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The character following `§` in legacy formatted text, absent for RGB colors.
+    pub fn legacy_code(&self) -> Option<char> {
+        Self::NAMED
+            .iter()
+            .find(|(named, ..)| named == self)
+            .map(|(_, _, code)| *code)
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        if let Some(hex) = value.strip_prefix('#') {
+            if hex.len() != 6 {
+                return None;
+            }
+            return u32::from_str_radix(hex, 16).ok().map(TextColor::Rgb);
+        }
+        Self::NAMED
+            .iter()
+            .find(|(_, name, _)| *name == value)
+            .map(|(color, ..)| *color)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClickEvent {
+    OpenUrl(String),
+    RunCommand(String),
+    SuggestCommand(String),
+    ChangePage(i32),
+    CopyToClipboard(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HoverEvent {
+    ShowText(Box<TextComponent>),
+    ShowItem {
+        id: String,
+        count: i32,
+        /// The item's NBT in SNBT form.
+        tag: Option<String>,
+    },
+    ShowEntity {
+        entity_type: String,
+        id: Uuid,
+        name: Option<Box<TextComponent>>,
+    },
+}
+
+pub enum TextErr {
+    UnexpectedType(&'static str),
+    MissingContent,
+    InvalidColor(String),
+    InvalidClickEvent(String),
+    InvalidHoverEvent(String),
+    InvalidUuid,
+}
+
+impl Display for TextErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TextErr::UnexpectedType(field) => write!(f, "unexpected type for field {}", field),
+            TextErr::MissingContent => write!(f, "component has no content"),
+            TextErr::InvalidColor(color) => write!(f, "invalid color: {}", color),
+            TextErr::InvalidClickEvent(action) => write!(f, "invalid click event: {}", action),
+            TextErr::InvalidHoverEvent(action) => write!(f, "invalid hover event: {}", action),
+            TextErr::InvalidUuid => write!(f, "invalid uuid"),
+        }
+    }
+}
+
+impl Debug for TextErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <dyn Display>::fmt(self, f)
+    }
+}
+
+macro_rules! style_setter {
+    ($($name:ident: $ty:ty),*) => {
+        $(
+            pub fn $name(mut self, $name: $ty) -> Self {
+                self.style.$name = Some($name.into());
+                self
+            }
+        )*
+    };
+}
+
+impl TextComponent {
+    style_setter! {
+        color: TextColor, bold: bool, italic: bool, underlined: bool, strikethrough: bool,
+        obfuscated: bool, font: String, insertion: String, click_event: ClickEvent,
+        hover_event: HoverEvent
+    }
+
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::from_content(TextContent::Text(text.into()))
+    }
+
+    pub fn translate(key: impl Into<String>, with: Vec<TextComponent>) -> Self {
+        Self::from_content(TextContent::Translate {
+            key: key.into(),
+            fallback: None,
+            with,
+        })
+    }
+
+    pub fn score(name: impl Into<String>, objective: impl Into<String>) -> Self {
+        Self::from_content(TextContent::Score {
+            name: name.into(),
+            objective: objective.into(),
+        })
+    }
+
+    pub fn selector(selector: impl Into<String>) -> Self {
+        Self::from_content(TextContent::Selector {
+            selector: selector.into(),
+            separator: None,
+        })
+    }
+
+    pub fn keybind(key: impl Into<String>) -> Self {
+        Self::from_content(TextContent::Keybind(key.into()))
+    }
+
+    fn from_content(content: TextContent) -> Self {
+        Self {
+            content,
+            ..Default::default()
+        }
+    }
+
+    pub fn append(mut self, child: impl Into<TextComponent>) -> Self {
+        self.extra.push(child.into());
+        self
+    }
+
+    /// The text of this component and its children without any formatting. Translations are
+    /// not resolved, their fallback or key is used instead.
+    pub fn to_plain(&self) -> String {
+        let mut plain = String::new();
+        self.write_plain(&mut plain);
+        plain
+    }
+
+    fn write_plain(&self, plain: &mut String) {
+        plain.push_str(self.content_text());
+        for child in self.extra.iter() {
+            child.write_plain(plain);
+        }
+    }
+
+    /// The text of this component with colors and formatting as `§` codes, the format of the
+    /// legacy server list ping.
+    pub fn to_legacy(&self) -> String {
+        let mut legacy = String::new();
+        self.write_legacy(&Style::default(), &mut legacy, &mut String::new());
+        legacy
+    }
+
+    fn write_legacy(&self, parent: &Style, legacy: &mut String, applied: &mut String) {
+        let style = self.style.inherit(parent);
+        let text = self.content_text();
+        if !text.is_empty() {
+            let codes = style.legacy_codes();
+            if codes != *applied {
+                // a color code resets the formatting on its own
+                let has_color = style.color.and_then(|color| color.legacy_code()).is_some();
+                if !applied.is_empty() && !has_color {
+                    legacy.push_str("§r");
+                }
+                legacy.push_str(codes.as_str());
+                *applied = codes;
+            }
+            legacy.push_str(text);
+        }
+        for child in self.extra.iter() {
+            child.write_legacy(&style, legacy, applied);
+        }
+    }
+
+    fn content_text(&self) -> &str {
+        match &self.content {
+            TextContent::Text(text) => text,
+            TextContent::Translate { key, fallback, .. } => fallback.as_ref().unwrap_or(key),
+            TextContent::Keybind(key) => key,
+            TextContent::Score { .. } | TextContent::Selector { .. } => "",
+        }
+    }
+
+    fn is_plain_text(&self) -> bool {
+        matches!(self.content, TextContent::Text(_))
+            && self.style.is_empty()
+            && self.extra.is_empty()
+    }
+
+    pub fn to_json(&self) -> Value {
+        self.to_value(true)
+    }
+
+    pub fn from_json(value: &Value) -> Result<Self, TextErr> {
+        Self::from_value(value)
+    }
+
+    /// Components without style or children are sent as a bare string tag, like vanilla does.
+    /// Nested components are always compounds since NBT lists can't mix tag types.
+    pub fn to_nbt(&self) -> Tag {
+        match &self.content {
+            TextContent::Text(text) if self.is_plain_text() => Tag::String(text.clone()),
+            _ => json_to_tag(&self.to_value(false)),
+        }
+    }
+
+    pub fn from_nbt(tag: &Tag) -> Result<Self, TextErr> {
+        Self::from_value(&tag_to_json(tag))
+    }
+
+    fn to_value(&self, collapse: bool) -> Value {
+        if let TextContent::Text(text) = &self.content {
+            if collapse && self.is_plain_text() {
+                return Value::String(text.clone());
+            }
+        }
+        let mut map = Map::new();
+        match &self.content {
+            TextContent::Text(text) => {
+                map.insert("text".into(), text.as_str().into());
+            }
+            TextContent::Translate {
+                key,
+                fallback,
+                with,
+            } => {
+                map.insert("translate".into(), key.as_str().into());
+                if let Some(fallback) = fallback {
+                    map.insert("fallback".into(), fallback.as_str().into());
+                }
+                if !with.is_empty() {
+                    map.insert("with".into(), to_values(with, collapse));
+                }
+            }
+            TextContent::Score { name, objective } => {
+                let mut score = Map::new();
+                score.insert("name".into(), name.as_str().into());
+                score.insert("objective".into(), objective.as_str().into());
+                map.insert("score".into(), Value::Object(score));
+            }
+            TextContent::Selector {
+                selector,
+                separator,
+            } => {
+                map.insert("selector".into(), selector.as_str().into());
+                if let Some(separator) = separator {
+                    map.insert("separator".into(), separator.to_value(collapse));
+                }
+            }
+            TextContent::Keybind(key) => {
+                map.insert("keybind".into(), key.as_str().into());
+            }
+        }
+        self.style.write_value(&mut map, collapse);
+        if !self.extra.is_empty() {
+            map.insert("extra".into(), to_values(&self.extra, collapse));
+        }
+        Value::Object(map)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, TextErr> {
+        let map = match value {
+            Value::String(text) => return Ok(Self::text(text.as_str())),
+            Value::Number(number) => return Ok(Self::text(number.to_string())),
+            Value::Bool(value) => return Ok(Self::text(value.to_string())),
+            Value::Array(values) => {
+                let (first, rest) = values.split_first().ok_or(TextErr::MissingContent)?;
+                let mut component = Self::from_value(first)?;
+                for value in rest {
+                    component.extra.push(Self::from_value(value)?);
+                }
+                return Ok(component);
+            }
+            Value::Object(map) => map,
+            Value::Null => return Err(TextErr::MissingContent),
+        };
+        let content = if let Some(text) = get_str(map, "text")? {
+            TextContent::Text(text.to_string())
+        } else if let Some(key) = get_str(map, "translate")? {
+            TextContent::Translate {
+                key: key.to_string(),
+                fallback: get_str(map, "fallback")?.map(|s| s.to_string()),
+                with: from_values(map.get("with"), "with")?,
+            }
+        } else if let Some(score) = map.get("score") {
+            let score = score.as_object().ok_or(TextErr::UnexpectedType("score"))?;
+            TextContent::Score {
+                name: get_str(score, "name")?.unwrap_or_default().to_string(),
+                objective: get_str(score, "objective")?.unwrap_or_default().to_string(),
+            }
+        } else if let Some(selector) = get_str(map, "selector")? {
+            TextContent::Selector {
+                selector: selector.to_string(),
+                separator: map
+                    .get("separator")
+                    .map(|value| Self::from_value(value).map(Box::new))
+                    .transpose()?,
+            }
+        } else if let Some(key) = get_str(map, "keybind")? {
+            TextContent::Keybind(key.to_string())
+        } else {
+            return Err(TextErr::MissingContent);
+        };
+        Ok(Self {
+            content,
+            style: Style::from_value(map)?,
+            extra: from_values(map.get("extra"), "extra")?,
+        })
+    }
+}
+
+impl Style {
+    fn write_value(&self, map: &mut Map<String, Value>, collapse: bool) {
+        if let Some(color) = self.color {
+            map.insert("color".into(), color.name().into());
+        }
+        for (name, value) in [
+            ("bold", self.bold),
+            ("italic", self.italic),
+            ("underlined", self.underlined),
+            ("strikethrough", self.strikethrough),
+            ("obfuscated", self.obfuscated),
+        ] {
+            if let Some(value) = value {
+                map.insert(name.into(), value.into());
+            }
+        }
+        if let Some(font) = self.font.as_ref() {
+            map.insert("font".into(), font.as_str().into());
+        }
+        if let Some(insertion) = self.insertion.as_ref() {
+            map.insert("insertion".into(), insertion.as_str().into());
+        }
+        if let Some(click_event) = self.click_event.as_ref() {
+            let (action, value) = match click_event {
+                ClickEvent::OpenUrl(url) => ("open_url", url.clone()),
+                ClickEvent::RunCommand(command) => ("run_command", command.clone()),
+                ClickEvent::SuggestCommand(command) => ("suggest_command", command.clone()),
+                ClickEvent::ChangePage(page) => ("change_page", page.to_string()),
+                ClickEvent::CopyToClipboard(value) => ("copy_to_clipboard", value.clone()),
+            };
+            let mut event = Map::new();
+            event.insert("action".into(), action.into());
+            event.insert("value".into(), value.into());
+            map.insert("clickEvent".into(), Value::Object(event));
+        }
+        if let Some(hover_event) = self.hover_event.as_ref() {
+            let (action, contents) = match hover_event {
+                HoverEvent::ShowText(text) => ("show_text", text.to_value(collapse)),
+                HoverEvent::ShowItem { id, count, tag } => {
+                    let mut item = Map::new();
+                    item.insert("id".into(), id.as_str().into());
+                    item.insert("count".into(), (*count).into());
+                    if let Some(tag) = tag {
+                        item.insert("tag".into(), tag.as_str().into());
+                    }
+                    ("show_item", Value::Object(item))
+                }
+                HoverEvent::ShowEntity {
+                    entity_type,
+                    id,
+                    name,
+                } => {
+                    let mut entity = Map::new();
+                    entity.insert("type".into(), entity_type.as_str().into());
+                    let (most, least) = id.as_u64_pair();
+                    entity.insert(
+                        "id".into(),
+                        Value::Array(
+                            [most >> 32, most, least >> 32, least]
+                                .into_iter()
+                                .map(|part| (part as i32).into())
+                                .collect(),
+                        ),
+                    );
+                    if let Some(name) = name {
+                        entity.insert("name".into(), name.to_value(collapse));
+                    }
+                    ("show_entity", Value::Object(entity))
+                }
+            };
+            let mut event = Map::new();
+            event.insert("action".into(), action.into());
+            event.insert("contents".into(), contents);
+            map.insert("hoverEvent".into(), Value::Object(event));
+        }
+    }
+
+    fn from_value(map: &Map<String, Value>) -> Result<Self, TextErr> {
+        let color = get_str(map, "color")?
+            .map(|color| TextColor::parse(color).ok_or(TextErr::InvalidColor(color.to_string())))
+            .transpose()?;
+        let click_event = map
+            .get("clickEvent")
+            .map(click_event_from_value)
+            .transpose()?;
+        let hover_event = map
+            .get("hoverEvent")
+            .map(hover_event_from_value)
+            .transpose()?;
+        Ok(Self {
+            color,
+            bold: get_bool(map, "bold")?,
+            italic: get_bool(map, "italic")?,
+            underlined: get_bool(map, "underlined")?,
+            strikethrough: get_bool(map, "strikethrough")?,
+            obfuscated: get_bool(map, "obfuscated")?,
+            font: get_str(map, "font")?.map(|s| s.to_string()),
+            insertion: get_str(map, "insertion")?.map(|s| s.to_string()),
+            click_event,
+            hover_event,
+        })
+    }
+}
+
+fn click_event_from_value(value: &Value) -> Result<ClickEvent, TextErr> {
+    let event = value
+        .as_object()
+        .ok_or(TextErr::UnexpectedType("clickEvent"))?;
+    let action = get_str(event, "action")?.unwrap_or_default();
+    let value = match event.get("value") {
+        Some(Value::String(value)) => value.clone(),
+        Some(Value::Number(value)) => value.to_string(),
+        _ => return Err(TextErr::UnexpectedType("value")),
+    };
+    match action {
+        "open_url" => Ok(ClickEvent::OpenUrl(value)),
+        "run_command" => Ok(ClickEvent::RunCommand(value)),
+        "suggest_command" => Ok(ClickEvent::SuggestCommand(value)),
+        "change_page" => value
+            .parse()
+            .map(ClickEvent::ChangePage)
+            .map_err(|_| TextErr::InvalidClickEvent(action.to_string())),
+        "copy_to_clipboard" => Ok(ClickEvent::CopyToClipboard(value)),
+        action => Err(TextErr::InvalidClickEvent(action.to_string())),
+    }
+}
+
+fn hover_event_from_value(value: &Value) -> Result<HoverEvent, TextErr> {
+    let event = value
+        .as_object()
+        .ok_or(TextErr::UnexpectedType("hoverEvent"))?;
+    let action = get_str(event, "action")?.unwrap_or_default();
+    let contents = event
+        .get("contents")
+        .ok_or(TextErr::InvalidHoverEvent(action.to_string()))?;
+    match action {
+        "show_text" => Ok(HoverEvent::ShowText(Box::new(TextComponent::from_value(
+            contents,
+        )?))),
+        "show_item" => {
+            if let Value::String(id) = contents {
+                return Ok(HoverEvent::ShowItem {
+                    id: id.clone(),
+                    count: 1,
+                    tag: None,
+                });
+            }
+            let item = contents
+                .as_object()
+                .ok_or(TextErr::UnexpectedType("contents"))?;
+            Ok(HoverEvent::ShowItem {
+                id: get_str(item, "id")?
+                    .ok_or(TextErr::InvalidHoverEvent(action.to_string()))?
+                    .to_string(),
+                count: match item.get("count") {
+                    Some(count) => count
+                        .as_i64()
+                        .and_then(|count| i32::try_from(count).ok())
+                        .ok_or(TextErr::UnexpectedType("count"))?,
+                    None => 1,
+                },
+                tag: get_str(item, "tag")?.map(|s| s.to_string()),
+            })
+        }
+        "show_entity" => {
+            let entity = contents
+                .as_object()
+                .ok_or(TextErr::UnexpectedType("contents"))?;
+            Ok(HoverEvent::ShowEntity {
+                entity_type: get_str(entity, "type")?
+                    .ok_or(TextErr::InvalidHoverEvent(action.to_string()))?
+                    .to_string(),
+                id: uuid_from_value(entity.get("id").ok_or(TextErr::InvalidUuid)?)?,
+                name: entity
+                    .get("name")
+                    .map(|name| TextComponent::from_value(name).map(Box::new))
+                    .transpose()?,
+            })
+        }
+        action => Err(TextErr::InvalidHoverEvent(action.to_string())),
+    }
+}
+
+/// Accepts both the int array form vanilla writes and the hyphenated string form.
+fn uuid_from_value(value: &Value) -> Result<Uuid, TextErr> {
+    match value {
+        Value::String(id) => Uuid::parse_str(id).map_err(|_| TextErr::InvalidUuid),
+        Value::Array(parts) if parts.len() == 4 => {
+            let mut id = 0u128;
+            for part in parts {
+                let part = part
+                    .as_i64()
+                    .and_then(|part| i32::try_from(part).ok())
+                    .ok_or(TextErr::InvalidUuid)?;
+                id = (id << 32) | u128::from(part as u32);
+            }
+            Ok(Uuid::from_u128(id))
+        }
+        _ => Err(TextErr::InvalidUuid),
+    }
+}
+
+fn to_values(components: &[TextComponent], collapse: bool) -> Value {
+    Value::Array(
+        components
+            .iter()
+            .map(|component| component.to_value(collapse))
+            .collect(),
+    )
+}
+
+fn from_values(value: Option<&Value>, field: &'static str) -> Result<Vec<TextComponent>, TextErr> {
+    match value {
+        None => Ok(Vec::new()),
+        Some(Value::Array(values)) => values.iter().map(TextComponent::from_value).collect(),
+        Some(_) => Err(TextErr::UnexpectedType(field)),
+    }
+}
+
+fn get_str<'a>(
+    map: &'a Map<String, Value>,
+    field: &'static str,
+) -> Result<Option<&'a str>, TextErr> {
+    match map.get(field) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.as_str())),
+        Some(_) => Err(TextErr::UnexpectedType(field)),
+    }
+}
+
+fn get_bool(map: &Map<String, Value>, field: &'static str) -> Result<Option<bool>, TextErr> {
+    match map.get(field) {
+        None => Ok(None),
+        Some(Value::Bool(value)) => Ok(Some(*value)),
+        Some(_) => Err(TextErr::UnexpectedType(field)),
+    }
+}
+
+/// Converts the JSON form of a component to NBT, booleans become bytes and arrays of integers
+/// become int arrays.
+fn json_to_tag(value: &Value) -> Tag {
+    match value {
+        Value::Null => Tag::End,
+        Value::Bool(value) => Tag::Byte(*value as i8),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => match i32::try_from(value) {
+                Ok(value) => Tag::Int(value),
+                Err(_) => Tag::Long(value),
+            },
+            None => Tag::Double(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(value) => Tag::String(value.clone()),
+        Value::Array(values) => {
+            let ints = values
+                .iter()
+                .map(|value| value.as_i64().and_then(|value| i32::try_from(value).ok()))
+                .collect::<Option<Vec<_>>>();
+            match ints {
+                Some(ints) if !ints.is_empty() => Tag::IntArray(ints),
+                _ => Tag::List(values.iter().map(json_to_tag).collect()),
+            }
+        }
+        Value::Object(map) => Tag::Compound(
+            map.iter()
+                .map(|(name, value)| NamedTag {
+                    name: name.clone(),
+                    payload: json_to_tag(value),
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Converts NBT to the JSON form of a component, bytes are read as booleans.
+fn tag_to_json(tag: &Tag) -> Value {
+    match tag {
+        Tag::End => Value::Null,
+        Tag::Byte(value) => Value::Bool(*value != 0),
+        Tag::Short(value) => (*value).into(),
+        Tag::Int(value) => (*value).into(),
+        Tag::Long(value) => (*value).into(),
+        Tag::Float(value) => Number::from_f64(f64::from(*value)).map_or(Value::Null, Value::Number),
+        Tag::Double(value) => Number::from_f64(*value).map_or(Value::Null, Value::Number),
+        Tag::ByteArray(values) => values.iter().map(|value| Value::from(*value)).collect(),
+        Tag::String(value) => Value::String(value.clone()),
+        Tag::List(values) => values.iter().map(tag_to_json).collect(),
+        Tag::Compound(tags) => Value::Object(
+            tags.iter()
+                .map(|tag| (tag.name.clone(), tag_to_json(&tag.payload)))
+                .collect(),
+        ),
+        Tag::IntArray(values) => values.iter().map(|value| Value::from(*value)).collect(),
+        Tag::LongArray(values) => values.iter().map(|value| Value::from(*value)).collect(),
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(value: &str) -> Self {
+        Self::text(value)
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(value: String) -> Self {
+        Self::text(value)
+    }
+}
+
+impl ProtoEncode for TextComponent {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        let tag = data.to_nbt();
+        <i8 as ProtoEncode>::encode(&TagType::of(&tag).into(), writer)?;
+        <Tag as NbtEncode>::encode(&tag, writer).map_err(ProtoEncodeErr::NbtEncodeErr)
+    }
+}
+
+impl ProtoDecode for TextComponent {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let tag_type = decode_root_tag_type(reader, alloc_tracker)?;
+        let tag = decode_root_tag(tag_type, reader, alloc_tracker)?;
+        TextComponent::from_nbt(&tag).map_err(ProtoDecodeErr::InvalidTextComponent)
+    }
+}
+
+/// A text component sent as a JSON string, used before the connection reaches Configuration.
+pub struct JsonTextComponent;
+
+impl ProtoEncode for JsonTextComponent {
+    type Repr = TextComponent;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        Value::encode(&data.to_json(), writer)
+    }
+}
+
+impl ProtoDecode for JsonTextComponent {
+    type Repr = TextComponent;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let value = Value::decode(reader, alloc_tracker)?;
+        TextComponent::from_json(&value).map_err(ProtoDecodeErr::InvalidTextComponent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serde_json::json;
+    use serverx_nbt::{NamedTag, Tag};
+    use uuid::Uuid;
+
+    use crate::{
+        decode::{BasicAllocTracker, ProtoDecode},
+        encode::ProtoEncode,
+        text::{ClickEvent, HoverEvent, JsonTextComponent, TextColor, TextComponent, TextContent},
+    };
+
+    fn styled() -> TextComponent {
+        TextComponent::text("Hello ")
+            .color(TextColor::Gold)
+            .bold(true)
+            .click_event(ClickEvent::RunCommand("/spawn".to_string()))
+            .hover_event(HoverEvent::ShowText(Box::new(
+                TextComponent::text("Teleport").italic(false),
+            )))
+            .append(TextComponent::selector("@p"))
+            .append(TextComponent::translate("chat.type.text", vec![
+                "Notch".into(),
+                TextComponent::keybind("key.jump"),
+            ]))
+            .append(
+                TextComponent::score("Notch", "kills")
+                    .color(TextColor::Rgb(0x12ab34))
+                    .hover_event(HoverEvent::ShowEntity {
+                        entity_type: "minecraft:pig".to_string(),
+                        id: Uuid::from_u128(0x0102030405060708090a0b0c0d0e0f10),
+                        name: None,
+                    }),
+            )
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(TextComponent::text("hi").to_json(), json!("hi"));
+        assert_eq!(
+            styled().to_json(),
+            json!({
+                "text": "Hello ",
+                "color": "gold",
+                "bold": true,
+                "clickEvent": {"action": "run_command", "value": "/spawn"},
+                "hoverEvent": {
+                    "action": "show_text",
+                    "contents": {"text": "Teleport", "italic": false}
+                },
+                "extra": [
+                    {"selector": "@p"},
+                    {"translate": "chat.type.text", "with": ["Notch", {"keybind": "key.jump"}]},
+                    {
+                        "score": {"name": "Notch", "objective": "kills"},
+                        "color": "#12ab34",
+                        "hoverEvent": {
+                            "action": "show_entity",
+                            "contents": {
+                                "type": "minecraft:pig",
+                                "id": [0x01020304, 0x05060708, 0x090a0b0c, 0x0d0e0f10]
+                            }
+                        }
+                    }
+                ]
+            })
+        );
+        assert_eq!(
+            TextComponent::from_json(&styled().to_json()).unwrap(),
+            styled()
+        );
+    }
+
+    #[test]
+    fn test_json_shorthand() {
+        let component =
+            TextComponent::from_json(&json!(["a", {"text": "b", "color": "red"}, 3])).unwrap();
+        assert_eq!(
+            component,
+            TextComponent::text("a")
+                .append(TextComponent::text("b").color(TextColor::Red))
+                .append("3")
+        );
+        assert_eq!(component.to_plain(), "ab3");
+        assert!(TextComponent::from_json(&json!({"color": "red"})).is_err());
+        assert!(TextComponent::from_json(&json!({"text": "", "color": "pink"})).is_err());
+        let legacy_uuid = json!({
+            "text": "",
+            "hoverEvent": {
+                "action": "show_entity",
+                "contents": {"type": "minecraft:pig", "id": "01020304-0506-0708-090a-0b0c0d0e0f10"}
+            }
+        });
+        assert!(matches!(
+            TextComponent::from_json(&legacy_uuid).unwrap().style.hover_event,
+            Some(HoverEvent::ShowEntity { id, .. }) if id.as_u128() == 0x0102030405060708090a0b0c0d0e0f10
+        ));
+    }
+
+    #[test]
+    fn test_legacy() {
+        let motd = TextComponent::text("A ")
+            .color(TextColor::Gold)
+            .append(TextComponent::text("Minecraft").bold(true))
+            .append(" Server")
+            .append(TextComponent::text("!").color(TextColor::Rgb(0x123456)));
+        assert_eq!(motd.to_legacy(), "§6A §6§lMinecraft§6 Server§r!");
+        assert_eq!(motd.to_plain(), "A Minecraft Server!");
+    }
+
+    #[test]
+    fn test_nbt() {
+        assert_eq!(
+            TextComponent::text("hi").to_nbt(),
+            Tag::String("hi".to_string())
+        );
+        let Tag::Compound(tags) = TextComponent::text("hi").bold(true).to_nbt() else {
+            panic!("styled component is not a compound");
+        };
+        assert_eq!(tags.len(), 2);
+        assert!(tags.contains(&NamedTag {
+            name: "text".to_string(),
+            payload: Tag::String("hi".to_string()),
+        }));
+        assert!(tags.contains(&NamedTag {
+            name: "bold".to_string(),
+            payload: Tag::Byte(1),
+        }));
+        assert_eq!(
+            TextComponent::from_nbt(&styled().to_nbt()).unwrap(),
+            styled()
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut cursor = Cursor::new(Vec::new());
+        TextComponent::encode(&TextComponent::text("hi"), &mut cursor).unwrap();
+        assert_eq!(cursor.get_ref().as_slice(), &[0x08, 0x00, 0x02, b'h', b'i']);
+
+        let mut cursor = Cursor::new(Vec::new());
+        TextComponent::encode(&TextComponent::keybind("a"), &mut cursor).unwrap();
+        assert_eq!(cursor.get_ref().as_slice(), &[
+            0x0a, 0x08, 0x00, 0x07, b'k', b'e', b'y', b'b', b'i', b'n', b'd', 0x00, 0x01, b'a',
+            0x00
+        ]);
+        cursor.set_position(0);
+        let decoded =
+            TextComponent::decode(&mut cursor, &mut BasicAllocTracker::new(1 << 20)).unwrap();
+        assert_eq!(decoded.content, TextContent::Keybind("a".to_string()));
+
+        let mut cursor = Cursor::new(Vec::new());
+        JsonTextComponent::encode(&TextComponent::text("hi"), &mut cursor).unwrap();
+        assert_eq!(cursor.get_ref().as_slice(), b"\x04\"hi\"");
+        cursor.set_position(0);
+        let decoded =
+            JsonTextComponent::decode(&mut cursor, &mut BasicAllocTracker::new(1 << 20)).unwrap();
+        assert_eq!(decoded, TextComponent::text("hi"));
+    }
+}
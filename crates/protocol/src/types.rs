@@ -218,12 +218,69 @@ impl ProtoDecode for Position {
     ) -> Result<Self::Repr, ProtoDecodeErr> {
         let val: i64 = <i64 as ProtoDecode>::decode(reader, alloc_tracker)?;
         let x = (val >> 38) as i32;
-        let y = (val & 0xfff) as i32;
+        let y = (val << 52 >> 52) as i32;
         let z = (val << 26 >> 38) as i32;
         Ok((x, y, z))
     }
 }
 
+/// A chunk section coordinate packed into a single long, as used by Update Section Blocks.
+pub struct SectionPosition;
+
+impl ProtoEncode for SectionPosition {
+    type Repr = (i32, i32, i32);
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        let x: u64 = ((data.0 as u64 & 0x3fffff) << 42)
+            | (data.1 as u64 & 0xfffff)
+            | ((data.2 as u64 & 0x3fffff) << 20);
+        u64::encode(&x, writer)
+    }
+}
+
+impl ProtoDecode for SectionPosition {
+    type Repr = (i32, i32, i32);
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let val: i64 = <i64 as ProtoDecode>::decode(reader, alloc_tracker)?;
+        let x = (val >> 42) as i32;
+        let y = (val << 44 >> 44) as i32;
+        let z = (val << 22 >> 42) as i32;
+        Ok((x, y, z))
+    }
+}
+
+/// An optional non-negative VarInt, sent as 0 when absent and as the value plus one otherwise.
+pub struct OptionalVarInt;
+
+impl ProtoEncode for OptionalVarInt {
+    type Repr = Option<i32>;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match data {
+            Some(val) => VarInt::encode(&(val + 1), writer),
+            None => VarInt::encode(&0, writer),
+        }
+    }
+}
+
+impl ProtoDecode for OptionalVarInt {
+    type Repr = Option<i32>;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        match VarInt::decode(reader, alloc_tracker)? {
+            0 => Ok(None),
+            val => Ok(Some(val - 1)),
+        }
+    }
+}
+
 impl<T> ProtoEncode for Option<T>
 where
     T: ProtoEncode,
@@ -553,6 +610,34 @@ impl ProtoDecodeSeq for RemainingBytes {
     }
 }
 
+/// A byte array with a length known ahead of time, sent without a length prefix.
+pub struct FixedBytes<const N: usize>;
+
+impl<const N: usize> ProtoEncode for FixedBytes<N> {
+    type Repr = [u8; N];
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        writer
+            .write_all(data.as_slice())
+            .map_err(|err| ProtoEncodeErr::IoErr(err))
+    }
+}
+
+impl<const N: usize> ProtoDecode for FixedBytes<N> {
+    type Repr = [u8; N];
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        _alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let mut buf = [0u8; N];
+        reader
+            .read_exact(buf.as_mut_slice())
+            .map_err(|err| ProtoDecodeErr::IoErr(err))?;
+        Ok(buf)
+    }
+}
+
 impl<T: ProtoEncode> ProtoEncode for Arc<T> {
     type Repr = Arc<T::Repr>;
 
@@ -595,26 +680,75 @@ impl ProtoDecode for TagRoot {
         reader: &mut R,
         alloc_tracker: &mut A,
     ) -> Result<Self::Repr, ProtoDecodeErr> {
-        let tag_type_id = <i8 as ProtoDecode>::decode(reader, alloc_tracker)?;
-        let tag_type = TagType::try_from(tag_type_id).map_err(|err| {
-            ProtoDecodeErr::NbtDecodeErr(NbtDecodeErr::InvalidTagType(tag_type_id))
-        })?;
+        let tag_type = decode_root_tag_type(reader, alloc_tracker)?;
         if tag_type != TagType::Compound {
             return Err(ProtoDecodeErr::NbtDecodeErr(
                 NbtDecodeErr::UnexpectedTagType(tag_type),
             ));
         }
-        let allocated_start = alloc_tracker.allocated();
-        let mut nbt_resource_tracker = BasicResourceTracker {
-            allocated: allocated_start,
-            max_allocated: MAX_TAG_SIZE,
-            depth: 0,
-            max_depth: MAX_RECURSION_DEPTH,
-        };
-        let result = <Tag as NbtDecode>::decode(tag_type, reader, &mut nbt_resource_tracker)
-            .map_err(|err| ProtoDecodeErr::NbtDecodeErr(err))?;
-        alloc_tracker.alloc(nbt_resource_tracker.allocated - allocated_start)?;
-        Ok(result)
+        decode_root_tag(tag_type, reader, alloc_tracker)
+    }
+}
+
+pub(crate) fn decode_root_tag_type<R: Read + Seek, A: AllocTracker>(
+    reader: &mut R,
+    alloc_tracker: &mut A,
+) -> Result<TagType, ProtoDecodeErr> {
+    let tag_type_id = <i8 as ProtoDecode>::decode(reader, alloc_tracker)?;
+    TagType::try_from(tag_type_id)
+        .map_err(|_| ProtoDecodeErr::NbtDecodeErr(NbtDecodeErr::InvalidTagType(tag_type_id)))
+}
+
+pub(crate) fn decode_root_tag<R: Read + Seek, A: AllocTracker>(
+    tag_type: TagType,
+    reader: &mut R,
+    alloc_tracker: &mut A,
+) -> Result<Tag, ProtoDecodeErr> {
+    let allocated_start = alloc_tracker.allocated();
+    let mut nbt_resource_tracker = BasicResourceTracker {
+        allocated: allocated_start,
+        max_allocated: MAX_TAG_SIZE,
+        depth: 0,
+        max_depth: MAX_RECURSION_DEPTH,
+    };
+    let result = <Tag as NbtDecode>::decode(tag_type, reader, &mut nbt_resource_tracker)
+        .map_err(|err| ProtoDecodeErr::NbtDecodeErr(err))?;
+    alloc_tracker.alloc(nbt_resource_tracker.allocated - allocated_start)?;
+    Ok(result)
+}
+
+/// A root compound tag that may be absent, in which case a single `TAG_End` is sent instead.
+pub struct OptionalTagRoot;
+
+impl ProtoEncode for OptionalTagRoot {
+    type Repr = Option<Tag>;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match data {
+            Some(tag) => TagRoot::encode(tag, writer),
+            None => <i8 as ProtoEncode>::encode(&TagType::End.into(), writer),
+        }
+    }
+}
+
+impl ProtoDecode for OptionalTagRoot {
+    type Repr = Option<Tag>;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        match decode_root_tag_type(reader, alloc_tracker)? {
+            TagType::End => Ok(None),
+            TagType::Compound => Ok(Some(decode_root_tag(
+                TagType::Compound,
+                reader,
+                alloc_tracker,
+            )?)),
+            tag_type => Err(ProtoDecodeErr::NbtDecodeErr(
+                NbtDecodeErr::UnexpectedTagType(tag_type),
+            )),
+        }
     }
 }
 
@@ -10,7 +10,13 @@ use serverx_nbt as nbt;
 use uuid::Uuid;
 
 use crate as protocol;
-use crate::{types::*, v765::types::*};
+use crate::{
+    decode::{AllocTracker, ProtoDecode, ProtoDecodeErr},
+    encode::{ProtoEncode, ProtoEncodeErr},
+    text::{JsonTextComponent, TextComponent},
+    types::*,
+    v765::types::*,
+};
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x00, ClientBound, Status)]
@@ -27,7 +33,8 @@ pub struct StatusPingResponse {
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x00, ClientBound, Login)]
 pub struct LoginDisconnect {
-    pub reason: serde_json::Value,
+    #[proto(repr = "JsonTextComponent")]
+    pub reason: TextComponent,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
@@ -77,7 +84,7 @@ pub struct ConfigClientBoundPluginMessage {
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x01, ClientBound, Configuration)]
 pub struct ConfigDisconnect {
-    pub reason: String,
+    pub reason: TextComponent,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
@@ -133,29 +140,113 @@ pub struct UpdateTags {
     pub tags: Vec<RegistryTag>,
 }
 
-// Game join
-// Difficulty
-// Play abilities
-// Selected slot
-// Recipes
-// EntityStatus
-// Command Tree
-// Unlock Recipes
-// Position Look
-// Server Metadata
-// Player List
-// World Border
-// World Time Update
-// Spawn Position
-// Game State Change
-// Update Tick Rate
-// Tick Step
-// Chunk Render Distance Center
-// Inventory
-// Entity Tracker
-// Entity Attributes
-// Advancement Update
-//
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x00, ClientBound, Play)]
+pub struct BundleDelimiter;
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x01, ClientBound, Play)]
+pub struct SpawnEntity {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub entity_uuid: Uuid,
+    #[proto(repr = "VarInt")]
+    pub entity_type: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    #[proto(repr = "Angle")]
+    pub pitch: f32,
+    #[proto(repr = "Angle")]
+    pub yaw: f32,
+    #[proto(repr = "Angle")]
+    pub head_yaw: f32,
+    #[proto(repr = "VarInt")]
+    pub data: i32,
+    pub velocity_x: i16,
+    pub velocity_y: i16,
+    pub velocity_z: i16,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x02, ClientBound, Play)]
+pub struct SpawnExperienceOrb {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub count: i16,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x03, ClientBound, Play)]
+pub struct EntityAnimation {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub animation: EntityAnimationType,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x04, ClientBound, Play)]
+pub struct AwardStatistics {
+    pub statistics: Vec<Statistic>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x05, ClientBound, Play)]
+pub struct AcknowledgeBlockChange {
+    #[proto(repr = "VarInt")]
+    pub sequence: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x06, ClientBound, Play)]
+pub struct SetBlockDestroyStage {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub destroy_stage: i8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x07, ClientBound, Play)]
+pub struct BlockEntityData {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    #[proto(repr = "VarInt")]
+    pub block_entity_type: i32,
+    #[proto(repr = "OptionalTagRoot")]
+    pub data: Option<nbt::Tag>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x08, ClientBound, Play)]
+pub struct BlockAction {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub action_id: u8,
+    pub action_param: u8,
+    #[proto(repr = "VarInt")]
+    pub block_type: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x09, ClientBound, Play)]
+pub struct BlockUpdate {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    #[proto(repr = "VarInt")]
+    pub block_state: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0A, ClientBound, Play)]
+pub struct BossBar {
+    pub uuid: Uuid,
+    pub action: BossBarAction,
+}
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x0B, ClientBound, Play)]
@@ -163,17 +254,174 @@ pub struct ChangeDifficulty {
     pub difficulty: Difficulty,
     pub locked: bool,
 }
-
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x0C, ClientBound, Play)]
 pub struct ChunkBatchFinish {
     #[proto(repr = "VarInt")]
     pub size: i32,
 }
-
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x0D, ClientBound, Play)]
 pub struct ChunkBatchStart;
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0E, ClientBound, Play)]
+pub struct ChunkBiomes {
+    pub chunks: Vec<ChunkBiomeData>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0F, ClientBound, Play)]
+pub struct ClearTitles {
+    pub reset: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x10, ClientBound, Play)]
+pub struct CommandSuggestionsResponse {
+    #[proto(repr = "VarInt")]
+    pub transaction_id: i32,
+    #[proto(repr = "VarInt")]
+    pub start: i32,
+    #[proto(repr = "VarInt")]
+    pub length: i32,
+    pub matches: Vec<CommandSuggestion>,
+}
+
+/// The command graph, gives the client completion and highlighting for the commands it can use.
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x11, ClientBound, Play)]
+pub struct Commands {
+    pub nodes: Vec<CommandNode>,
+    #[proto(repr = "VarInt")]
+    pub root_index: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x12, ClientBound, Play)]
+pub struct CloseContainer {
+    pub window_id: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x13, ClientBound, Play)]
+pub struct SetContainerContent {
+    pub window_id: u8,
+    #[proto(repr = "VarInt")]
+    pub state_id: i32,
+    pub slots: Vec<Option<ItemStack>>,
+    pub carried_item: Option<ItemStack>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x14, ClientBound, Play)]
+pub struct SetContainerProperty {
+    pub window_id: u8,
+    pub property: i16,
+    pub value: i16,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x15, ClientBound, Play)]
+pub struct SetContainerSlot {
+    pub window_id: i8,
+    #[proto(repr = "VarInt")]
+    pub state_id: i32,
+    pub slot: i16,
+    pub item: Option<ItemStack>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x16, ClientBound, Play)]
+pub struct SetCooldown {
+    #[proto(repr = "VarInt")]
+    pub item_id: i32,
+    #[proto(repr = "VarInt")]
+    pub cooldown_ticks: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x17, ClientBound, Play)]
+pub struct ChatSuggestions {
+    pub action: ChatSuggestionsAction,
+    pub entries: Vec<String>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x18, ClientBound, Play)]
+pub struct ClientBoundPluginMessage {
+    pub channel: Identifier,
+    #[proto(repr = "RemainingBytes")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x19, ClientBound, Play)]
+pub struct DamageEvent {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "VarInt")]
+    pub source_type_id: i32,
+    /// The id of the entity causing the damage plus one, or 0 if there is none.
+    #[proto(repr = "VarInt")]
+    pub source_cause_id: i32,
+    /// The id of the entity directly dealing the damage plus one, or 0 if there is none.
+    #[proto(repr = "VarInt")]
+    pub source_direct_id: i32,
+    pub source_position: Option<DamageSourcePosition>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1A, ClientBound, Play)]
+pub struct DeleteMessage {
+    pub signature: MessageSignatureRef,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1B, ClientBound, Play)]
+pub struct Disconnect {
+    pub reason: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1C, ClientBound, Play)]
+pub struct DisguisedChatMessage {
+    pub message: TextComponent,
+    #[proto(repr = "VarInt")]
+    pub chat_type: i32,
+    pub sender_name: TextComponent,
+    pub target_name: Option<TextComponent>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1D, ClientBound, Play)]
+pub struct EntityEvent {
+    pub entity_id: i32,
+    pub status: i8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1E, ClientBound, Play)]
+pub struct Explosion {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub strength: f32,
+    pub records: Vec<ExplosionRecord>,
+    pub player_motion_x: f32,
+    pub player_motion_y: f32,
+    pub player_motion_z: f32,
+    pub block_interaction: ExplosionBlockInteraction,
+    pub small_explosion_particle: Particle,
+    pub large_explosion_particle: Particle,
+    pub sound: SoundEvent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1F, ClientBound, Play)]
+pub struct UnloadChunk {
+    pub z: i32,
+    pub x: i32,
+}
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x20, ClientBound, Play)]
@@ -181,6 +429,45 @@ pub struct ServerGameEvent {
     pub event: GameEvent,
     pub value: f32,
 }
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x21, ClientBound, Play)]
+pub struct OpenHorseScreen {
+    pub window_id: u8,
+    #[proto(repr = "VarInt")]
+    pub slot_count: i32,
+    pub entity_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x22, ClientBound, Play)]
+pub struct HurtAnimation {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub yaw: f32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x23, ClientBound, Play)]
+pub struct InitializeWorldBorder {
+    pub x: f64,
+    pub z: f64,
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    #[proto(repr = "VarLong")]
+    pub speed: i64,
+    #[proto(repr = "VarInt")]
+    pub portal_teleport_boundary: i32,
+    #[proto(repr = "VarInt")]
+    pub warning_blocks: i32,
+    #[proto(repr = "VarInt")]
+    pub warning_time: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x24, ClientBound, Play)]
+pub struct KeepAlive {
+    pub id: i64,
+}
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x25, ClientBound, Play)]
@@ -193,6 +480,89 @@ pub struct ChunkDataAndLight {
     pub block_entities: Vec<BlockEntityRecord>,
     pub chunk_lighting: ChunkLighting,
 }
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x26, ClientBound, Play)]
+pub struct WorldEvent {
+    pub event: i32,
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub data: i32,
+    pub disable_relative_volume: bool,
+}
+
+#[derive(Packet, Debug, Clone)]
+#[packet(0x27, ClientBound, Play)]
+pub struct SpawnParticle {
+    pub long_distance: bool,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub offset_x: f32,
+    pub offset_y: f32,
+    pub offset_z: f32,
+    pub max_speed: f32,
+    pub count: i32,
+    pub particle: Particle,
+}
+
+// the particle id is sent first and its data last
+impl ProtoEncode for SpawnParticle {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        VarInt::encode(&data.particle.id, writer)?;
+        bool::encode(&data.long_distance, writer)?;
+        for val in [&data.x, &data.y, &data.z] {
+            f64::encode(val, writer)?;
+        }
+        for val in [
+            &data.offset_x,
+            &data.offset_y,
+            &data.offset_z,
+            &data.max_speed,
+        ] {
+            f32::encode(val, writer)?;
+        }
+        i32::encode(&data.count, writer)?;
+        data.particle.data.encode(writer)
+    }
+}
+
+impl ProtoDecode for SpawnParticle {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let particle_id = VarInt::decode(reader, alloc_tracker)?;
+        Ok(Self {
+            long_distance: bool::decode(reader, alloc_tracker)?,
+            x: f64::decode(reader, alloc_tracker)?,
+            y: f64::decode(reader, alloc_tracker)?,
+            z: f64::decode(reader, alloc_tracker)?,
+            offset_x: f32::decode(reader, alloc_tracker)?,
+            offset_y: f32::decode(reader, alloc_tracker)?,
+            offset_z: f32::decode(reader, alloc_tracker)?,
+            max_speed: f32::decode(reader, alloc_tracker)?,
+            count: i32::decode(reader, alloc_tracker)?,
+            particle: Particle {
+                id: particle_id,
+                data: ParticleData::decode(particle_id, reader, alloc_tracker)?,
+            },
+        })
+    }
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x28, ClientBound, Play)]
+pub struct UpdateLight {
+    #[proto(repr = "VarInt")]
+    pub x: i32,
+    #[proto(repr = "VarInt")]
+    pub z: i32,
+    pub chunk_lighting: ChunkLighting,
+}
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x29, ClientBound, Play)]
@@ -220,45 +590,1928 @@ pub struct GameJoin {
     #[proto(repr = "VarInt")]
     pub portal_cooldown: i32,
 }
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2A, ClientBound, Play)]
+pub struct MapData {
+    #[proto(repr = "VarInt")]
+    pub map_id: i32,
+    pub scale: i8,
+    pub locked: bool,
+    pub icons: Option<Vec<MapIcon>>,
+    #[proto(repr = "OptionalMapPatch")]
+    pub patch: Option<MapPatch>,
+}
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
-#[packet(0x36, ClientBound, Play)]
-pub struct PlayerAbilities {
-    pub flags: u8,
-    pub fly_speed: f32,
-    pub fov_modifier: f32,
+#[packet(0x2B, ClientBound, Play)]
+pub struct MerchantOffers {
+    #[proto(repr = "VarInt")]
+    pub window_id: i32,
+    pub trades: Vec<Trade>,
+    #[proto(repr = "VarInt")]
+    pub villager_level: i32,
+    #[proto(repr = "VarInt")]
+    pub experience: i32,
+    pub is_regular_villager: bool,
+    pub can_restock: bool,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
-#[packet(0x3E, ClientBound, Play)]
-pub struct SyncPlayerPosition {
+#[packet(0x2C, ClientBound, Play)]
+pub struct UpdateEntityPosition {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2D, ClientBound, Play)]
+pub struct UpdateEntityPositionAndRotation {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    #[proto(repr = "Angle")]
+    pub yaw: f32,
+    #[proto(repr = "Angle")]
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2E, ClientBound, Play)]
+pub struct UpdateEntityRotation {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "Angle")]
+    pub yaw: f32,
+    #[proto(repr = "Angle")]
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2F, ClientBound, Play)]
+pub struct MoveVehicle {
     pub x: f64,
     pub y: f64,
     pub z: f64,
     pub yaw: f32,
     pub pitch: f32,
-    pub flags: u8,
-    #[proto(repr = "VarInt")]
-    pub teleport_id: i32,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
-#[packet(0x52, ClientBound, Play)]
-pub struct SetCenterChunk {
+#[packet(0x30, ClientBound, Play)]
+pub struct OpenBook {
+    pub hand: Hand,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x31, ClientBound, Play)]
+pub struct OpenScreen {
     #[proto(repr = "VarInt")]
-    pub x: i32,
+    pub window_id: i32,
     #[proto(repr = "VarInt")]
-    pub z: i32,
+    pub window_type: i32,
+    pub title: TextComponent,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
-#[packet(0x54, ClientBound, Play)]
-pub struct DefaultSpawnPosition {
+#[packet(0x32, ClientBound, Play)]
+pub struct OpenSignEditor {
     #[proto(repr = "Position")]
     pub location: (i32, i32, i32),
-    pub angle: f32,
+    pub front_text: bool,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
-#[packet(0x67, ClientBound, Play)]
-pub struct StartConfiguration;
+#[packet(0x33, ClientBound, Play)]
+pub struct Ping {
+    pub id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x34, ClientBound, Play)]
+pub struct PingResponse {
+    pub payload: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x35, ClientBound, Play)]
+pub struct PlaceGhostRecipe {
+    pub window_id: i8,
+    pub recipe: Identifier,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x36, ClientBound, Play)]
+pub struct PlayerAbilities {
+    pub flags: u8,
+    pub fly_speed: f32,
+    pub fov_modifier: f32,
+}
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x37, ClientBound, Play)]
+pub struct PlayerChatMessage {
+    pub sender: Uuid,
+    #[proto(repr = "VarInt")]
+    pub index: i32,
+    #[proto(repr = "Option<FixedBytes<256>>")]
+    pub signature: Option<[u8; 256]>,
+    #[proto(max_len = 256)]
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    #[proto(max_len = 20)]
+    pub previous_messages: Vec<MessageSignatureRef>,
+    pub unsigned_content: Option<TextComponent>,
+    pub filter: FilterType,
+    #[proto(repr = "VarInt")]
+    pub chat_type: i32,
+    pub sender_name: TextComponent,
+    pub target_name: Option<TextComponent>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x38, ClientBound, Play)]
+pub struct EndCombat {
+    #[proto(repr = "VarInt")]
+    pub duration: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x39, ClientBound, Play)]
+pub struct EnterCombat;
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x3A, ClientBound, Play)]
+pub struct CombatDeath {
+    #[proto(repr = "VarInt")]
+    pub player_id: i32,
+    pub message: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x3B, ClientBound, Play)]
+pub struct PlayerInfoRemove {
+    pub players: Vec<Uuid>,
+}
+
+#[derive(Packet, Debug, Clone)]
+#[packet(0x3C, ClientBound, Play)]
+pub struct PlayerInfoUpdate {
+    pub actions: u8,
+    pub players: Vec<PlayerInfoEntry>,
+}
+
+impl PlayerInfoUpdate {
+    pub const ADD_PLAYER: u8 = 0x01;
+    pub const INITIALIZE_CHAT: u8 = 0x02;
+    pub const UPDATE_DISPLAY_NAME: u8 = 0x20;
+    pub const UPDATE_GAME_MODE: u8 = 0x04;
+    pub const UPDATE_LATENCY: u8 = 0x10;
+    pub const UPDATE_LISTED: u8 = 0x08;
+}
+
+impl ProtoEncode for PlayerInfoUpdate {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        let actions = data.actions;
+        u8::encode(&actions, writer)?;
+        let len = i32::try_from(data.players.len())
+            .map_err(|_| ProtoEncodeErr::InvalidSeqLen(data.players.len()))?;
+        VarInt::encode(&len, writer)?;
+        for player in data.players.iter() {
+            Uuid::encode(&player.uuid, writer)?;
+            if actions & PlayerInfoUpdate::ADD_PLAYER != 0 {
+                if player.name.len() > 16 {
+                    return Err(ProtoEncodeErr::SeqTooLong(player.name.len(), 16));
+                }
+                String::encode(&player.name, writer)?;
+                Vec::<LoginProperty>::encode(&player.properties, writer)?;
+            }
+            if actions & PlayerInfoUpdate::INITIALIZE_CHAT != 0 {
+                Option::<PlayerChatSession>::encode(&player.chat_session, writer)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_GAME_MODE != 0 {
+                VarInt::encode(&player.game_mode, writer)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_LISTED != 0 {
+                bool::encode(&player.listed, writer)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_LATENCY != 0 {
+                VarInt::encode(&player.latency, writer)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_DISPLAY_NAME != 0 {
+                Option::<TextComponent>::encode(&player.display_name, writer)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProtoDecode for PlayerInfoUpdate {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let actions = u8::decode(reader, alloc_tracker)?;
+        let len = VarInt::decode(reader, alloc_tracker)?;
+        let len = usize::try_from(len).map_err(|_| ProtoDecodeErr::InvalidSeqLen(len))?;
+        if len > MAX_VEC_LEN {
+            return Err(ProtoDecodeErr::SeqTooLong(len, MAX_VEC_LEN));
+        }
+        alloc_tracker.alloc(len * std::mem::size_of::<PlayerInfoEntry>())?;
+        let mut players = Vec::with_capacity(len);
+        for _ in 0..len {
+            let mut player = PlayerInfoEntry {
+                uuid: Uuid::decode(reader, alloc_tracker)?,
+                ..Default::default()
+            };
+            if actions & PlayerInfoUpdate::ADD_PLAYER != 0 {
+                player.name = String::decode(reader, alloc_tracker)?;
+                if player.name.len() > 16 {
+                    return Err(ProtoDecodeErr::SeqTooLong(player.name.len(), 16));
+                }
+                player.properties = Vec::<LoginProperty>::decode(reader, alloc_tracker)?;
+            }
+            if actions & PlayerInfoUpdate::INITIALIZE_CHAT != 0 {
+                player.chat_session = Option::<PlayerChatSession>::decode(reader, alloc_tracker)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_GAME_MODE != 0 {
+                player.game_mode = VarInt::decode(reader, alloc_tracker)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_LISTED != 0 {
+                player.listed = bool::decode(reader, alloc_tracker)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_LATENCY != 0 {
+                player.latency = VarInt::decode(reader, alloc_tracker)?;
+            }
+            if actions & PlayerInfoUpdate::UPDATE_DISPLAY_NAME != 0 {
+                player.display_name = Option::<TextComponent>::decode(reader, alloc_tracker)?;
+            }
+            players.push(player);
+        }
+        Ok(Self { actions, players })
+    }
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x3D, ClientBound, Play)]
+pub struct LookAt {
+    pub anchor: LookAtAnchor,
+    pub target_x: f64,
+    pub target_y: f64,
+    pub target_z: f64,
+    pub entity: Option<LookAtEntity>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x3E, ClientBound, Play)]
+pub struct SyncPlayerPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: u8,
+    #[proto(repr = "VarInt")]
+    pub teleport_id: i32,
+}
+#[derive(Packet, Debug, Clone)]
+#[packet(0x3F, ClientBound, Play)]
+pub struct UpdateRecipeBook {
+    pub action: RecipeBookAction,
+    pub settings: RecipeBookSettings,
+    pub recipe_ids: Vec<Identifier>,
+    /// Recipes to highlight as new, only sent with [`RecipeBookAction::Init`].
+    pub highlighted_recipe_ids: Vec<Identifier>,
+}
+
+impl ProtoEncode for UpdateRecipeBook {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        RecipeBookAction::encode(&data.action, writer)?;
+        RecipeBookSettings::encode(&data.settings, writer)?;
+        Vec::<Identifier>::encode(&data.recipe_ids, writer)?;
+        if let RecipeBookAction::Init = data.action {
+            Vec::<Identifier>::encode(&data.highlighted_recipe_ids, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProtoDecode for UpdateRecipeBook {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let action = RecipeBookAction::decode(reader, alloc_tracker)?;
+        let settings = RecipeBookSettings::decode(reader, alloc_tracker)?;
+        let recipe_ids = Vec::<Identifier>::decode(reader, alloc_tracker)?;
+        let highlighted_recipe_ids = if let RecipeBookAction::Init = action {
+            Vec::<Identifier>::decode(reader, alloc_tracker)?
+        } else {
+            Vec::new()
+        };
+        Ok(Self {
+            action,
+            settings,
+            recipe_ids,
+            highlighted_recipe_ids,
+        })
+    }
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x40, ClientBound, Play)]
+pub struct RemoveEntities {
+    #[proto(repr = "Vec<VarInt>")]
+    pub entity_ids: Vec<i32>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x41, ClientBound, Play)]
+pub struct RemoveEntityEffect {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "VarInt")]
+    pub effect_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x42, ClientBound, Play)]
+pub struct ResetScore {
+    #[proto(max_len = 32767)]
+    pub entity_name: String,
+    pub objective_name: Option<String>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x43, ClientBound, Play)]
+pub struct RemoveResourcePack {
+    pub uuid: Option<Uuid>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x44, ClientBound, Play)]
+pub struct AddResourcePack {
+    pub uuid: Uuid,
+    #[proto(max_len = 32767)]
+    pub url: String,
+    #[proto(max_len = 40)]
+    pub hash: String,
+    pub forced: bool,
+    pub prompt_message: Option<TextComponent>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x45, ClientBound, Play)]
+pub struct Respawn {
+    pub dimension_type: Identifier,
+    pub dimension_name: Identifier,
+    pub hashed_seed: i64,
+    pub game_mode: GameMode,
+    pub previous_game_mode: LastGameMode,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    #[proto(repr = "VarInt")]
+    pub portal_cooldown: i32,
+    pub data_kept: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x46, ClientBound, Play)]
+pub struct SetHeadRotation {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "Angle")]
+    pub head_yaw: f32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x47, ClientBound, Play)]
+pub struct UpdateSectionBlocks {
+    #[proto(repr = "SectionPosition")]
+    pub section: (i32, i32, i32),
+    pub blocks: Vec<SectionBlock>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x48, ClientBound, Play)]
+pub struct SelectAdvancementsTab {
+    pub tab_id: Option<Identifier>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x49, ClientBound, Play)]
+pub struct ServerData {
+    pub motd: TextComponent,
+    pub icon: Option<Vec<u8>>,
+    pub enforces_secure_chat: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4A, ClientBound, Play)]
+pub struct SetActionBarText {
+    pub text: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4B, ClientBound, Play)]
+pub struct SetBorderCenter {
+    pub x: f64,
+    pub z: f64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4C, ClientBound, Play)]
+pub struct SetBorderLerpSize {
+    pub old_diameter: f64,
+    pub new_diameter: f64,
+    #[proto(repr = "VarLong")]
+    pub speed: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4D, ClientBound, Play)]
+pub struct SetBorderSize {
+    pub diameter: f64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4E, ClientBound, Play)]
+pub struct SetBorderWarningDelay {
+    #[proto(repr = "VarInt")]
+    pub warning_time: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4F, ClientBound, Play)]
+pub struct SetBorderWarningDistance {
+    #[proto(repr = "VarInt")]
+    pub warning_blocks: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x50, ClientBound, Play)]
+pub struct SetCamera {
+    #[proto(repr = "VarInt")]
+    pub camera_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x51, ClientBound, Play)]
+pub struct SetHeldItem {
+    pub slot: i8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x52, ClientBound, Play)]
+pub struct SetCenterChunk {
+    #[proto(repr = "VarInt")]
+    pub x: i32,
+    #[proto(repr = "VarInt")]
+    pub z: i32,
+}
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x53, ClientBound, Play)]
+pub struct SetRenderDistance {
+    #[proto(repr = "VarInt")]
+    pub view_distance: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x54, ClientBound, Play)]
+pub struct DefaultSpawnPosition {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub angle: f32,
+}
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x55, ClientBound, Play)]
+pub struct DisplayObjective {
+    #[proto(repr = "VarInt")]
+    pub position: i32,
+    #[proto(max_len = 32767)]
+    pub score_name: String,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x56, ClientBound, Play)]
+pub struct SetEntityMetadata {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "EntityMetadata")]
+    pub metadata: Vec<MetadataEntry>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x57, ClientBound, Play)]
+pub struct LinkEntities {
+    pub attached_entity_id: i32,
+    pub holding_entity_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x58, ClientBound, Play)]
+pub struct SetEntityVelocity {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub velocity_x: i16,
+    pub velocity_y: i16,
+    pub velocity_z: i16,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x59, ClientBound, Play)]
+pub struct SetEquipment {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "Equipment")]
+    pub equipment: Vec<EquipmentEntry>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5A, ClientBound, Play)]
+pub struct SetExperience {
+    pub experience_bar: f32,
+    #[proto(repr = "VarInt")]
+    pub level: i32,
+    #[proto(repr = "VarInt")]
+    pub total_experience: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5B, ClientBound, Play)]
+pub struct SetHealth {
+    pub health: f32,
+    #[proto(repr = "VarInt")]
+    pub food: i32,
+    pub food_saturation: f32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5C, ClientBound, Play)]
+pub struct UpdateObjectives {
+    #[proto(max_len = 32767)]
+    pub objective_name: String,
+    pub action: ObjectiveAction,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5D, ClientBound, Play)]
+pub struct SetPassengers {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "Vec<VarInt>")]
+    pub passengers: Vec<i32>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5E, ClientBound, Play)]
+pub struct UpdateTeams {
+    #[proto(max_len = 32767)]
+    pub team_name: String,
+    pub action: TeamAction,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x5F, ClientBound, Play)]
+pub struct UpdateScore {
+    #[proto(max_len = 32767)]
+    pub entity_name: String,
+    #[proto(max_len = 32767)]
+    pub objective_name: String,
+    #[proto(repr = "VarInt")]
+    pub value: i32,
+    pub display_name: Option<TextComponent>,
+    pub number_format: Option<NumberFormat>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x60, ClientBound, Play)]
+pub struct SetSimulationDistance {
+    #[proto(repr = "VarInt")]
+    pub simulation_distance: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x61, ClientBound, Play)]
+pub struct SetSubtitleText {
+    pub text: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x62, ClientBound, Play)]
+pub struct UpdateTime {
+    pub world_age: i64,
+    pub time_of_day: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x63, ClientBound, Play)]
+pub struct SetTitleText {
+    pub text: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x64, ClientBound, Play)]
+pub struct SetTitleAnimationTimes {
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x65, ClientBound, Play)]
+pub struct EntitySoundEffect {
+    pub sound: SoundRef,
+    pub category: SoundCategory,
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub volume: f32,
+    pub pitch: f32,
+    pub seed: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x66, ClientBound, Play)]
+pub struct SoundEffect {
+    pub sound: SoundRef,
+    pub category: SoundCategory,
+    /// Fixed-point coordinates, multiplied by 8.
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+    pub volume: f32,
+    pub pitch: f32,
+    pub seed: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x67, ClientBound, Play)]
+pub struct StartConfiguration;
+#[derive(Packet, Debug, Clone)]
+#[packet(0x68, ClientBound, Play)]
+pub struct StopSound {
+    pub category: Option<SoundCategory>,
+    pub sound: Option<Identifier>,
+}
+
+impl ProtoEncode for StopSound {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        let flags = u8::from(data.category.is_some()) | (u8::from(data.sound.is_some()) << 1);
+        u8::encode(&flags, writer)?;
+        if let Some(category) = data.category.as_ref() {
+            SoundCategory::encode(category, writer)?;
+        }
+        if let Some(sound) = data.sound.as_ref() {
+            Identifier::encode(sound, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProtoDecode for StopSound {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let flags = u8::decode(reader, alloc_tracker)?;
+        let category = if flags & 0x01 != 0 {
+            Some(SoundCategory::decode(reader, alloc_tracker)?)
+        } else {
+            None
+        };
+        let sound = if flags & 0x02 != 0 {
+            Some(Identifier::decode(reader, alloc_tracker)?)
+        } else {
+            None
+        };
+        Ok(Self { category, sound })
+    }
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x69, ClientBound, Play)]
+pub struct SystemChatMessage {
+    pub content: TextComponent,
+    pub overlay: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6A, ClientBound, Play)]
+pub struct SetTabListHeaderAndFooter {
+    pub header: TextComponent,
+    pub footer: TextComponent,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6B, ClientBound, Play)]
+pub struct TagQueryResponse {
+    #[proto(repr = "VarInt")]
+    pub transaction_id: i32,
+    #[proto(repr = "OptionalTagRoot")]
+    pub nbt: Option<nbt::Tag>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6C, ClientBound, Play)]
+pub struct PickupItem {
+    #[proto(repr = "VarInt")]
+    pub collected_entity_id: i32,
+    #[proto(repr = "VarInt")]
+    pub collector_entity_id: i32,
+    #[proto(repr = "VarInt")]
+    pub pickup_item_count: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6D, ClientBound, Play)]
+pub struct TeleportEntity {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    #[proto(repr = "Angle")]
+    pub yaw: f32,
+    #[proto(repr = "Angle")]
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6E, ClientBound, Play)]
+pub struct SetTickingState {
+    pub tick_rate: f32,
+    pub is_frozen: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6F, ClientBound, Play)]
+pub struct StepTick {
+    #[proto(repr = "VarInt")]
+    pub tick_steps: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x70, ClientBound, Play)]
+pub struct UpdateAdvancements {
+    pub reset: bool,
+    pub advancements: Vec<AdvancementMapping>,
+    pub removed: Vec<Identifier>,
+    pub progress: Vec<AdvancementProgress>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x71, ClientBound, Play)]
+pub struct UpdateAttributes {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub properties: Vec<AttributeProperty>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x72, ClientBound, Play)]
+pub struct EntityEffect {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(repr = "VarInt")]
+    pub effect_id: i32,
+    pub amplifier: i8,
+    #[proto(repr = "VarInt")]
+    pub duration: i32,
+    pub flags: u8,
+    #[proto(repr = "Option<nbt::TagRoot>")]
+    pub factor_codec: Option<nbt::Tag>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x73, ClientBound, Play)]
+pub struct UpdateRecipes {
+    pub recipes: Vec<Recipe>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x74, ClientBound, Play)]
+pub struct PlayUpdateTags {
+    pub tags: Vec<RegistryTag>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serverx_common::{collections::bit_vec::BitVec, identifier};
+    use serverx_macros::identifier;
+    use serverx_nbt::{NamedTag, Tag};
+    use uuid::Uuid;
+
+    use crate::{
+        decode::BasicAllocTracker,
+        packet::{
+            ConnectionState::Play, Packet, PacketDecoder, PacketDirection::ClientBound,
+            PacketEncoder,
+        },
+        v765::{clientbound::*, PacketDecoderImpl, PacketEncoderImpl},
+    };
+
+    const UUID: u128 = 0x0102030405060708090a0b0c0d0e0f10;
+    const UUID_BYTES: [u8; 16] = [
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x10,
+    ];
+
+    fn encode<P: Packet>(packet: &P) -> Vec<u8> {
+        let mut cursor = Cursor::new(Vec::new());
+        PacketEncoderImpl::encode_packet(packet, packet.id(), ClientBound, Play, &mut cursor)
+            .unwrap();
+        cursor.into_inner()
+    }
+
+    fn decode<P: Packet + 'static>(id: i32, buf: &[u8]) -> P {
+        let mut reader = Cursor::new(buf);
+        let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+        let packet = PacketDecoderImpl::decode_packet(
+            id,
+            ClientBound,
+            Play,
+            &mut reader,
+            &mut alloc_tracker,
+        )
+        .unwrap();
+        assert_eq!(reader.position() as usize, buf.len());
+        *packet.into_any().downcast::<P>().unwrap()
+    }
+
+    fn assert_fixture<P: Packet + 'static>(packet: P, fixture: &[u8]) {
+        assert_eq!(encode(&packet), fixture, "{:?}", packet);
+        let decoded = decode::<P>(packet.id(), fixture);
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", packet));
+    }
+
+    fn round_trip<P: Packet + 'static>(packet: P) {
+        let buf = encode(&packet);
+        let decoded = decode::<P>(packet.id(), buf.as_slice());
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", packet));
+    }
+
+    fn text(value: &str) -> TextComponent {
+        TextComponent::text(value)
+    }
+
+    fn stone(count: i8) -> Option<ItemStack> {
+        Some(ItemStack {
+            item_id: 1,
+            count,
+            nbt: None,
+        })
+    }
+
+    #[test]
+    fn test_bundle_delimiter() {
+        assert_fixture(BundleDelimiter, &[]);
+    }
+
+    #[test]
+    fn test_spawn_entity() {
+        let mut fixture = vec![0x01];
+        fixture.extend_from_slice(&UUID_BYTES);
+        fixture.extend_from_slice(&[
+            0x7c, // entity type
+            0x3f, 0xf8, 0, 0, 0, 0, 0, 0, // x
+            0x40, 0x50, 0, 0, 0, 0, 0, 0, // y
+            0xc0, 0x04, 0, 0, 0, 0, 0, 0, // z
+            0x00, 0x40, 0x80, // pitch, yaw, head yaw
+            0x00, // data
+            0x00, 0x00, 0x01, 0x90, 0xfe, 0x70, // velocity
+        ]);
+        assert_fixture(
+            SpawnEntity {
+                entity_id: 1,
+                entity_uuid: Uuid::from_u128(UUID),
+                entity_type: 124,
+                x: 1.5,
+                y: 64.0,
+                z: -2.5,
+                pitch: 0.0,
+                yaw: 90.0,
+                head_yaw: 180.0,
+                data: 0,
+                velocity_x: 0,
+                velocity_y: 400,
+                velocity_z: -400,
+            },
+            fixture.as_slice(),
+        );
+    }
+
+    #[test]
+    fn test_commands() {
+        let node = |kind: CommandNodeKind, executable: bool, children: Vec<i32>| CommandNode {
+            kind,
+            executable,
+            children,
+            redirect: None,
+        };
+        let argument = |name: &str, parser: CommandParser| CommandNodeKind::Argument {
+            name: name.to_string(),
+            parser,
+            suggestions: None,
+        };
+        let literal = |name: &str| CommandNodeKind::Literal {
+            name: name.to_string(),
+        };
+        let mut fixture = vec![
+            0x07, // node count
+            0x00, 0x03, 0x01, 0x04, 0x05, // root
+            0x01, 0x01, 0x02, 0x04, b'k', b'i', b'c', b'k', // literal
+            0x16, 0x01, 0x03, 0x07, b't', b'a', b'r', b'g', b'e', b't', b's', 0x06, 0x02, 0x14,
+        ];
+        fixture.extend_from_slice(b"minecraft:ask_server");
+        fixture.extend_from_slice(&[
+            0x06, 0x00, 0x06, b'r', b'e', b'a', b's', b'o', b'n', 0x05, 0x02, // greedy string
+            0x01, 0x01, 0x06, 0x04, b't', b'i', b'm', b'e', // literal
+            0x09, 0x00, 0x04, 0x01, b't', // redirect
+            0x06, 0x00, 0x05, b't', b'i', b'c', b'k', b's', 0x03, 0x01, 0x00, 0x00, 0x00, 0x00,
+            0x00, // root index
+        ]);
+        assert_fixture(
+            Commands {
+                nodes: vec![
+                    node(CommandNodeKind::Root, false, vec![1, 4, 5]),
+                    node(literal("kick"), false, vec![2]),
+                    node(
+                        CommandNodeKind::Argument {
+                            name: "targets".to_string(),
+                            parser: CommandParser::Entity {
+                                single: false,
+                                players_only: true,
+                            },
+                            suggestions: Some(identifier!("ask_server")),
+                        },
+                        true,
+                        vec![3],
+                    ),
+                    node(
+                        argument("reason", CommandParser::String(StringKind::GreedyPhrase)),
+                        true,
+                        Vec::new(),
+                    ),
+                    node(literal("time"), false, vec![6]),
+                    CommandNode {
+                        kind: literal("t"),
+                        executable: false,
+                        children: Vec::new(),
+                        redirect: Some(4),
+                    },
+                    node(
+                        argument("ticks", CommandParser::Integer {
+                            min: Some(0),
+                            max: None,
+                        }),
+                        true,
+                        Vec::new(),
+                    ),
+                ],
+                root_index: 0,
+            },
+            fixture.as_slice(),
+        );
+    }
+
+    #[test]
+    fn test_entity_movement() {
+        assert_fixture(
+            UpdateEntityPositionAndRotation {
+                entity_id: 300,
+                delta_x: 4096,
+                delta_y: -1,
+                delta_z: 0,
+                yaw: 45.0,
+                pitch: 22.5,
+                on_ground: true,
+            },
+            &[
+                0xac, 0x02, 0x10, 0x00, 0xff, 0xff, 0x00, 0x00, 0x20, 0x10, 0x01,
+            ],
+        );
+        assert_fixture(
+            UpdateEntityPosition {
+                entity_id: 1,
+                delta_x: -2,
+                delta_y: 0,
+                delta_z: 1,
+                on_ground: false,
+            },
+            &[0x01, 0xff, 0xfe, 0x00, 0x00, 0x00, 0x01, 0x00],
+        );
+        assert_fixture(
+            UpdateEntityRotation {
+                entity_id: 1,
+                yaw: 270.0,
+                pitch: 0.0,
+                on_ground: true,
+            },
+            &[0x01, 0xc0, 0x00, 0x01],
+        );
+        assert_fixture(
+            SetHeadRotation {
+                entity_id: 2,
+                head_yaw: 90.0,
+            },
+            &[0x02, 0x40],
+        );
+        assert_fixture(
+            RemoveEntities {
+                entity_ids: vec![1, 128],
+            },
+            &[0x02, 0x01, 0x80, 0x01],
+        );
+    }
+
+    #[test]
+    fn test_entity_metadata() {
+        assert_fixture(
+            SetEntityMetadata {
+                entity_id: 5,
+                metadata: vec![
+                    MetadataEntry {
+                        index: 0,
+                        value: MetadataValue::Byte(0x02),
+                    },
+                    MetadataEntry {
+                        index: 6,
+                        value: MetadataValue::Pose(EntityPose::Sneaking),
+                    },
+                    MetadataEntry {
+                        index: 9,
+                        value: MetadataValue::Float(20.0),
+                    },
+                    MetadataEntry {
+                        index: 2,
+                        value: MetadataValue::OptionalTextComponent(None),
+                    },
+                ],
+            },
+            &[
+                0x05, // entity id
+                0x00, 0x00, 0x02, // byte
+                0x06, 0x14, 0x05, // pose
+                0x09, 0x03, 0x41, 0xa0, 0x00, 0x00, // float
+                0x02, 0x06, 0x00, // optional text component
+                0xff,
+            ],
+        );
+        round_trip(SetEntityMetadata {
+            entity_id: 6,
+            metadata: vec![
+                MetadataEntry {
+                    index: 8,
+                    value: MetadataValue::Slot(stone(3)),
+                },
+                MetadataEntry {
+                    index: 10,
+                    value: MetadataValue::OptionalVarInt(Some(0)),
+                },
+                MetadataEntry {
+                    index: 11,
+                    value: MetadataValue::Particle(Particle {
+                        id: Particle::DUST,
+                        data: ParticleData::Dust {
+                            red: 1.0,
+                            green: 0.0,
+                            blue: 0.5,
+                            scale: 2.0,
+                        },
+                    }),
+                },
+                MetadataEntry {
+                    index: 12,
+                    value: MetadataValue::VillagerData {
+                        villager_type: 2,
+                        profession: 5,
+                        level: 1,
+                    },
+                },
+                MetadataEntry {
+                    index: 13,
+                    value: MetadataValue::OptionalPosition(Some((1, -2, 3))),
+                },
+                MetadataEntry {
+                    index: 14,
+                    value: MetadataValue::Quaternion(0.0, 0.0, 0.0, 1.0),
+                },
+            ],
+        });
+    }
+
+    #[test]
+    fn test_block_update() {
+        assert_fixture(
+            BlockUpdate {
+                location: (-1, 64, 2),
+                block_state: 1,
+            },
+            &[0xff, 0xff, 0xff, 0xc0, 0x00, 0x00, 0x20, 0x40, 0x01],
+        );
+        assert_fixture(AcknowledgeBlockChange { sequence: 12 }, &[0x0c]);
+    }
+
+    #[test]
+    fn test_section_blocks_update() {
+        assert_fixture(
+            UpdateSectionBlocks {
+                section: (1, -1, -2),
+                blocks: vec![SectionBlock {
+                    block_state: 9,
+                    x: 1,
+                    y: 2,
+                    z: 3,
+                }],
+            },
+            &[
+                0x00, 0x00, 0x07, 0xff, 0xff, 0xef, 0xff, 0xff, // section position
+                0x01, 0xb2, 0xa2, 0x02,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_system_chat() {
+        assert_fixture(
+            SystemChatMessage {
+                content: text("hi"),
+                overlay: false,
+            },
+            &[0x08, 0x00, 0x02, b'h', b'i', 0x00],
+        );
+    }
+
+    #[test]
+    fn test_player_info() {
+        let mut fixture = vec![0x19, 0x01];
+        fixture.extend_from_slice(&UUID_BYTES);
+        fixture.extend_from_slice(&[0x05, b'N', b'o', b't', b'c', b'h', 0x00, 0x01, 0x96, 0x01]);
+        assert_fixture(
+            PlayerInfoUpdate {
+                actions: PlayerInfoUpdate::ADD_PLAYER
+                    | PlayerInfoUpdate::UPDATE_LISTED
+                    | PlayerInfoUpdate::UPDATE_LATENCY,
+                players: vec![PlayerInfoEntry {
+                    uuid: Uuid::from_u128(UUID),
+                    name: "Notch".to_string(),
+                    listed: true,
+                    latency: 150,
+                    ..Default::default()
+                }],
+            },
+            fixture.as_slice(),
+        );
+        round_trip(PlayerInfoUpdate {
+            actions: 0x3f,
+            players: vec![PlayerInfoEntry {
+                uuid: Uuid::from_u128(UUID),
+                name: "Notch".to_string(),
+                properties: vec![LoginProperty {
+                    name: "textures".to_string(),
+                    value: "e30=".to_string(),
+                    signature: None,
+                }],
+                chat_session: Some(PlayerChatSession {
+                    session_id: Uuid::from_u128(2),
+                    expires_at: 1_700_000_000_000,
+                    public_key: vec![1, 2, 3],
+                    key_signature: vec![4, 5, 6],
+                }),
+                game_mode: 1,
+                listed: true,
+                latency: 20,
+                display_name: Some(text("Notch")),
+            }],
+        });
+        let mut fixture = vec![0x01];
+        fixture.extend_from_slice(&UUID_BYTES);
+        assert_fixture(
+            PlayerInfoRemove {
+                players: vec![Uuid::from_u128(UUID)],
+            },
+            fixture.as_slice(),
+        );
+    }
+
+    #[test]
+    fn test_container_packets() {
+        assert_fixture(
+            SetContainerSlot {
+                window_id: 0,
+                state_id: 3,
+                slot: 36,
+                item: stone(64),
+            },
+            &[0x00, 0x03, 0x00, 0x24, 0x01, 0x01, 0x40, 0x00],
+        );
+        assert_fixture(
+            SetContainerContent {
+                window_id: 1,
+                state_id: 2,
+                slots: vec![None, stone(1)],
+                carried_item: None,
+            },
+            &[0x01, 0x02, 0x02, 0x00, 0x01, 0x01, 0x01, 0x00, 0x00],
+        );
+        assert_fixture(CloseContainer { window_id: 1 }, &[0x01]);
+        assert_fixture(
+            SetContainerProperty {
+                window_id: 1,
+                property: 0,
+                value: 200,
+            },
+            &[0x01, 0x00, 0x00, 0x00, 0xc8],
+        );
+        assert_fixture(
+            OpenScreen {
+                window_id: 1,
+                window_type: 2,
+                title: text("Chest"),
+            },
+            &[0x01, 0x02, 0x08, 0x00, 0x05, b'C', b'h', b'e', b's', b't'],
+        );
+        assert_fixture(
+            SetEquipment {
+                entity_id: 1,
+                equipment: vec![
+                    EquipmentEntry {
+                        slot: EquipmentSlot::MainHand,
+                        item: stone(1),
+                    },
+                    EquipmentEntry {
+                        slot: EquipmentSlot::Helmet,
+                        item: None,
+                    },
+                ],
+            },
+            &[0x01, 0x80, 0x01, 0x01, 0x01, 0x00, 0x05, 0x00],
+        );
+        round_trip(MerchantOffers {
+            window_id: 3,
+            trades: vec![Trade {
+                input_item_1: stone(10),
+                output_item: stone(1),
+                input_item_2: None,
+                disabled: false,
+                uses: 0,
+                max_uses: 12,
+                experience: 2,
+                special_price: 0,
+                price_multiplier: 0.05,
+                demand: 0,
+            }],
+            villager_level: 1,
+            experience: 0,
+            is_regular_villager: true,
+            can_restock: true,
+        });
+    }
+
+    #[test]
+    fn test_chat_packets() {
+        round_trip(PlayerChatMessage {
+            sender: Uuid::from_u128(UUID),
+            index: 4,
+            signature: Some([0x11; 256]),
+            message: "hello".to_string(),
+            timestamp: 1_700_000_000_000,
+            salt: 42,
+            previous_messages: vec![
+                MessageSignatureRef::Id(0),
+                MessageSignatureRef::Signature(Box::new([0x22; 256])),
+            ],
+            unsigned_content: None,
+            filter: FilterType::PartiallyFiltered {
+                mask: BitVec::from_raw_parts(vec![0b101]),
+            },
+            chat_type: 0,
+            sender_name: text("Notch"),
+            target_name: None,
+        });
+        round_trip(DisguisedChatMessage {
+            message: text("hi"),
+            chat_type: 1,
+            sender_name: text("Server"),
+            target_name: Some(text("Notch")),
+        });
+        assert_fixture(
+            DeleteMessage {
+                signature: MessageSignatureRef::Id(2),
+            },
+            &[0x03],
+        );
+        round_trip(Disconnect {
+            reason: text("bye"),
+        });
+        round_trip(CommandSuggestionsResponse {
+            transaction_id: 1,
+            start: 5,
+            length: 2,
+            matches: vec![CommandSuggestion {
+                text: "creative".to_string(),
+                tooltip: Some(text("Creative mode")),
+            }],
+        });
+        round_trip(ChatSuggestions {
+            action: ChatSuggestionsAction::Add,
+            entries: vec!["Notch".to_string()],
+        });
+        round_trip(SetActionBarText { text: text("a") });
+        round_trip(SetTitleText { text: text("b") });
+        round_trip(SetSubtitleText { text: text("c") });
+        round_trip(SetTitleAnimationTimes {
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+        });
+        round_trip(ClearTitles { reset: true });
+        round_trip(SetTabListHeaderAndFooter {
+            header: text("header"),
+            footer: text("footer"),
+        });
+        round_trip(ServerData {
+            motd: text("A Minecraft Server"),
+            icon: Some(vec![0x89, b'P', b'N', b'G']),
+            enforces_secure_chat: false,
+        });
+    }
+
+    #[test]
+    fn test_sound_and_particle_packets() {
+        assert_fixture(
+            StopSound {
+                category: Some(SoundCategory::Player),
+                sound: None,
+            },
+            &[0x01, 0x07],
+        );
+        round_trip(StopSound {
+            category: None,
+            sound: Some(identifier!("entity.pig.ambient")),
+        });
+        round_trip(SoundEffect {
+            sound: SoundRef::Registry(100),
+            category: SoundCategory::Block,
+            x: 8,
+            y: 512,
+            z: -8,
+            volume: 1.0,
+            pitch: 0.5,
+            seed: 7,
+        });
+        round_trip(EntitySoundEffect {
+            sound: SoundRef::Inline(SoundEvent {
+                name: identifier!("custom.sound"),
+                fixed_range: Some(16.0),
+            }),
+            category: SoundCategory::Hostile,
+            entity_id: 3,
+            volume: 1.0,
+            pitch: 1.0,
+            seed: -7,
+        });
+        round_trip(SpawnParticle {
+            long_distance: true,
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            offset_x: 0.1,
+            offset_y: 0.2,
+            offset_z: 0.3,
+            max_speed: 0.0,
+            count: 10,
+            particle: Particle {
+                id: Particle::BLOCK,
+                data: ParticleData::BlockState(1),
+            },
+        });
+        round_trip(SpawnParticle {
+            long_distance: false,
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            offset_x: 0.0,
+            offset_y: 0.0,
+            offset_z: 0.0,
+            max_speed: 1.0,
+            count: 1,
+            particle: Particle {
+                id: Particle::VIBRATION,
+                data: ParticleData::Vibration {
+                    source: VibrationSource::Entity {
+                        entity_id: 4,
+                        eye_height: 1.62,
+                    },
+                    ticks: 20,
+                },
+            },
+        });
+        round_trip(Explosion {
+            x: 0.5,
+            y: 64.0,
+            z: 0.5,
+            strength: 4.0,
+            records: vec![ExplosionRecord { x: 1, y: -1, z: 0 }],
+            player_motion_x: 0.0,
+            player_motion_y: 0.5,
+            player_motion_z: 0.0,
+            block_interaction: ExplosionBlockInteraction::DestroyWithDecay,
+            small_explosion_particle: Particle::simple(Particle::EXPLOSION),
+            large_explosion_particle: Particle::simple(Particle::EXPLOSION_EMITTER),
+            sound: SoundEvent {
+                name: identifier!("entity.generic.explode"),
+                fixed_range: None,
+            },
+        });
+        round_trip(WorldEvent {
+            event: 1000,
+            location: (1, 2, 3),
+            data: 0,
+            disable_relative_volume: false,
+        });
+    }
+
+    #[test]
+    fn test_world_packets() {
+        round_trip(SpawnExperienceOrb {
+            entity_id: 9,
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            count: 7,
+        });
+        round_trip(EntityAnimation {
+            entity_id: 9,
+            animation: EntityAnimationType::SwingOffhand,
+        });
+        round_trip(AwardStatistics {
+            statistics: vec![Statistic {
+                category_id: 8,
+                statistic_id: 3,
+                value: 100,
+            }],
+        });
+        round_trip(SetBlockDestroyStage {
+            entity_id: 1,
+            location: (5, -60, 5),
+            destroy_stage: 3,
+        });
+        round_trip(BlockEntityData {
+            location: (0, 0, 0),
+            block_entity_type: 7,
+            data: Some(Tag::Compound(vec![NamedTag {
+                name: "id".to_string(),
+                payload: Tag::String("minecraft:sign".to_string()),
+            }])),
+        });
+        round_trip(BlockAction {
+            location: (0, 1, 0),
+            action_id: 1,
+            action_param: 1,
+            block_type: 150,
+        });
+        round_trip(BossBar {
+            uuid: Uuid::from_u128(UUID),
+            action: BossBarAction::Add {
+                title: text("Wither"),
+                health: 1.0,
+                color: BossBarColor::Purple,
+                division: BossBarDivision::TwelveNotches,
+                flags: 0x01,
+            },
+        });
+        round_trip(BossBar {
+            uuid: Uuid::from_u128(UUID),
+            action: BossBarAction::UpdateHealth { health: 0.5 },
+        });
+        round_trip(ChunkBiomes {
+            chunks: vec![ChunkBiomeData {
+                z: -1,
+                x: 1,
+                data: vec![0, 1, 2],
+            }],
+        });
+        round_trip(UnloadChunk { z: 3, x: -3 });
+        round_trip(InitializeWorldBorder {
+            x: 0.0,
+            z: 0.0,
+            old_diameter: 100.0,
+            new_diameter: 200.0,
+            speed: 60_000,
+            portal_teleport_boundary: 29_999_984,
+            warning_blocks: 5,
+            warning_time: 15,
+        });
+        round_trip(SetBorderCenter { x: 1.0, z: -1.0 });
+        round_trip(SetBorderLerpSize {
+            old_diameter: 10.0,
+            new_diameter: 20.0,
+            speed: 1000,
+        });
+        round_trip(SetBorderSize { diameter: 50.0 });
+        round_trip(SetBorderWarningDelay { warning_time: 10 });
+        round_trip(SetBorderWarningDistance { warning_blocks: 3 });
+        round_trip(UpdateLight {
+            x: 1,
+            z: 2,
+            chunk_lighting: ChunkLighting {
+                sky_light_mask: BitVec::from_raw_parts(vec![0b10]),
+                block_light_mask: BitVec::new(),
+                empty_sky_light_mask: BitVec::new(),
+                empty_block_light_mask: BitVec::new(),
+                sky_light_sections: vec![LightingArray {
+                    data: vec![0xff; 2048],
+                }],
+                block_light_sections: vec![],
+            },
+        });
+        round_trip(MapData {
+            map_id: 0,
+            scale: 2,
+            locked: false,
+            icons: Some(vec![MapIcon {
+                icon_type: 0,
+                x: 10,
+                z: -10,
+                direction: 4,
+                display_name: None,
+            }]),
+            patch: Some(MapPatch {
+                columns: 2,
+                rows: 1,
+                x: 0,
+                z: 0,
+                data: vec![34, 35],
+            }),
+        });
+        round_trip(MapData {
+            map_id: 1,
+            scale: 0,
+            locked: true,
+            icons: None,
+            patch: None,
+        });
+        round_trip(UpdateTime {
+            world_age: 24000,
+            time_of_day: -6000,
+        });
+        round_trip(SetTickingState {
+            tick_rate: 20.0,
+            is_frozen: false,
+        });
+        round_trip(StepTick { tick_steps: 5 });
+        round_trip(TagQueryResponse {
+            transaction_id: 1,
+            nbt: None,
+        });
+    }
+
+    #[test]
+    fn test_entity_packets() {
+        round_trip(HurtAnimation {
+            entity_id: 2,
+            yaw: 30.0,
+        });
+        round_trip(DamageEvent {
+            entity_id: 2,
+            source_type_id: 1,
+            source_cause_id: 0,
+            source_direct_id: 4,
+            source_position: Some(DamageSourcePosition {
+                x: 1.0,
+                y: 2.0,
+                z: 3.0,
+            }),
+        });
+        round_trip(EntityEvent {
+            entity_id: 2,
+            status: 35,
+        });
+        round_trip(TeleportEntity {
+            entity_id: 2,
+            x: 10.0,
+            y: 70.0,
+            z: -10.0,
+            yaw: 180.0,
+            pitch: 0.0,
+            on_ground: true,
+        });
+        round_trip(SetEntityVelocity {
+            entity_id: 2,
+            velocity_x: 100,
+            velocity_y: -100,
+            velocity_z: 0,
+        });
+        round_trip(LinkEntities {
+            attached_entity_id: 2,
+            holding_entity_id: 3,
+        });
+        round_trip(SetPassengers {
+            entity_id: 1,
+            passengers: vec![2, 3],
+        });
+        round_trip(PickupItem {
+            collected_entity_id: 5,
+            collector_entity_id: 1,
+            pickup_item_count: 3,
+        });
+        round_trip(UpdateAttributes {
+            entity_id: 1,
+            properties: vec![AttributeProperty {
+                key: identifier!("generic.movement_speed"),
+                value: 0.1,
+                modifiers: vec![AttributeModifier {
+                    uuid: Uuid::from_u128(3),
+                    amount: 0.03,
+                    operation: 2,
+                }],
+            }],
+        });
+        round_trip(EntityEffect {
+            entity_id: 1,
+            effect_id: 1,
+            amplifier: 1,
+            duration: 600,
+            flags: 0x02,
+            factor_codec: None,
+        });
+        round_trip(RemoveEntityEffect {
+            entity_id: 1,
+            effect_id: 1,
+        });
+        round_trip(SetCamera { camera_id: 1 });
+        round_trip(OpenHorseScreen {
+            window_id: 2,
+            slot_count: 3,
+            entity_id: 4,
+        });
+        round_trip(LookAt {
+            anchor: LookAtAnchor::Eyes,
+            target_x: 1.0,
+            target_y: 2.0,
+            target_z: 3.0,
+            entity: Some(LookAtEntity {
+                entity_id: 5,
+                anchor: LookAtAnchor::Feet,
+            }),
+        });
+    }
+
+    #[test]
+    fn test_player_state_packets() {
+        round_trip(KeepAlive { id: 99 });
+        round_trip(Ping { id: 1 });
+        round_trip(PingResponse { payload: 2 });
+        round_trip(MoveVehicle {
+            x: 1.0,
+            y: 2.0,
+            z: 3.0,
+            yaw: 4.0,
+            pitch: 5.0,
+        });
+        round_trip(SetHeldItem { slot: 4 });
+        round_trip(SetCooldown {
+            item_id: 10,
+            cooldown_ticks: 20,
+        });
+        round_trip(ClientBoundPluginMessage {
+            channel: identifier!("brand"),
+            data: vec![1, 2, 3],
+        });
+        round_trip(OpenBook {
+            hand: Hand::OffHand,
+        });
+        round_trip(OpenSignEditor {
+            location: (1, 2, 3),
+            front_text: true,
+        });
+        round_trip(PlaceGhostRecipe {
+            window_id: 1,
+            recipe: identifier!("stick"),
+        });
+        round_trip(EndCombat { duration: 40 });
+        round_trip(EnterCombat);
+        round_trip(CombatDeath {
+            player_id: 1,
+            message: text("died"),
+        });
+        round_trip(Respawn {
+            dimension_type: identifier!("overworld"),
+            dimension_name: identifier!("overworld"),
+            hashed_seed: 123,
+            game_mode: GameMode::Survival,
+            previous_game_mode: LastGameMode::Undefined,
+            is_debug: false,
+            is_flat: true,
+            death_location: None,
+            portal_cooldown: 0,
+            data_kept: 0x03,
+        });
+        round_trip(SetRenderDistance { view_distance: 10 });
+        round_trip(SetSimulationDistance {
+            simulation_distance: 8,
+        });
+        round_trip(SetExperience {
+            experience_bar: 0.5,
+            level: 3,
+            total_experience: 30,
+        });
+        round_trip(SetHealth {
+            health: 20.0,
+            food: 20,
+            food_saturation: 5.0,
+        });
+        round_trip(RemoveResourcePack { uuid: None });
+        round_trip(AddResourcePack {
+            uuid: Uuid::from_u128(UUID),
+            url: "https://example.com/pack.zip".to_string(),
+            hash: String::new(),
+            forced: true,
+            prompt_message: Some(text("please")),
+        });
+        round_trip(SelectAdvancementsTab {
+            tab_id: Some(identifier!("story/root")),
+        });
+        round_trip(UpdateRecipeBook {
+            action: RecipeBookAction::Init,
+            settings: RecipeBookSettings {
+                crafting_open: true,
+                ..Default::default()
+            },
+            recipe_ids: vec![identifier!("stick")],
+            highlighted_recipe_ids: vec![identifier!("stick")],
+        });
+        round_trip(UpdateRecipeBook {
+            action: RecipeBookAction::Remove,
+            settings: Default::default(),
+            recipe_ids: vec![identifier!("torch")],
+            highlighted_recipe_ids: vec![],
+        });
+    }
+
+    #[test]
+    fn test_scoreboard_packets() {
+        round_trip(DisplayObjective {
+            position: 1,
+            score_name: "kills".to_string(),
+        });
+        round_trip(UpdateObjectives {
+            objective_name: "kills".to_string(),
+            action: ObjectiveAction::Create {
+                value: text("Kills"),
+                objective_type: ObjectiveType::Integer,
+                number_format: Some(NumberFormat::Fixed { content: text("-") }),
+            },
+        });
+        round_trip(UpdateObjectives {
+            objective_name: "kills".to_string(),
+            action: ObjectiveAction::Remove,
+        });
+        round_trip(UpdateScore {
+            entity_name: "Notch".to_string(),
+            objective_name: "kills".to_string(),
+            value: 3,
+            display_name: None,
+            number_format: Some(NumberFormat::Blank),
+        });
+        round_trip(ResetScore {
+            entity_name: "Notch".to_string(),
+            objective_name: Some("kills".to_string()),
+        });
+        round_trip(UpdateTeams {
+            team_name: "red".to_string(),
+            action: TeamAction::Create {
+                info: TeamInfo {
+                    display_name: text("Red"),
+                    friendly_flags: 0x01,
+                    name_tag_visibility: "always".to_string(),
+                    collision_rule: "always".to_string(),
+                    color: 12,
+                    prefix: text("[R] "),
+                    suffix: text(""),
+                },
+                entities: vec!["Notch".to_string()],
+            },
+        });
+        round_trip(UpdateTeams {
+            team_name: "red".to_string(),
+            action: TeamAction::RemoveEntities {
+                entities: vec!["Notch".to_string()],
+            },
+        });
+    }
+
+    #[test]
+    fn test_registry_packets() {
+        round_trip(UpdateRecipes {
+            recipes: vec![
+                Recipe {
+                    recipe_type: identifier!("crafting_shaped"),
+                    id: identifier!("stick"),
+                    data: RecipeData::Shaped {
+                        group: "sticks".to_string(),
+                        category: CraftingCategory::Misc,
+                        width: 1,
+                        height: 2,
+                        ingredients: vec![
+                            Ingredient {
+                                items: vec![stone(1)],
+                            },
+                            Ingredient {
+                                items: vec![stone(1)],
+                            },
+                        ],
+                        result: stone(4),
+                        show_notification: true,
+                    },
+                },
+                Recipe {
+                    recipe_type: identifier!("smelting"),
+                    id: identifier!("glass"),
+                    data: RecipeData::Cooking {
+                        group: String::new(),
+                        category: CookingCategory::Blocks,
+                        ingredient: Ingredient {
+                            items: vec![stone(1)],
+                        },
+                        result: stone(1),
+                        experience: 0.1,
+                        cooking_time: 200,
+                    },
+                },
+                Recipe {
+                    recipe_type: identifier!("crafting_special_armordye"),
+                    id: identifier!("armor_dye"),
+                    data: RecipeData::Special {
+                        category: CraftingCategory::Misc,
+                    },
+                },
+                Recipe {
+                    recipe_type: identifier!("smithing_trim"),
+                    id: identifier!("trim"),
+                    data: RecipeData::SmithingTrim {
+                        template: Ingredient { items: vec![] },
+                        base: Ingredient { items: vec![] },
+                        addition: Ingredient { items: vec![] },
+                    },
+                },
+            ],
+        });
+        round_trip(UpdateAdvancements {
+            reset: true,
+            advancements: vec![AdvancementMapping {
+                key: identifier!("story/root"),
+                value: Advancement {
+                    parent: None,
+                    display: Some(AdvancementDisplay {
+                        title: text("Minecraft"),
+                        description: text("The heart and story of the game"),
+                        icon: stone(1),
+                        frame: AdvancementFrame::Task,
+                        flags: AdvancementDisplay::HAS_BACKGROUND,
+                        background_texture: Some(identifier!(
+                            "textures/gui/advancements/backgrounds/stone.png"
+                        )),
+                        x: 0.0,
+                        y: 0.0,
+                    }),
+                    requirements: vec![vec!["crafting_table".to_string()]],
+                    sends_telemetry: false,
+                },
+            }],
+            removed: vec![],
+            progress: vec![AdvancementProgress {
+                advancement: identifier!("story/root"),
+                criteria: vec![CriterionProgress {
+                    criterion: identifier!("crafting_table"),
+                    achieved_at: Some(1_700_000_000_000),
+                }],
+            }],
+        });
+        round_trip(PlayUpdateTags { tags: vec![] });
+    }
+}
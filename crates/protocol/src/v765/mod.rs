@@ -5,8 +5,8 @@ use crate::{
     decode::{AllocTracker, ProtoDecodeErr},
     encode::ProtoEncodeErr,
     packet::{
-        decode_packet_impl, encode_packet_impl, ConnectionState, Packet, PacketDecoder,
-        PacketDirection, PacketEncoder,
+        decode_packet_impl, encode_packet_impl, ConnectionState, DecodedPacket, Packet,
+        PacketDecoder, PacketDirection, PacketEncoder,
     },
     v765::{clientbound::*, serverbound::*},
 };
@@ -52,17 +52,123 @@ impl PacketEncoder for PacketEncoderImpl {
                         packet,
                         id,
                         writer,
-                        ChangeDifficulty,
+                        BundleDelimiter,
+                        SpawnEntity,
+                        SpawnExperienceOrb,
+                        EntityAnimation,
+                        AwardStatistics,
+                        AcknowledgeBlockChange,
+                        SetBlockDestroyStage,
+                        BlockEntityData,
+                        BlockAction,
+                        BlockUpdate,
+                        BossBar,
+                        clientbound::ChangeDifficulty,
+                        ChunkBatchFinish,
+                        ChunkBatchStart,
+                        ChunkBiomes,
+                        ClearTitles,
+                        CommandSuggestionsResponse,
+                        Commands,
+                        clientbound::CloseContainer,
+                        SetContainerContent,
+                        SetContainerProperty,
+                        SetContainerSlot,
+                        SetCooldown,
+                        ChatSuggestions,
+                        ClientBoundPluginMessage,
+                        DamageEvent,
+                        DeleteMessage,
+                        Disconnect,
+                        DisguisedChatMessage,
+                        EntityEvent,
+                        Explosion,
+                        UnloadChunk,
+                        ServerGameEvent,
+                        OpenHorseScreen,
+                        HurtAnimation,
+                        InitializeWorldBorder,
+                        clientbound::KeepAlive,
                         ChunkDataAndLight,
+                        WorldEvent,
+                        SpawnParticle,
+                        UpdateLight,
                         GameJoin,
-                        PlayerAbilities,
+                        MapData,
+                        MerchantOffers,
+                        UpdateEntityPosition,
+                        UpdateEntityPositionAndRotation,
+                        UpdateEntityRotation,
+                        clientbound::MoveVehicle,
+                        OpenBook,
+                        OpenScreen,
+                        OpenSignEditor,
+                        Ping,
+                        PingResponse,
+                        PlaceGhostRecipe,
+                        clientbound::PlayerAbilities,
+                        PlayerChatMessage,
+                        EndCombat,
+                        EnterCombat,
+                        CombatDeath,
+                        PlayerInfoRemove,
+                        PlayerInfoUpdate,
+                        LookAt,
                         SyncPlayerPosition,
-                        DefaultSpawnPosition,
+                        UpdateRecipeBook,
+                        RemoveEntities,
+                        RemoveEntityEffect,
+                        ResetScore,
+                        RemoveResourcePack,
+                        AddResourcePack,
+                        Respawn,
+                        SetHeadRotation,
+                        UpdateSectionBlocks,
+                        SelectAdvancementsTab,
+                        ServerData,
+                        SetActionBarText,
+                        SetBorderCenter,
+                        SetBorderLerpSize,
+                        SetBorderSize,
+                        SetBorderWarningDelay,
+                        SetBorderWarningDistance,
+                        SetCamera,
+                        clientbound::SetHeldItem,
                         SetCenterChunk,
-                        ChunkBatchFinish,
-                        ChunkBatchStart,
-                        ServerGameEvent,
-                        StartConfiguration
+                        SetRenderDistance,
+                        DefaultSpawnPosition,
+                        DisplayObjective,
+                        SetEntityMetadata,
+                        LinkEntities,
+                        SetEntityVelocity,
+                        SetEquipment,
+                        SetExperience,
+                        SetHealth,
+                        UpdateObjectives,
+                        SetPassengers,
+                        UpdateTeams,
+                        UpdateScore,
+                        SetSimulationDistance,
+                        SetSubtitleText,
+                        UpdateTime,
+                        SetTitleText,
+                        SetTitleAnimationTimes,
+                        EntitySoundEffect,
+                        SoundEffect,
+                        StartConfiguration,
+                        StopSound,
+                        SystemChatMessage,
+                        SetTabListHeaderAndFooter,
+                        TagQueryResponse,
+                        PickupItem,
+                        TeleportEntity,
+                        SetTickingState,
+                        StepTick,
+                        UpdateAdvancements,
+                        UpdateAttributes,
+                        EntityEffect,
+                        UpdateRecipes,
+                        PlayUpdateTags
                     )
                 }
                 ConnectionState::Configuration => {
@@ -102,7 +208,66 @@ impl PacketEncoder for PacketEncoderImpl {
                     )
                 }
                 ConnectionState::Play => {
-                    encode_packet_impl!(packet, id, writer,)
+                    encode_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        ConfirmTeleportation,
+                        QueryBlockEntityTag,
+                        serverbound::ChangeDifficulty,
+                        AcknowledgeMessage,
+                        ChatCommand,
+                        ChatMessage,
+                        PlayerSession,
+                        ChunkBatchReceived,
+                        ClientCommand,
+                        ClientInformation,
+                        CommandSuggestionsRequest,
+                        ConfigurationAck,
+                        ClickContainerButton,
+                        ClickContainer,
+                        serverbound::CloseContainer,
+                        ChangeContainerSlotState,
+                        ServerBoundPluginMessage,
+                        EditBook,
+                        QueryEntityTag,
+                        Interact,
+                        JigsawGenerate,
+                        serverbound::KeepAlive,
+                        LockDifficulty,
+                        SetPlayerPosition,
+                        SetPlayerPositionAndRotation,
+                        SetPlayerRotation,
+                        SetPlayerOnGround,
+                        serverbound::MoveVehicle,
+                        PaddleBoat,
+                        PickItem,
+                        PingRequest,
+                        PlaceRecipe,
+                        serverbound::PlayerAbilities,
+                        PlayerAction,
+                        PlayerCommand,
+                        PlayerInput,
+                        Pong,
+                        ChangeRecipeBookSettings,
+                        SetSeenRecipe,
+                        RenameItem,
+                        ResourcePackResponse,
+                        SeenAdvancements,
+                        SelectTrade,
+                        SetBeaconEffect,
+                        serverbound::SetHeldItem,
+                        ProgramCommandBlock,
+                        ProgramCommandBlockMinecart,
+                        SetCreativeModeSlot,
+                        ProgramJigsawBlock,
+                        ProgramStructureBlock,
+                        UpdateSign,
+                        SwingArm,
+                        TeleportToEntity,
+                        UseItemOn,
+                        UseItem
+                    )
                 }
                 ConnectionState::Configuration => {
                     encode_packet_impl!(
@@ -125,13 +290,13 @@ impl PacketEncoder for PacketEncoderImpl {
 pub struct PacketDecoderImpl;
 
 impl PacketDecoder for PacketDecoderImpl {
-    fn decode_packet<R: Read + Seek, T: AllocTracker>(
+    fn decode_as<P: DecodedPacket, R: Read + Seek, T: AllocTracker>(
         id: i32,
         direction: PacketDirection,
         state: ConnectionState,
         reader: &mut R,
         alloc_tracker: &mut T,
-    ) -> Result<Box<dyn Packet>, ProtoDecodeErr> {
+    ) -> Result<P, ProtoDecodeErr> {
         match direction {
             PacketDirection::ClientBound => match state {
                 ConnectionState::Handshake => {
@@ -159,7 +324,128 @@ impl PacketDecoder for PacketDecoderImpl {
                     )
                 }
                 ConnectionState::Play => {
-                    decode_packet_impl!(id, reader, alloc_tracker,)
+                    decode_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        BundleDelimiter,
+                        SpawnEntity,
+                        SpawnExperienceOrb,
+                        EntityAnimation,
+                        AwardStatistics,
+                        AcknowledgeBlockChange,
+                        SetBlockDestroyStage,
+                        BlockEntityData,
+                        BlockAction,
+                        BlockUpdate,
+                        BossBar,
+                        clientbound::ChangeDifficulty,
+                        ChunkBatchFinish,
+                        ChunkBatchStart,
+                        ChunkBiomes,
+                        ClearTitles,
+                        CommandSuggestionsResponse,
+                        Commands,
+                        clientbound::CloseContainer,
+                        SetContainerContent,
+                        SetContainerProperty,
+                        SetContainerSlot,
+                        SetCooldown,
+                        ChatSuggestions,
+                        ClientBoundPluginMessage,
+                        DamageEvent,
+                        DeleteMessage,
+                        Disconnect,
+                        DisguisedChatMessage,
+                        EntityEvent,
+                        Explosion,
+                        UnloadChunk,
+                        ServerGameEvent,
+                        OpenHorseScreen,
+                        HurtAnimation,
+                        InitializeWorldBorder,
+                        clientbound::KeepAlive,
+                        ChunkDataAndLight,
+                        WorldEvent,
+                        SpawnParticle,
+                        UpdateLight,
+                        GameJoin,
+                        MapData,
+                        MerchantOffers,
+                        UpdateEntityPosition,
+                        UpdateEntityPositionAndRotation,
+                        UpdateEntityRotation,
+                        clientbound::MoveVehicle,
+                        OpenBook,
+                        OpenScreen,
+                        OpenSignEditor,
+                        Ping,
+                        PingResponse,
+                        PlaceGhostRecipe,
+                        clientbound::PlayerAbilities,
+                        PlayerChatMessage,
+                        EndCombat,
+                        EnterCombat,
+                        CombatDeath,
+                        PlayerInfoRemove,
+                        PlayerInfoUpdate,
+                        LookAt,
+                        SyncPlayerPosition,
+                        UpdateRecipeBook,
+                        RemoveEntities,
+                        RemoveEntityEffect,
+                        ResetScore,
+                        RemoveResourcePack,
+                        AddResourcePack,
+                        Respawn,
+                        SetHeadRotation,
+                        UpdateSectionBlocks,
+                        SelectAdvancementsTab,
+                        ServerData,
+                        SetActionBarText,
+                        SetBorderCenter,
+                        SetBorderLerpSize,
+                        SetBorderSize,
+                        SetBorderWarningDelay,
+                        SetBorderWarningDistance,
+                        SetCamera,
+                        clientbound::SetHeldItem,
+                        SetCenterChunk,
+                        SetRenderDistance,
+                        DefaultSpawnPosition,
+                        DisplayObjective,
+                        SetEntityMetadata,
+                        LinkEntities,
+                        SetEntityVelocity,
+                        SetEquipment,
+                        SetExperience,
+                        SetHealth,
+                        UpdateObjectives,
+                        SetPassengers,
+                        UpdateTeams,
+                        UpdateScore,
+                        SetSimulationDistance,
+                        SetSubtitleText,
+                        UpdateTime,
+                        SetTitleText,
+                        SetTitleAnimationTimes,
+                        EntitySoundEffect,
+                        SoundEffect,
+                        StartConfiguration,
+                        StopSound,
+                        SystemChatMessage,
+                        SetTabListHeaderAndFooter,
+                        TagQueryResponse,
+                        PickupItem,
+                        TeleportEntity,
+                        SetTickingState,
+                        StepTick,
+                        UpdateAdvancements,
+                        UpdateAttributes,
+                        EntityEffect,
+                        UpdateRecipes,
+                        PlayUpdateTags
+                    )
                 }
                 ConnectionState::Configuration => {
                     decode_packet_impl!(
@@ -169,7 +455,7 @@ impl PacketDecoder for PacketDecoderImpl {
                         ConfigClientBoundPluginMessage,
                         ConfigDisconnect,
                         ServerFinishConfiguration,
-                        serverbound::ConfigKeepAlive,
+                        clientbound::ConfigKeepAlive,
                         ConfigPing,
                         RegistryData,
                         ConfigRemoveResourcePack,
@@ -198,7 +484,66 @@ impl PacketDecoder for PacketDecoderImpl {
                     )
                 }
                 ConnectionState::Play => {
-                    decode_packet_impl!(id, reader, alloc_tracker,)
+                    decode_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        ConfirmTeleportation,
+                        QueryBlockEntityTag,
+                        serverbound::ChangeDifficulty,
+                        AcknowledgeMessage,
+                        ChatCommand,
+                        ChatMessage,
+                        PlayerSession,
+                        ChunkBatchReceived,
+                        ClientCommand,
+                        ClientInformation,
+                        CommandSuggestionsRequest,
+                        ConfigurationAck,
+                        ClickContainerButton,
+                        ClickContainer,
+                        serverbound::CloseContainer,
+                        ChangeContainerSlotState,
+                        ServerBoundPluginMessage,
+                        EditBook,
+                        QueryEntityTag,
+                        Interact,
+                        JigsawGenerate,
+                        serverbound::KeepAlive,
+                        LockDifficulty,
+                        SetPlayerPosition,
+                        SetPlayerPositionAndRotation,
+                        SetPlayerRotation,
+                        SetPlayerOnGround,
+                        serverbound::MoveVehicle,
+                        PaddleBoat,
+                        PickItem,
+                        PingRequest,
+                        PlaceRecipe,
+                        serverbound::PlayerAbilities,
+                        PlayerAction,
+                        PlayerCommand,
+                        PlayerInput,
+                        Pong,
+                        ChangeRecipeBookSettings,
+                        SetSeenRecipe,
+                        RenameItem,
+                        ResourcePackResponse,
+                        SeenAdvancements,
+                        SelectTrade,
+                        SetBeaconEffect,
+                        serverbound::SetHeldItem,
+                        ProgramCommandBlock,
+                        ProgramCommandBlockMinecart,
+                        SetCreativeModeSlot,
+                        ProgramJigsawBlock,
+                        ProgramStructureBlock,
+                        UpdateSign,
+                        SwingArm,
+                        TeleportToEntity,
+                        UseItemOn,
+                        UseItem
+                    )
                 }
                 ConnectionState::Configuration => {
                     decode_packet_impl!(
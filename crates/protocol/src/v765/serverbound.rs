@@ -15,7 +15,9 @@ use crate::{types::*, v765::types::*};
 pub struct HandshakeRequest {
     #[proto(repr = "VarInt")]
     pub version: i32,
-    #[proto(max_len = 255)]
+    /// Vanilla allows 255 characters, proxies forwarding player info in the address need more so
+    /// servers check the vanilla limit themselves unless forwarding is enabled.
+    #[proto(max_len = 32767)]
     pub server_addr: String,
     pub server_port: u16,
     pub next_state: HandshakeNextState,
@@ -89,7 +91,7 @@ pub struct ClientFinishConfiguration;
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x03, ServerBound, Configuration)]
 pub struct ConfigKeepAlive {
-    id: i64,
+    pub id: i64,
 }
 
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
@@ -105,6 +107,870 @@ pub struct ConfigResourcePackResponse {
     result: ResourcePackResult,
 }
 
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x00, ServerBound, Play)]
+pub struct ConfirmTeleportation {
+    #[proto(repr = "VarInt")]
+    pub teleport_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x01, ServerBound, Play)]
+pub struct QueryBlockEntityTag {
+    #[proto(repr = "VarInt")]
+    pub transaction_id: i32,
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x02, ServerBound, Play)]
+pub struct ChangeDifficulty {
+    pub difficulty: Difficulty,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x03, ServerBound, Play)]
+pub struct AcknowledgeMessage {
+    #[proto(repr = "VarInt")]
+    pub message_count: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x04, ServerBound, Play)]
+pub struct ChatCommand {
+    #[proto(max_len = 256)]
+    pub command: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub argument_signatures: Vec<ArgumentSignature>,
+    #[proto(repr = "VarInt")]
+    pub message_count: i32,
+    #[proto(repr = "FixedBytes<3>")]
+    pub acknowledged: [u8; 3],
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x05, ServerBound, Play)]
+pub struct ChatMessage {
+    #[proto(max_len = 256)]
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    #[proto(repr = "Option<FixedBytes<256>>")]
+    pub signature: Option<[u8; 256]>,
+    #[proto(repr = "VarInt")]
+    pub message_count: i32,
+    #[proto(repr = "FixedBytes<3>")]
+    pub acknowledged: [u8; 3],
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x06, ServerBound, Play)]
+pub struct PlayerSession {
+    pub session_id: Uuid,
+    pub expires_at: i64,
+    #[proto(max_len = 512)]
+    pub public_key: Vec<u8>,
+    #[proto(max_len = 4096)]
+    pub key_signature: Vec<u8>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x07, ServerBound, Play)]
+pub struct ChunkBatchReceived {
+    pub chunks_per_tick: f32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x08, ServerBound, Play)]
+pub struct ClientCommand {
+    pub action: ClientCommandAction,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x09, ServerBound, Play)]
+pub struct ClientInformation {
+    #[proto(max_len = 16)]
+    pub locale: String,
+    pub view_distance: i8,
+    pub chat_mode: ChatMode,
+    pub chat_colors: bool,
+    pub skin_parts: u8,
+    pub main_hand: MainHand,
+    pub text_filtering: bool,
+    pub server_listings: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0a, ServerBound, Play)]
+pub struct CommandSuggestionsRequest {
+    #[proto(repr = "VarInt")]
+    pub transaction_id: i32,
+    #[proto(max_len = 32500)]
+    pub text: String,
+}
+
 #[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
 #[packet(0x0b, ServerBound, Play)]
 pub struct ConfigurationAck;
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0c, ServerBound, Play)]
+pub struct ClickContainerButton {
+    pub window_id: i8,
+    pub button_id: i8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0d, ServerBound, Play)]
+pub struct ClickContainer {
+    pub window_id: u8,
+    #[proto(repr = "VarInt")]
+    pub state_id: i32,
+    pub slot: i16,
+    pub button: i8,
+    pub mode: ClickMode,
+    #[proto(max_len = 128)]
+    pub changed_slots: Vec<ChangedSlot>,
+    pub carried_item: Option<ItemStack>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0e, ServerBound, Play)]
+pub struct CloseContainer {
+    pub window_id: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0f, ServerBound, Play)]
+pub struct ChangeContainerSlotState {
+    #[proto(repr = "VarInt")]
+    pub slot_id: i32,
+    #[proto(repr = "VarInt")]
+    pub window_id: i32,
+    pub state: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x10, ServerBound, Play)]
+pub struct ServerBoundPluginMessage {
+    pub channel: Identifier,
+    #[proto(repr = "RemainingBytes")]
+    pub data: Vec<u8>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x11, ServerBound, Play)]
+pub struct EditBook {
+    #[proto(repr = "VarInt")]
+    pub slot: i32,
+    #[proto(max_len = 200)]
+    pub entries: Vec<String>,
+    pub title: Option<String>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x12, ServerBound, Play)]
+pub struct QueryEntityTag {
+    #[proto(repr = "VarInt")]
+    pub transaction_id: i32,
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x13, ServerBound, Play)]
+pub struct Interact {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub action: InteractAction,
+    pub sneaking: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x14, ServerBound, Play)]
+pub struct JigsawGenerate {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    #[proto(repr = "VarInt")]
+    pub levels: i32,
+    pub keep_jigsaws: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x15, ServerBound, Play)]
+pub struct KeepAlive {
+    pub id: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x16, ServerBound, Play)]
+pub struct LockDifficulty {
+    pub locked: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x17, ServerBound, Play)]
+pub struct SetPlayerPosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x18, ServerBound, Play)]
+pub struct SetPlayerPositionAndRotation {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x19, ServerBound, Play)]
+pub struct SetPlayerRotation {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1a, ServerBound, Play)]
+pub struct SetPlayerOnGround {
+    pub on_ground: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1b, ServerBound, Play)]
+pub struct MoveVehicle {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1c, ServerBound, Play)]
+pub struct PaddleBoat {
+    pub left_turning: bool,
+    pub right_turning: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1d, ServerBound, Play)]
+pub struct PickItem {
+    #[proto(repr = "VarInt")]
+    pub slot: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1e, ServerBound, Play)]
+pub struct PingRequest {
+    pub payload: i64,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1f, ServerBound, Play)]
+pub struct PlaceRecipe {
+    pub window_id: i8,
+    pub recipe: Identifier,
+    pub make_all: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x20, ServerBound, Play)]
+pub struct PlayerAbilities {
+    pub flags: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x21, ServerBound, Play)]
+pub struct PlayerAction {
+    pub status: PlayerActionStatus,
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub face: BlockFace,
+    #[proto(repr = "VarInt")]
+    pub sequence: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x22, ServerBound, Play)]
+pub struct PlayerCommand {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub action: PlayerCommandAction,
+    #[proto(repr = "VarInt")]
+    pub jump_boost: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x23, ServerBound, Play)]
+pub struct PlayerInput {
+    pub sideways: f32,
+    pub forward: f32,
+    pub flags: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x24, ServerBound, Play)]
+pub struct Pong {
+    pub id: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x25, ServerBound, Play)]
+pub struct ChangeRecipeBookSettings {
+    pub book: RecipeBookType,
+    pub book_open: bool,
+    pub filter_active: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x26, ServerBound, Play)]
+pub struct SetSeenRecipe {
+    pub recipe: Identifier,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x27, ServerBound, Play)]
+pub struct RenameItem {
+    #[proto(max_len = 32767)]
+    pub name: String,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x28, ServerBound, Play)]
+pub struct ResourcePackResponse {
+    pub uuid: Uuid,
+    pub result: ResourcePackResult,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x29, ServerBound, Play)]
+pub struct SeenAdvancements {
+    pub action: SeenAdvancementsAction,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2a, ServerBound, Play)]
+pub struct SelectTrade {
+    #[proto(repr = "VarInt")]
+    pub slot: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2b, ServerBound, Play)]
+pub struct SetBeaconEffect {
+    #[proto(repr = "Option<VarInt>")]
+    pub primary_effect: Option<i32>,
+    #[proto(repr = "Option<VarInt>")]
+    pub secondary_effect: Option<i32>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2c, ServerBound, Play)]
+pub struct SetHeldItem {
+    pub slot: i16,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2d, ServerBound, Play)]
+pub struct ProgramCommandBlock {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    #[proto(max_len = 32767)]
+    pub command: String,
+    pub mode: CommandBlockMode,
+    pub flags: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2e, ServerBound, Play)]
+pub struct ProgramCommandBlockMinecart {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    #[proto(max_len = 32767)]
+    pub command: String,
+    pub track_output: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2f, ServerBound, Play)]
+pub struct SetCreativeModeSlot {
+    pub slot: i16,
+    pub item: Option<ItemStack>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x30, ServerBound, Play)]
+pub struct ProgramJigsawBlock {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub name: Identifier,
+    pub target: Identifier,
+    pub pool: Identifier,
+    #[proto(max_len = 32767)]
+    pub final_state: String,
+    #[proto(max_len = 32767)]
+    pub joint_type: String,
+    #[proto(repr = "VarInt")]
+    pub selection_priority: i32,
+    #[proto(repr = "VarInt")]
+    pub placement_priority: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x31, ServerBound, Play)]
+pub struct ProgramStructureBlock {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub action: StructureBlockAction,
+    pub mode: StructureBlockMode,
+    #[proto(max_len = 32767)]
+    pub name: String,
+    pub offset_x: i8,
+    pub offset_y: i8,
+    pub offset_z: i8,
+    pub size_x: i8,
+    pub size_y: i8,
+    pub size_z: i8,
+    pub mirror: StructureMirror,
+    pub rotation: StructureRotation,
+    #[proto(max_len = 128)]
+    pub metadata: String,
+    pub integrity: f32,
+    #[proto(repr = "VarLong")]
+    pub seed: i64,
+    pub flags: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x32, ServerBound, Play)]
+pub struct UpdateSign {
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub front_text: bool,
+    #[proto(max_len = 384)]
+    pub line_1: String,
+    #[proto(max_len = 384)]
+    pub line_2: String,
+    #[proto(max_len = 384)]
+    pub line_3: String,
+    #[proto(max_len = 384)]
+    pub line_4: String,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x33, ServerBound, Play)]
+pub struct SwingArm {
+    pub hand: Hand,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x34, ServerBound, Play)]
+pub struct TeleportToEntity {
+    pub target: Uuid,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x35, ServerBound, Play)]
+pub struct UseItemOn {
+    pub hand: Hand,
+    #[proto(repr = "Position")]
+    pub location: (i32, i32, i32),
+    pub face: BlockFace,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    pub inside_block: bool,
+    #[proto(repr = "VarInt")]
+    pub sequence: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x36, ServerBound, Play)]
+pub struct UseItem {
+    pub hand: Hand,
+    #[proto(repr = "VarInt")]
+    pub sequence: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serverx_common::identifier;
+    use serverx_macros::identifier;
+    use serverx_nbt::{NamedTag, Tag};
+    use uuid::Uuid;
+
+    use crate::{
+        decode::BasicAllocTracker,
+        packet::{
+            ConnectionState::Play, Packet, PacketDecoder, PacketDirection::ServerBound,
+            PacketEncoder,
+        },
+        v765::{serverbound::*, PacketDecoderImpl, PacketEncoderImpl},
+    };
+
+    fn round_trip<P: Packet + 'static>(packet: P) {
+        let mut cursor = Cursor::new(Vec::new());
+        PacketEncoderImpl::encode_packet(&packet, packet.id(), ServerBound, Play, &mut cursor)
+            .unwrap();
+        let buf = cursor.into_inner();
+        let mut reader = Cursor::new(buf.as_slice());
+        let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+        let decoded = PacketDecoderImpl::decode_packet(
+            packet.id(),
+            ServerBound,
+            Play,
+            &mut reader,
+            &mut alloc_tracker,
+        )
+        .unwrap();
+        assert_eq!(reader.position() as usize, buf.len(), "{:?}", packet);
+        assert_eq!(decoded.id(), packet.id());
+        let decoded = decoded.into_any().downcast::<P>().unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", packet));
+    }
+
+    fn item_stack() -> ItemStack {
+        ItemStack {
+            item_id: 802,
+            count: 64,
+            nbt: Some(Tag::Compound(vec![NamedTag {
+                name: "Damage".to_string(),
+                payload: Tag::Int(3),
+            }])),
+        }
+    }
+
+    #[test]
+    fn test_movement_packets() {
+        round_trip(ConfirmTeleportation { teleport_id: 7 });
+        round_trip(SetPlayerPosition {
+            x: 1.5,
+            y: -60.0,
+            z: 2.5,
+            on_ground: true,
+        });
+        round_trip(SetPlayerPositionAndRotation {
+            x: -1.5,
+            y: 64.0,
+            z: 1000.25,
+            yaw: 90.0,
+            pitch: -45.0,
+            on_ground: false,
+        });
+        round_trip(SetPlayerRotation {
+            yaw: 180.0,
+            pitch: 10.0,
+            on_ground: true,
+        });
+        round_trip(SetPlayerOnGround { on_ground: false });
+        round_trip(MoveVehicle {
+            x: 3.0,
+            y: 70.0,
+            z: -3.0,
+            yaw: 12.5,
+            pitch: 0.0,
+        });
+        round_trip(PaddleBoat {
+            left_turning: true,
+            right_turning: false,
+        });
+        round_trip(PlayerInput {
+            sideways: 0.98,
+            forward: -0.98,
+            flags: 0x03,
+        });
+        round_trip(PlayerAbilities { flags: 0x02 });
+        round_trip(PlayerCommand {
+            entity_id: 42,
+            action: PlayerCommandAction::StartSprinting,
+            jump_boost: 0,
+        });
+        round_trip(TeleportToEntity {
+            target: Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5),
+        });
+    }
+
+    #[test]
+    fn test_chat_packets() {
+        round_trip(AcknowledgeMessage { message_count: 3 });
+        round_trip(ChatCommand {
+            command: "msg Notch hello".to_string(),
+            timestamp: 1_700_000_000_000,
+            salt: -12345,
+            argument_signatures: vec![ArgumentSignature {
+                name: "message".to_string(),
+                signature: [0xab; 256],
+            }],
+            message_count: 1,
+            acknowledged: [0x01, 0x00, 0x80],
+        });
+        round_trip(ChatMessage {
+            message: "hello world".to_string(),
+            timestamp: 1_700_000_000_000,
+            salt: 99,
+            signature: Some([0x5a; 256]),
+            message_count: 0,
+            acknowledged: [0; 3],
+        });
+        round_trip(ChatMessage {
+            message: "unsigned".to_string(),
+            timestamp: 0,
+            salt: 0,
+            signature: None,
+            message_count: 2,
+            acknowledged: [0x03, 0, 0],
+        });
+        round_trip(PlayerSession {
+            session_id: Uuid::from_u128(1),
+            expires_at: 1_700_000_000_000,
+            public_key: vec![0x30; 294],
+            key_signature: vec![0x01; 512],
+        });
+        round_trip(CommandSuggestionsRequest {
+            transaction_id: 5,
+            text: "/gamemode cr".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_client_state_packets() {
+        round_trip(ChangeDifficulty {
+            difficulty: Difficulty::Hard,
+        });
+        round_trip(LockDifficulty { locked: true });
+        round_trip(ChunkBatchReceived {
+            chunks_per_tick: 9.5,
+        });
+        round_trip(ClientCommand {
+            action: ClientCommandAction::PerformRespawn,
+        });
+        round_trip(ClientInformation {
+            locale: "en_us".to_string(),
+            view_distance: 12,
+            chat_mode: ChatMode::Enabled,
+            chat_colors: true,
+            skin_parts: 0x7f,
+            main_hand: MainHand::Right,
+            text_filtering: false,
+            server_listings: true,
+        });
+        round_trip(ConfigurationAck);
+        round_trip(ServerBoundPluginMessage {
+            channel: identifier!("brand"),
+            data: b"\x07vanilla".to_vec(),
+        });
+        round_trip(KeepAlive {
+            id: 0x1234_5678_9abc,
+        });
+        round_trip(PingRequest { payload: -1 });
+        round_trip(Pong { id: 17 });
+        round_trip(ResourcePackResponse {
+            uuid: Uuid::from_u128(2),
+            result: ResourcePackResult::Downloaded,
+        });
+        round_trip(SeenAdvancements {
+            action: SeenAdvancementsAction::OpenedTab {
+                tab_id: identifier!("story/root"),
+            },
+        });
+        round_trip(SeenAdvancements {
+            action: SeenAdvancementsAction::ClosedScreen,
+        });
+        round_trip(ChangeRecipeBookSettings {
+            book: RecipeBookType::BlastFurnace,
+            book_open: true,
+            filter_active: false,
+        });
+        round_trip(SetSeenRecipe {
+            recipe: identifier!("oak_planks"),
+        });
+    }
+
+    #[test]
+    fn test_inventory_packets() {
+        round_trip(ClickContainerButton {
+            window_id: 3,
+            button_id: 1,
+        });
+        round_trip(ClickContainer {
+            window_id: 1,
+            state_id: 12,
+            slot: 36,
+            button: 0,
+            mode: ClickMode::QuickMove,
+            changed_slots: vec![
+                ChangedSlot {
+                    slot: 36,
+                    item: None,
+                },
+                ChangedSlot {
+                    slot: 9,
+                    item: Some(item_stack()),
+                },
+            ],
+            carried_item: Some(ItemStack {
+                item_id: 1,
+                count: 1,
+                nbt: None,
+            }),
+        });
+        round_trip(CloseContainer { window_id: 0 });
+        round_trip(ChangeContainerSlotState {
+            slot_id: 2,
+            window_id: 4,
+            state: true,
+        });
+        round_trip(EditBook {
+            slot: 0,
+            entries: vec!["page one".to_string(), "page two".to_string()],
+            title: Some("Diary".to_string()),
+        });
+        round_trip(PickItem { slot: 12 });
+        round_trip(PlaceRecipe {
+            window_id: 1,
+            recipe: identifier!("crafting_table"),
+            make_all: true,
+        });
+        round_trip(RenameItem {
+            name: "Excalibur".to_string(),
+        });
+        round_trip(SelectTrade { slot: 2 });
+        round_trip(SetBeaconEffect {
+            primary_effect: Some(1),
+            secondary_effect: None,
+        });
+        round_trip(SetHeldItem { slot: 8 });
+        round_trip(SetCreativeModeSlot {
+            slot: 40,
+            item: Some(item_stack()),
+        });
+        round_trip(SetCreativeModeSlot {
+            slot: -1,
+            item: None,
+        });
+    }
+
+    #[test]
+    fn test_world_interaction_packets() {
+        round_trip(QueryBlockEntityTag {
+            transaction_id: 1,
+            location: (-100, -64, 2000),
+        });
+        round_trip(QueryEntityTag {
+            transaction_id: 2,
+            entity_id: 77,
+        });
+        round_trip(Interact {
+            entity_id: 5,
+            action: InteractAction::Attack,
+            sneaking: false,
+        });
+        round_trip(Interact {
+            entity_id: 6,
+            action: InteractAction::InteractAt {
+                target_x: 0.5,
+                target_y: 1.25,
+                target_z: -0.5,
+                hand: Hand::OffHand,
+            },
+            sneaking: true,
+        });
+        round_trip(Interact {
+            entity_id: 7,
+            action: InteractAction::Interact {
+                hand: Hand::MainHand,
+            },
+            sneaking: false,
+        });
+        round_trip(PlayerAction {
+            status: PlayerActionStatus::FinishedDigging,
+            location: (10, 63, -10),
+            face: BlockFace::Top,
+            sequence: 31,
+        });
+        round_trip(SwingArm {
+            hand: Hand::MainHand,
+        });
+        round_trip(UseItemOn {
+            hand: Hand::MainHand,
+            location: (33554431, 2047, -33554432),
+            face: BlockFace::East,
+            cursor_x: 1.0,
+            cursor_y: 0.5,
+            cursor_z: 0.0,
+            inside_block: false,
+            sequence: 32,
+        });
+        round_trip(UseItem {
+            hand: Hand::OffHand,
+            sequence: 33,
+        });
+        round_trip(UpdateSign {
+            location: (1, 2, 3),
+            front_text: true,
+            line_1: "a".to_string(),
+            line_2: String::new(),
+            line_3: "c".to_string(),
+            line_4: "d".to_string(),
+        });
+    }
+
+    #[test]
+    fn test_block_programming_packets() {
+        round_trip(JigsawGenerate {
+            location: (0, 80, 0),
+            levels: 7,
+            keep_jigsaws: true,
+        });
+        round_trip(ProgramCommandBlock {
+            location: (5, 6, 7),
+            command: "say hi".to_string(),
+            mode: CommandBlockMode::Redstone,
+            flags: 0x04,
+        });
+        round_trip(ProgramCommandBlockMinecart {
+            entity_id: 9,
+            command: "say hi".to_string(),
+            track_output: true,
+        });
+        round_trip(ProgramJigsawBlock {
+            location: (8, 9, 10),
+            name: identifier!("bottom"),
+            target: identifier!("top"),
+            pool: identifier!("village/plains/houses"),
+            final_state: "minecraft:structure_void".to_string(),
+            joint_type: "rollable".to_string(),
+            selection_priority: 1,
+            placement_priority: -1,
+        });
+        round_trip(ProgramStructureBlock {
+            location: (-1, -2, -3),
+            action: StructureBlockAction::SaveStructure,
+            mode: StructureBlockMode::Save,
+            name: "house".to_string(),
+            offset_x: 0,
+            offset_y: 1,
+            offset_z: -1,
+            size_x: 16,
+            size_y: 8,
+            size_z: 48,
+            mirror: StructureMirror::FrontBack,
+            rotation: StructureRotation::Clockwise90,
+            metadata: String::new(),
+            integrity: 1.0,
+            seed: -987654321,
+            flags: 0x07,
+        });
+    }
+}
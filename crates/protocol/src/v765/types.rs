@@ -3,9 +3,15 @@ use std::io::{Read, Seek, Write};
 use serverx_common::{collections::bit_vec::BitVec, identifier::Identifier};
 use serverx_macros::{ProtoDecode, ProtoEncode};
 use serverx_nbt as nbt;
+use uuid::Uuid;
 
 use crate as protocol;
-use crate::types::*;
+use crate::{
+    decode::{AllocTracker, ProtoDecode, ProtoDecodeErr},
+    encode::{ProtoEncode, ProtoEncodeErr},
+    text::TextComponent,
+    types::*,
+};
 
 #[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
 #[proto(tag_repr = "VarInt")]
@@ -149,3 +155,1723 @@ pub enum GameEvent {
     LimitedCrafting,
     StartWaitingForLevelChunks,
 }
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct ItemStack {
+    #[proto(repr = "VarInt")]
+    pub item_id: i32,
+    pub count: i8,
+    #[proto(repr = "OptionalTagRoot")]
+    pub nbt: Option<nbt::Tag>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct ChangedSlot {
+    pub slot: i16,
+    pub item: Option<ItemStack>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct ArgumentSignature {
+    #[proto(max_len = 16)]
+    pub name: String,
+    #[proto(repr = "FixedBytes<256>")]
+    pub signature: [u8; 256],
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    MainHand,
+    OffHand,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum InteractAction {
+    Interact {
+        hand: Hand,
+    },
+    Attack,
+    InteractAt {
+        target_x: f32,
+        target_y: f32,
+        target_z: f32,
+        hand: Hand,
+    },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum ClientCommandAction {
+    PerformRespawn,
+    RequestStats,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum ClickMode {
+    Pickup,
+    QuickMove,
+    Swap,
+    Clone,
+    Throw,
+    QuickCraft,
+    PickupAll,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum PlayerActionStatus {
+    StartedDigging,
+    CancelledDigging,
+    FinishedDigging,
+    DropItemStack,
+    DropItem,
+    ShootArrowOrFinishEating,
+    SwapItemInHand,
+}
+
+// Faces are sent as a VarInt or a byte depending on the packet, both encode 0-5 identically.
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockFace {
+    Bottom,
+    Top,
+    North,
+    South,
+    West,
+    East,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum PlayerCommandAction {
+    StartSneaking,
+    StopSneaking,
+    LeaveBed,
+    StartSprinting,
+    StopSprinting,
+    StartHorseJump,
+    StopHorseJump,
+    OpenVehicleInventory,
+    StartFlyingWithElytra,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum RecipeBookType {
+    Crafting,
+    Furnace,
+    BlastFurnace,
+    Smoker,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum SeenAdvancementsAction {
+    OpenedTab { tab_id: Identifier },
+    ClosedScreen,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum CommandBlockMode {
+    Sequence,
+    Auto,
+    Redstone,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum StructureBlockAction {
+    UpdateData,
+    SaveStructure,
+    LoadStructure,
+    DetectSize,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum StructureBlockMode {
+    Save,
+    Load,
+    Corner,
+    Data,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum StructureMirror {
+    None,
+    LeftRight,
+    FrontBack,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum StructureRotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Counterclockwise90,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+#[proto(tag_repr = "u8")]
+pub enum EntityAnimationType {
+    #[proto(tag = 0)]
+    SwingMainArm,
+    #[proto(tag = 2)]
+    LeaveBed,
+    #[proto(tag = 3)]
+    SwingOffhand,
+    #[proto(tag = 4)]
+    CriticalEffect,
+    #[proto(tag = 5)]
+    MagicCriticalEffect,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct Statistic {
+    #[proto(repr = "VarInt")]
+    pub category_id: i32,
+    #[proto(repr = "VarInt")]
+    pub statistic_id: i32,
+    #[proto(repr = "VarInt")]
+    pub value: i32,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum BossBarColor {
+    Pink,
+    Blue,
+    Red,
+    Green,
+    Yellow,
+    Purple,
+    White,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum BossBarDivision {
+    NoDivision,
+    SixNotches,
+    TenNotches,
+    TwelveNotches,
+    TwentyNotches,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum BossBarAction {
+    Add {
+        title: TextComponent,
+        health: f32,
+        color: BossBarColor,
+        division: BossBarDivision,
+        flags: u8,
+    },
+    Remove,
+    UpdateHealth {
+        health: f32,
+    },
+    UpdateTitle {
+        title: TextComponent,
+    },
+    UpdateStyle {
+        color: BossBarColor,
+        division: BossBarDivision,
+    },
+    UpdateFlags {
+        flags: u8,
+    },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct ChunkBiomeData {
+    pub z: i32,
+    pub x: i32,
+    pub data: Vec<u8>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct CommandSuggestion {
+    #[proto(max_len = 32767)]
+    pub text: String,
+    pub tooltip: Option<TextComponent>,
+}
+
+/// A node of the graph sent in the Commands packet, children and redirects are indices into the
+/// list of nodes.
+#[derive(Debug, Clone)]
+pub struct CommandNode {
+    pub kind: CommandNodeKind,
+    pub executable: bool,
+    pub children: Vec<i32>,
+    pub redirect: Option<i32>,
+}
+
+#[derive(Debug, Clone)]
+pub enum CommandNodeKind {
+    Root,
+    Literal {
+        name: String,
+    },
+    Argument {
+        name: String,
+        parser: CommandParser,
+        /// Where the client gets completions from, like `minecraft:ask_server`.
+        suggestions: Option<Identifier>,
+    },
+}
+
+impl CommandNode {
+    pub const ARGUMENT: u8 = 0x02;
+    pub const EXECUTABLE: u8 = 0x04;
+    pub const HAS_REDIRECT: u8 = 0x08;
+    pub const HAS_SUGGESTIONS: u8 = 0x10;
+    pub const LITERAL: u8 = 0x01;
+    pub const ROOT: u8 = 0x00;
+    pub const TYPE_MASK: u8 = 0x03;
+}
+
+impl ProtoEncode for CommandNode {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        data.encode_mapped(writer, |id| id)
+    }
+}
+
+impl ProtoDecode for CommandNode {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        CommandNode::decode_mapped(reader, alloc_tracker, |id| id)
+    }
+}
+
+impl CommandNode {
+    /// Encodes the node with the parser ids of another version, `parser_id` maps the ids of this
+    /// one to them.
+    pub fn encode_mapped<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        parser_id: fn(i32) -> i32,
+    ) -> Result<(), ProtoEncodeErr> {
+        let mut flags = match &self.kind {
+            CommandNodeKind::Root => CommandNode::ROOT,
+            CommandNodeKind::Literal { .. } => CommandNode::LITERAL,
+            CommandNodeKind::Argument { suggestions, .. } => {
+                if suggestions.is_some() {
+                    CommandNode::ARGUMENT | CommandNode::HAS_SUGGESTIONS
+                } else {
+                    CommandNode::ARGUMENT
+                }
+            }
+        };
+        if self.executable {
+            flags |= CommandNode::EXECUTABLE;
+        }
+        if self.redirect.is_some() {
+            flags |= CommandNode::HAS_REDIRECT;
+        }
+        u8::encode(&flags, writer)?;
+        Vec::<VarInt>::encode(&self.children, writer)?;
+        if let Some(redirect) = self.redirect.as_ref() {
+            VarInt::encode(redirect, writer)?;
+        }
+        match &self.kind {
+            CommandNodeKind::Root => Ok(()),
+            CommandNodeKind::Literal { name } => String::encode(name, writer),
+            CommandNodeKind::Argument {
+                name,
+                parser,
+                suggestions,
+            } => {
+                String::encode(name, writer)?;
+                parser.encode_mapped(writer, parser_id)?;
+                if let Some(suggestions) = suggestions.as_ref() {
+                    Identifier::encode(suggestions, writer)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Decodes a node using the parser ids of another version, `parser_id` maps them to the ids of
+    /// this one.
+    pub fn decode_mapped<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+        parser_id: fn(i32) -> i32,
+    ) -> Result<Self, ProtoDecodeErr> {
+        let flags = u8::decode(reader, alloc_tracker)?;
+        let children = Vec::<VarInt>::decode(reader, alloc_tracker)?;
+        let redirect = if flags & CommandNode::HAS_REDIRECT != 0 {
+            Some(VarInt::decode(reader, alloc_tracker)?)
+        } else {
+            None
+        };
+        let kind = match flags & CommandNode::TYPE_MASK {
+            CommandNode::ROOT => CommandNodeKind::Root,
+            CommandNode::LITERAL => CommandNodeKind::Literal {
+                name: String::decode(reader, alloc_tracker)?,
+            },
+            CommandNode::ARGUMENT => CommandNodeKind::Argument {
+                name: String::decode(reader, alloc_tracker)?,
+                parser: CommandParser::decode_mapped(reader, alloc_tracker, parser_id)?,
+                suggestions: if flags & CommandNode::HAS_SUGGESTIONS != 0 {
+                    Some(Identifier::decode(reader, alloc_tracker)?)
+                } else {
+                    None
+                },
+            },
+            _ => return Err(ProtoDecodeErr::InvalidEnumTag),
+        };
+        Ok(Self {
+            kind,
+            executable: flags & CommandNode::EXECUTABLE != 0,
+            children,
+            redirect,
+        })
+    }
+}
+
+/// How a `brigadier:string` argument reads its value.
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StringKind {
+    SingleWord,
+    QuotablePhrase,
+    GreedyPhrase,
+}
+
+/// Decides how the client reads and highlights an argument, parsers with properties have a
+/// variant of their own.
+#[derive(Debug, Clone)]
+pub enum CommandParser {
+    Bool,
+    Float {
+        min: Option<f32>,
+        max: Option<f32>,
+    },
+    Double {
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    Integer {
+        min: Option<i32>,
+        max: Option<i32>,
+    },
+    Long {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    String(StringKind),
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    GameProfile,
+    BlockPos,
+    Message,
+    ScoreHolder {
+        allow_multiple: bool,
+    },
+    ResourceLocation,
+    Time {
+        min: i32,
+    },
+    ResourceOrTag {
+        registry: Identifier,
+    },
+    ResourceOrTagKey {
+        registry: Identifier,
+    },
+    Resource {
+        registry: Identifier,
+    },
+    ResourceKey {
+        registry: Identifier,
+    },
+    /// Any other parser by its id, none of them have properties.
+    Other(i32),
+}
+
+impl CommandParser {
+    pub const BLOCK_POS: i32 = 8;
+    pub const BOOL: i32 = 0;
+    pub const DOUBLE: i32 = 2;
+    pub const ENTITY: i32 = 6;
+    pub const FLOAT: i32 = 1;
+    pub const GAME_PROFILE: i32 = 7;
+    pub const INTEGER: i32 = 3;
+    pub const LONG: i32 = 4;
+    pub const MESSAGE: i32 = 19;
+    pub const RESOURCE: i32 = 44;
+    pub const RESOURCE_KEY: i32 = 45;
+    pub const RESOURCE_LOCATION: i32 = 34;
+    pub const RESOURCE_OR_TAG: i32 = 42;
+    pub const RESOURCE_OR_TAG_KEY: i32 = 43;
+    pub const SCORE_HOLDER: i32 = 30;
+    pub const STRING: i32 = 5;
+    pub const TIME: i32 = 41;
+
+    pub fn id(&self) -> i32 {
+        match self {
+            CommandParser::Bool => CommandParser::BOOL,
+            CommandParser::Float { .. } => CommandParser::FLOAT,
+            CommandParser::Double { .. } => CommandParser::DOUBLE,
+            CommandParser::Integer { .. } => CommandParser::INTEGER,
+            CommandParser::Long { .. } => CommandParser::LONG,
+            CommandParser::String(_) => CommandParser::STRING,
+            CommandParser::Entity { .. } => CommandParser::ENTITY,
+            CommandParser::GameProfile => CommandParser::GAME_PROFILE,
+            CommandParser::BlockPos => CommandParser::BLOCK_POS,
+            CommandParser::Message => CommandParser::MESSAGE,
+            CommandParser::ScoreHolder { .. } => CommandParser::SCORE_HOLDER,
+            CommandParser::ResourceLocation => CommandParser::RESOURCE_LOCATION,
+            CommandParser::Time { .. } => CommandParser::TIME,
+            CommandParser::ResourceOrTag { .. } => CommandParser::RESOURCE_OR_TAG,
+            CommandParser::ResourceOrTagKey { .. } => CommandParser::RESOURCE_OR_TAG_KEY,
+            CommandParser::Resource { .. } => CommandParser::RESOURCE,
+            CommandParser::ResourceKey { .. } => CommandParser::RESOURCE_KEY,
+            CommandParser::Other(id) => *id,
+        }
+    }
+}
+
+/// Bounds of the numeric parsers, prefixed by a byte telling which of them are present.
+fn encode_bounds<T: ProtoEncode<Repr = T>, W: Write + Seek>(
+    min: &Option<T>,
+    max: &Option<T>,
+    writer: &mut W,
+) -> Result<(), ProtoEncodeErr> {
+    let flags = u8::from(min.is_some()) | (u8::from(max.is_some()) << 1);
+    u8::encode(&flags, writer)?;
+    if let Some(min) = min.as_ref() {
+        T::encode(min, writer)?;
+    }
+    if let Some(max) = max.as_ref() {
+        T::encode(max, writer)?;
+    }
+    Ok(())
+}
+
+fn decode_bounds<T: ProtoDecode<Repr = T>, R: Read + Seek, A: AllocTracker>(
+    reader: &mut R,
+    alloc_tracker: &mut A,
+) -> Result<(Option<T>, Option<T>), ProtoDecodeErr> {
+    let flags = u8::decode(reader, alloc_tracker)?;
+    let min = if flags & 0x01 != 0 {
+        Some(T::decode(reader, alloc_tracker)?)
+    } else {
+        None
+    };
+    let max = if flags & 0x02 != 0 {
+        Some(T::decode(reader, alloc_tracker)?)
+    } else {
+        None
+    };
+    Ok((min, max))
+}
+
+impl ProtoEncode for CommandParser {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        data.encode_mapped(writer, |id| id)
+    }
+}
+
+impl ProtoDecode for CommandParser {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        CommandParser::decode_mapped(reader, alloc_tracker, |id| id)
+    }
+}
+
+impl CommandParser {
+    fn encode_mapped<W: Write + Seek>(
+        &self,
+        writer: &mut W,
+        parser_id: fn(i32) -> i32,
+    ) -> Result<(), ProtoEncodeErr> {
+        VarInt::encode(&parser_id(self.id()), writer)?;
+        match self {
+            CommandParser::Float { min, max } => encode_bounds(min, max, writer),
+            CommandParser::Double { min, max } => encode_bounds(min, max, writer),
+            CommandParser::Integer { min, max } => encode_bounds(min, max, writer),
+            CommandParser::Long { min, max } => encode_bounds(min, max, writer),
+            CommandParser::String(kind) => StringKind::encode(kind, writer),
+            CommandParser::Entity {
+                single,
+                players_only,
+            } => u8::encode(
+                &(u8::from(*single) | (u8::from(*players_only) << 1)),
+                writer,
+            ),
+            CommandParser::ScoreHolder { allow_multiple } => {
+                u8::encode(&u8::from(*allow_multiple), writer)
+            }
+            CommandParser::Time { min } => i32::encode(min, writer),
+            CommandParser::ResourceOrTag { registry }
+            | CommandParser::ResourceOrTagKey { registry }
+            | CommandParser::Resource { registry }
+            | CommandParser::ResourceKey { registry } => Identifier::encode(registry, writer),
+            _ => Ok(()),
+        }
+    }
+
+    fn decode_mapped<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+        parser_id: fn(i32) -> i32,
+    ) -> Result<Self, ProtoDecodeErr> {
+        let id = parser_id(VarInt::decode(reader, alloc_tracker)?);
+        Ok(match id {
+            CommandParser::BOOL => CommandParser::Bool,
+            CommandParser::FLOAT => {
+                let (min, max) = decode_bounds::<f32, _, _>(reader, alloc_tracker)?;
+                CommandParser::Float { min, max }
+            }
+            CommandParser::DOUBLE => {
+                let (min, max) = decode_bounds::<f64, _, _>(reader, alloc_tracker)?;
+                CommandParser::Double { min, max }
+            }
+            CommandParser::INTEGER => {
+                let (min, max) = decode_bounds::<i32, _, _>(reader, alloc_tracker)?;
+                CommandParser::Integer { min, max }
+            }
+            CommandParser::LONG => {
+                let (min, max) = decode_bounds::<i64, _, _>(reader, alloc_tracker)?;
+                CommandParser::Long { min, max }
+            }
+            CommandParser::STRING => {
+                CommandParser::String(StringKind::decode(reader, alloc_tracker)?)
+            }
+            CommandParser::ENTITY => {
+                let flags = u8::decode(reader, alloc_tracker)?;
+                CommandParser::Entity {
+                    single: flags & 0x01 != 0,
+                    players_only: flags & 0x02 != 0,
+                }
+            }
+            CommandParser::GAME_PROFILE => CommandParser::GameProfile,
+            CommandParser::BLOCK_POS => CommandParser::BlockPos,
+            CommandParser::MESSAGE => CommandParser::Message,
+            CommandParser::SCORE_HOLDER => CommandParser::ScoreHolder {
+                allow_multiple: u8::decode(reader, alloc_tracker)? & 0x01 != 0,
+            },
+            CommandParser::RESOURCE_LOCATION => CommandParser::ResourceLocation,
+            CommandParser::TIME => CommandParser::Time {
+                min: i32::decode(reader, alloc_tracker)?,
+            },
+            CommandParser::RESOURCE_OR_TAG => CommandParser::ResourceOrTag {
+                registry: Identifier::decode(reader, alloc_tracker)?,
+            },
+            CommandParser::RESOURCE_OR_TAG_KEY => CommandParser::ResourceOrTagKey {
+                registry: Identifier::decode(reader, alloc_tracker)?,
+            },
+            CommandParser::RESOURCE => CommandParser::Resource {
+                registry: Identifier::decode(reader, alloc_tracker)?,
+            },
+            CommandParser::RESOURCE_KEY => CommandParser::ResourceKey {
+                registry: Identifier::decode(reader, alloc_tracker)?,
+            },
+            id => CommandParser::Other(id),
+        })
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum ChatSuggestionsAction {
+    Add,
+    Remove,
+    Set,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct DamageSourcePosition {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// A reference to a previously sent message, either by its index in the client's message cache or
+/// by its full signature.
+#[derive(Debug, Clone)]
+pub enum MessageSignatureRef {
+    Id(i32),
+    Signature(Box<[u8; 256]>),
+}
+
+impl ProtoEncode for MessageSignatureRef {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match data {
+            MessageSignatureRef::Id(id) => VarInt::encode(&(id + 1), writer),
+            MessageSignatureRef::Signature(signature) => {
+                VarInt::encode(&0, writer)?;
+                FixedBytes::<256>::encode(signature, writer)
+            }
+        }
+    }
+}
+
+impl ProtoDecode for MessageSignatureRef {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        match VarInt::decode(reader, alloc_tracker)? {
+            0 => Ok(MessageSignatureRef::Signature(Box::new(
+                FixedBytes::<256>::decode(reader, alloc_tracker)?,
+            ))),
+            id => Ok(MessageSignatureRef::Id(id - 1)),
+        }
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum FilterType {
+    PassThrough,
+    FullyFiltered,
+    PartiallyFiltered { mask: BitVec },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct ExplosionRecord {
+    pub x: i8,
+    pub y: i8,
+    pub z: i8,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum ExplosionBlockInteraction {
+    Keep,
+    Destroy,
+    DestroyWithDecay,
+    TriggerBlock,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct SoundEvent {
+    pub name: Identifier,
+    pub fixed_range: Option<f32>,
+}
+
+/// A sound given either by its id in the sound event registry or inline.
+#[derive(Debug, Clone)]
+pub enum SoundRef {
+    Registry(i32),
+    Inline(SoundEvent),
+}
+
+impl ProtoEncode for SoundRef {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match data {
+            SoundRef::Registry(id) => VarInt::encode(&(id + 1), writer),
+            SoundRef::Inline(sound) => {
+                VarInt::encode(&0, writer)?;
+                SoundEvent::encode(sound, writer)
+            }
+        }
+    }
+}
+
+impl ProtoDecode for SoundRef {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        match VarInt::decode(reader, alloc_tracker)? {
+            0 => Ok(SoundRef::Inline(SoundEvent::decode(reader, alloc_tracker)?)),
+            id => Ok(SoundRef::Registry(id - 1)),
+        }
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundCategory {
+    Master,
+    Music,
+    Record,
+    Weather,
+    Block,
+    Hostile,
+    Neutral,
+    Player,
+    Ambient,
+    Voice,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum VibrationSource {
+    Block {
+        #[proto(repr = "Position")]
+        position: (i32, i32, i32),
+    },
+    Entity {
+        #[proto(repr = "VarInt")]
+        entity_id: i32,
+        eye_height: f32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum ParticleData {
+    None,
+    BlockState(i32),
+    Dust {
+        red: f32,
+        green: f32,
+        blue: f32,
+        scale: f32,
+    },
+    DustColorTransition {
+        from_red: f32,
+        from_green: f32,
+        from_blue: f32,
+        scale: f32,
+        to_red: f32,
+        to_green: f32,
+        to_blue: f32,
+    },
+    SculkCharge {
+        roll: f32,
+    },
+    Item(Option<ItemStack>),
+    Vibration {
+        source: VibrationSource,
+        ticks: i32,
+    },
+    Shriek {
+        delay: i32,
+    },
+}
+
+impl ParticleData {
+    pub fn encode<W: Write + Seek>(&self, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match self {
+            ParticleData::None => Ok(()),
+            ParticleData::BlockState(state) => VarInt::encode(state, writer),
+            ParticleData::Dust {
+                red,
+                green,
+                blue,
+                scale,
+            } => {
+                for val in [red, green, blue, scale] {
+                    f32::encode(val, writer)?;
+                }
+                Ok(())
+            }
+            ParticleData::DustColorTransition {
+                from_red,
+                from_green,
+                from_blue,
+                scale,
+                to_red,
+                to_green,
+                to_blue,
+            } => {
+                for val in [
+                    from_red, from_green, from_blue, scale, to_red, to_green, to_blue,
+                ] {
+                    f32::encode(val, writer)?;
+                }
+                Ok(())
+            }
+            ParticleData::SculkCharge { roll } => f32::encode(roll, writer),
+            ParticleData::Item(item) => Option::<ItemStack>::encode(item, writer),
+            ParticleData::Vibration { source, ticks } => {
+                VibrationSource::encode(source, writer)?;
+                VarInt::encode(ticks, writer)
+            }
+            ParticleData::Shriek { delay } => VarInt::encode(delay, writer),
+        }
+    }
+
+    /// Decodes the data that follows a particle of the given type, which is empty for most types.
+    pub fn decode<R: Read + Seek, A: AllocTracker>(
+        particle_id: i32,
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self, ProtoDecodeErr> {
+        match particle_id {
+            Particle::BLOCK | Particle::BLOCK_MARKER | Particle::FALLING_DUST => Ok(
+                ParticleData::BlockState(VarInt::decode(reader, alloc_tracker)?),
+            ),
+            Particle::DUST => Ok(ParticleData::Dust {
+                red: f32::decode(reader, alloc_tracker)?,
+                green: f32::decode(reader, alloc_tracker)?,
+                blue: f32::decode(reader, alloc_tracker)?,
+                scale: f32::decode(reader, alloc_tracker)?,
+            }),
+            Particle::DUST_COLOR_TRANSITION => Ok(ParticleData::DustColorTransition {
+                from_red: f32::decode(reader, alloc_tracker)?,
+                from_green: f32::decode(reader, alloc_tracker)?,
+                from_blue: f32::decode(reader, alloc_tracker)?,
+                scale: f32::decode(reader, alloc_tracker)?,
+                to_red: f32::decode(reader, alloc_tracker)?,
+                to_green: f32::decode(reader, alloc_tracker)?,
+                to_blue: f32::decode(reader, alloc_tracker)?,
+            }),
+            Particle::SCULK_CHARGE => Ok(ParticleData::SculkCharge {
+                roll: f32::decode(reader, alloc_tracker)?,
+            }),
+            Particle::ITEM => Ok(ParticleData::Item(Option::<ItemStack>::decode(
+                reader,
+                alloc_tracker,
+            )?)),
+            Particle::VIBRATION => Ok(ParticleData::Vibration {
+                source: VibrationSource::decode(reader, alloc_tracker)?,
+                ticks: VarInt::decode(reader, alloc_tracker)?,
+            }),
+            Particle::SHRIEK => Ok(ParticleData::Shriek {
+                delay: VarInt::decode(reader, alloc_tracker)?,
+            }),
+            _ => Ok(ParticleData::None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Particle {
+    pub id: i32,
+    pub data: ParticleData,
+}
+
+impl Particle {
+    pub const BLOCK: i32 = 2;
+    pub const BLOCK_MARKER: i32 = 3;
+    pub const DUST: i32 = 14;
+    pub const DUST_COLOR_TRANSITION: i32 = 15;
+    pub const EXPLOSION: i32 = 23;
+    pub const EXPLOSION_EMITTER: i32 = 22;
+    pub const FALLING_DUST: i32 = 27;
+    pub const ITEM: i32 = 42;
+    pub const SCULK_CHARGE: i32 = 33;
+    pub const SHRIEK: i32 = 96;
+    pub const VIBRATION: i32 = 43;
+
+    pub fn simple(id: i32) -> Self {
+        Self {
+            id,
+            data: ParticleData::None,
+        }
+    }
+}
+
+impl ProtoEncode for Particle {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        VarInt::encode(&data.id, writer)?;
+        data.data.encode(writer)
+    }
+}
+
+impl ProtoDecode for Particle {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let id = VarInt::decode(reader, alloc_tracker)?;
+        let data = ParticleData::decode(id, reader, alloc_tracker)?;
+        Ok(Self { id, data })
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct MapIcon {
+    #[proto(repr = "VarInt")]
+    pub icon_type: i32,
+    pub x: i8,
+    pub z: i8,
+    pub direction: i8,
+    pub display_name: Option<TextComponent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MapPatch {
+    pub columns: u8,
+    pub rows: u8,
+    pub x: u8,
+    pub z: u8,
+    pub data: Vec<u8>,
+}
+
+/// A map color patch, sent as a single zero byte when the map contents didn't change.
+pub struct OptionalMapPatch;
+
+impl ProtoEncode for OptionalMapPatch {
+    type Repr = Option<MapPatch>;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        match data {
+            Some(patch) if patch.columns > 0 => {
+                u8::encode(&patch.columns, writer)?;
+                u8::encode(&patch.rows, writer)?;
+                u8::encode(&patch.x, writer)?;
+                u8::encode(&patch.z, writer)?;
+                Vec::<u8>::encode(&patch.data, writer)
+            }
+            Some(_) => Err(ProtoEncodeErr::MalformedPacket),
+            None => u8::encode(&0, writer),
+        }
+    }
+}
+
+impl ProtoDecode for OptionalMapPatch {
+    type Repr = Option<MapPatch>;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let columns = u8::decode(reader, alloc_tracker)?;
+        if columns == 0 {
+            return Ok(None);
+        }
+        Ok(Some(MapPatch {
+            columns,
+            rows: u8::decode(reader, alloc_tracker)?,
+            x: u8::decode(reader, alloc_tracker)?,
+            z: u8::decode(reader, alloc_tracker)?,
+            data: Vec::<u8>::decode(reader, alloc_tracker)?,
+        }))
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct Trade {
+    pub input_item_1: Option<ItemStack>,
+    pub output_item: Option<ItemStack>,
+    pub input_item_2: Option<ItemStack>,
+    pub disabled: bool,
+    pub uses: i32,
+    pub max_uses: i32,
+    pub experience: i32,
+    pub special_price: i32,
+    pub price_multiplier: f32,
+    pub demand: i32,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct PlayerChatSession {
+    pub session_id: Uuid,
+    pub expires_at: i64,
+    #[proto(max_len = 512)]
+    pub public_key: Vec<u8>,
+    #[proto(max_len = 4096)]
+    pub key_signature: Vec<u8>,
+}
+
+/// A player list entry. Only the fields selected by the actions of the enclosing
+/// [`PlayerInfoUpdate`](crate::v765::clientbound::PlayerInfoUpdate) are sent.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerInfoEntry {
+    pub uuid: Uuid,
+    pub name: String,
+    pub properties: Vec<LoginProperty>,
+    pub chat_session: Option<PlayerChatSession>,
+    pub game_mode: i32,
+    pub listed: bool,
+    pub latency: i32,
+    pub display_name: Option<TextComponent>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum LookAtAnchor {
+    Feet,
+    Eyes,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct LookAtEntity {
+    #[proto(repr = "VarInt")]
+    pub entity_id: i32,
+    pub anchor: LookAtAnchor,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum RecipeBookAction {
+    Init,
+    Add,
+    Remove,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone, Default)]
+pub struct RecipeBookSettings {
+    pub crafting_open: bool,
+    pub crafting_filter_active: bool,
+    pub smelting_open: bool,
+    pub smelting_filter_active: bool,
+    pub blast_furnace_open: bool,
+    pub blast_furnace_filter_active: bool,
+    pub smoker_open: bool,
+    pub smoker_filter_active: bool,
+}
+
+/// A single block change within a chunk section, packed into a VarLong together with the
+/// section-relative coordinates.
+#[derive(Debug, Clone)]
+pub struct SectionBlock {
+    pub block_state: i32,
+    pub x: u8,
+    pub y: u8,
+    pub z: u8,
+}
+
+impl ProtoEncode for SectionBlock {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        let val = ((data.block_state as i64) << 12)
+            | ((data.x as i64 & 0xf) << 8)
+            | ((data.z as i64 & 0xf) << 4)
+            | (data.y as i64 & 0xf);
+        VarLong::encode(&val, writer)
+    }
+}
+
+impl ProtoDecode for SectionBlock {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let val = VarLong::decode(reader, alloc_tracker)?;
+        Ok(Self {
+            block_state: (val >> 12) as i32,
+            x: ((val >> 8) & 0xf) as u8,
+            y: (val & 0xf) as u8,
+            z: ((val >> 4) & 0xf) as u8,
+        })
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum EntityPose {
+    Standing,
+    FallFlying,
+    Sleeping,
+    Swimming,
+    SpinAttack,
+    Sneaking,
+    LongJumping,
+    Dying,
+    Croaking,
+    UsingTongue,
+    Sitting,
+    Roaring,
+    Sniffing,
+    Emerging,
+    Digging,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum MetadataValue {
+    Byte(i8),
+    VarInt(#[proto(repr = "VarInt")] i32),
+    VarLong(#[proto(repr = "VarLong")] i64),
+    Float(f32),
+    String(String),
+    TextComponent(TextComponent),
+    OptionalTextComponent(Option<TextComponent>),
+    Slot(Option<ItemStack>),
+    Boolean(bool),
+    Rotation(f32, f32, f32),
+    Position(#[proto(repr = "Position")] (i32, i32, i32)),
+    OptionalPosition(#[proto(repr = "Option<Position>")] Option<(i32, i32, i32)>),
+    Direction(BlockFace),
+    OptionalUuid(Option<Uuid>),
+    BlockState(#[proto(repr = "VarInt")] i32),
+    /// A block state id where 0 (air) means absent.
+    OptionalBlockState(#[proto(repr = "VarInt")] i32),
+    Nbt(#[proto(repr = "OptionalTagRoot")] Option<nbt::Tag>),
+    Particle(Particle),
+    VillagerData {
+        #[proto(repr = "VarInt")]
+        villager_type: i32,
+        #[proto(repr = "VarInt")]
+        profession: i32,
+        #[proto(repr = "VarInt")]
+        level: i32,
+    },
+    OptionalVarInt(#[proto(repr = "OptionalVarInt")] Option<i32>),
+    Pose(EntityPose),
+    CatVariant(#[proto(repr = "VarInt")] i32),
+    FrogVariant(#[proto(repr = "VarInt")] i32),
+    OptionalGlobalPosition(Option<DeathLocation>),
+    PaintingVariant(#[proto(repr = "VarInt")] i32),
+    SnifferState(#[proto(repr = "VarInt")] i32),
+    Vector3(f32, f32, f32),
+    Quaternion(f32, f32, f32, f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataEntry {
+    pub index: u8,
+    pub value: MetadataValue,
+}
+
+/// Entity metadata entries, terminated by an index of `0xff`.
+pub struct EntityMetadata;
+
+impl EntityMetadata {
+    pub const END: u8 = 0xff;
+    pub const MAX_ENTRIES: usize = 256;
+}
+
+impl ProtoEncode for EntityMetadata {
+    type Repr = Vec<MetadataEntry>;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        for entry in data {
+            if entry.index == EntityMetadata::END {
+                return Err(ProtoEncodeErr::MalformedPacket);
+            }
+            u8::encode(&entry.index, writer)?;
+            MetadataValue::encode(&entry.value, writer)?;
+        }
+        u8::encode(&EntityMetadata::END, writer)
+    }
+}
+
+impl ProtoDecode for EntityMetadata {
+    type Repr = Vec<MetadataEntry>;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let mut entries = Vec::new();
+        loop {
+            let index = u8::decode(reader, alloc_tracker)?;
+            if index == EntityMetadata::END {
+                return Ok(entries);
+            }
+            if entries.len() == EntityMetadata::MAX_ENTRIES {
+                return Err(ProtoDecodeErr::SeqTooLong(
+                    entries.len() + 1,
+                    EntityMetadata::MAX_ENTRIES,
+                ));
+            }
+            alloc_tracker.alloc(std::mem::size_of::<MetadataEntry>())?;
+            let value = MetadataValue::decode(reader, alloc_tracker)?;
+            entries.push(MetadataEntry { index, value });
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EquipmentSlot {
+    MainHand,
+    OffHand,
+    Boots,
+    Leggings,
+    Chestplate,
+    Helmet,
+}
+
+impl TryFrom<u8> for EquipmentSlot {
+    type Error = ProtoDecodeErr;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(EquipmentSlot::MainHand),
+            1 => Ok(EquipmentSlot::OffHand),
+            2 => Ok(EquipmentSlot::Boots),
+            3 => Ok(EquipmentSlot::Leggings),
+            4 => Ok(EquipmentSlot::Chestplate),
+            5 => Ok(EquipmentSlot::Helmet),
+            _ => Err(ProtoDecodeErr::InvalidEnumTag),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EquipmentEntry {
+    pub slot: EquipmentSlot,
+    pub item: Option<ItemStack>,
+}
+
+/// Equipment entries, where the top bit of each slot byte signals that another entry follows.
+pub struct Equipment;
+
+impl ProtoEncode for Equipment {
+    type Repr = Vec<EquipmentEntry>;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        if data.is_empty() {
+            return Err(ProtoEncodeErr::SeqTooShort(0, 1));
+        }
+        for (i, entry) in data.iter().enumerate() {
+            let more = if i + 1 < data.len() { 0x80 } else { 0 };
+            u8::encode(&(entry.slot as u8 | more), writer)?;
+            Option::<ItemStack>::encode(&entry.item, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl ProtoDecode for Equipment {
+    type Repr = Vec<EquipmentEntry>;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let mut entries = Vec::new();
+        loop {
+            let slot = u8::decode(reader, alloc_tracker)?;
+            if entries.len() == 6 {
+                return Err(ProtoDecodeErr::SeqTooLong(entries.len() + 1, 6));
+            }
+            alloc_tracker.alloc(std::mem::size_of::<EquipmentEntry>())?;
+            entries.push(EquipmentEntry {
+                slot: EquipmentSlot::try_from(slot & 0x7f)?,
+                item: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+            });
+            if slot & 0x80 == 0 {
+                return Ok(entries);
+            }
+        }
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum NumberFormat {
+    Blank,
+    Styled {
+        #[proto(repr = "nbt::TagRoot")]
+        styling: nbt::Tag,
+    },
+    Fixed {
+        content: TextComponent,
+    },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum ObjectiveType {
+    Integer,
+    Hearts,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+#[proto(tag_repr = "i8")]
+pub enum ObjectiveAction {
+    Create {
+        value: TextComponent,
+        objective_type: ObjectiveType,
+        number_format: Option<NumberFormat>,
+    },
+    Remove,
+    Update {
+        value: TextComponent,
+        objective_type: ObjectiveType,
+        number_format: Option<NumberFormat>,
+    },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct TeamInfo {
+    pub display_name: TextComponent,
+    pub friendly_flags: i8,
+    #[proto(max_len = 40)]
+    pub name_tag_visibility: String,
+    #[proto(max_len = 40)]
+    pub collision_rule: String,
+    #[proto(repr = "VarInt")]
+    pub color: i32,
+    pub prefix: TextComponent,
+    pub suffix: TextComponent,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+#[proto(tag_repr = "i8")]
+pub enum TeamAction {
+    Create {
+        info: TeamInfo,
+        entities: Vec<String>,
+    },
+    Remove,
+    UpdateInfo {
+        info: TeamInfo,
+    },
+    AddEntities {
+        entities: Vec<String>,
+    },
+    RemoveEntities {
+        entities: Vec<String>,
+    },
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct AttributeModifier {
+    pub uuid: Uuid,
+    pub amount: f64,
+    pub operation: i8,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct AttributeProperty {
+    pub key: Identifier,
+    pub value: f64,
+    pub modifiers: Vec<AttributeModifier>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum AdvancementFrame {
+    Task,
+    Challenge,
+    Goal,
+}
+
+#[derive(Debug, Clone)]
+pub struct AdvancementDisplay {
+    pub title: TextComponent,
+    pub description: TextComponent,
+    pub icon: Option<ItemStack>,
+    pub frame: AdvancementFrame,
+    pub flags: i32,
+    pub background_texture: Option<Identifier>,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl AdvancementDisplay {
+    pub const HAS_BACKGROUND: i32 = 0x01;
+    pub const HIDDEN: i32 = 0x04;
+    pub const SHOW_TOAST: i32 = 0x02;
+}
+
+impl ProtoEncode for AdvancementDisplay {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        TextComponent::encode(&data.title, writer)?;
+        TextComponent::encode(&data.description, writer)?;
+        Option::<ItemStack>::encode(&data.icon, writer)?;
+        AdvancementFrame::encode(&data.frame, writer)?;
+        let mut flags = data.flags & !AdvancementDisplay::HAS_BACKGROUND;
+        if data.background_texture.is_some() {
+            flags |= AdvancementDisplay::HAS_BACKGROUND;
+        }
+        i32::encode(&flags, writer)?;
+        if let Some(background_texture) = data.background_texture.as_ref() {
+            Identifier::encode(background_texture, writer)?;
+        }
+        f32::encode(&data.x, writer)?;
+        f32::encode(&data.y, writer)
+    }
+}
+
+impl ProtoDecode for AdvancementDisplay {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let title = TextComponent::decode(reader, alloc_tracker)?;
+        let description = TextComponent::decode(reader, alloc_tracker)?;
+        let icon = Option::<ItemStack>::decode(reader, alloc_tracker)?;
+        let frame = AdvancementFrame::decode(reader, alloc_tracker)?;
+        let flags = i32::decode(reader, alloc_tracker)?;
+        let background_texture = if flags & AdvancementDisplay::HAS_BACKGROUND != 0 {
+            Some(Identifier::decode(reader, alloc_tracker)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            title,
+            description,
+            icon,
+            frame,
+            flags,
+            background_texture,
+            x: f32::decode(reader, alloc_tracker)?,
+            y: f32::decode(reader, alloc_tracker)?,
+        })
+    }
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct Advancement {
+    pub parent: Option<Identifier>,
+    pub display: Option<AdvancementDisplay>,
+    pub requirements: Vec<Vec<String>>,
+    pub sends_telemetry: bool,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct AdvancementMapping {
+    pub key: Identifier,
+    pub value: Advancement,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct CriterionProgress {
+    pub criterion: Identifier,
+    pub achieved_at: Option<i64>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct AdvancementProgress {
+    pub advancement: Identifier,
+    pub criteria: Vec<CriterionProgress>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum CraftingCategory {
+    Building,
+    Redstone,
+    Equipment,
+    Misc,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum CookingCategory {
+    Food,
+    Blocks,
+    Misc,
+}
+
+/// The item stacks accepted by a recipe slot.
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct Ingredient {
+    pub items: Vec<Option<ItemStack>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum RecipeData {
+    Shapeless {
+        group: String,
+        category: CraftingCategory,
+        ingredients: Vec<Ingredient>,
+        result: Option<ItemStack>,
+    },
+    Shaped {
+        group: String,
+        category: CraftingCategory,
+        width: i32,
+        height: i32,
+        /// `width * height` ingredients, row by row.
+        ingredients: Vec<Ingredient>,
+        result: Option<ItemStack>,
+        show_notification: bool,
+    },
+    Special {
+        category: CraftingCategory,
+    },
+    Cooking {
+        group: String,
+        category: CookingCategory,
+        ingredient: Ingredient,
+        result: Option<ItemStack>,
+        experience: f32,
+        cooking_time: i32,
+    },
+    Stonecutting {
+        group: String,
+        ingredient: Ingredient,
+        result: Option<ItemStack>,
+    },
+    SmithingTransform {
+        template: Ingredient,
+        base: Ingredient,
+        addition: Ingredient,
+        result: Option<ItemStack>,
+    },
+    SmithingTrim {
+        template: Ingredient,
+        base: Ingredient,
+        addition: Ingredient,
+    },
+}
+
+/// A recipe, whose data layout is selected by its serializer type.
+#[derive(Debug, Clone)]
+pub struct Recipe {
+    pub recipe_type: Identifier,
+    pub id: Identifier,
+    pub data: RecipeData,
+}
+
+impl ProtoEncode for Recipe {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        Identifier::encode(&data.recipe_type, writer)?;
+        Identifier::encode(&data.id, writer)?;
+        match &data.data {
+            RecipeData::Shapeless {
+                group,
+                category,
+                ingredients,
+                result,
+            } => {
+                String::encode(group, writer)?;
+                CraftingCategory::encode(category, writer)?;
+                Vec::<Ingredient>::encode(ingredients, writer)?;
+                Option::<ItemStack>::encode(result, writer)
+            }
+            RecipeData::Shaped {
+                group,
+                category,
+                width,
+                height,
+                ingredients,
+                result,
+                show_notification,
+            } => {
+                if usize::try_from(width * height).ok() != Some(ingredients.len()) {
+                    return Err(ProtoEncodeErr::MalformedPacket);
+                }
+                String::encode(group, writer)?;
+                CraftingCategory::encode(category, writer)?;
+                VarInt::encode(width, writer)?;
+                VarInt::encode(height, writer)?;
+                for ingredient in ingredients {
+                    Ingredient::encode(ingredient, writer)?;
+                }
+                Option::<ItemStack>::encode(result, writer)?;
+                bool::encode(show_notification, writer)
+            }
+            RecipeData::Special { category } => CraftingCategory::encode(category, writer),
+            RecipeData::Cooking {
+                group,
+                category,
+                ingredient,
+                result,
+                experience,
+                cooking_time,
+            } => {
+                String::encode(group, writer)?;
+                CookingCategory::encode(category, writer)?;
+                Ingredient::encode(ingredient, writer)?;
+                Option::<ItemStack>::encode(result, writer)?;
+                f32::encode(experience, writer)?;
+                VarInt::encode(cooking_time, writer)
+            }
+            RecipeData::Stonecutting {
+                group,
+                ingredient,
+                result,
+            } => {
+                String::encode(group, writer)?;
+                Ingredient::encode(ingredient, writer)?;
+                Option::<ItemStack>::encode(result, writer)
+            }
+            RecipeData::SmithingTransform {
+                template,
+                base,
+                addition,
+                result,
+            } => {
+                Ingredient::encode(template, writer)?;
+                Ingredient::encode(base, writer)?;
+                Ingredient::encode(addition, writer)?;
+                Option::<ItemStack>::encode(result, writer)
+            }
+            RecipeData::SmithingTrim {
+                template,
+                base,
+                addition,
+            } => {
+                Ingredient::encode(template, writer)?;
+                Ingredient::encode(base, writer)?;
+                Ingredient::encode(addition, writer)
+            }
+        }
+    }
+}
+
+impl ProtoDecode for Recipe {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        let recipe_type = Identifier::decode(reader, alloc_tracker)?;
+        let id = Identifier::decode(reader, alloc_tracker)?;
+        let data = match recipe_type.path() {
+            "crafting_shapeless" => RecipeData::Shapeless {
+                group: String::decode(reader, alloc_tracker)?,
+                category: CraftingCategory::decode(reader, alloc_tracker)?,
+                ingredients: Vec::<Ingredient>::decode(reader, alloc_tracker)?,
+                result: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+            },
+            "crafting_shaped" => {
+                let group = String::decode(reader, alloc_tracker)?;
+                let category = CraftingCategory::decode(reader, alloc_tracker)?;
+                let width = VarInt::decode(reader, alloc_tracker)?;
+                let height = VarInt::decode(reader, alloc_tracker)?;
+                let count = usize::try_from(width)
+                    .ok()
+                    .zip(usize::try_from(height).ok())
+                    .and_then(|(width, height)| width.checked_mul(height))
+                    .filter(|count| *count <= MAX_VEC_LEN)
+                    .ok_or(ProtoDecodeErr::MalformedPacket)?;
+                alloc_tracker.alloc(count * std::mem::size_of::<Ingredient>())?;
+                let mut ingredients = Vec::with_capacity(count);
+                for _ in 0..count {
+                    ingredients.push(Ingredient::decode(reader, alloc_tracker)?);
+                }
+                RecipeData::Shaped {
+                    group,
+                    category,
+                    width,
+                    height,
+                    ingredients,
+                    result: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+                    show_notification: bool::decode(reader, alloc_tracker)?,
+                }
+            }
+            "smelting" | "blasting" | "smoking" | "campfire_cooking" => RecipeData::Cooking {
+                group: String::decode(reader, alloc_tracker)?,
+                category: CookingCategory::decode(reader, alloc_tracker)?,
+                ingredient: Ingredient::decode(reader, alloc_tracker)?,
+                result: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+                experience: f32::decode(reader, alloc_tracker)?,
+                cooking_time: VarInt::decode(reader, alloc_tracker)?,
+            },
+            "stonecutting" => RecipeData::Stonecutting {
+                group: String::decode(reader, alloc_tracker)?,
+                ingredient: Ingredient::decode(reader, alloc_tracker)?,
+                result: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+            },
+            "smithing_transform" => RecipeData::SmithingTransform {
+                template: Ingredient::decode(reader, alloc_tracker)?,
+                base: Ingredient::decode(reader, alloc_tracker)?,
+                addition: Ingredient::decode(reader, alloc_tracker)?,
+                result: Option::<ItemStack>::decode(reader, alloc_tracker)?,
+            },
+            "smithing_trim" => RecipeData::SmithingTrim {
+                template: Ingredient::decode(reader, alloc_tracker)?,
+                base: Ingredient::decode(reader, alloc_tracker)?,
+                addition: Ingredient::decode(reader, alloc_tracker)?,
+            },
+            path if path.starts_with("crafting_special_") || path == "crafting_decorated_pot" => {
+                RecipeData::Special {
+                    category: CraftingCategory::decode(reader, alloc_tracker)?,
+                }
+            }
+            _ => return Err(ProtoDecodeErr::MalformedPacket),
+        };
+        Ok(Self {
+            recipe_type,
+            id,
+            data,
+        })
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    any::Any,
+    io::{Read, Seek, Write},
+};
+
+use serverx_common::identifier::Identifier;
+use serverx_macros::{Packet, ProtoDecode, ProtoEncode};
+use uuid::Uuid;
+
+use crate as protocol;
+use crate::{
+    text::TextComponent,
+    types::*,
+    v765::types::{DeathLocation, GameMode, LastGameMode, LoginProperty},
+    v766::types::*,
+};
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x01, ClientBound, Login)]
+pub struct EncryptionRequest {
+    #[proto(max_len = 20)]
+    pub server_id: String,
+    pub public_key: Vec<u8>,
+    #[proto(exact_len = 4)]
+    pub verify_token: Vec<u8>,
+    pub should_authenticate: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x02, ClientBound, Login)]
+pub struct LoginSuccess {
+    pub uuid: Uuid,
+    #[proto(max_len = 16)]
+    pub username: String,
+    pub properties: Vec<LoginProperty>,
+    pub strict_error_handling: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x05, ClientBound, Login)]
+pub struct LoginCookieRequest {
+    pub key: Identifier,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x00, ClientBound, Configuration)]
+pub struct ConfigCookieRequest {
+    pub key: Identifier,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x06, ClientBound, Configuration)]
+pub struct ResetChat;
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x07, ClientBound, Configuration)]
+pub struct RegistryData {
+    pub registry: Identifier,
+    pub entries: Vec<RegistryDataEntry>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0A, ClientBound, Configuration)]
+pub struct ConfigStoreCookie {
+    pub key: Identifier,
+    #[proto(max_len = 5120)]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0B, ClientBound, Configuration)]
+pub struct ConfigTransfer {
+    pub host: String,
+    #[proto(repr = "VarInt")]
+    pub port: i32,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x0E, ClientBound, Configuration)]
+pub struct ClientBoundKnownPacks {
+    pub packs: Vec<KnownPack>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x11, ClientBound, Play)]
+pub struct Commands {
+    pub nodes: Vec<CommandNode>,
+    #[proto(repr = "VarInt")]
+    pub root_index: i32,
+}
+
+impl From<crate::v765::clientbound::Commands> for Commands {
+    fn from(commands: crate::v765::clientbound::Commands) -> Self {
+        Self {
+            nodes: commands.nodes.into_iter().map(CommandNode).collect(),
+            root_index: commands.root_index,
+        }
+    }
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x16, ClientBound, Play)]
+pub struct CookieRequest {
+    pub key: Identifier,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x1B, ClientBound, Play)]
+pub struct DebugSample {
+    pub sample: Vec<i64>,
+    pub sample_type: DebugSampleType,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x2B, ClientBound, Play)]
+pub struct GameJoin {
+    pub entity_id: i32,
+    pub is_hardcore: bool,
+    pub dimensions: Vec<Identifier>,
+    #[proto(repr = "VarInt")]
+    pub max_players: i32,
+    #[proto(repr = "VarInt")]
+    pub view_distance: i32,
+    #[proto(repr = "VarInt")]
+    pub sim_distance: i32,
+    pub reduced_debug: bool,
+    pub enable_respawn: bool,
+    pub limited_crafting: bool,
+    #[proto(repr = "VarInt")]
+    pub dimension_type: i32,
+    pub dimension_name: Identifier,
+    pub seed: i64,
+    pub game_mode: GameMode,
+    pub last_game_mode: LastGameMode,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    #[proto(repr = "VarInt")]
+    pub portal_cooldown: i32,
+    pub enforces_secure_chat: bool,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x47, ClientBound, Play)]
+pub struct Respawn {
+    #[proto(repr = "VarInt")]
+    pub dimension_type: i32,
+    pub dimension_name: Identifier,
+    pub hashed_seed: i64,
+    pub game_mode: GameMode,
+    pub previous_game_mode: LastGameMode,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    #[proto(repr = "VarInt")]
+    pub portal_cooldown: i32,
+    pub data_kept: u8,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x4B, ClientBound, Play)]
+pub struct ServerData {
+    pub motd: TextComponent,
+    pub icon: Option<Vec<u8>>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x6B, ClientBound, Play)]
+pub struct StoreCookie {
+    pub key: Identifier,
+    #[proto(max_len = 5120)]
+    pub payload: Vec<u8>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x73, ClientBound, Play)]
+pub struct Transfer {
+    pub host: String,
+    #[proto(repr = "VarInt")]
+    pub port: i32,
+}
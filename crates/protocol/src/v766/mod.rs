@@ -0,0 +1,1026 @@
+use std::io::{Read, Seek, Write};
+
+use crate as protocol;
+use crate::{
+    decode::{AllocTracker, ProtoDecodeErr},
+    encode::ProtoEncodeErr,
+    packet::{
+        decode_mapped_packet_impl, encode_mapped_packet_impl, mapped_packet_id_impl,
+        ConnectionState, DecodedPacket, Packet, PacketDecoder, PacketDirection, PacketEncoder,
+    },
+    v765,
+    v765::{clientbound::*, serverbound::*},
+};
+pub const PROTO_NAME: &str = "1.20.5";
+pub const PROTO_VER: i32 = 766;
+
+pub mod clientbound;
+pub mod serverbound;
+pub mod types;
+
+// 1.20.5 renumbers most packets but keeps the layout of many of them, those are shared with v765
+// and mapped to their new ids here. Packets carrying item stacks or particles changed their layout
+// and are not supported yet.
+
+pub struct PacketEncoderImpl;
+
+impl PacketEncoder for PacketEncoderImpl {
+    fn packet_id(
+        packet: &dyn Packet,
+        direction: PacketDirection,
+        state: ConnectionState,
+    ) -> Option<i32> {
+        match direction {
+            PacketDirection::ClientBound => match state {
+                ConnectionState::Handshake => {
+                    mapped_packet_id_impl!(packet,)
+                }
+                ConnectionState::Status => {
+                    mapped_packet_id_impl!(packet, StatusResponse = 0x00, StatusPingResponse = 0x01)
+                }
+                ConnectionState::Login => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        LoginDisconnect = 0x00,
+                        clientbound::EncryptionRequest = 0x01,
+                        clientbound::LoginSuccess = 0x02,
+                        SetCompression = 0x03,
+                        LoginPluginRequest = 0x04,
+                        clientbound::LoginCookieRequest = 0x05
+                    )
+                }
+                ConnectionState::Play => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        BundleDelimiter = 0x00,
+                        SpawnEntity = 0x01,
+                        SpawnExperienceOrb = 0x02,
+                        EntityAnimation = 0x03,
+                        AwardStatistics = 0x04,
+                        AcknowledgeBlockChange = 0x05,
+                        SetBlockDestroyStage = 0x06,
+                        BlockEntityData = 0x07,
+                        BlockAction = 0x08,
+                        BlockUpdate = 0x09,
+                        BossBar = 0x0A,
+                        v765::clientbound::ChangeDifficulty = 0x0B,
+                        ChunkBatchFinish = 0x0C,
+                        ChunkBatchStart = 0x0D,
+                        ChunkBiomes = 0x0E,
+                        ClearTitles = 0x0F,
+                        CommandSuggestionsResponse = 0x10,
+                        clientbound::Commands = 0x11,
+                        v765::clientbound::CloseContainer = 0x12,
+                        SetContainerProperty = 0x14,
+                        clientbound::CookieRequest = 0x16,
+                        SetCooldown = 0x17,
+                        ChatSuggestions = 0x18,
+                        ClientBoundPluginMessage = 0x19,
+                        DamageEvent = 0x1A,
+                        clientbound::DebugSample = 0x1B,
+                        DeleteMessage = 0x1C,
+                        Disconnect = 0x1D,
+                        EntityEvent = 0x1F,
+                        UnloadChunk = 0x21,
+                        ServerGameEvent = 0x22,
+                        OpenHorseScreen = 0x23,
+                        HurtAnimation = 0x24,
+                        InitializeWorldBorder = 0x25,
+                        v765::clientbound::KeepAlive = 0x26,
+                        ChunkDataAndLight = 0x27,
+                        WorldEvent = 0x28,
+                        UpdateLight = 0x2A,
+                        clientbound::GameJoin = 0x2B,
+                        MapData = 0x2C,
+                        UpdateEntityPosition = 0x2E,
+                        UpdateEntityPositionAndRotation = 0x2F,
+                        UpdateEntityRotation = 0x30,
+                        v765::clientbound::MoveVehicle = 0x31,
+                        OpenBook = 0x32,
+                        OpenScreen = 0x33,
+                        OpenSignEditor = 0x34,
+                        Ping = 0x35,
+                        PingResponse = 0x36,
+                        PlaceGhostRecipe = 0x37,
+                        v765::clientbound::PlayerAbilities = 0x38,
+                        EndCombat = 0x3A,
+                        EnterCombat = 0x3B,
+                        CombatDeath = 0x3C,
+                        PlayerInfoRemove = 0x3D,
+                        PlayerInfoUpdate = 0x3E,
+                        LookAt = 0x3F,
+                        SyncPlayerPosition = 0x40,
+                        UpdateRecipeBook = 0x41,
+                        RemoveEntities = 0x42,
+                        RemoveEntityEffect = 0x43,
+                        ResetScore = 0x44,
+                        RemoveResourcePack = 0x45,
+                        AddResourcePack = 0x46,
+                        clientbound::Respawn = 0x47,
+                        SetHeadRotation = 0x48,
+                        UpdateSectionBlocks = 0x49,
+                        SelectAdvancementsTab = 0x4A,
+                        clientbound::ServerData = 0x4B,
+                        SetActionBarText = 0x4C,
+                        SetBorderCenter = 0x4D,
+                        SetBorderLerpSize = 0x4E,
+                        SetBorderSize = 0x4F,
+                        SetBorderWarningDelay = 0x50,
+                        SetBorderWarningDistance = 0x51,
+                        SetCamera = 0x52,
+                        v765::clientbound::SetHeldItem = 0x53,
+                        SetCenterChunk = 0x54,
+                        SetRenderDistance = 0x55,
+                        DefaultSpawnPosition = 0x56,
+                        DisplayObjective = 0x57,
+                        LinkEntities = 0x59,
+                        SetEntityVelocity = 0x5A,
+                        SetExperience = 0x5C,
+                        SetHealth = 0x5D,
+                        UpdateObjectives = 0x5E,
+                        SetPassengers = 0x5F,
+                        UpdateTeams = 0x60,
+                        UpdateScore = 0x61,
+                        SetSimulationDistance = 0x62,
+                        SetSubtitleText = 0x63,
+                        UpdateTime = 0x64,
+                        SetTitleText = 0x65,
+                        SetTitleAnimationTimes = 0x66,
+                        EntitySoundEffect = 0x67,
+                        SoundEffect = 0x68,
+                        StartConfiguration = 0x69,
+                        StopSound = 0x6A,
+                        clientbound::StoreCookie = 0x6B,
+                        SystemChatMessage = 0x6C,
+                        SetTabListHeaderAndFooter = 0x6D,
+                        TagQueryResponse = 0x6E,
+                        PickupItem = 0x6F,
+                        TeleportEntity = 0x70,
+                        SetTickingState = 0x71,
+                        StepTick = 0x72,
+                        clientbound::Transfer = 0x73,
+                        PlayUpdateTags = 0x78
+                    )
+                }
+                ConnectionState::Configuration => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        clientbound::ConfigCookieRequest = 0x00,
+                        ConfigClientBoundPluginMessage = 0x01,
+                        ConfigDisconnect = 0x02,
+                        ServerFinishConfiguration = 0x03,
+                        v765::clientbound::ConfigKeepAlive = 0x04,
+                        ConfigPing = 0x05,
+                        clientbound::ResetChat = 0x06,
+                        clientbound::RegistryData = 0x07,
+                        ConfigRemoveResourcePack = 0x08,
+                        ConfigAddResourcePack = 0x09,
+                        clientbound::ConfigStoreCookie = 0x0A,
+                        clientbound::ConfigTransfer = 0x0B,
+                        FeatureFlags = 0x0C,
+                        UpdateTags = 0x0D,
+                        clientbound::ClientBoundKnownPacks = 0x0E
+                    )
+                }
+            },
+            PacketDirection::ServerBound => match state {
+                ConnectionState::Handshake => {
+                    mapped_packet_id_impl!(packet, HandshakeRequest = 0x00)
+                }
+                ConnectionState::Status => {
+                    mapped_packet_id_impl!(packet, StatusRequest = 0x00, StatusPingRequest = 0x01)
+                }
+                ConnectionState::Login => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        LoginStart = 0x00,
+                        EncryptionResponse = 0x01,
+                        LoginPluginResponse = 0x02,
+                        LoginAck = 0x03,
+                        serverbound::LoginCookieResponse = 0x04
+                    )
+                }
+                ConnectionState::Play => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        ConfirmTeleportation = 0x00,
+                        QueryBlockEntityTag = 0x01,
+                        v765::serverbound::ChangeDifficulty = 0x02,
+                        AcknowledgeMessage = 0x03,
+                        serverbound::ChatCommand = 0x04,
+                        ChatCommand = 0x05,
+                        ChatMessage = 0x06,
+                        PlayerSession = 0x07,
+                        ChunkBatchReceived = 0x08,
+                        ClientCommand = 0x09,
+                        ClientInformation = 0x0A,
+                        CommandSuggestionsRequest = 0x0B,
+                        ConfigurationAck = 0x0C,
+                        ClickContainerButton = 0x0D,
+                        v765::serverbound::CloseContainer = 0x0F,
+                        ChangeContainerSlotState = 0x10,
+                        serverbound::CookieResponse = 0x11,
+                        ServerBoundPluginMessage = 0x12,
+                        serverbound::DebugSampleSubscription = 0x13,
+                        EditBook = 0x14,
+                        QueryEntityTag = 0x15,
+                        Interact = 0x16,
+                        JigsawGenerate = 0x17,
+                        v765::serverbound::KeepAlive = 0x18,
+                        LockDifficulty = 0x19,
+                        SetPlayerPosition = 0x1A,
+                        SetPlayerPositionAndRotation = 0x1B,
+                        SetPlayerRotation = 0x1C,
+                        SetPlayerOnGround = 0x1D,
+                        v765::serverbound::MoveVehicle = 0x1E,
+                        PaddleBoat = 0x1F,
+                        PickItem = 0x20,
+                        PingRequest = 0x21,
+                        PlaceRecipe = 0x22,
+                        v765::serverbound::PlayerAbilities = 0x23,
+                        PlayerAction = 0x24,
+                        PlayerCommand = 0x25,
+                        PlayerInput = 0x26,
+                        Pong = 0x27,
+                        ChangeRecipeBookSettings = 0x28,
+                        SetSeenRecipe = 0x29,
+                        RenameItem = 0x2A,
+                        ResourcePackResponse = 0x2B,
+                        SeenAdvancements = 0x2C,
+                        SelectTrade = 0x2D,
+                        SetBeaconEffect = 0x2E,
+                        v765::serverbound::SetHeldItem = 0x2F,
+                        ProgramCommandBlock = 0x30,
+                        ProgramCommandBlockMinecart = 0x31,
+                        ProgramJigsawBlock = 0x33,
+                        ProgramStructureBlock = 0x34,
+                        UpdateSign = 0x35,
+                        SwingArm = 0x36,
+                        TeleportToEntity = 0x37,
+                        UseItemOn = 0x38,
+                        UseItem = 0x39
+                    )
+                }
+                ConnectionState::Configuration => {
+                    mapped_packet_id_impl!(
+                        packet,
+                        ConfigClientInformation = 0x00,
+                        serverbound::ConfigCookieResponse = 0x01,
+                        ConfigServerBoundPluginMessage = 0x02,
+                        ClientFinishConfiguration = 0x03,
+                        v765::serverbound::ConfigKeepAlive = 0x04,
+                        ConfigPong = 0x05,
+                        ConfigResourcePackResponse = 0x06,
+                        serverbound::ServerBoundKnownPacks = 0x07
+                    )
+                }
+            },
+        }
+    }
+
+    fn encode_packet<W: Write + Seek>(
+        packet: &dyn Packet,
+        id: i32,
+        direction: PacketDirection,
+        state: ConnectionState,
+        writer: &mut W,
+    ) -> Result<(), ProtoEncodeErr> {
+        match direction {
+            PacketDirection::ClientBound => match state {
+                ConnectionState::Handshake => {
+                    encode_mapped_packet_impl!(packet, id, writer,)
+                }
+                ConnectionState::Status => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        StatusResponse = 0x00,
+                        StatusPingResponse = 0x01
+                    )
+                }
+                ConnectionState::Login => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        LoginDisconnect = 0x00,
+                        clientbound::EncryptionRequest = 0x01,
+                        clientbound::LoginSuccess = 0x02,
+                        SetCompression = 0x03,
+                        LoginPluginRequest = 0x04,
+                        clientbound::LoginCookieRequest = 0x05
+                    )
+                }
+                ConnectionState::Play => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        BundleDelimiter = 0x00,
+                        SpawnEntity = 0x01,
+                        SpawnExperienceOrb = 0x02,
+                        EntityAnimation = 0x03,
+                        AwardStatistics = 0x04,
+                        AcknowledgeBlockChange = 0x05,
+                        SetBlockDestroyStage = 0x06,
+                        BlockEntityData = 0x07,
+                        BlockAction = 0x08,
+                        BlockUpdate = 0x09,
+                        BossBar = 0x0A,
+                        v765::clientbound::ChangeDifficulty = 0x0B,
+                        ChunkBatchFinish = 0x0C,
+                        ChunkBatchStart = 0x0D,
+                        ChunkBiomes = 0x0E,
+                        ClearTitles = 0x0F,
+                        CommandSuggestionsResponse = 0x10,
+                        clientbound::Commands = 0x11,
+                        v765::clientbound::CloseContainer = 0x12,
+                        SetContainerProperty = 0x14,
+                        clientbound::CookieRequest = 0x16,
+                        SetCooldown = 0x17,
+                        ChatSuggestions = 0x18,
+                        ClientBoundPluginMessage = 0x19,
+                        DamageEvent = 0x1A,
+                        clientbound::DebugSample = 0x1B,
+                        DeleteMessage = 0x1C,
+                        Disconnect = 0x1D,
+                        EntityEvent = 0x1F,
+                        UnloadChunk = 0x21,
+                        ServerGameEvent = 0x22,
+                        OpenHorseScreen = 0x23,
+                        HurtAnimation = 0x24,
+                        InitializeWorldBorder = 0x25,
+                        v765::clientbound::KeepAlive = 0x26,
+                        ChunkDataAndLight = 0x27,
+                        WorldEvent = 0x28,
+                        UpdateLight = 0x2A,
+                        clientbound::GameJoin = 0x2B,
+                        MapData = 0x2C,
+                        UpdateEntityPosition = 0x2E,
+                        UpdateEntityPositionAndRotation = 0x2F,
+                        UpdateEntityRotation = 0x30,
+                        v765::clientbound::MoveVehicle = 0x31,
+                        OpenBook = 0x32,
+                        OpenScreen = 0x33,
+                        OpenSignEditor = 0x34,
+                        Ping = 0x35,
+                        PingResponse = 0x36,
+                        PlaceGhostRecipe = 0x37,
+                        v765::clientbound::PlayerAbilities = 0x38,
+                        EndCombat = 0x3A,
+                        EnterCombat = 0x3B,
+                        CombatDeath = 0x3C,
+                        PlayerInfoRemove = 0x3D,
+                        PlayerInfoUpdate = 0x3E,
+                        LookAt = 0x3F,
+                        SyncPlayerPosition = 0x40,
+                        UpdateRecipeBook = 0x41,
+                        RemoveEntities = 0x42,
+                        RemoveEntityEffect = 0x43,
+                        ResetScore = 0x44,
+                        RemoveResourcePack = 0x45,
+                        AddResourcePack = 0x46,
+                        clientbound::Respawn = 0x47,
+                        SetHeadRotation = 0x48,
+                        UpdateSectionBlocks = 0x49,
+                        SelectAdvancementsTab = 0x4A,
+                        clientbound::ServerData = 0x4B,
+                        SetActionBarText = 0x4C,
+                        SetBorderCenter = 0x4D,
+                        SetBorderLerpSize = 0x4E,
+                        SetBorderSize = 0x4F,
+                        SetBorderWarningDelay = 0x50,
+                        SetBorderWarningDistance = 0x51,
+                        SetCamera = 0x52,
+                        v765::clientbound::SetHeldItem = 0x53,
+                        SetCenterChunk = 0x54,
+                        SetRenderDistance = 0x55,
+                        DefaultSpawnPosition = 0x56,
+                        DisplayObjective = 0x57,
+                        LinkEntities = 0x59,
+                        SetEntityVelocity = 0x5A,
+                        SetExperience = 0x5C,
+                        SetHealth = 0x5D,
+                        UpdateObjectives = 0x5E,
+                        SetPassengers = 0x5F,
+                        UpdateTeams = 0x60,
+                        UpdateScore = 0x61,
+                        SetSimulationDistance = 0x62,
+                        SetSubtitleText = 0x63,
+                        UpdateTime = 0x64,
+                        SetTitleText = 0x65,
+                        SetTitleAnimationTimes = 0x66,
+                        EntitySoundEffect = 0x67,
+                        SoundEffect = 0x68,
+                        StartConfiguration = 0x69,
+                        StopSound = 0x6A,
+                        clientbound::StoreCookie = 0x6B,
+                        SystemChatMessage = 0x6C,
+                        SetTabListHeaderAndFooter = 0x6D,
+                        TagQueryResponse = 0x6E,
+                        PickupItem = 0x6F,
+                        TeleportEntity = 0x70,
+                        SetTickingState = 0x71,
+                        StepTick = 0x72,
+                        clientbound::Transfer = 0x73,
+                        PlayUpdateTags = 0x78
+                    )
+                }
+                ConnectionState::Configuration => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        clientbound::ConfigCookieRequest = 0x00,
+                        ConfigClientBoundPluginMessage = 0x01,
+                        ConfigDisconnect = 0x02,
+                        ServerFinishConfiguration = 0x03,
+                        v765::clientbound::ConfigKeepAlive = 0x04,
+                        ConfigPing = 0x05,
+                        clientbound::ResetChat = 0x06,
+                        clientbound::RegistryData = 0x07,
+                        ConfigRemoveResourcePack = 0x08,
+                        ConfigAddResourcePack = 0x09,
+                        clientbound::ConfigStoreCookie = 0x0A,
+                        clientbound::ConfigTransfer = 0x0B,
+                        FeatureFlags = 0x0C,
+                        UpdateTags = 0x0D,
+                        clientbound::ClientBoundKnownPacks = 0x0E
+                    )
+                }
+            },
+            PacketDirection::ServerBound => match state {
+                ConnectionState::Handshake => {
+                    encode_mapped_packet_impl!(packet, id, writer, HandshakeRequest = 0x00)
+                }
+                ConnectionState::Status => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        StatusRequest = 0x00,
+                        StatusPingRequest = 0x01
+                    )
+                }
+                ConnectionState::Login => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        LoginStart = 0x00,
+                        EncryptionResponse = 0x01,
+                        LoginPluginResponse = 0x02,
+                        LoginAck = 0x03,
+                        serverbound::LoginCookieResponse = 0x04
+                    )
+                }
+                ConnectionState::Play => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        ConfirmTeleportation = 0x00,
+                        QueryBlockEntityTag = 0x01,
+                        v765::serverbound::ChangeDifficulty = 0x02,
+                        AcknowledgeMessage = 0x03,
+                        serverbound::ChatCommand = 0x04,
+                        ChatCommand = 0x05,
+                        ChatMessage = 0x06,
+                        PlayerSession = 0x07,
+                        ChunkBatchReceived = 0x08,
+                        ClientCommand = 0x09,
+                        ClientInformation = 0x0A,
+                        CommandSuggestionsRequest = 0x0B,
+                        ConfigurationAck = 0x0C,
+                        ClickContainerButton = 0x0D,
+                        v765::serverbound::CloseContainer = 0x0F,
+                        ChangeContainerSlotState = 0x10,
+                        serverbound::CookieResponse = 0x11,
+                        ServerBoundPluginMessage = 0x12,
+                        serverbound::DebugSampleSubscription = 0x13,
+                        EditBook = 0x14,
+                        QueryEntityTag = 0x15,
+                        Interact = 0x16,
+                        JigsawGenerate = 0x17,
+                        v765::serverbound::KeepAlive = 0x18,
+                        LockDifficulty = 0x19,
+                        SetPlayerPosition = 0x1A,
+                        SetPlayerPositionAndRotation = 0x1B,
+                        SetPlayerRotation = 0x1C,
+                        SetPlayerOnGround = 0x1D,
+                        v765::serverbound::MoveVehicle = 0x1E,
+                        PaddleBoat = 0x1F,
+                        PickItem = 0x20,
+                        PingRequest = 0x21,
+                        PlaceRecipe = 0x22,
+                        v765::serverbound::PlayerAbilities = 0x23,
+                        PlayerAction = 0x24,
+                        PlayerCommand = 0x25,
+                        PlayerInput = 0x26,
+                        Pong = 0x27,
+                        ChangeRecipeBookSettings = 0x28,
+                        SetSeenRecipe = 0x29,
+                        RenameItem = 0x2A,
+                        ResourcePackResponse = 0x2B,
+                        SeenAdvancements = 0x2C,
+                        SelectTrade = 0x2D,
+                        SetBeaconEffect = 0x2E,
+                        v765::serverbound::SetHeldItem = 0x2F,
+                        ProgramCommandBlock = 0x30,
+                        ProgramCommandBlockMinecart = 0x31,
+                        ProgramJigsawBlock = 0x33,
+                        ProgramStructureBlock = 0x34,
+                        UpdateSign = 0x35,
+                        SwingArm = 0x36,
+                        TeleportToEntity = 0x37,
+                        UseItemOn = 0x38,
+                        UseItem = 0x39
+                    )
+                }
+                ConnectionState::Configuration => {
+                    encode_mapped_packet_impl!(
+                        packet,
+                        id,
+                        writer,
+                        ConfigClientInformation = 0x00,
+                        serverbound::ConfigCookieResponse = 0x01,
+                        ConfigServerBoundPluginMessage = 0x02,
+                        ClientFinishConfiguration = 0x03,
+                        v765::serverbound::ConfigKeepAlive = 0x04,
+                        ConfigPong = 0x05,
+                        ConfigResourcePackResponse = 0x06,
+                        serverbound::ServerBoundKnownPacks = 0x07
+                    )
+                }
+            },
+        }
+    }
+}
+
+pub struct PacketDecoderImpl;
+
+impl PacketDecoder for PacketDecoderImpl {
+    fn decode_as<P: DecodedPacket, R: Read + Seek, T: AllocTracker>(
+        id: i32,
+        direction: PacketDirection,
+        state: ConnectionState,
+        reader: &mut R,
+        alloc_tracker: &mut T,
+    ) -> Result<P, ProtoDecodeErr> {
+        match direction {
+            PacketDirection::ClientBound => match state {
+                ConnectionState::Handshake => {
+                    decode_mapped_packet_impl!(id, reader, alloc_tracker,)
+                }
+                ConnectionState::Status => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        StatusResponse = 0x00,
+                        StatusPingResponse = 0x01
+                    )
+                }
+                ConnectionState::Login => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        LoginDisconnect = 0x00,
+                        clientbound::EncryptionRequest = 0x01,
+                        clientbound::LoginSuccess = 0x02,
+                        SetCompression = 0x03,
+                        LoginPluginRequest = 0x04,
+                        clientbound::LoginCookieRequest = 0x05
+                    )
+                }
+                ConnectionState::Play => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        BundleDelimiter = 0x00,
+                        SpawnEntity = 0x01,
+                        SpawnExperienceOrb = 0x02,
+                        EntityAnimation = 0x03,
+                        AwardStatistics = 0x04,
+                        AcknowledgeBlockChange = 0x05,
+                        SetBlockDestroyStage = 0x06,
+                        BlockEntityData = 0x07,
+                        BlockAction = 0x08,
+                        BlockUpdate = 0x09,
+                        BossBar = 0x0A,
+                        v765::clientbound::ChangeDifficulty = 0x0B,
+                        ChunkBatchFinish = 0x0C,
+                        ChunkBatchStart = 0x0D,
+                        ChunkBiomes = 0x0E,
+                        ClearTitles = 0x0F,
+                        CommandSuggestionsResponse = 0x10,
+                        clientbound::Commands = 0x11,
+                        v765::clientbound::CloseContainer = 0x12,
+                        SetContainerProperty = 0x14,
+                        clientbound::CookieRequest = 0x16,
+                        SetCooldown = 0x17,
+                        ChatSuggestions = 0x18,
+                        ClientBoundPluginMessage = 0x19,
+                        DamageEvent = 0x1A,
+                        clientbound::DebugSample = 0x1B,
+                        DeleteMessage = 0x1C,
+                        Disconnect = 0x1D,
+                        EntityEvent = 0x1F,
+                        UnloadChunk = 0x21,
+                        ServerGameEvent = 0x22,
+                        OpenHorseScreen = 0x23,
+                        HurtAnimation = 0x24,
+                        InitializeWorldBorder = 0x25,
+                        v765::clientbound::KeepAlive = 0x26,
+                        ChunkDataAndLight = 0x27,
+                        WorldEvent = 0x28,
+                        UpdateLight = 0x2A,
+                        clientbound::GameJoin = 0x2B,
+                        MapData = 0x2C,
+                        UpdateEntityPosition = 0x2E,
+                        UpdateEntityPositionAndRotation = 0x2F,
+                        UpdateEntityRotation = 0x30,
+                        v765::clientbound::MoveVehicle = 0x31,
+                        OpenBook = 0x32,
+                        OpenScreen = 0x33,
+                        OpenSignEditor = 0x34,
+                        Ping = 0x35,
+                        PingResponse = 0x36,
+                        PlaceGhostRecipe = 0x37,
+                        v765::clientbound::PlayerAbilities = 0x38,
+                        EndCombat = 0x3A,
+                        EnterCombat = 0x3B,
+                        CombatDeath = 0x3C,
+                        PlayerInfoRemove = 0x3D,
+                        PlayerInfoUpdate = 0x3E,
+                        LookAt = 0x3F,
+                        SyncPlayerPosition = 0x40,
+                        UpdateRecipeBook = 0x41,
+                        RemoveEntities = 0x42,
+                        RemoveEntityEffect = 0x43,
+                        ResetScore = 0x44,
+                        RemoveResourcePack = 0x45,
+                        AddResourcePack = 0x46,
+                        clientbound::Respawn = 0x47,
+                        SetHeadRotation = 0x48,
+                        UpdateSectionBlocks = 0x49,
+                        SelectAdvancementsTab = 0x4A,
+                        clientbound::ServerData = 0x4B,
+                        SetActionBarText = 0x4C,
+                        SetBorderCenter = 0x4D,
+                        SetBorderLerpSize = 0x4E,
+                        SetBorderSize = 0x4F,
+                        SetBorderWarningDelay = 0x50,
+                        SetBorderWarningDistance = 0x51,
+                        SetCamera = 0x52,
+                        v765::clientbound::SetHeldItem = 0x53,
+                        SetCenterChunk = 0x54,
+                        SetRenderDistance = 0x55,
+                        DefaultSpawnPosition = 0x56,
+                        DisplayObjective = 0x57,
+                        LinkEntities = 0x59,
+                        SetEntityVelocity = 0x5A,
+                        SetExperience = 0x5C,
+                        SetHealth = 0x5D,
+                        UpdateObjectives = 0x5E,
+                        SetPassengers = 0x5F,
+                        UpdateTeams = 0x60,
+                        UpdateScore = 0x61,
+                        SetSimulationDistance = 0x62,
+                        SetSubtitleText = 0x63,
+                        UpdateTime = 0x64,
+                        SetTitleText = 0x65,
+                        SetTitleAnimationTimes = 0x66,
+                        EntitySoundEffect = 0x67,
+                        SoundEffect = 0x68,
+                        StartConfiguration = 0x69,
+                        StopSound = 0x6A,
+                        clientbound::StoreCookie = 0x6B,
+                        SystemChatMessage = 0x6C,
+                        SetTabListHeaderAndFooter = 0x6D,
+                        TagQueryResponse = 0x6E,
+                        PickupItem = 0x6F,
+                        TeleportEntity = 0x70,
+                        SetTickingState = 0x71,
+                        StepTick = 0x72,
+                        clientbound::Transfer = 0x73,
+                        PlayUpdateTags = 0x78
+                    )
+                }
+                ConnectionState::Configuration => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        clientbound::ConfigCookieRequest = 0x00,
+                        ConfigClientBoundPluginMessage = 0x01,
+                        ConfigDisconnect = 0x02,
+                        ServerFinishConfiguration = 0x03,
+                        v765::clientbound::ConfigKeepAlive = 0x04,
+                        ConfigPing = 0x05,
+                        clientbound::ResetChat = 0x06,
+                        clientbound::RegistryData = 0x07,
+                        ConfigRemoveResourcePack = 0x08,
+                        ConfigAddResourcePack = 0x09,
+                        clientbound::ConfigStoreCookie = 0x0A,
+                        clientbound::ConfigTransfer = 0x0B,
+                        FeatureFlags = 0x0C,
+                        UpdateTags = 0x0D,
+                        clientbound::ClientBoundKnownPacks = 0x0E
+                    )
+                }
+            },
+            PacketDirection::ServerBound => match state {
+                ConnectionState::Handshake => {
+                    decode_mapped_packet_impl!(id, reader, alloc_tracker, HandshakeRequest = 0x00)
+                }
+                ConnectionState::Status => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        StatusRequest = 0x00,
+                        StatusPingRequest = 0x01
+                    )
+                }
+                ConnectionState::Login => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        LoginStart = 0x00,
+                        EncryptionResponse = 0x01,
+                        LoginPluginResponse = 0x02,
+                        LoginAck = 0x03,
+                        serverbound::LoginCookieResponse = 0x04
+                    )
+                }
+                ConnectionState::Play => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        ConfirmTeleportation = 0x00,
+                        QueryBlockEntityTag = 0x01,
+                        v765::serverbound::ChangeDifficulty = 0x02,
+                        AcknowledgeMessage = 0x03,
+                        serverbound::ChatCommand = 0x04,
+                        ChatCommand = 0x05,
+                        ChatMessage = 0x06,
+                        PlayerSession = 0x07,
+                        ChunkBatchReceived = 0x08,
+                        ClientCommand = 0x09,
+                        ClientInformation = 0x0A,
+                        CommandSuggestionsRequest = 0x0B,
+                        ConfigurationAck = 0x0C,
+                        ClickContainerButton = 0x0D,
+                        v765::serverbound::CloseContainer = 0x0F,
+                        ChangeContainerSlotState = 0x10,
+                        serverbound::CookieResponse = 0x11,
+                        ServerBoundPluginMessage = 0x12,
+                        serverbound::DebugSampleSubscription = 0x13,
+                        EditBook = 0x14,
+                        QueryEntityTag = 0x15,
+                        Interact = 0x16,
+                        JigsawGenerate = 0x17,
+                        v765::serverbound::KeepAlive = 0x18,
+                        LockDifficulty = 0x19,
+                        SetPlayerPosition = 0x1A,
+                        SetPlayerPositionAndRotation = 0x1B,
+                        SetPlayerRotation = 0x1C,
+                        SetPlayerOnGround = 0x1D,
+                        v765::serverbound::MoveVehicle = 0x1E,
+                        PaddleBoat = 0x1F,
+                        PickItem = 0x20,
+                        PingRequest = 0x21,
+                        PlaceRecipe = 0x22,
+                        v765::serverbound::PlayerAbilities = 0x23,
+                        PlayerAction = 0x24,
+                        PlayerCommand = 0x25,
+                        PlayerInput = 0x26,
+                        Pong = 0x27,
+                        ChangeRecipeBookSettings = 0x28,
+                        SetSeenRecipe = 0x29,
+                        RenameItem = 0x2A,
+                        ResourcePackResponse = 0x2B,
+                        SeenAdvancements = 0x2C,
+                        SelectTrade = 0x2D,
+                        SetBeaconEffect = 0x2E,
+                        v765::serverbound::SetHeldItem = 0x2F,
+                        ProgramCommandBlock = 0x30,
+                        ProgramCommandBlockMinecart = 0x31,
+                        ProgramJigsawBlock = 0x33,
+                        ProgramStructureBlock = 0x34,
+                        UpdateSign = 0x35,
+                        SwingArm = 0x36,
+                        TeleportToEntity = 0x37,
+                        UseItemOn = 0x38,
+                        UseItem = 0x39
+                    )
+                }
+                ConnectionState::Configuration => {
+                    decode_mapped_packet_impl!(
+                        id,
+                        reader,
+                        alloc_tracker,
+                        ConfigClientInformation = 0x00,
+                        serverbound::ConfigCookieResponse = 0x01,
+                        ConfigServerBoundPluginMessage = 0x02,
+                        ClientFinishConfiguration = 0x03,
+                        v765::serverbound::ConfigKeepAlive = 0x04,
+                        ConfigPong = 0x05,
+                        ConfigResourcePackResponse = 0x06,
+                        serverbound::ServerBoundKnownPacks = 0x07
+                    )
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use serverx_common::identifier;
+    use serverx_macros::identifier;
+
+    use crate::{
+        decode::BasicAllocTracker,
+        encode::ProtoEncodeErr,
+        packet::{
+            ConnectionState,
+            ConnectionState::{Configuration, Login, Play},
+            Packet, PacketDecoder, PacketDirection,
+            PacketDirection::{ClientBound, ServerBound},
+            PacketEncoder,
+        },
+        v765,
+        v765::types::{CommandNode, CommandNodeKind, CommandParser},
+        v766::{clientbound, serverbound, types::KnownPack, PacketDecoderImpl, PacketEncoderImpl},
+    };
+
+    fn encode(packet: &dyn Packet, direction: PacketDirection, state: ConnectionState) -> Vec<u8> {
+        let id = PacketEncoderImpl::packet_id(packet, direction, state).unwrap();
+        let mut cursor = Cursor::new(vec![id as u8]);
+        cursor.set_position(1);
+        PacketEncoderImpl::encode_packet(packet, id, direction, state, &mut cursor).unwrap();
+        cursor.into_inner()
+    }
+
+    fn decode(buf: &[u8], direction: PacketDirection, state: ConnectionState) -> Box<dyn Packet> {
+        let mut reader = Cursor::new(&buf[1..]);
+        let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+        let packet = PacketDecoderImpl::decode_packet(
+            buf[0] as i32,
+            direction,
+            state,
+            &mut reader,
+            &mut alloc_tracker,
+        )
+        .unwrap();
+        assert_eq!(reader.position() as usize, buf.len() - 1);
+        packet
+    }
+
+    #[test]
+    fn test_shared_packets_are_renumbered() {
+        let set_cooldown = v765::clientbound::SetCooldown {
+            item_id: 10,
+            cooldown_ticks: 20,
+        };
+        let buf = encode(&set_cooldown, ClientBound, Play);
+        assert_eq!(buf, vec![0x17, 0x0a, 0x14]);
+        let decoded = decode(buf.as_slice(), ClientBound, Play);
+        let decoded = decoded
+            .as_any()
+            .downcast_ref::<v765::clientbound::SetCooldown>()
+            .unwrap();
+        assert_eq!(decoded.cooldown_ticks, 20);
+
+        let buf = encode(
+            &v765::clientbound::ServerFinishConfiguration,
+            ClientBound,
+            Configuration,
+        );
+        assert_eq!(buf, vec![0x03]);
+        let buf = encode(&v765::serverbound::ConfigurationAck, ServerBound, Play);
+        assert_eq!(buf, vec![0x0c]);
+
+        // signed commands keep the 1.20.4 layout under a new id
+        let signed_command = v765::serverbound::ChatCommand {
+            command: "tp".to_string(),
+            timestamp: 1,
+            salt: 2,
+            argument_signatures: vec![],
+            message_count: 0,
+            acknowledged: [0; 3],
+        };
+        let buf = encode(&signed_command, ServerBound, Play);
+        assert_eq!(buf[0], 0x05);
+        assert!(decode(buf.as_slice(), ServerBound, Play)
+            .as_any()
+            .is::<v765::serverbound::ChatCommand>());
+    }
+
+    #[test]
+    fn test_version_specific_packets() {
+        let buf = encode(
+            &serverbound::ChatCommand {
+                command: "tp".to_string(),
+            },
+            ServerBound,
+            Play,
+        );
+        assert_eq!(buf, vec![0x04, 0x02, b't', b'p']);
+
+        let login_success = clientbound::LoginSuccess {
+            uuid: uuid::Uuid::nil(),
+            username: "Notch".to_string(),
+            properties: vec![],
+            strict_error_handling: true,
+        };
+        let buf = encode(&login_success, ClientBound, Login);
+        assert_eq!(buf[0], 0x02);
+        assert_eq!(buf[buf.len() - 2..], [0x00, 0x01]);
+
+        let known_packs = clientbound::ClientBoundKnownPacks {
+            packs: vec![KnownPack {
+                namespace: "minecraft".to_string(),
+                id: "core".to_string(),
+                version: "1.20.5".to_string(),
+            }],
+        };
+        let buf = encode(&known_packs, ClientBound, Configuration);
+        assert_eq!(buf[0], 0x0e);
+        let decoded = decode(buf.as_slice(), ClientBound, Configuration);
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", known_packs));
+
+        let registry_data = clientbound::RegistryData {
+            registry: identifier!("damage_type"),
+            entries: vec![],
+        };
+        let buf = encode(&registry_data, ClientBound, Configuration);
+        assert_eq!(buf[0], 0x07);
+
+        let commands = clientbound::Commands::from(v765::clientbound::Commands {
+            nodes: vec![
+                CommandNode {
+                    kind: CommandNodeKind::Root,
+                    executable: false,
+                    children: vec![1],
+                    redirect: None,
+                },
+                CommandNode {
+                    kind: CommandNodeKind::Argument {
+                        name: "id".to_string(),
+                        parser: CommandParser::ResourceLocation,
+                        suggestions: None,
+                    },
+                    executable: true,
+                    children: vec![],
+                    redirect: None,
+                },
+            ],
+            root_index: 0,
+        });
+        let buf = encode(&commands, ClientBound, Play);
+        // resource_location moved from 34 to 35
+        assert_eq!(buf, vec![
+            0x11, 0x02, 0x00, 0x01, 0x01, 0x06, 0x00, 0x02, b'i', b'd', 0x23, 0x00
+        ]);
+        let decoded = decode(buf.as_slice(), ClientBound, Play);
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", commands));
+    }
+
+    #[test]
+    fn test_changed_packets_are_rejected() {
+        let set_container_slot = v765::clientbound::SetContainerSlot {
+            window_id: 0,
+            state_id: 0,
+            slot: 0,
+            item: None,
+        };
+        assert_eq!(
+            PacketEncoderImpl::packet_id(&set_container_slot, ClientBound, Play),
+            None
+        );
+        let login_success = v765::clientbound::LoginSuccess {
+            uuid: uuid::Uuid::nil(),
+            username: "Notch".to_string(),
+            properties: vec![],
+        };
+        assert_eq!(
+            PacketEncoderImpl::packet_id(&login_success, ClientBound, Login),
+            None
+        );
+        let mut cursor = Cursor::new(Vec::new());
+        assert!(matches!(
+            PacketEncoderImpl::encode_packet(&login_success, 0x02, ClientBound, Login, &mut cursor),
+            Err(ProtoEncodeErr::MalformedPacket)
+        ));
+    }
+}
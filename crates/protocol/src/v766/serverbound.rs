@@ -0,0 +1,51 @@
+use std::{
+    any::Any,
+    io::{Read, Seek, Write},
+};
+
+use serverx_common::identifier::Identifier;
+use serverx_macros::{Packet, ProtoDecode, ProtoEncode};
+
+use crate as protocol;
+use crate::v766::types::*;
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x04, ServerBound, Login)]
+pub struct LoginCookieResponse {
+    pub key: Identifier,
+    pub payload: Option<Vec<u8>>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x01, ServerBound, Configuration)]
+pub struct ConfigCookieResponse {
+    pub key: Identifier,
+    pub payload: Option<Vec<u8>>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x07, ServerBound, Configuration)]
+pub struct ServerBoundKnownPacks {
+    pub packs: Vec<KnownPack>,
+}
+
+/// An unsigned command, signed commands are sent with the layout 1.20.4 used for every command.
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x04, ServerBound, Play)]
+pub struct ChatCommand {
+    #[proto(max_len = 32767)]
+    pub command: String,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x11, ServerBound, Play)]
+pub struct CookieResponse {
+    pub key: Identifier,
+    pub payload: Option<Vec<u8>>,
+}
+
+#[derive(Packet, ProtoEncode, ProtoDecode, Debug, Clone)]
+#[packet(0x13, ServerBound, Play)]
+pub struct DebugSampleSubscription {
+    pub sample_type: DebugSampleType,
+}
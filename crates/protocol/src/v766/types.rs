@@ -0,0 +1,82 @@
+use std::io::{Read, Seek, Write};
+
+use serverx_common::identifier::Identifier;
+use serverx_macros::{ProtoDecode, ProtoEncode};
+use serverx_nbt as nbt;
+
+use crate as protocol;
+use crate::{
+    decode::{AllocTracker, ProtoDecode, ProtoDecodeErr},
+    encode::{ProtoEncode, ProtoEncodeErr},
+    types::*,
+    v765,
+};
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct KnownPack {
+    pub namespace: String,
+    pub id: String,
+    pub version: String,
+}
+
+/// An entry of a single registry. The data is omitted when both sides know the entry from a
+/// shared data pack.
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub struct RegistryDataEntry {
+    pub id: Identifier,
+    #[proto(repr = "Option<nbt::TagRoot>")]
+    pub data: Option<nbt::Tag>,
+}
+
+#[derive(ProtoEncode, ProtoDecode, Debug, Clone)]
+pub enum DebugSampleType {
+    TickTime,
+}
+
+/// The parser 1.20.5 inserted after `item_slot`, the loot parsers were added before `uuid`.
+const ITEM_SLOTS_PARSER: i32 = 34;
+/// Where parsers 1.20.4 does not know are kept once decoded, past the ids it uses.
+const UNKNOWN_PARSER: i32 = 54;
+
+/// Maps the id of a 1.20.4 parser to the one it has since 1.20.5.
+fn parser_id(id: i32) -> i32 {
+    match id {
+        34..=48 => id + 1,
+        49 => 53,
+        UNKNOWN_PARSER => ITEM_SLOTS_PARSER,
+        id => id,
+    }
+}
+
+fn v765_parser_id(id: i32) -> i32 {
+    match id {
+        ITEM_SLOTS_PARSER => UNKNOWN_PARSER,
+        35..=49 => id - 1,
+        53 => 49,
+        id => id,
+    }
+}
+
+/// A node of the command graph, the same as in 1.20.4 apart from the ids of the parsers.
+#[derive(Debug, Clone)]
+pub struct CommandNode(pub v765::types::CommandNode);
+
+impl ProtoEncode for CommandNode {
+    type Repr = Self;
+
+    fn encode<W: Write + Seek>(data: &Self::Repr, writer: &mut W) -> Result<(), ProtoEncodeErr> {
+        data.0.encode_mapped(writer, parser_id)
+    }
+}
+
+impl ProtoDecode for CommandNode {
+    type Repr = Self;
+
+    fn decode<R: Read + Seek, A: AllocTracker>(
+        reader: &mut R,
+        alloc_tracker: &mut A,
+    ) -> Result<Self::Repr, ProtoDecodeErr> {
+        v765::types::CommandNode::decode_mapped(reader, alloc_tracker, v765_parser_id)
+            .map(CommandNode)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::{v765, v766};
+
+/// The protocol versions the server can speak, keyed by the number clients send in their
+/// handshake.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProtocolVersion {
+    V765,
+    V766,
+}
+
+impl ProtocolVersion {
+    pub const LATEST: ProtocolVersion = ProtocolVersion::V766;
+    pub const SUPPORTED: [ProtocolVersion; 2] = [ProtocolVersion::V765, ProtocolVersion::V766];
+
+    pub fn from_protocol(protocol: i32) -> Option<Self> {
+        Self::SUPPORTED
+            .into_iter()
+            .find(|version| version.protocol() == protocol)
+    }
+
+    pub fn protocol(&self) -> i32 {
+        match self {
+            ProtocolVersion::V765 => v765::PROTO_VER,
+            ProtocolVersion::V766 => v766::PROTO_VER,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProtocolVersion::V765 => v765::PROTO_NAME,
+            ProtocolVersion::V766 => v766::PROTO_NAME,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::version::ProtocolVersion;
+
+    #[test]
+    fn test_from_protocol() {
+        assert_eq!(
+            ProtocolVersion::from_protocol(765),
+            Some(ProtocolVersion::V765)
+        );
+        assert_eq!(
+            ProtocolVersion::from_protocol(766),
+            Some(ProtocolVersion::V766)
+        );
+        assert_eq!(ProtocolVersion::from_protocol(764), None);
+        for version in ProtocolVersion::SUPPORTED {
+            assert_eq!(
+                ProtocolVersion::from_protocol(version.protocol()),
+                Some(version)
+            );
+        }
+        assert_eq!(ProtocolVersion::LATEST.name(), "1.20.5");
+    }
+}
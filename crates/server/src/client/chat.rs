@@ -0,0 +1,343 @@
+use std::fmt::{Debug, Formatter};
+
+use serverx_protocol::{
+    text::TextComponent,
+    v765::{
+        serverbound::{ChatCommand, ChatMessage, PlayerSession},
+        types::PlayerChatSession,
+    },
+};
+use uuid::Uuid;
+
+use crate::client::{
+    chat_session::{
+        now_millis, ChatSession, LastSeenValidator, MessageBody, MessageSignature,
+        ProfileKeyVerifier,
+    },
+    ClientHandle,
+};
+
+/// Longest message vanilla clients send, longer ones are rejected.
+pub const MAX_MESSAGE_LEN: usize = 256;
+/// Added to the spam count by every message and command, one is taken away every tick.
+const SPAM_INCREMENT: u32 = 20;
+/// Clients going over this are disconnected, about ten messages in quick succession.
+const SPAM_THRESHOLD: u32 = 200;
+
+pub enum ChatErr {
+    TooLong(usize),
+    IllegalCharacters,
+    Spam,
+    OutOfOrder,
+    ExpiredPublicKey,
+    InvalidPublicKey,
+    MissingProfileKey,
+    ExpiredProfileKey,
+    InvalidSignature,
+    ChainBroken,
+    ValidationFailed,
+    TooManyPending,
+}
+
+impl Debug for ChatErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatErr::TooLong(len) => write!(f, "message of {} characters is too long", len),
+            ChatErr::IllegalCharacters => write!(f, "message contains illegal characters"),
+            ChatErr::Spam => write!(f, "client is sending messages too quickly"),
+            ChatErr::OutOfOrder => write!(f, "message is older than the previous one"),
+            ChatErr::ExpiredPublicKey => write!(f, "profile public key has expired"),
+            ChatErr::InvalidPublicKey => {
+                write!(f, "profile public key is not signed by a trusted key")
+            }
+            ChatErr::MissingProfileKey => write!(f, "message is not signed"),
+            ChatErr::ExpiredProfileKey => write!(f, "message is signed with an expired key"),
+            ChatErr::InvalidSignature => write!(f, "message signature is invalid"),
+            ChatErr::ChainBroken => write!(f, "message chain is broken"),
+            ChatErr::ValidationFailed => write!(f, "acknowledged messages are invalid"),
+            ChatErr::TooManyPending => write!(f, "too many messages are not acknowledged"),
+        }
+    }
+}
+
+impl ChatErr {
+    /// The reason the client is disconnected with, or told about if the error is not fatal.
+    pub fn reason(&self) -> TextComponent {
+        let key = match self {
+            ChatErr::TooLong(_) | ChatErr::IllegalCharacters => {
+                "multiplayer.disconnect.illegal_characters"
+            }
+            ChatErr::Spam => "disconnect.spam",
+            ChatErr::OutOfOrder => "multiplayer.disconnect.out_of_order_chat",
+            ChatErr::ExpiredPublicKey => "multiplayer.disconnect.expired_public_key",
+            ChatErr::InvalidPublicKey => "multiplayer.disconnect.invalid_public_key_signature.new",
+            ChatErr::MissingProfileKey => "chat.disabled.missingProfileKey",
+            ChatErr::ExpiredProfileKey => "chat.disabled.expiredProfileKey",
+            ChatErr::InvalidSignature => "chat.disabled.invalid_signature",
+            ChatErr::ChainBroken => "chat.disabled.chain_broken",
+            ChatErr::ValidationFailed => "multiplayer.disconnect.chat_validation_failed",
+            ChatErr::TooManyPending => "multiplayer.disconnect.too_many_pending_chats",
+        };
+        TextComponent::translate(key, Vec::new())
+    }
+
+    /// Whether the client is disconnected, otherwise only the message is dropped.
+    pub fn disconnects(&self) -> bool {
+        !matches!(
+            self,
+            ChatErr::MissingProfileKey | ChatErr::ExpiredProfileKey | ChatErr::ChainBroken
+        )
+    }
+}
+
+/// Rejects messages vanilla would not let a client send, formatting codes and control characters
+/// are not allowed.
+pub fn validate_message(message: &str) -> Result<(), ChatErr> {
+    let len = message.chars().count();
+    if len > MAX_MESSAGE_LEN {
+        return Err(ChatErr::TooLong(len));
+    }
+    validate_characters(message)
+}
+
+/// Commands are not limited in length once clients stop signing them, only their characters are
+/// checked.
+pub fn validate_characters(message: &str) -> Result<(), ChatErr> {
+    if message
+        .chars()
+        .any(|c| c == '§' || c < ' ' || c == '\u{7f}')
+    {
+        return Err(ChatErr::IllegalCharacters);
+    }
+    Ok(())
+}
+
+/// The chat state of a client, how much it has been talking lately and which signed messages it
+/// has seen.
+#[derive(Debug, Default)]
+pub struct ChatState {
+    spam: u32,
+    last_timestamp: i64,
+    pub session: Option<ChatSession>,
+    pub last_seen: LastSeenValidator,
+}
+
+/// The signature of a player chat message, relayed along with the messages it acknowledged.
+#[derive(Debug, Clone)]
+pub struct SignedMessage {
+    pub index: i32,
+    pub signature: MessageSignature,
+    pub last_seen: Vec<MessageSignature>,
+}
+
+impl ChatState {
+    /// Validates a message and the signature it came with, unsigned messages are only accepted
+    /// from clients without a chat session while secure profiles are not enforced.
+    pub fn accept_message(
+        &mut self,
+        sender: Uuid,
+        message: &ChatMessage,
+        enforce_secure_profile: bool,
+    ) -> Result<Option<SignedMessage>, ChatErr> {
+        validate_message(message.message.as_str())?;
+        self.record()?;
+        self.update_order(message.timestamp)?;
+        let last_seen = self
+            .last_seen
+            .apply_update(message.message_count, message.acknowledged)?;
+        match (self.session.as_mut(), message.signature.as_ref()) {
+            (Some(session), Some(signature)) => {
+                let body = MessageBody {
+                    content: message.message.as_str(),
+                    timestamp: message.timestamp,
+                    salt: message.salt,
+                    last_seen: last_seen.as_slice(),
+                };
+                let index = session.verify(sender, &body, signature, now_millis())?;
+                Ok(Some(SignedMessage {
+                    index,
+                    signature: *signature,
+                    last_seen,
+                }))
+            }
+            (Some(_), None) => Err(ChatErr::MissingProfileKey),
+            (None, _) if enforce_secure_profile => Err(ChatErr::MissingProfileKey),
+            (None, _) => Ok(None),
+        }
+    }
+
+    /// Validates a command of the layout that carries acknowledgements. None of the commands take
+    /// signed arguments, so their signatures are not checked.
+    pub fn accept_signed_command(&mut self, command: &ChatCommand) -> Result<(), ChatErr> {
+        self.accept_command(command.command.as_str())?;
+        self.update_order(command.timestamp)?;
+        self.last_seen
+            .apply_update(command.message_count, command.acknowledged)?;
+        Ok(())
+    }
+
+    pub fn accept_command(&mut self, command: &str) -> Result<(), ChatErr> {
+        validate_characters(command)?;
+        self.record()
+    }
+
+    /// Replaces the chat session of the client, returns it if it changed so it can be sent to the
+    /// other players. Sessions are ignored when there is no key to verify them with.
+    pub fn update_session(
+        &mut self,
+        profile_id: Uuid,
+        session: &PlayerSession,
+        verifier: Option<&ProfileKeyVerifier>,
+    ) -> Result<Option<PlayerChatSession>, ChatErr> {
+        let session = PlayerChatSession {
+            session_id: session.session_id,
+            expires_at: session.expires_at,
+            public_key: session.public_key.clone(),
+            key_signature: session.key_signature.clone(),
+        };
+        if let Some(current) = self.session.as_ref() {
+            if current.same_key(&session) {
+                return Ok(None);
+            }
+            if session.expires_at < current.data.expires_at {
+                return Err(ChatErr::ExpiredPublicKey);
+            }
+        }
+        let Some(verifier) = verifier else {
+            tracing::debug!("ignoring chat session, no profile key trust root is configured");
+            return Ok(None);
+        };
+        let session = verifier.verify(profile_id, session, now_millis())?;
+        let data = session.data.clone();
+        self.session = Some(session);
+        Ok(Some(data))
+    }
+
+    fn update_order(&mut self, timestamp: i64) -> Result<(), ChatErr> {
+        if timestamp < self.last_timestamp {
+            return Err(ChatErr::OutOfOrder);
+        }
+        self.last_timestamp = timestamp;
+        Ok(())
+    }
+
+    /// Counts a message or command, fails once the client sends them faster than vanilla allows.
+    pub fn record(&mut self) -> Result<(), ChatErr> {
+        self.spam += SPAM_INCREMENT;
+        if self.spam > SPAM_THRESHOLD {
+            return Err(ChatErr::Spam);
+        }
+        Ok(())
+    }
+
+    pub fn tick(&mut self) {
+        self.spam = self.spam.saturating_sub(1);
+    }
+}
+
+/// A message a player sent, handed to the chat hooks before it is broadcast.
+#[derive(Debug, Clone)]
+pub struct ChatEvent {
+    pub sender: ClientHandle,
+    pub sender_uuid: Uuid,
+    pub sender_name: String,
+    pub message: String,
+    pub timestamp: i64,
+    pub salt: i64,
+    /// Absent for unsigned messages. Clients only accept the signature with the original
+    /// `message`, use `decorated` to format signed messages.
+    pub signed: Option<SignedMessage>,
+    /// Shown instead of `message` while keeping the player chat layout.
+    pub decorated: Option<TextComponent>,
+    /// Sends exactly this as system chat instead of player chat.
+    pub system: Option<TextComponent>,
+    pub cancelled: bool,
+}
+
+impl ChatEvent {
+    /// The message as vanilla shows it, `<name> message`.
+    pub fn content(&self) -> TextComponent {
+        if let Some(system) = self.system.as_ref() {
+            return system.clone();
+        }
+        TextComponent::translate("chat.type.text", vec![
+            TextComponent::text(self.sender_name.clone()),
+            self.decorated
+                .clone()
+                .unwrap_or_else(|| TextComponent::text(self.message.clone())),
+        ])
+    }
+}
+
+/// Lets the game layer format, filter or cancel messages before they are broadcast.
+pub trait ChatHook: Send {
+    fn on_chat(&mut self, event: &mut ChatEvent);
+}
+
+#[cfg(test)]
+mod tests {
+    use serverx_protocol::v765::serverbound::ChatMessage;
+    use uuid::Uuid;
+
+    use crate::client::chat::{validate_message, ChatErr, ChatState, MAX_MESSAGE_LEN};
+
+    #[test]
+    fn test_validate_message() {
+        assert!(validate_message("hello world").is_ok());
+        assert!(validate_message(&"é".repeat(MAX_MESSAGE_LEN)).is_ok());
+        assert!(matches!(
+            validate_message(&"a".repeat(MAX_MESSAGE_LEN + 1)),
+            Err(ChatErr::TooLong(257))
+        ));
+        assert!(matches!(
+            validate_message("§cred"),
+            Err(ChatErr::IllegalCharacters)
+        ));
+        assert!(matches!(
+            validate_message("a\nb"),
+            Err(ChatErr::IllegalCharacters)
+        ));
+    }
+
+    #[test]
+    fn test_spam() {
+        let mut chat = ChatState::default();
+        for _ in 0..10 {
+            assert!(chat.record().is_ok());
+        }
+        assert!(matches!(chat.record(), Err(ChatErr::Spam)));
+
+        let mut chat = ChatState::default();
+        for _ in 0..100 {
+            assert!(chat.record().is_ok());
+            for _ in 0..20 {
+                chat.tick();
+            }
+        }
+    }
+
+    #[test]
+    fn test_accept_unsigned() {
+        let message = |timestamp: i64| ChatMessage {
+            message: "hello".to_string(),
+            timestamp,
+            salt: 0,
+            signature: None,
+            message_count: 0,
+            acknowledged: [0; 3],
+        };
+        let sender = Uuid::from_u128(1);
+        let mut chat = ChatState::default();
+        assert!(matches!(
+            chat.accept_message(sender, &message(10), false),
+            Ok(None)
+        ));
+        assert!(matches!(
+            chat.accept_message(sender, &message(5), false),
+            Err(ChatErr::OutOfOrder)
+        ));
+        let err = chat.accept_message(sender, &message(20), true).unwrap_err();
+        assert!(matches!(err, ChatErr::MissingProfileKey));
+        assert!(!err.disconnects());
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    collections::VecDeque,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Sign, RsaPublicKey};
+use serverx_protocol::v765::types::PlayerChatSession;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::client::chat::ChatErr;
+
+/// How many of the latest messages a client may acknowledge with a single message.
+pub const LAST_SEEN_COUNT: usize = 20;
+/// Clients that leave more messages unacknowledged are disconnected.
+const MAX_PENDING_MESSAGES: usize = 4096;
+
+pub type MessageSignature = [u8; 256];
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |now| now.as_millis() as i64)
+}
+
+/// The keys profile keys have to be signed with, usually the services key of Mojang.
+#[derive(Debug, Clone)]
+pub struct ProfileKeyVerifier {
+    keys: Vec<RsaPublicKey>,
+}
+
+impl ProfileKeyVerifier {
+    pub fn new(keys: Vec<RsaPublicKey>) -> Self {
+        Self { keys }
+    }
+
+    /// Checks that the key of a session is signed for the profile and not yet expired.
+    pub fn verify(
+        &self,
+        profile_id: Uuid,
+        session: PlayerChatSession,
+        now: i64,
+    ) -> Result<ChatSession, ChatErr> {
+        if session.expires_at < now {
+            return Err(ChatErr::ExpiredPublicKey);
+        }
+        let public_key = RsaPublicKey::from_public_key_der(session.public_key.as_slice())
+            .map_err(|_| ChatErr::InvalidPublicKey)?;
+        let hashed = Sha1::digest(key_payload(profile_id, &session));
+        let signed = self.keys.iter().any(|key| {
+            key.verify(Pkcs1v15Sign::new::<Sha1>(), &hashed, &session.key_signature)
+                .is_ok()
+        });
+        if !signed {
+            return Err(ChatErr::InvalidPublicKey);
+        }
+        Ok(ChatSession {
+            data: session,
+            public_key,
+            next_index: 0,
+            broken: false,
+        })
+    }
+}
+
+/// What the services key signs, the profile id and expiry followed by the key itself.
+pub fn key_payload(profile_id: Uuid, session: &PlayerChatSession) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(24 + session.public_key.len());
+    payload.extend_from_slice(profile_id.as_bytes());
+    payload.extend_from_slice(&session.expires_at.to_be_bytes());
+    payload.extend_from_slice(session.public_key.as_slice());
+    payload
+}
+
+/// The signed parts of a chat message, timestamps are in milliseconds like on the wire.
+pub struct MessageBody<'a> {
+    pub content: &'a str,
+    pub timestamp: i64,
+    pub salt: i64,
+    pub last_seen: &'a [MessageSignature],
+}
+
+/// What a client signs for a message, its position in the chain of the session and the body.
+pub fn message_payload(
+    sender: Uuid,
+    session_id: Uuid,
+    index: i32,
+    body: &MessageBody<'_>,
+) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(68 + body.content.len() + body.last_seen.len() * 256);
+    payload.extend_from_slice(&1i32.to_be_bytes());
+    payload.extend_from_slice(sender.as_bytes());
+    payload.extend_from_slice(session_id.as_bytes());
+    payload.extend_from_slice(&index.to_be_bytes());
+    payload.extend_from_slice(&body.salt.to_be_bytes());
+    payload.extend_from_slice(&body.timestamp.div_euclid(1000).to_be_bytes());
+    payload.extend_from_slice(&(body.content.len() as i32).to_be_bytes());
+    payload.extend_from_slice(body.content.as_bytes());
+    payload.extend_from_slice(&(body.last_seen.len() as i32).to_be_bytes());
+    for signature in body.last_seen {
+        payload.extend_from_slice(signature);
+    }
+    payload
+}
+
+/// A verified chat session, every message signed with its key continues the chain.
+#[derive(Debug)]
+pub struct ChatSession {
+    pub data: PlayerChatSession,
+    public_key: RsaPublicKey,
+    next_index: i32,
+    broken: bool,
+}
+
+impl ChatSession {
+    /// Verifies the next message of the chain, returns the index it was signed with. The chain
+    /// stays broken after an invalid signature until the client starts a new session.
+    pub fn verify(
+        &mut self,
+        sender: Uuid,
+        body: &MessageBody<'_>,
+        signature: &MessageSignature,
+        now: i64,
+    ) -> Result<i32, ChatErr> {
+        if self.broken {
+            return Err(ChatErr::ChainBroken);
+        }
+        if self.data.expires_at < now {
+            return Err(ChatErr::ExpiredProfileKey);
+        }
+        let index = self.next_index;
+        let hashed = Sha256::digest(message_payload(sender, self.data.session_id, index, body));
+        if self
+            .public_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, signature)
+            .is_err()
+        {
+            self.broken = true;
+            return Err(ChatErr::InvalidSignature);
+        }
+        self.next_index += 1;
+        Ok(index)
+    }
+
+    /// Whether a session update carries the same key, those are ignored.
+    pub fn same_key(&self, session: &PlayerChatSession) -> bool {
+        self.data.expires_at == session.expires_at
+            && self.data.public_key == session.public_key
+            && self.data.key_signature == session.key_signature
+    }
+}
+
+#[derive(Debug)]
+struct TrackedMessage {
+    signature: MessageSignature,
+    pending: bool,
+}
+
+/// Mirrors the messages a client has seen, it acknowledges them by their position in the window
+/// of the latest `LAST_SEEN_COUNT` signatures sent to it.
+#[derive(Debug)]
+pub struct LastSeenValidator {
+    tracked: VecDeque<Option<TrackedMessage>>,
+    last_pending: Option<MessageSignature>,
+}
+
+impl Default for LastSeenValidator {
+    fn default() -> Self {
+        Self {
+            tracked: (0..LAST_SEEN_COUNT).map(|_| None).collect(),
+            last_pending: None,
+        }
+    }
+}
+
+impl LastSeenValidator {
+    /// Tracks a signed message sent to the client.
+    pub fn add_pending(&mut self, signature: &MessageSignature) -> Result<(), ChatErr> {
+        if self.last_pending.as_ref() == Some(signature) {
+            return Ok(());
+        }
+        self.tracked.push_back(Some(TrackedMessage {
+            signature: *signature,
+            pending: true,
+        }));
+        self.last_pending = Some(*signature);
+        if self.tracked.len() > MAX_PENDING_MESSAGES {
+            return Err(ChatErr::TooManyPending);
+        }
+        Ok(())
+    }
+
+    /// Drops the messages that left the window of the client.
+    pub fn apply_offset(&mut self, offset: i32) -> Result<(), ChatErr> {
+        let movable = self.tracked.len() - LAST_SEEN_COUNT;
+        match usize::try_from(offset) {
+            Ok(offset) if offset <= movable => {
+                self.tracked.drain(..offset);
+                Ok(())
+            }
+            _ => Err(ChatErr::ValidationFailed),
+        }
+    }
+
+    /// Applies the acknowledgements of a message, returns the signatures it acknowledged in the
+    /// order they are signed.
+    pub fn apply_update(
+        &mut self,
+        offset: i32,
+        acknowledged: [u8; 3],
+    ) -> Result<Vec<MessageSignature>, ChatErr> {
+        self.apply_offset(offset)?;
+        if acknowledged[2] & 0xf0 != 0 {
+            return Err(ChatErr::ValidationFailed);
+        }
+        let mut last_seen = Vec::new();
+        for (i, entry) in self.tracked.iter_mut().take(LAST_SEEN_COUNT).enumerate() {
+            if acknowledged[i / 8] & (1 << (i % 8)) != 0 {
+                let Some(message) = entry.as_mut() else {
+                    return Err(ChatErr::ValidationFailed);
+                };
+                message.pending = false;
+                last_seen.push(message.signature);
+            } else {
+                if entry.as_ref().is_some_and(|message| !message.pending) {
+                    return Err(ChatErr::ValidationFailed);
+                }
+                *entry = None;
+            }
+        }
+        Ok(last_seen)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rsa::{pkcs8::EncodePublicKey, Pkcs1v15Sign, RsaPrivateKey, RsaPublicKey};
+    use serverx_protocol::v765::types::PlayerChatSession;
+    use sha1::Sha1;
+    use sha2::{Digest, Sha256};
+    use uuid::Uuid;
+
+    use crate::client::{
+        chat::ChatErr,
+        chat_session::{
+            key_payload, message_payload, LastSeenValidator, MessageBody, ProfileKeyVerifier,
+        },
+    };
+
+    #[test]
+    fn test_chat_session() {
+        let mut rng = rand::thread_rng();
+        let root = RsaPrivateKey::new(&mut rng, 1024).unwrap();
+        let player_key = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        let profile_id = Uuid::from_u128(0x1234);
+        let mut session = PlayerChatSession {
+            session_id: Uuid::from_u128(0x5678),
+            expires_at: 2_000,
+            public_key: RsaPublicKey::from(&player_key)
+                .to_public_key_der()
+                .unwrap()
+                .into_vec(),
+            key_signature: Vec::new(),
+        };
+        session.key_signature = root
+            .sign(
+                Pkcs1v15Sign::new::<Sha1>(),
+                &Sha1::digest(key_payload(profile_id, &session)),
+            )
+            .unwrap();
+
+        let verifier = ProfileKeyVerifier::new(vec![RsaPublicKey::from(&root)]);
+        assert!(matches!(
+            verifier.verify(Uuid::from_u128(1), session.clone(), 1_000),
+            Err(ChatErr::InvalidPublicKey)
+        ));
+        assert!(matches!(
+            verifier.verify(profile_id, session.clone(), 3_000),
+            Err(ChatErr::ExpiredPublicKey)
+        ));
+        let mut chat_session = verifier.verify(profile_id, session.clone(), 1_000).unwrap();
+        assert!(chat_session.same_key(&session));
+
+        let sign = |index: i32, body: &MessageBody<'_>| -> [u8; 256] {
+            let hashed =
+                Sha256::digest(message_payload(profile_id, session.session_id, index, body));
+            player_key
+                .sign(Pkcs1v15Sign::new::<Sha256>(), &hashed)
+                .unwrap()
+                .try_into()
+                .unwrap()
+        };
+        let first = MessageBody {
+            content: "hello",
+            timestamp: 1_500,
+            salt: 7,
+            last_seen: &[],
+        };
+        let first_signature = sign(0, &first);
+        assert_eq!(
+            chat_session
+                .verify(profile_id, &first, &first_signature, 1_000)
+                .unwrap(),
+            0
+        );
+        let second = MessageBody {
+            content: "world",
+            timestamp: 1_600,
+            salt: 8,
+            last_seen: &[first_signature],
+        };
+        // signed for the index that was already used
+        assert!(matches!(
+            chat_session.verify(profile_id, &second, &sign(0, &second), 1_000),
+            Err(ChatErr::InvalidSignature)
+        ));
+        assert!(matches!(
+            chat_session.verify(profile_id, &second, &sign(1, &second), 1_000),
+            Err(ChatErr::ChainBroken)
+        ));
+    }
+
+    #[test]
+    fn test_last_seen() {
+        let mut validator = LastSeenValidator::default();
+        for i in 0..3u8 {
+            validator.add_pending(&[i; 256]).unwrap();
+        }
+        // the same message is only tracked once
+        validator.add_pending(&[2; 256]).unwrap();
+        assert!(matches!(
+            validator.apply_offset(4),
+            Err(ChatErr::ValidationFailed)
+        ));
+        // the three messages take the end of the window once the oldest ones are dropped
+        let last_seen = validator.apply_update(3, [0, 0, 0b0110]).unwrap();
+        assert_eq!(last_seen, vec![[0; 256], [1; 256]]);
+        // acknowledged messages may not be forgotten
+        assert!(matches!(
+            validator.apply_update(0, [0, 0, 0b0010]),
+            Err(ChatErr::ValidationFailed)
+        ));
+
+        let mut validator = LastSeenValidator::default();
+        validator.add_pending(&[1; 256]).unwrap();
+        assert!(matches!(
+            validator.apply_update(0, [0b010, 0, 0]),
+            Err(ChatErr::ValidationFailed)
+        ));
+        assert!(matches!(
+            validator.apply_update(0, [0, 0, 0x10]),
+            Err(ChatErr::ValidationFailed)
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+use serverx_protocol::text::TextComponent;
+
+use crate::client::{profile::Profile, ClientHandle};
+
+/// What happened to clients during a tick, consumed by the game layer.
+#[derive(Debug)]
+pub enum ClientEvent {
+    Disconnected {
+        handle: ClientHandle,
+        profile: Profile,
+        /// Absent if the client left on its own.
+        reason: Option<TextComponent>,
+    },
+}
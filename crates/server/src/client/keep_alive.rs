@@ -0,0 +1,152 @@
+use std::{
+    fmt::{Debug, Formatter},
+    time::{Duration, Instant},
+};
+
+use serverx_protocol::text::TextComponent;
+
+/// How often a keep alive is sent, the same as vanilla.
+pub const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+/// How long a client has to answer a keep alive before it is disconnected.
+pub const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub enum KeepAliveErr {
+    TimedOut,
+    UnexpectedId(i64),
+}
+
+impl Debug for KeepAliveErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KeepAliveErr::TimedOut => write!(f, "keep alive timed out"),
+            KeepAliveErr::UnexpectedId(id) => write!(f, "unexpected keep alive id {}", id),
+        }
+    }
+}
+
+impl KeepAliveErr {
+    /// What the client is told when it is disconnected for the error.
+    pub fn reason(&self) -> TextComponent {
+        match self {
+            KeepAliveErr::TimedOut => TextComponent::translate("disconnect.timeout", Vec::new()),
+            KeepAliveErr::UnexpectedId(_) => TextComponent::translate(
+                "disconnect.genericReason",
+                vec![TextComponent::text("Invalid keep alive")],
+            ),
+        }
+    }
+}
+
+/// Tracks the keep alive a client still has to answer and the latency measured from the answers.
+#[derive(Debug)]
+pub struct KeepAlive {
+    pending: Option<(i64, Instant)>,
+    last_sent: Instant,
+    latency: Option<Duration>,
+}
+
+impl KeepAlive {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            pending: None,
+            last_sent: now,
+            latency: None,
+        }
+    }
+
+    /// Returns the id of the keep alive to send if one is due, or an error if the pending one
+    /// was not answered in time.
+    pub fn poll(&mut self, now: Instant) -> Result<Option<i64>, KeepAliveErr> {
+        if let Some((_, sent)) = self.pending {
+            if now.saturating_duration_since(sent) >= KEEP_ALIVE_TIMEOUT {
+                return Err(KeepAliveErr::TimedOut);
+            }
+            return Ok(None);
+        }
+        if now.saturating_duration_since(self.last_sent) < KEEP_ALIVE_INTERVAL {
+            return Ok(None);
+        }
+        let id: i64 = rand::random();
+        self.pending = Some((id, now));
+        self.last_sent = now;
+        Ok(Some(id))
+    }
+
+    /// Handles the answer of a client, like vanilla any id other than the pending one is an
+    /// error.
+    pub fn acknowledge(&mut self, id: i64, now: Instant) -> Result<(), KeepAliveErr> {
+        match self.pending {
+            Some((pending_id, sent)) if pending_id == id => {
+                let sample = now.saturating_duration_since(sent);
+                // smoothed the same way vanilla does it
+                self.latency = Some(match self.latency {
+                    Some(latency) => (latency * 3 + sample) / 4,
+                    None => sample,
+                });
+                self.pending = None;
+                Ok(())
+            }
+            _ => Err(KeepAliveErr::UnexpectedId(id)),
+        }
+    }
+
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use crate::client::keep_alive::{
+        KeepAlive, KeepAliveErr, KEEP_ALIVE_INTERVAL, KEEP_ALIVE_TIMEOUT,
+    };
+
+    #[test]
+    fn test_keep_alive() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        assert!(matches!(keep_alive.poll(start), Ok(None)));
+        let sent = start + KEEP_ALIVE_INTERVAL;
+        let id = keep_alive.poll(sent).unwrap().unwrap();
+        // nothing new is sent while one is pending
+        assert!(matches!(
+            keep_alive.poll(sent + KEEP_ALIVE_INTERVAL),
+            Ok(None)
+        ));
+        let err = keep_alive
+            .acknowledge(id.wrapping_add(1), sent)
+            .unwrap_err();
+        assert!(matches!(err, KeepAliveErr::UnexpectedId(_)));
+        // a wrong answer is not reported as a timeout
+        assert_ne!(err.reason(), KeepAliveErr::TimedOut.reason());
+        keep_alive
+            .acknowledge(id, sent + Duration::from_millis(100))
+            .unwrap();
+        assert_eq!(keep_alive.latency(), Some(Duration::from_millis(100)));
+
+        let sent = sent + KEEP_ALIVE_INTERVAL;
+        let id = keep_alive.poll(sent).unwrap().unwrap();
+        keep_alive
+            .acknowledge(id, sent + Duration::from_millis(20))
+            .unwrap();
+        assert_eq!(keep_alive.latency(), Some(Duration::from_millis(80)));
+    }
+
+    #[test]
+    fn test_keep_alive_timeout() {
+        let start = Instant::now();
+        let mut keep_alive = KeepAlive::new(start);
+        let sent = start + KEEP_ALIVE_INTERVAL;
+        keep_alive.poll(sent).unwrap().unwrap();
+        assert!(matches!(
+            keep_alive.poll(sent + KEEP_ALIVE_TIMEOUT - Duration::from_millis(1)),
+            Ok(None)
+        ));
+        assert!(matches!(
+            keep_alive.poll(sent + KEEP_ALIVE_TIMEOUT),
+            Err(KeepAliveErr::TimedOut)
+        ));
+    }
+}
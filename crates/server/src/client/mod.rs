@@ -1,16 +1,37 @@
+pub mod chat;
+pub mod chat_session;
+pub mod event;
+pub mod keep_alive;
 pub mod profile;
 pub mod status;
 pub mod sync;
+pub mod tab_list;
 pub mod update;
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Instant};
 
-use flume::{Receiver, Sender};
-use serverx_protocol::packet::{ConnectionState, Packet};
+use flume::Receiver;
+use serverx_protocol::{
+    io::PreEncodedPacket,
+    packet::{ConnectionState, Packet, PacketDirection::ClientBound},
+    text::TextComponent,
+    v765::clientbound::{ConfigDisconnect, Disconnect, LoginDisconnect},
+    version::ProtocolVersion,
+};
 use slab::Slab;
+use smallvec::SmallVec;
 use uuid::Uuid;
 
-use crate::client::{profile::Profile, status::ClientStatus};
+use crate::{
+    client::{
+        chat::ChatState, keep_alive::KeepAlive, profile::Profile, status::ClientStatus,
+        tab_list::TabEntry,
+    },
+    network::{
+        event::IncomingPacket,
+        queue::{OutgoingQueue, PacketPriority},
+    },
+};
 
 pub struct Clients {
     pub clients: Slab<Client>,
@@ -30,8 +51,9 @@ impl Clients {
     pub fn add(
         &mut self,
         addr: SocketAddr,
-        outgoing: Sender<Box<dyn Packet>>,
-        incoming: Receiver<Box<dyn Packet>>,
+        version: ProtocolVersion,
+        outgoing: OutgoingQueue,
+        incoming: Receiver<IncomingPacket>,
         profile: Profile,
     ) -> &mut Client {
         let vacant_entry = self.clients.vacant_entry();
@@ -46,13 +68,54 @@ impl Clients {
             },
             status: ClientStatus::Init,
             state: ConnectionState::Configuration,
+            version,
             addr,
             outgoing,
             incoming,
             profile,
+            keep_alive: KeepAlive::new(Instant::now()),
+            chat: ChatState::default(),
+            tab: TabEntry::default(),
+            disconnect_reason: None,
         })
     }
 
+    /// Sends a packet to every connected client, encoding it once per protocol version and
+    /// compression threshold instead of once per client.
+    pub fn broadcast(&self, packet: &dyn Packet, priority: PacketPriority) {
+        // failures are cached as well, a packet that has no encoding for a version is skipped for
+        // its clients only
+        type Encoded = ((ProtocolVersion, Option<usize>), Option<PreEncodedPacket>);
+        let mut encoded: SmallVec<[Encoded; 2]> = SmallVec::new();
+        for (_, client) in self.clients.iter() {
+            if client.status != ClientStatus::Connected {
+                continue;
+            }
+            let key = (client.outgoing.version(), client.outgoing.compression());
+            let pre_encoded = match encoded.iter().find(|(cached, _)| *cached == key) {
+                Some((_, pre_encoded)) => pre_encoded.clone(),
+                None => {
+                    let pre_encoded = PreEncodedPacket::new(key.0, ClientBound, packet, key.1)
+                        .map_err(|err| {
+                            tracing::error!(
+                                ?err,
+                                ?packet,
+                                version = ?key.0,
+                                "unable to encode broadcast packet"
+                            );
+                        })
+                        .ok();
+                    encoded.push((key, pre_encoded.clone()));
+                    pre_encoded
+                }
+            };
+            let Some(pre_encoded) = pre_encoded else {
+                continue;
+            };
+            let _ = client.outgoing.send_pre_encoded(pre_encoded, priority);
+        }
+    }
+
     pub fn get(&self, handle: ClientHandle) -> Option<&Client> {
         if let Some(client) = self.clients.get(handle.slab_key) {
             if client.handle.generation == handle.generation {
@@ -85,10 +148,45 @@ pub struct Client {
     pub handle: ClientHandle,
     pub status: ClientStatus,
     pub state: ConnectionState,
+    pub version: ProtocolVersion,
     pub addr: SocketAddr,
-    pub outgoing: Sender<Box<dyn Packet>>,
-    pub incoming: Receiver<Box<dyn Packet>>,
+    pub outgoing: OutgoingQueue,
+    pub incoming: Receiver<IncomingPacket>,
     pub profile: Profile,
+    pub keep_alive: KeepAlive,
+    pub chat: ChatState,
+    pub tab: TabEntry,
+    /// Why the server disconnected the client, handed to the game layer once it is gone.
+    pub disconnect_reason: Option<TextComponent>,
+}
+
+impl Client {
+    /// Sends the disconnect packet of the current state, the write loop closes the socket once it
+    /// is flushed and the client is removed on a following tick.
+    pub fn disconnect(&mut self, reason: TextComponent) {
+        if matches!(
+            self.status,
+            ClientStatus::Disconnecting | ClientStatus::Disconnected
+        ) {
+            return;
+        }
+        tracing::debug!(profile = ?self.profile, reason = reason.to_plain(), "disconnecting client");
+        let _ = match self.state {
+            ConnectionState::Login => self.outgoing.send(&LoginDisconnect {
+                reason: reason.clone(),
+            }),
+            ConnectionState::Configuration => self.outgoing.send(&ConfigDisconnect {
+                reason: reason.clone(),
+            }),
+            ConnectionState::Play => self.outgoing.send(&Disconnect {
+                reason: reason.clone(),
+            }),
+            ConnectionState::Handshake | ConnectionState::Status => Ok(()),
+        };
+        self.outgoing.close();
+        self.disconnect_reason = Some(reason);
+        self.status = ClientStatus::Disconnecting;
+    }
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -96,3 +194,129 @@ pub struct ClientHandle {
     pub generation: u64,
     pub slab_key: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        net::{Ipv4Addr, SocketAddr},
+    };
+
+    use serverx_protocol::{
+        decode::{BasicAllocTracker, ProtoDecode},
+        io::EncodedPacket,
+        packet::ConnectionState,
+        text::TextComponent,
+        types::VarInt,
+        v765::clientbound::{ConfigDisconnect, Disconnect, KeepAlive, ServerData},
+        version::ProtocolVersion,
+    };
+
+    use crate::{
+        client::{
+            profile::{offline_uuid, Profile},
+            status::ClientStatus,
+            Clients,
+        },
+        network::{
+            event::IncomingPacket,
+            queue::{
+                outgoing_queue, OutgoingPacket, OutgoingReceiver, PacketPriority, QueueLimits,
+            },
+        },
+    };
+
+    async fn recv_id(receiver: &OutgoingReceiver) -> i32 {
+        let Some(OutgoingPacket::Encoded(EncodedPacket::Body(body))) = receiver.recv().await else {
+            panic!("expected an encoded packet");
+        };
+        VarInt::decode(
+            &mut Cursor::new(body.as_slice()),
+            &mut BasicAllocTracker::new(1024),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_disconnect() {
+        let mut clients = Clients::new();
+        let (outgoing, receiver) =
+            outgoing_queue(ProtocolVersion::V765, None, QueueLimits::default());
+        let (_incoming_tx, incoming_rx) = flume::unbounded::<IncomingPacket>();
+        let profile = Profile::try_from(("Notch".to_string(), offline_uuid("Notch"))).unwrap();
+        let client = clients.add(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 25565),
+            ProtocolVersion::V765,
+            outgoing,
+            incoming_rx,
+            profile,
+        );
+        client.disconnect(TextComponent::text("kicked"));
+        assert_eq!(recv_id(&receiver).await, ConfigDisconnect::ID);
+        assert!(matches!(receiver.recv().await, Some(OutgoingPacket::Close)));
+        assert_eq!(client.status, ClientStatus::Disconnecting);
+        assert_eq!(
+            client.disconnect_reason,
+            Some(TextComponent::text("kicked"))
+        );
+
+        // only the first reason is delivered
+        client.disconnect(TextComponent::text("again"));
+        assert_eq!(client.outgoing.metrics().queued_packets, 0);
+
+        client.status = ClientStatus::Connected;
+        client.state = ConnectionState::Play;
+        client.disconnect(TextComponent::text("bye"));
+        assert_eq!(recv_id(&receiver).await, Disconnect::ID);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast() {
+        let mut clients = Clients::new();
+        let mut receivers = Vec::new();
+        for (name, version) in [
+            ("Dinnerbone", ProtocolVersion::V766),
+            ("Notch", ProtocolVersion::V765),
+            ("jeb_", ProtocolVersion::V765),
+        ] {
+            let (outgoing, receiver) = outgoing_queue(version, Some(256), QueueLimits::default());
+            let (_incoming_tx, incoming_rx) = flume::unbounded::<IncomingPacket>();
+            let profile = Profile::try_from((name.to_string(), offline_uuid(name))).unwrap();
+            let client = clients.add(
+                SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 25565),
+                version,
+                outgoing,
+                incoming_rx,
+                profile,
+            );
+            client.status = ClientStatus::Connected;
+            receivers.push(receiver);
+        }
+        clients.broadcast(&KeepAlive { id: 7 }, PacketPriority::Normal);
+        let mut frames = Vec::new();
+        for receiver in receivers.iter() {
+            let Some(OutgoingPacket::Encoded(EncodedPacket::PreEncoded(packet))) =
+                receiver.recv().await
+            else {
+                panic!("expected a pre-encoded packet");
+            };
+            frames.push(packet.frame().to_vec());
+        }
+        assert_eq!(frames[1], frames[2]);
+        // keep alive is renumbered in 1.20.5
+        assert_ne!(frames[0], frames[1]);
+
+        // 1.20.5 replaced this packet, the others still get it
+        clients.broadcast(
+            &ServerData {
+                motd: TextComponent::text("motd"),
+                icon: None,
+                enforces_secure_chat: false,
+            },
+            PacketPriority::Normal,
+        );
+        assert!(receivers[0].try_recv().is_none());
+        assert!(receivers[1].try_recv().is_some());
+        assert!(receivers[2].try_recv().is_some());
+    }
+}
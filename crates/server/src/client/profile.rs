@@ -1,5 +1,9 @@
-use std::fmt::{Debug, Display, Formatter};
+use std::{
+    fmt::{Debug, Display, Formatter},
+    net::IpAddr,
+};
 
+use serverx_protocol::v765::types::LoginProperty;
 use uuid::Uuid;
 
 pub const MAX_USERNAME_LEN: usize = 16;
@@ -8,6 +12,9 @@ pub const MAX_USERNAME_LEN: usize = 16;
 pub struct Profile {
     pub name: String,
     pub uuid: Uuid,
+    pub properties: Vec<LoginProperty>,
+    /// The address of the player as reported by a proxy.
+    pub forwarded_addr: Option<IpAddr>,
 }
 
 impl TryFrom<(String, Uuid)> for Profile {
@@ -20,11 +27,20 @@ impl TryFrom<(String, Uuid)> for Profile {
             Ok(Self {
                 name: value.0,
                 uuid: value.1,
+                properties: Vec::new(),
+                forwarded_addr: None,
             })
         }
     }
 }
 
+/// Computes the UUID vanilla assigns to players in offline mode, a name based (version 3) UUID of
+/// `OfflinePlayer:<name>`.
+pub fn offline_uuid(name: &str) -> Uuid {
+    let digest = md5::compute(format!("OfflinePlayer:{}", name));
+    uuid::Builder::from_md5_bytes(digest.0).into_uuid()
+}
+
 pub enum ProfileErr {
     InvalidUsername(String),
 }
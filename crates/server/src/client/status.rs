@@ -1,8 +1,12 @@
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ClientStatus {
     Init,
+    /// The configuration was sent, the client is joined once it finishes it.
+    Configuring,
     Connecting,
     Connected,
+    /// The client did not answer a keep alive in time and was sent a disconnect.
+    TimedOut,
     Disconnecting,
     Disconnected,
 }
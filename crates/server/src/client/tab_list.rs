@@ -0,0 +1,366 @@
+use serverx_protocol::{
+    text::TextComponent,
+    v765::{
+        clientbound::{PlayerInfoRemove, PlayerInfoUpdate, SetTabListHeaderAndFooter},
+        types::{GameMode, PlayerInfoEntry},
+    },
+};
+use smallvec::SmallVec;
+use uuid::Uuid;
+
+use crate::{
+    client::{status::ClientStatus, Client, ClientHandle, Clients},
+    network::queue::PacketPriority,
+};
+
+/// Every field of an entry, sent when a player is added.
+const ALL_ACTIONS: u8 = PlayerInfoUpdate::ADD_PLAYER
+    | PlayerInfoUpdate::INITIALIZE_CHAT
+    | PlayerInfoUpdate::UPDATE_GAME_MODE
+    | PlayerInfoUpdate::UPDATE_LISTED
+    | PlayerInfoUpdate::UPDATE_LATENCY
+    | PlayerInfoUpdate::UPDATE_DISPLAY_NAME;
+
+/// How a player is shown in the tab list, changes are sent to everyone on the next sync.
+#[derive(Debug)]
+pub struct TabEntry {
+    display_name: Option<TextComponent>,
+    game_mode: GameMode,
+    listed: bool,
+    /// Milliseconds, the client picks the signal bars from it.
+    latency: i32,
+    /// The player info actions covering the fields that changed.
+    changed: u8,
+}
+
+impl Default for TabEntry {
+    fn default() -> Self {
+        Self {
+            display_name: None,
+            game_mode: GameMode::Survival,
+            listed: true,
+            latency: 0,
+            changed: 0,
+        }
+    }
+}
+
+impl TabEntry {
+    pub fn display_name(&self) -> Option<&TextComponent> {
+        self.display_name.as_ref()
+    }
+
+    /// The name is shown instead of the profile name, `None` restores the latter.
+    pub fn set_display_name(&mut self, display_name: Option<TextComponent>) {
+        self.display_name = display_name;
+        self.changed |= PlayerInfoUpdate::UPDATE_DISPLAY_NAME;
+    }
+
+    pub fn game_mode(&self) -> &GameMode {
+        &self.game_mode
+    }
+
+    pub fn set_game_mode(&mut self, game_mode: GameMode) {
+        self.game_mode = game_mode;
+        self.changed |= PlayerInfoUpdate::UPDATE_GAME_MODE;
+    }
+
+    pub fn listed(&self) -> bool {
+        self.listed
+    }
+
+    /// Unlisted players are known to clients, for chat and skins, without being shown.
+    pub fn set_listed(&mut self, listed: bool) {
+        if self.listed != listed {
+            self.listed = listed;
+            self.changed |= PlayerInfoUpdate::UPDATE_LISTED;
+        }
+    }
+
+    pub fn latency(&self) -> i32 {
+        self.latency
+    }
+
+    pub fn set_latency(&mut self, latency: i32) {
+        if self.latency != latency {
+            self.latency = latency;
+            self.changed |= PlayerInfoUpdate::UPDATE_LATENCY;
+        }
+    }
+}
+
+fn game_mode_id(game_mode: &GameMode) -> i32 {
+    match game_mode {
+        GameMode::Survival => 0,
+        GameMode::Creative => 1,
+        GameMode::Adventure => 2,
+        GameMode::Spectator => 3,
+    }
+}
+
+/// The player info of a client, holding every field whatever actions it is sent with.
+pub fn player_info(client: &Client) -> PlayerInfoEntry {
+    PlayerInfoEntry {
+        uuid: client.profile.uuid,
+        name: client.profile.name.clone(),
+        properties: client.profile.properties.clone(),
+        chat_session: client
+            .chat
+            .session
+            .as_ref()
+            .map(|session| session.data.clone()),
+        game_mode: game_mode_id(&client.tab.game_mode),
+        listed: client.tab.listed,
+        latency: client.tab.latency,
+        display_name: client.tab.display_name.clone(),
+    }
+}
+
+/// The tab list everyone shares. Players joining and leaving are queued and sent along with the
+/// entries that changed once per tick.
+pub struct TabList {
+    header: TextComponent,
+    footer: TextComponent,
+    header_changed: bool,
+    joined: Vec<ClientHandle>,
+    left: Vec<Uuid>,
+}
+
+impl TabList {
+    pub fn new(header: TextComponent, footer: TextComponent) -> Self {
+        Self {
+            header,
+            footer,
+            header_changed: false,
+            joined: Vec::new(),
+            left: Vec::new(),
+        }
+    }
+
+    pub fn set_header_footer(&mut self, header: TextComponent, footer: TextComponent) {
+        self.header = header;
+        self.footer = footer;
+        self.header_changed = true;
+    }
+
+    /// Adds a client that entered play, it is shown to everyone and gets the current list.
+    pub fn join(&mut self, handle: ClientHandle) {
+        self.joined.push(handle);
+    }
+
+    pub fn leave(&mut self, uuid: Uuid) {
+        self.left.push(uuid);
+    }
+
+    /// Sends what changed since the last sync, latencies are first taken from the keep alives if
+    /// `update_latency` is set.
+    pub fn sync(&mut self, clients: &mut Clients, update_latency: bool) {
+        if !self.left.is_empty() {
+            clients.broadcast(
+                &PlayerInfoRemove {
+                    players: std::mem::take(&mut self.left),
+                },
+                PacketPriority::Normal,
+            );
+        }
+        let joined = std::mem::take(&mut self.joined);
+        let mut changes: SmallVec<[(u8, Vec<PlayerInfoEntry>); 2]> = SmallVec::new();
+        for (_, client) in clients.clients.iter_mut() {
+            if update_latency {
+                if let Some(latency) = client.keep_alive.latency() {
+                    client.tab.set_latency(latency.as_millis() as i32);
+                }
+            }
+            let changed = std::mem::take(&mut client.tab.changed);
+            // entries of new players are sent in full below
+            if changed == 0
+                || client.status != ClientStatus::Connected
+                || joined.contains(&client.handle)
+            {
+                continue;
+            }
+            let entry = player_info(client);
+            match changes.iter_mut().find(|(actions, _)| *actions == changed) {
+                Some((_, players)) => players.push(entry),
+                None => changes.push((changed, vec![entry])),
+            }
+        }
+        for (actions, players) in changes {
+            clients.broadcast(
+                &PlayerInfoUpdate { actions, players },
+                PacketPriority::Normal,
+            );
+        }
+        if std::mem::take(&mut self.header_changed) {
+            clients.broadcast(
+                &SetTabListHeaderAndFooter {
+                    header: self.header.clone(),
+                    footer: self.footer.clone(),
+                },
+                PacketPriority::Normal,
+            );
+        }
+        self.add_players(clients, joined);
+    }
+
+    fn add_players(&self, clients: &Clients, joined: Vec<ClientHandle>) {
+        let added: Vec<PlayerInfoEntry> = joined
+            .iter()
+            .filter_map(|handle| clients.get(*handle))
+            .filter(|client| client.status == ClientStatus::Connected)
+            .map(player_info)
+            .collect();
+        if added.is_empty() {
+            return;
+        }
+        clients.broadcast(
+            &PlayerInfoUpdate {
+                actions: ALL_ACTIONS,
+                players: added,
+            },
+            PacketPriority::Normal,
+        );
+        let present = PlayerInfoUpdate {
+            actions: ALL_ACTIONS,
+            players: clients
+                .clients
+                .iter()
+                .filter(|(_, client)| {
+                    client.status == ClientStatus::Connected && !joined.contains(&client.handle)
+                })
+                .map(|(_, client)| player_info(client))
+                .collect(),
+        };
+        let header_footer = SetTabListHeaderAndFooter {
+            header: self.header.clone(),
+            footer: self.footer.clone(),
+        };
+        let empty = TextComponent::default();
+        for client in joined.iter().filter_map(|handle| clients.get(*handle)) {
+            if !present.players.is_empty() {
+                let _ = client.outgoing.send(&present);
+            }
+            if self.header != empty || self.footer != empty {
+                let _ = client.outgoing.send(&header_footer);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        net::{Ipv4Addr, SocketAddr},
+    };
+
+    use serverx_protocol::{
+        decode::{BasicAllocTracker, ProtoDecode},
+        io::EncodedPacket,
+        text::TextComponent,
+        types::VarInt,
+        v765::clientbound::{PlayerInfoRemove, PlayerInfoUpdate, SetTabListHeaderAndFooter},
+        version::ProtocolVersion,
+    };
+
+    use crate::{
+        client::{
+            profile::{offline_uuid, Profile},
+            status::ClientStatus,
+            tab_list::TabList,
+            ClientHandle, Clients,
+        },
+        network::{
+            event::IncomingPacket,
+            queue::{outgoing_queue, OutgoingPacket, OutgoingReceiver, QueueLimits},
+        },
+    };
+
+    /// The ids of the packets queued for a client.
+    fn queued_ids(receiver: &OutgoingReceiver) -> Vec<i32> {
+        let mut ids = Vec::new();
+        while let Some(packet) = receiver.try_recv() {
+            let body = match packet {
+                OutgoingPacket::Encoded(EncodedPacket::Body(body)) => body.to_vec(),
+                OutgoingPacket::Encoded(EncodedPacket::PreEncoded(packet)) => {
+                    // uncompressed frames are the length followed by the body
+                    let mut frame = Cursor::new(packet.frame());
+                    VarInt::decode(&mut frame, &mut BasicAllocTracker::new(1024)).unwrap();
+                    frame.get_ref()[frame.position() as usize..].to_vec()
+                }
+                _ => continue,
+            };
+            ids.push(
+                VarInt::decode(
+                    &mut Cursor::new(body.as_slice()),
+                    &mut BasicAllocTracker::new(1024),
+                )
+                .unwrap(),
+            );
+        }
+        ids
+    }
+
+    fn connect(clients: &mut Clients, name: &str) -> (ClientHandle, OutgoingReceiver) {
+        let (outgoing, receiver) =
+            outgoing_queue(ProtocolVersion::V765, None, QueueLimits::default());
+        let (_incoming_tx, incoming_rx) = flume::unbounded::<IncomingPacket>();
+        let profile = Profile::try_from((name.to_string(), offline_uuid(name))).unwrap();
+        let client = clients.add(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 25565),
+            ProtocolVersion::V765,
+            outgoing,
+            incoming_rx,
+            profile,
+        );
+        client.status = ClientStatus::Connected;
+        (client.handle, receiver)
+    }
+
+    #[test]
+    fn test_tab_list() {
+        let mut clients = Clients::new();
+        let mut tab_list = TabList::new(TextComponent::text("header"), TextComponent::default());
+        let (notch, notch_queue) = connect(&mut clients, "Notch");
+        tab_list.join(notch);
+        tab_list.sync(&mut clients, false);
+        assert_eq!(queued_ids(&notch_queue), vec![
+            PlayerInfoUpdate::ID,
+            SetTabListHeaderAndFooter::ID
+        ]);
+
+        let (jeb, jeb_queue) = connect(&mut clients, "jeb_");
+        tab_list.join(jeb);
+        tab_list.sync(&mut clients, false);
+        assert_eq!(queued_ids(&notch_queue), vec![PlayerInfoUpdate::ID]);
+        // the new player itself, then everyone that was already there
+        assert_eq!(queued_ids(&jeb_queue), vec![
+            PlayerInfoUpdate::ID,
+            PlayerInfoUpdate::ID,
+            SetTabListHeaderAndFooter::ID
+        ]);
+
+        clients.get_mut(jeb).unwrap().tab.set_listed(true);
+        tab_list.sync(&mut clients, false);
+        assert!(queued_ids(&notch_queue).is_empty());
+        clients
+            .get_mut(jeb)
+            .unwrap()
+            .tab
+            .set_display_name(Some(TextComponent::text("Jeb")));
+        clients.get_mut(notch).unwrap().tab.set_listed(false);
+        tab_list.sync(&mut clients, false);
+        // both changes differ in their actions
+        for queue in [&notch_queue, &jeb_queue] {
+            assert_eq!(queued_ids(queue), vec![
+                PlayerInfoUpdate::ID,
+                PlayerInfoUpdate::ID
+            ]);
+        }
+
+        let uuid = clients.remove(jeb).unwrap().profile.uuid;
+        tab_list.leave(uuid);
+        tab_list.sync(&mut clients, false);
+        assert_eq!(queued_ids(&notch_queue), vec![PlayerInfoRemove::ID]);
+    }
+}
@@ -1,154 +1,549 @@
+use std::time::Instant;
+
 use serverx_block::blocks::Block;
 use serverx_common::{collections::bit_vec::BitVec, identifier};
 use serverx_game::chunk::generators::{flat::FlatGeneratorBuilder, ChunkGenerator};
 use serverx_macros::identifier;
 use serverx_protocol::{
     chunk::encode_chunk,
+    dispatch::{ServerboundConfiguration, ServerboundPlay},
+    io::PreEncodedPacket,
+    packet::{ConnectionState, PacketDirection::ClientBound},
+    text::{TextColor, TextComponent},
     v765::{
+        clientbound,
         clientbound::{
             ChangeDifficulty, ChunkBatchFinish, ChunkBatchStart, ChunkDataAndLight,
             DefaultSpawnPosition, FeatureFlags, GameJoin, PlayerAbilities, RegistryData,
-            ServerFinishConfiguration, ServerGameEvent, SetCenterChunk, SyncPlayerPosition,
-            UpdateTags,
+            ServerData, ServerFinishConfiguration, ServerGameEvent, SetCenterChunk,
+            SyncPlayerPosition, SystemChatMessage, UpdateTags,
         },
-        types::{ChunkLighting, Difficulty, GameEvent, GameMode, LastGameMode},
+        types::{ChunkLighting, Difficulty, GameEvent, GameMode, LastGameMode, PlayerInfoEntry},
     },
+    v766,
+    v766::types::KnownPack,
+    version::ProtocolVersion,
 };
 use tracing::instrument;
 
 use crate::{
-    client::{status::ClientStatus, Client},
-    network::brand::make_server_brand_message,
+    client::{
+        chat::{ChatErr, ChatEvent},
+        event::ClientEvent,
+        keep_alive::KeepAliveErr,
+        status::ClientStatus,
+        Client,
+    },
+    command::CommandSource,
+    network::{
+        brand::make_server_brand_message,
+        event::IncomingPacket,
+        queue::{PacketPriority, QueueErr},
+    },
     server::Server,
 };
 
 #[instrument(skip_all)]
 pub fn update_client(client: &mut Client, server: &mut Server) {
     match client.status {
-        ClientStatus::Init => {
-            let server_brand = make_server_brand_message(&identifier!("vanilla"));
-            tracing::trace!(?server_brand, "sending server brand message");
-            let _ = client.outgoing.send(Box::new(server_brand));
-            let feature_flags = FeatureFlags {
-                flags: vec![identifier!("vanilla")],
-            };
-            tracing::trace!(?feature_flags, "sending feature flags packet");
-            let _ = client.outgoing.send(Box::new(feature_flags));
-            let registry_data = RegistryData {
-                registries: server.resources.registry_data.clone(),
-            };
-            tracing::trace!("sending registry data packet");
-            let _ = client.outgoing.send(Box::new(registry_data));
-            let update_tags = UpdateTags { tags: vec![] };
-            tracing::trace!(?update_tags, "sending update tags packet");
-            let _ = client.outgoing.send(Box::new(update_tags));
-            let finish_config = ServerFinishConfiguration;
-            tracing::trace!("sending finish configuration packet");
-            let _ = client.outgoing.send(Box::new(finish_config));
-
-            // TEST
-            let _ = client.outgoing.send(Box::new(GameJoin {
-                entity_id: 0,
-                is_hardcore: false,
-                dimensions: vec![
-                    identifier!("overworld"),
-                    identifier!("the_nether"),
-                    identifier!("the_end"),
-                ],
-                max_players: 100,
-                view_distance: 6,
-                sim_distance: 6,
-                reduced_debug: false,
-                enable_respawn: false,
-                limited_crafting: false,
-                dimension_type: identifier!("overworld"),
-                dimension_name: identifier!("overworld"),
-                seed: 0,
-                game_mode: GameMode::Survival,
-                last_game_mode: LastGameMode::Undefined,
-                is_debug: false,
-                is_flag: false,
-                death_location: None,
-                portal_cooldown: 0,
-            }));
-            let _ = client.outgoing.send(Box::new(ChangeDifficulty {
-                difficulty: Difficulty::Hard,
-                locked: false,
-            }));
-            let _ = client.outgoing.send(Box::new(PlayerAbilities {
-                flags: 0,
-                fly_speed: 0.5,
-                fov_modifier: 0.1,
-            }));
-
-            let _ = client
-                .outgoing
-                .send(Box::new(SetCenterChunk { x: 0, z: 0 }));
-
-            let generator = FlatGeneratorBuilder::new(384)
-                .layer(Block::IronBlock, 64)
-                .build();
-            let chunk = generator.generate((0, 0).into());
-            // println!("{:?}", chunk.sections().get(4).unwrap().blocks);
-            let _ = client.outgoing.send(Box::new(ServerGameEvent {
-                event: GameEvent::StartWaitingForLevelChunks,
-                value: 0.0,
-            }));
-            let _ = client.outgoing.send(Box::new(ChunkBatchStart));
-            if let Ok(encoded) = encode_chunk(&chunk) {
-                let heightmaps = chunk.heightmaps_tag();
-                for i in -6..=6 {
-                    for j in -6..=6 {
-                        let _ = client.outgoing.send(Box::new(ChunkDataAndLight {
-                            x: i,
-                            z: j,
-                            heightmaps: heightmaps.clone(),
-                            chunk_data: encoded.clone(),
-                            block_entities: vec![],
-                            chunk_lighting: ChunkLighting {
-                                sky_light_mask: BitVec::zeros(chunk.sections().len()),
-                                block_light_mask: BitVec::zeros(chunk.sections().len()),
-                                empty_sky_light_mask: BitVec::ones(chunk.sections().len()),
-                                empty_block_light_mask: BitVec::ones(chunk.sections().len()),
-                                sky_light_sections: vec![],
-                                block_light_sections: vec![],
-                            },
-                        }));
-                    }
-                }
-            }
-            let _ = client
-                .outgoing
-                .send(Box::new(ChunkBatchFinish { size: 49 }));
-            let _ = client.outgoing.send(Box::new(DefaultSpawnPosition {
-                location: (0, 10, 0),
-                angle: 0.0,
-            }));
-
-            let _ = client.outgoing.send(Box::new(SyncPlayerPosition {
-                x: 0.0,
-                y: 10.0,
-                z: 0.0,
-                yaw: 0.0,
-                pitch: 0.0,
-                flags: 0,
-                teleport_id: 0,
-            }));
-
-            client.status = ClientStatus::Connecting;
-        }
+        ClientStatus::Init => match configure(client, server) {
+            Ok(()) => client.status = ClientStatus::Configuring,
+            Err(err) => send_failed(client, err),
+        },
         ClientStatus::Connecting => {
             client.status = ClientStatus::Connected;
+            server.tab_list.join(client.handle);
         }
-        ClientStatus::Connected => {
-            if client.incoming.is_disconnected() || client.outgoing.is_disconnected() {
+        ClientStatus::Configuring | ClientStatus::Connected => {
+            if client.incoming.is_disconnected() || client.outgoing.is_closed() {
                 client.status = ClientStatus::Disconnecting;
+            } else if let Err(err) = client.outgoing.check_backlog(Instant::now()) {
+                tracing::warn!(
+                    ?err,
+                    profile = ?client.profile,
+                    metrics = ?client.outgoing.metrics(),
+                    "client is not keeping up with its packets"
+                );
+                // the backlog would otherwise delay the disconnect
+                client.outgoing.clear();
+                client.disconnect(TextComponent::translate("disconnect.overflow", Vec::new()));
+            } else if let Err(err) =
+                process_packets(client, server).and_then(|()| keep_alive(client))
+            {
+                tracing::debug!(?err, profile = ?client.profile, "keep alive failed");
+                client.disconnect(err.reason());
+                if let KeepAliveErr::TimedOut = err {
+                    client.status = ClientStatus::TimedOut;
+                }
             }
         }
+        ClientStatus::TimedOut => {
+            client.status = ClientStatus::Disconnecting;
+        }
         ClientStatus::Disconnecting => {
-            tracing::debug!(profile = ?client.profile, "client disconnected");
+            tracing::debug!(
+                profile = ?client.profile,
+                metrics = ?client.outgoing.metrics(),
+                "client disconnected"
+            );
             client.status = ClientStatus::Disconnected;
+            server.tab_list.leave(client.profile.uuid);
+            let _ = server.client_events.send(ClientEvent::Disconnected {
+                handle: client.handle,
+                profile: client.profile.clone(),
+                reason: client.disconnect_reason.take(),
+            });
         }
         ClientStatus::Disconnected => {}
     }
 }
+
+/// Disconnects a client whose configuration or join packets could not be queued.
+fn send_failed(client: &mut Client, err: QueueErr) {
+    tracing::warn!(?err, profile = ?client.profile, "unable to send join packets");
+    client.disconnect(TextComponent::translate("disconnect.genericReason", vec![
+        TextComponent::text(format!("{:?}", err)),
+    ]));
+}
+
+/// Sends what the client needs to know before entering play and finishes the configuration, the
+/// client stays in the configuration state until it acknowledges it.
+fn configure(client: &mut Client, server: &Server) -> Result<(), QueueErr> {
+    let server_brand = make_server_brand_message(&identifier!("vanilla"));
+    tracing::trace!(?server_brand, "sending server brand message");
+    client.outgoing.send(&server_brand)?;
+    let feature_flags = FeatureFlags {
+        flags: vec![identifier!("vanilla")],
+    };
+    tracing::trace!(?feature_flags, "sending feature flags packet");
+    client.outgoing.send(&feature_flags)?;
+    tracing::trace!("sending registry data");
+    if client.version == ProtocolVersion::V765 {
+        client.outgoing.send(&RegistryData {
+            registries: server.resources.registry_data.clone(),
+        })?;
+    } else {
+        // every entry carries its data, the known packs only tell the client what the server has
+        client
+            .outgoing
+            .send(&v766::clientbound::ClientBoundKnownPacks {
+                packs: vec![KnownPack {
+                    namespace: "minecraft".to_string(),
+                    id: "core".to_string(),
+                    version: v766::PROTO_NAME.to_string(),
+                }],
+            })?;
+        for registry_data in server.resources.registry_packets.iter() {
+            client.outgoing.send(registry_data)?;
+        }
+    }
+    let update_tags = UpdateTags { tags: vec![] };
+    tracing::trace!(?update_tags, "sending update tags packet");
+    client.outgoing.send(&update_tags)?;
+    tracing::trace!("sending finish configuration packet");
+    client.outgoing.send(&ServerFinishConfiguration)
+}
+
+/// Sends the join packet and the world around the spawn.
+fn join(client: &mut Client, server: &mut Server) -> Result<(), QueueErr> {
+    let dimensions = vec![
+        identifier!("overworld"),
+        identifier!("the_nether"),
+        identifier!("the_end"),
+    ];
+    if client.version == ProtocolVersion::V765 {
+        client.outgoing.send(&GameJoin {
+            entity_id: 0,
+            is_hardcore: false,
+            dimensions,
+            max_players: 100,
+            view_distance: 6,
+            sim_distance: 6,
+            reduced_debug: false,
+            enable_respawn: false,
+            limited_crafting: false,
+            dimension_type: identifier!("overworld"),
+            dimension_name: identifier!("overworld"),
+            seed: 0,
+            game_mode: GameMode::Survival,
+            last_game_mode: LastGameMode::Undefined,
+            is_debug: false,
+            is_flag: false,
+            death_location: None,
+            portal_cooldown: 0,
+        })?;
+        // tells the client whether unsigned chat is accepted, 1.20.5 moved this to the join
+        // packet
+        client.outgoing.send(&ServerData {
+            motd: TextComponent::text(server.config.motd.clone()),
+            icon: None,
+            enforces_secure_chat: server.config.enforce_secure_profile,
+        })?;
+    } else {
+        client.outgoing.send(&v766::clientbound::GameJoin {
+            entity_id: 0,
+            is_hardcore: false,
+            dimensions,
+            max_players: 100,
+            view_distance: 6,
+            sim_distance: 6,
+            reduced_debug: false,
+            enable_respawn: false,
+            limited_crafting: false,
+            dimension_type: server
+                .resources
+                .dimension_types
+                .get("minecraft:overworld")
+                .copied()
+                .unwrap_or(0),
+            dimension_name: identifier!("overworld"),
+            seed: 0,
+            game_mode: GameMode::Survival,
+            last_game_mode: LastGameMode::Undefined,
+            is_debug: false,
+            is_flat: false,
+            death_location: None,
+            portal_cooldown: 0,
+            enforces_secure_chat: server.config.enforce_secure_profile,
+        })?;
+    }
+    let commands = server.commands.graph(&CommandSource::Player(client.handle));
+    if client.version == ProtocolVersion::V765 {
+        client.outgoing.send(&commands)?;
+    } else {
+        client
+            .outgoing
+            .send(&v766::clientbound::Commands::from(commands))?;
+    }
+    client.outgoing.send(&ChangeDifficulty {
+        difficulty: Difficulty::Hard,
+        locked: false,
+    })?;
+    client.outgoing.send(&PlayerAbilities {
+        flags: 0,
+        fly_speed: 0.5,
+        fov_modifier: 0.1,
+    })?;
+
+    client.outgoing.send(&SetCenterChunk { x: 0, z: 0 })?;
+
+    client.outgoing.send(&ServerGameEvent {
+        event: GameEvent::StartWaitingForLevelChunks,
+        value: 0.0,
+    })?;
+    client.outgoing.send(&ChunkBatchStart)?;
+    let spawn_chunks = server.spawn_chunks(client.version, client.outgoing.compression());
+    for packet in spawn_chunks.iter() {
+        client
+            .outgoing
+            .send_pre_encoded(packet.clone(), PacketPriority::Normal)?;
+    }
+    client.outgoing.send(&ChunkBatchFinish {
+        size: spawn_chunks.len() as i32,
+    })?;
+    client.outgoing.send(&DefaultSpawnPosition {
+        location: (0, 10, 0),
+        angle: 0.0,
+    })?;
+
+    client.outgoing.send(&SyncPlayerPosition {
+        x: 0.0,
+        y: 10.0,
+        z: 0.0,
+        yaw: 0.0,
+        pitch: 0.0,
+        flags: 0,
+        teleport_id: 0,
+    })
+}
+
+/// Handles the packets the client sent since the last tick.
+fn process_packets(client: &mut Client, server: &mut Server) -> Result<(), KeepAliveErr> {
+    let now = Instant::now();
+    client.chat.tick();
+    while let Ok(packet) = client.incoming.try_recv() {
+        let packet = match packet {
+            IncomingPacket::Configuration(packet) => {
+                if process_configuration_packet(client, packet, now)? {
+                    client.state = ConnectionState::Play;
+                    match join(client, server) {
+                        Ok(()) => client.status = ClientStatus::Connecting,
+                        Err(err) => {
+                            send_failed(client, err);
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+            IncomingPacket::Play(packet) => packet,
+        };
+        let rejected = match packet {
+            ServerboundPlay::KeepAlive(keep_alive) => {
+                client.keep_alive.acknowledge(keep_alive.id, now)?;
+                tracing::trace!(latency = ?client.keep_alive.latency(), "received keep alive");
+                None
+            }
+            ServerboundPlay::CommandSuggestionsRequest(request) => {
+                server.suggestion_requests.push((client.handle, request));
+                None
+            }
+            ServerboundPlay::ChatMessage(message) => {
+                let accepted = client.chat.accept_message(
+                    client.profile.uuid,
+                    &message,
+                    server.config.enforce_secure_profile,
+                );
+                match accepted {
+                    Ok(signed) => {
+                        server.pending_chat.push(ChatEvent {
+                            sender: client.handle,
+                            sender_uuid: client.profile.uuid,
+                            sender_name: client.profile.name.clone(),
+                            message: message.message,
+                            timestamp: message.timestamp,
+                            salt: message.salt,
+                            signed,
+                            decorated: None,
+                            system: None,
+                            cancelled: false,
+                        });
+                        None
+                    }
+                    Err(err) => Some(err),
+                }
+            }
+            ServerboundPlay::ChatCommand(command) => {
+                match client.chat.accept_signed_command(&command) {
+                    Ok(()) => {
+                        server
+                            .pending_commands
+                            .push((client.handle, command.command));
+                        None
+                    }
+                    Err(err) => Some(err),
+                }
+            }
+            // 1.20.5 sends commands without signed arguments in a packet of their own
+            ServerboundPlay::ChatCommandV766(command) => {
+                match client.chat.accept_command(command.command.as_str()) {
+                    Ok(()) => {
+                        server
+                            .pending_commands
+                            .push((client.handle, command.command));
+                        None
+                    }
+                    Err(err) => Some(err),
+                }
+            }
+            ServerboundPlay::AcknowledgeMessage(ack) => {
+                client.chat.last_seen.apply_offset(ack.message_count).err()
+            }
+            ServerboundPlay::PlayerSession(session) => {
+                let updated = client.chat.update_session(
+                    client.profile.uuid,
+                    &session,
+                    server.profile_keys.as_ref(),
+                );
+                match updated {
+                    Ok(Some(chat_session)) => {
+                        server.chat_sessions.push(PlayerInfoEntry {
+                            uuid: client.profile.uuid,
+                            chat_session: Some(chat_session),
+                            ..PlayerInfoEntry::default()
+                        });
+                        None
+                    }
+                    Ok(None) => None,
+                    Err(err) => Some(err),
+                }
+            }
+            _ => None,
+        };
+        if let Some(err) = rejected {
+            if reject_chat(client, err) {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handles a packet the client sent while it is configured, returns whether the client finished
+/// its configuration.
+fn process_configuration_packet(
+    client: &mut Client,
+    packet: ServerboundConfiguration,
+    now: Instant,
+) -> Result<bool, KeepAliveErr> {
+    match packet {
+        ServerboundConfiguration::ConfigKeepAlive(keep_alive) => {
+            client.keep_alive.acknowledge(keep_alive.id, now)?;
+            tracing::trace!(latency = ?client.keep_alive.latency(), "received keep alive");
+            Ok(false)
+        }
+        ServerboundConfiguration::ClientFinishConfiguration(_) => Ok(true),
+        _ => Ok(false),
+    }
+}
+
+/// Disconnects the client if the error is fatal, otherwise tells it why its message was dropped.
+/// Returns whether the client was disconnected.
+fn reject_chat(client: &mut Client, err: ChatErr) -> bool {
+    tracing::debug!(?err, profile = ?client.profile, "rejected chat");
+    if err.disconnects() {
+        client.disconnect(err.reason());
+        return true;
+    }
+    let _ = client.outgoing.send(&SystemChatMessage {
+        content: err.reason().color(TextColor::Red),
+        overlay: false,
+    });
+    false
+}
+
+fn keep_alive(client: &mut Client) -> Result<(), KeepAliveErr> {
+    if let Some(id) = client.keep_alive.poll(Instant::now())? {
+        tracing::trace!(id, "sending keep alive");
+        let _ = match client.state {
+            ConnectionState::Configuration => client
+                .outgoing
+                .send(&clientbound::ConfigKeepAlive { keep_alive_id: id }),
+            _ => client.outgoing.send(&clientbound::KeepAlive { id }),
+        };
+    }
+    Ok(())
+}
+
+/// Encodes the flat chunks around spawn, shared by every client with the same protocol version
+/// and compression threshold.
+pub fn encode_spawn_chunks(
+    version: ProtocolVersion,
+    compression: Option<usize>,
+) -> Vec<PreEncodedPacket> {
+    let generator = FlatGeneratorBuilder::new(384)
+        .layer(Block::IronBlock, 64)
+        .build();
+    let chunk = generator.generate((0, 0).into());
+    let Ok(encoded) = encode_chunk(&chunk) else {
+        return Vec::new();
+    };
+    let heightmaps = chunk.heightmaps_tag();
+    let mut packets = Vec::new();
+    for i in -6..=6 {
+        for j in -6..=6 {
+            let packet = ChunkDataAndLight {
+                x: i,
+                z: j,
+                heightmaps: heightmaps.clone(),
+                chunk_data: encoded.clone(),
+                block_entities: vec![],
+                chunk_lighting: ChunkLighting {
+                    sky_light_mask: BitVec::zeros(chunk.sections().len()),
+                    block_light_mask: BitVec::zeros(chunk.sections().len()),
+                    empty_sky_light_mask: BitVec::ones(chunk.sections().len()),
+                    empty_block_light_mask: BitVec::ones(chunk.sections().len()),
+                    sky_light_sections: vec![],
+                    block_light_sections: vec![],
+                },
+            };
+            match PreEncodedPacket::new(version, ClientBound, &packet, compression) {
+                Ok(packet) => packets.push(packet),
+                Err(err) => tracing::error!(?err, "unable to encode spawn chunk"),
+            }
+        }
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        net::{Ipv4Addr, SocketAddr},
+        time::Instant,
+    };
+
+    use serverx_protocol::{
+        decode::{BasicAllocTracker, ProtoDecode},
+        dispatch::ServerboundConfiguration,
+        io::EncodedPacket,
+        packet::ConnectionState,
+        types::VarInt,
+        v765::{clientbound, serverbound},
+        version::ProtocolVersion,
+    };
+
+    use crate::{
+        client::{
+            keep_alive::{KeepAlive, KeepAliveErr, KEEP_ALIVE_INTERVAL},
+            profile::{offline_uuid, Profile},
+            update::{keep_alive, process_configuration_packet},
+            Clients,
+        },
+        network::{
+            event::IncomingPacket,
+            queue::{outgoing_queue, OutgoingPacket, OutgoingReceiver, QueueLimits},
+        },
+    };
+
+    /// Reads the id and body of the next packet queued for the client.
+    async fn recv_packet(receiver: &OutgoingReceiver) -> (i32, Vec<u8>) {
+        let Some(OutgoingPacket::Encoded(EncodedPacket::Body(body))) = receiver.recv().await else {
+            panic!("expected an encoded packet");
+        };
+        let mut cursor = Cursor::new(body.as_slice());
+        let id = VarInt::decode(&mut cursor, &mut BasicAllocTracker::new(1024)).unwrap();
+        (id, body[cursor.position() as usize..].to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_configuration_keep_alive() {
+        let mut clients = Clients::new();
+        let (outgoing, receiver) =
+            outgoing_queue(ProtocolVersion::V765, None, QueueLimits::default());
+        let (_incoming_tx, incoming_rx) = flume::unbounded::<IncomingPacket>();
+        let profile = Profile::try_from(("Notch".to_string(), offline_uuid("Notch"))).unwrap();
+        let client = clients.add(
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 25565),
+            ProtocolVersion::V765,
+            outgoing,
+            incoming_rx,
+            profile,
+        );
+        let now = Instant::now();
+        client.keep_alive = KeepAlive::new(now.checked_sub(KEEP_ALIVE_INTERVAL).unwrap());
+        keep_alive(client).unwrap();
+        let (id, body) = recv_packet(&receiver).await;
+        assert_eq!(id, clientbound::ConfigKeepAlive::ID);
+        let sent = clientbound::ConfigKeepAlive::decode(
+            &mut Cursor::new(body.as_slice()),
+            &mut BasicAllocTracker::new(1024),
+        )
+        .unwrap();
+
+        let wrong = ServerboundConfiguration::ConfigKeepAlive(serverbound::ConfigKeepAlive {
+            id: sent.keep_alive_id.wrapping_add(1),
+        });
+        assert!(matches!(
+            process_configuration_packet(client, wrong, now),
+            Err(KeepAliveErr::UnexpectedId(_))
+        ));
+        let answer = ServerboundConfiguration::ConfigKeepAlive(serverbound::ConfigKeepAlive {
+            id: sent.keep_alive_id,
+        });
+        assert!(!process_configuration_packet(client, answer, now).unwrap());
+        assert!(client.keep_alive.latency().is_some());
+
+        let finish = ServerboundConfiguration::ClientFinishConfiguration(
+            serverbound::ClientFinishConfiguration,
+        );
+        assert!(process_configuration_packet(client, finish, now).unwrap());
+
+        // once in play the play keep alive is sent
+        client.state = ConnectionState::Play;
+        client.keep_alive = KeepAlive::new(now.checked_sub(KEEP_ALIVE_INTERVAL).unwrap());
+        keep_alive(client).unwrap();
+        assert_eq!(recv_packet(&receiver).await.0, clientbound::KeepAlive::ID);
+    }
+}
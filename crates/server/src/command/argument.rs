@@ -0,0 +1,511 @@
+use serverx_common::identifier::{self, Identifier};
+use serverx_macros::identifier;
+use serverx_protocol::v765::types::{CommandParser, StringKind};
+use uuid::Uuid;
+
+use crate::{
+    client::profile::MAX_USERNAME_LEN,
+    command::{
+        dispatcher::{CommandErr, CommandSender},
+        reader::StringReader,
+    },
+};
+
+/// How an argument node parses its part of the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentType {
+    Integer {
+        min: i32,
+        max: i32,
+    },
+    Float {
+        min: f32,
+        max: f32,
+    },
+    /// A single word.
+    Word,
+    /// Everything up to the end of the command line.
+    GreedyString,
+    Entity {
+        single: bool,
+        players_only: bool,
+    },
+    BlockPos,
+    Identifier,
+}
+
+#[derive(Debug, Clone)]
+pub enum ArgumentValue {
+    Integer(i32),
+    Float(f32),
+    String(String),
+    Entity(EntitySelector),
+    BlockPos(BlockPos),
+    Identifier(Identifier),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorKind {
+    /// `@p`
+    NearestPlayer,
+    /// `@a`
+    AllPlayers,
+    /// `@r`
+    RandomPlayer,
+    /// `@e`
+    AllEntities,
+    /// `@s`
+    Executor,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EntitySelector {
+    Player(String),
+    Uuid(Uuid),
+    /// A target selector with its unparsed `key=value` arguments.
+    Selector {
+        kind: SelectorKind,
+        args: Vec<(String, String)>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinate {
+    Absolute(i32),
+    /// `~`, relative to the position of the source.
+    Relative(f64),
+    /// `^`, relative to the position and rotation of the source.
+    Local(f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlockPos {
+    pub x: Coordinate,
+    pub y: Coordinate,
+    pub z: Coordinate,
+}
+
+impl BlockPos {
+    /// Resolves the position against the block position of the source, local coordinates need
+    /// a rotation and resolve to `None`.
+    pub fn resolve(&self, origin: [i32; 3]) -> Option<[i32; 3]> {
+        let mut resolved = [0i32; 3];
+        for (i, coordinate) in [self.x, self.y, self.z].iter().enumerate() {
+            resolved[i] = match coordinate {
+                Coordinate::Absolute(value) => *value,
+                Coordinate::Relative(offset) => (origin[i] as f64 + offset).floor() as i32,
+                Coordinate::Local(_) => return None,
+            };
+        }
+        Some(resolved)
+    }
+}
+
+fn invalid(reader: &StringReader, message: impl Into<String>) -> CommandErr {
+    CommandErr::InvalidArgument(reader.cursor(), message.into())
+}
+
+fn is_number_char(c: char) -> bool {
+    c.is_ascii_digit() || c == '-' || c == '.'
+}
+
+fn read_int(reader: &mut StringReader) -> Result<i32, CommandErr> {
+    let start = *reader;
+    let number = reader.read_while(is_number_char);
+    if number.is_empty() {
+        return Err(invalid(&start, "Expected integer"));
+    }
+    number
+        .parse::<i32>()
+        .map_err(|_| invalid(&start, format!("Invalid integer '{}'", number)))
+}
+
+fn read_float(reader: &mut StringReader) -> Result<f64, CommandErr> {
+    let start = *reader;
+    let number = reader.read_while(is_number_char);
+    if number.is_empty() {
+        return Err(invalid(&start, "Expected float"));
+    }
+    number
+        .parse::<f64>()
+        .map_err(|_| invalid(&start, format!("Invalid float '{}'", number)))
+}
+
+fn read_coordinate(reader: &mut StringReader) -> Result<Coordinate, CommandErr> {
+    let prefix = reader.peek();
+    if prefix == Some('~') || prefix == Some('^') {
+        reader.skip();
+        let offset = match reader.peek() {
+            Some(c) if c != ' ' => read_float(reader)?,
+            _ => 0.0,
+        };
+        return Ok(if prefix == Some('~') {
+            Coordinate::Relative(offset)
+        } else {
+            Coordinate::Local(offset)
+        });
+    }
+    if !reader.can_read() || reader.peek() == Some(' ') {
+        return Err(invalid(reader, "Incomplete (expected 3 coordinates)"));
+    }
+    Ok(Coordinate::Absolute(read_int(reader)?))
+}
+
+fn read_block_pos(reader: &mut StringReader) -> Result<BlockPos, CommandErr> {
+    let start = *reader;
+    let x = read_coordinate(reader)?;
+    let mut coordinates = [x, x, x];
+    for coordinate in coordinates.iter_mut().skip(1) {
+        if reader.peek() != Some(' ') {
+            return Err(invalid(reader, "Incomplete (expected 3 coordinates)"));
+        }
+        reader.skip();
+        *coordinate = read_coordinate(reader)?;
+    }
+    let local = coordinates
+        .iter()
+        .filter(|c| matches!(c, Coordinate::Local(_)))
+        .count();
+    if local != 0 && local != 3 {
+        return Err(invalid(
+            &start,
+            "Cannot mix world & local coordinates (everything must either use ^ or not)",
+        ));
+    }
+    Ok(BlockPos {
+        x: coordinates[0],
+        y: coordinates[1],
+        z: coordinates[2],
+    })
+}
+
+fn read_selector_args(reader: &mut StringReader) -> Result<Vec<(String, String)>, CommandErr> {
+    let mut args = Vec::new();
+    if reader.peek() != Some('[') {
+        return Ok(args);
+    }
+    reader.skip();
+    loop {
+        match reader.peek() {
+            Some(']') => {
+                reader.skip();
+                return Ok(args);
+            }
+            None => return Err(invalid(reader, "Expected end of options")),
+            _ => {}
+        }
+        let key = reader.read_while(|c| c != '=' && c != ',' && c != ']' && c != ' ');
+        if reader.peek() != Some('=') {
+            return Err(invalid(
+                reader,
+                format!("Expected value for option '{}'", key),
+            ));
+        }
+        reader.skip();
+        // values may contain nested brackets, like scores={a=1}
+        let mut depth = 0;
+        let value = reader.read_while(|c| match c {
+            '{' | '[' => {
+                depth += 1;
+                true
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                true
+            }
+            ']' if depth > 0 => {
+                depth -= 1;
+                true
+            }
+            ',' | ']' => depth > 0,
+            _ => true,
+        });
+        args.push((key.to_string(), value.to_string()));
+        if reader.peek() == Some(',') {
+            reader.skip();
+        }
+    }
+}
+
+fn read_entity(
+    reader: &mut StringReader,
+    single: bool,
+    players_only: bool,
+) -> Result<EntitySelector, CommandErr> {
+    let start = *reader;
+    if reader.peek() == Some('@') {
+        reader.skip();
+        let kind = match reader.peek() {
+            Some('p') => SelectorKind::NearestPlayer,
+            Some('a') => SelectorKind::AllPlayers,
+            Some('r') => SelectorKind::RandomPlayer,
+            Some('e') => SelectorKind::AllEntities,
+            Some('s') => SelectorKind::Executor,
+            _ => {
+                let mut word_reader = start;
+                let word = word_reader.read_word();
+                return Err(invalid(&start, format!("Unknown selector type '{}'", word)));
+            }
+        };
+        reader.skip();
+        let args = read_selector_args(reader)?;
+        let has_arg = |key: &str, value: &str| args.iter().any(|(k, v)| k == key && v == value);
+        let multiple = matches!(kind, SelectorKind::AllPlayers | SelectorKind::AllEntities);
+        if single && multiple && !has_arg("limit", "1") {
+            return Err(invalid(
+                &start,
+                "Only one entity is allowed, but the provided selector allows more than one",
+            ));
+        }
+        if players_only && kind == SelectorKind::AllEntities && !has_arg("type", "player") {
+            return Err(invalid(
+                &start,
+                "Only players may be affected by this command, but the provided selector includes \
+                 entities",
+            ));
+        }
+        return Ok(EntitySelector::Selector { kind, args });
+    }
+    let word = reader.read_word();
+    if let Ok(uuid) = Uuid::try_parse(word) {
+        return Ok(EntitySelector::Uuid(uuid));
+    }
+    let valid_name = !word.is_empty()
+        && word.len() <= MAX_USERNAME_LEN
+        && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_name {
+        return Err(invalid(&start, "Invalid name or UUID"));
+    }
+    Ok(EntitySelector::Player(word.to_string()))
+}
+
+impl ArgumentType {
+    pub fn parse(&self, reader: &mut StringReader) -> Result<ArgumentValue, CommandErr> {
+        let start = *reader;
+        match self {
+            ArgumentType::Integer { min, max } => {
+                let value = read_int(reader)?;
+                if value < *min {
+                    Err(invalid(
+                        &start,
+                        format!("Integer must not be less than {}, found {}", min, value),
+                    ))
+                } else if value > *max {
+                    Err(invalid(
+                        &start,
+                        format!("Integer must not be more than {}, found {}", max, value),
+                    ))
+                } else {
+                    Ok(ArgumentValue::Integer(value))
+                }
+            }
+            ArgumentType::Float { min, max } => {
+                let value = read_float(reader)? as f32;
+                if value < *min {
+                    Err(invalid(
+                        &start,
+                        format!("Float must not be less than {}, found {}", min, value),
+                    ))
+                } else if value > *max {
+                    Err(invalid(
+                        &start,
+                        format!("Float must not be more than {}, found {}", max, value),
+                    ))
+                } else {
+                    Ok(ArgumentValue::Float(value))
+                }
+            }
+            ArgumentType::Word => {
+                let word = reader.read_word();
+                if word.is_empty() {
+                    return Err(invalid(&start, "Expected string"));
+                }
+                Ok(ArgumentValue::String(word.to_string()))
+            }
+            ArgumentType::GreedyString => {
+                Ok(ArgumentValue::String(reader.read_to_end().to_string()))
+            }
+            ArgumentType::Entity {
+                single,
+                players_only,
+            } => read_entity(reader, *single, *players_only).map(ArgumentValue::Entity),
+            ArgumentType::BlockPos => read_block_pos(reader).map(ArgumentValue::BlockPos),
+            ArgumentType::Identifier => {
+                let word = reader.read_word();
+                Identifier::try_from(word)
+                    .map(ArgumentValue::Identifier)
+                    .map_err(|_| invalid(&start, format!("Invalid ID '{}'", word)))
+            }
+        }
+    }
+
+    /// Completions for the partially typed `remaining` argument.
+    /// The parser the client uses to read and highlight the argument.
+    pub fn parser(&self) -> CommandParser {
+        match self {
+            ArgumentType::Integer { min, max } => CommandParser::Integer {
+                min: (*min > i32::MIN).then_some(*min),
+                max: (*max < i32::MAX).then_some(*max),
+            },
+            ArgumentType::Float { min, max } => CommandParser::Float {
+                min: (*min > f32::MIN).then_some(*min),
+                max: (*max < f32::MAX).then_some(*max),
+            },
+            ArgumentType::Word => CommandParser::String(StringKind::SingleWord),
+            ArgumentType::GreedyString => CommandParser::String(StringKind::GreedyPhrase),
+            ArgumentType::Entity {
+                single,
+                players_only,
+            } => CommandParser::Entity {
+                single: *single,
+                players_only: *players_only,
+            },
+            ArgumentType::BlockPos => CommandParser::BlockPos,
+            ArgumentType::Identifier => CommandParser::ResourceLocation,
+        }
+    }
+
+    /// Where the client gets completions from, arguments without one are completed by the client
+    /// on its own.
+    pub fn suggestions(&self) -> Option<Identifier> {
+        match self {
+            ArgumentType::Entity { .. } => Some(identifier!("ask_server")),
+            _ => None,
+        }
+    }
+
+    pub fn suggest<S: CommandSender>(&self, source: &S, remaining: &str) -> Vec<String> {
+        let candidates: Vec<String> = match self {
+            ArgumentType::Entity { .. } => {
+                let mut candidates: Vec<String> = ["@p", "@a", "@r", "@s", "@e"]
+                    .iter()
+                    .map(|selector| selector.to_string())
+                    .collect();
+                candidates.extend(source.player_names());
+                candidates
+            }
+            ArgumentType::BlockPos => {
+                // complete the coordinates that are still missing with ~
+                let typed = remaining.split(' ').count();
+                if typed > 3 {
+                    return Vec::new();
+                }
+                let mut suggestion = remaining.to_string();
+                if remaining.is_empty() || remaining.ends_with(' ') {
+                    suggestion.push('~');
+                }
+                for _ in typed..3 {
+                    suggestion.push_str(" ~");
+                }
+                vec![suggestion]
+            }
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(remaining))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::command::{
+        argument::{ArgumentType, ArgumentValue, Coordinate, EntitySelector, SelectorKind},
+        dispatcher::CommandErr,
+        reader::StringReader,
+    };
+
+    fn parse(ty: ArgumentType, input: &str) -> Result<ArgumentValue, CommandErr> {
+        ty.parse(&mut StringReader::new(input))
+    }
+
+    #[test]
+    fn test_parse_numbers() {
+        let int = ArgumentType::Integer { min: 0, max: 10 };
+        assert!(matches!(
+            parse(int.clone(), "7"),
+            Ok(ArgumentValue::Integer(7))
+        ));
+        assert!(matches!(
+            parse(int.clone(), "11"),
+            Err(CommandErr::InvalidArgument(0, _))
+        ));
+        assert!(parse(int, "x").is_err());
+        let float = ArgumentType::Float {
+            min: -1.0,
+            max: 1.0,
+        };
+        assert!(matches!(parse(float, "-0.5"), Ok(ArgumentValue::Float(v)) if v == -0.5));
+    }
+
+    fn parse_entity(ty: &ArgumentType, input: &str) -> EntitySelector {
+        match parse(ty.clone(), input) {
+            Ok(ArgumentValue::Entity(selector)) => selector,
+            result => panic!("expected an entity selector, got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_parse_entity() {
+        let players = ArgumentType::Entity {
+            single: false,
+            players_only: true,
+        };
+        let single = ArgumentType::Entity {
+            single: true,
+            players_only: true,
+        };
+        assert_eq!(
+            parse_entity(&players, "Notch"),
+            EntitySelector::Player("Notch".to_string())
+        );
+        assert_eq!(
+            parse_entity(&players, "069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+            EntitySelector::Uuid(Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5))
+        );
+        assert_eq!(
+            parse_entity(&players, "@a[distance=..5,scores={a=1,b=2}]"),
+            EntitySelector::Selector {
+                kind: SelectorKind::AllPlayers,
+                args: vec![
+                    ("distance".to_string(), "..5".to_string()),
+                    ("scores".to_string(), "{a=1,b=2}".to_string()),
+                ],
+            }
+        );
+        assert!(parse(single.clone(), "@a").is_err());
+        assert!(parse(single, "@a[limit=1]").is_ok());
+        assert!(parse(players.clone(), "@e").is_err());
+        assert!(parse(players.clone(), "@x").is_err());
+        assert!(parse(players, "a_name_that_is_too_long").is_err());
+    }
+
+    #[test]
+    fn test_parse_block_pos() {
+        let Ok(ArgumentValue::BlockPos(pos)) = parse(ArgumentType::BlockPos, "1 ~ ~-2.5") else {
+            panic!("expected a block position");
+        };
+        assert_eq!(pos.x, Coordinate::Absolute(1));
+        assert_eq!(pos.y, Coordinate::Relative(0.0));
+        assert_eq!(pos.resolve([10, 64, 10]), Some([1, 64, 7]));
+        let Ok(ArgumentValue::BlockPos(pos)) = parse(ArgumentType::BlockPos, "^ ^1 ^") else {
+            panic!("expected a block position");
+        };
+        assert_eq!(pos.resolve([0, 0, 0]), None);
+        assert!(parse(ArgumentType::BlockPos, "^ ~ ^").is_err());
+        assert!(parse(ArgumentType::BlockPos, "1 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_identifier() {
+        let Ok(ArgumentValue::Identifier(identifier)) = parse(ArgumentType::Identifier, "stone")
+        else {
+            panic!("expected an identifier");
+        };
+        assert_eq!(identifier.as_str(), "minecraft:stone");
+        assert!(parse(ArgumentType::Identifier, "Not:Valid").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use rand::seq::IteratorRandom;
+use serverx_protocol::text::TextComponent;
+
+use crate::{
+    client::{ClientHandle, Clients},
+    command::{
+        argument::{ArgumentType, EntitySelector, SelectorKind},
+        dispatcher::{argument, literal, Arguments, CommandDispatcher, CommandErr, CommandSender},
+        CommandSource,
+    },
+    server::Server,
+};
+
+/// Runs a parsed command, returns how many things it affected like vanilla's result value.
+pub type ServerExecutor =
+    for<'a> fn(&mut CommandExecution<'a>, &Arguments) -> Result<i32, CommandErr>;
+
+/// The state a command runs with, messages pushed to `output` are sent back to the source.
+pub struct CommandExecution<'a> {
+    pub server: &'a mut Server,
+    pub clients: &'a mut Clients,
+    pub source: CommandSource,
+    pub output: Vec<String>,
+}
+
+pub fn permission_level(source: CommandSource) -> u8 {
+    match source {
+        CommandSource::Console | CommandSource::Rcon(_) => 4,
+        CommandSource::Player(_) => 0,
+    }
+}
+
+impl CommandSender for CommandSource {
+    fn permission_level(&self) -> u8 {
+        permission_level(*self)
+    }
+}
+
+impl CommandSender for CommandExecution<'_> {
+    fn permission_level(&self) -> u8 {
+        permission_level(self.source)
+    }
+
+    fn player_names(&self) -> Vec<String> {
+        self.clients
+            .clients
+            .iter()
+            .map(|(_, client)| client.profile.name.clone())
+            .collect()
+    }
+}
+
+impl CommandExecution<'_> {
+    pub fn send_message(&mut self, message: impl Into<String>) {
+        self.output.push(message.into());
+    }
+
+    /// The players a selector matches, selector arguments are not applied.
+    pub fn select_players(
+        &self,
+        selector: &EntitySelector,
+    ) -> Result<Vec<ClientHandle>, CommandErr> {
+        let players = &self.clients.clients;
+        let sender = match self.source {
+            CommandSource::Player(handle) => self.clients.get(handle).map(|client| client.handle),
+            _ => None,
+        };
+        let selected: Vec<ClientHandle> = match selector {
+            EntitySelector::Player(name) => players
+                .iter()
+                .filter(|(_, client)| client.profile.name.eq_ignore_ascii_case(name))
+                .map(|(_, client)| client.handle)
+                .collect(),
+            EntitySelector::Uuid(uuid) => self
+                .clients
+                .client_lookup
+                .get(uuid)
+                .and_then(|key| players.get(*key))
+                .map(|client| client.handle)
+                .into_iter()
+                .collect(),
+            EntitySelector::Selector { kind, .. } => match kind {
+                SelectorKind::AllPlayers | SelectorKind::AllEntities => {
+                    players.iter().map(|(_, client)| client.handle).collect()
+                }
+                SelectorKind::RandomPlayer => players
+                    .iter()
+                    .map(|(_, client)| client.handle)
+                    .choose(&mut rand::thread_rng())
+                    .into_iter()
+                    .collect(),
+                // positions are not tracked yet, the sender is the nearest player to itself
+                SelectorKind::NearestPlayer => sender
+                    .or_else(|| players.iter().map(|(_, client)| client.handle).next())
+                    .into_iter()
+                    .collect(),
+                SelectorKind::Executor => sender.into_iter().collect(),
+            },
+        };
+        if selected.is_empty() {
+            return Err(CommandErr::Failed("No player was found".to_string()));
+        }
+        Ok(selected)
+    }
+}
+
+pub fn register_builtin(dispatcher: &mut CommandDispatcher<ServerExecutor>) {
+    dispatcher.register(literal::<ServerExecutor>("stop").requires(4).executes(stop));
+    dispatcher.register(
+        literal("kick").requires(3).then(
+            argument::<ServerExecutor>("targets", ArgumentType::Entity {
+                single: false,
+                players_only: true,
+            })
+            .executes(kick)
+            .then(argument::<ServerExecutor>("reason", ArgumentType::GreedyString).executes(kick)),
+        ),
+    );
+    dispatcher.register(literal::<ServerExecutor>("list").executes(list));
+    dispatcher.register(literal::<ServerExecutor>("help").executes(help));
+}
+
+fn stop(execution: &mut CommandExecution<'_>, _: &Arguments) -> Result<i32, CommandErr> {
+    execution.send_message("Stopping the server");
+    execution.server.stopping = true;
+    Ok(1)
+}
+
+fn kick(execution: &mut CommandExecution<'_>, args: &Arguments) -> Result<i32, CommandErr> {
+    let Some(targets) = args.entity("targets") else {
+        return Err(CommandErr::Failed("No player was found".to_string()));
+    };
+    let targets = execution.select_players(targets)?;
+    let reason = match args.string("reason") {
+        Some(reason) => TextComponent::text(reason),
+        None => TextComponent::translate("multiplayer.disconnect.kicked", Vec::new()),
+    };
+    let mut kicked = Vec::new();
+    for handle in targets.iter() {
+        if let Some(client) = execution.clients.get_mut(*handle) {
+            client.disconnect(reason.clone());
+            kicked.push(client.profile.name.clone());
+        }
+    }
+    for name in kicked {
+        execution.send_message(format!("Kicked {}: {}", name, reason.to_plain()));
+    }
+    Ok(targets.len() as i32)
+}
+
+fn list(execution: &mut CommandExecution<'_>, _: &Arguments) -> Result<i32, CommandErr> {
+    let names = execution.player_names();
+    execution.send_message(format!(
+        "There are {} of a max of {} players online: {}",
+        names.len(),
+        execution.server.config.max_players,
+        names.join(", ")
+    ));
+    Ok(names.len() as i32)
+}
+
+fn help(execution: &mut CommandExecution<'_>, _: &Arguments) -> Result<i32, CommandErr> {
+    let commands = execution.server.commands.clone();
+    let usage = commands.usage(&*execution);
+    let count = usage.len() as i32;
+    execution.output.extend(usage);
+    Ok(count)
+}
@@ -0,0 +1,39 @@
+use std::io::BufRead;
+
+use flume::Sender;
+
+use crate::command::{CommandRequest, CommandSource};
+
+/// Reads commands from stdin on a thread of its own and prints their output.
+pub fn spawn_console(commands: Sender<CommandRequest>) {
+    let spawned = std::thread::Builder::new()
+        .name("console".to_string())
+        .spawn(move || {
+            for line in std::io::stdin().lock().lines() {
+                let Ok(line) = line else {
+                    break;
+                };
+                let command = line.trim();
+                if command.is_empty() {
+                    continue;
+                }
+                let (reply, output) = flume::bounded::<String>(1);
+                let request = CommandRequest {
+                    source: CommandSource::Console,
+                    command: command.to_string(),
+                    reply,
+                };
+                if commands.send(request).is_err() {
+                    break;
+                }
+                match output.recv() {
+                    Ok(output) if output.is_empty() => {}
+                    Ok(output) => println!("{}", output),
+                    Err(_) => break,
+                }
+            }
+        });
+    if let Err(err) = spawned {
+        tracing::error!(?err, "unable to start the console");
+    }
+}
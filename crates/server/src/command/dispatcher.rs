@@ -0,0 +1,689 @@
+use std::fmt::{Debug, Display, Formatter};
+
+use serverx_common::identifier::Identifier;
+use serverx_protocol::v765::{
+    clientbound::Commands,
+    types::{CommandNode as GraphNode, CommandNodeKind},
+};
+
+use crate::command::{
+    argument::{ArgumentType, ArgumentValue, BlockPos, EntitySelector},
+    reader::StringReader,
+};
+
+/// The index of the root node, the parent of every command.
+pub const ROOT: usize = 0;
+/// How many characters before an error vanilla shows as context.
+const ERROR_CONTEXT_LEN: usize = 10;
+
+/// Whoever runs a command, decides which nodes are visible.
+pub trait CommandSender {
+    /// From 0 for regular players up to 4 for the console.
+    fn permission_level(&self) -> u8;
+
+    /// Names offered when completing entity arguments.
+    fn player_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum CommandErr {
+    UnknownCommand(usize),
+    IncompleteCommand(usize),
+    /// Input left over after a command that takes no more arguments.
+    IncorrectArgument(usize),
+    ExpectedSeparator(usize),
+    InvalidArgument(usize, String),
+    /// Returned by an executor, the message is shown as is.
+    Failed(String),
+}
+
+impl Debug for CommandErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandErr::UnknownCommand(_) | CommandErr::IncompleteCommand(_) => {
+                write!(f, "Unknown or incomplete command, see below for error")
+            }
+            CommandErr::IncorrectArgument(_) => write!(f, "Incorrect argument for command"),
+            CommandErr::ExpectedSeparator(_) => write!(
+                f,
+                "Expected whitespace to end one argument, but found trailing data"
+            ),
+            CommandErr::InvalidArgument(_, message) => write!(f, "{}", message),
+            CommandErr::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Display for CommandErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <dyn Debug>::fmt(self, f)
+    }
+}
+
+impl CommandErr {
+    /// Where in the command line the error happened.
+    pub fn cursor(&self) -> Option<usize> {
+        match self {
+            CommandErr::UnknownCommand(cursor)
+            | CommandErr::IncompleteCommand(cursor)
+            | CommandErr::IncorrectArgument(cursor)
+            | CommandErr::ExpectedSeparator(cursor)
+            | CommandErr::InvalidArgument(cursor, _) => Some(*cursor),
+            CommandErr::Failed(_) => None,
+        }
+    }
+
+    /// The error followed by the command line marked where it happened, the way vanilla shows
+    /// it.
+    pub fn describe(&self, input: &str) -> String {
+        let Some(cursor) = self.cursor() else {
+            return self.to_string();
+        };
+        let cursor = cursor.min(input.len());
+        let mut context_start = cursor.saturating_sub(ERROR_CONTEXT_LEN);
+        while !input.is_char_boundary(context_start) {
+            context_start -= 1;
+        }
+        format!(
+            "{}\n{}{}<--[HERE]",
+            self,
+            if context_start > 0 { "..." } else { "" },
+            &input[context_start..cursor]
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Literal(String),
+    Argument { name: String, ty: ArgumentType },
+}
+
+/// A node of the command tree, `E` is what runs a command ending at the node.
+pub struct CommandNode<E> {
+    pub kind: NodeKind,
+    pub children: Vec<usize>,
+    pub executor: Option<E>,
+    /// The permission level a sender needs to see the node.
+    pub permission: u8,
+}
+
+pub struct NodeBuilder<E> {
+    kind: NodeKind,
+    children: Vec<NodeBuilder<E>>,
+    executor: Option<E>,
+    permission: u8,
+}
+
+pub fn literal<E>(name: &str) -> NodeBuilder<E> {
+    NodeBuilder {
+        kind: NodeKind::Literal(name.to_string()),
+        children: Vec::new(),
+        executor: None,
+        permission: 0,
+    }
+}
+
+pub fn argument<E>(name: &str, ty: ArgumentType) -> NodeBuilder<E> {
+    NodeBuilder {
+        kind: NodeKind::Argument {
+            name: name.to_string(),
+            ty,
+        },
+        children: Vec::new(),
+        executor: None,
+        permission: 0,
+    }
+}
+
+impl<E> NodeBuilder<E> {
+    pub fn requires(mut self, permission: u8) -> Self {
+        self.permission = permission;
+        self
+    }
+
+    pub fn executes(mut self, executor: E) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    pub fn then(mut self, child: NodeBuilder<E>) -> Self {
+        self.children.push(child);
+        self
+    }
+}
+
+/// The values of the arguments of a parsed command.
+#[derive(Debug, Default)]
+pub struct Arguments {
+    values: Vec<(String, ArgumentValue)>,
+}
+
+impl Arguments {
+    pub fn get(&self, name: &str) -> Option<&ArgumentValue> {
+        self.values
+            .iter()
+            .find(|(arg_name, _)| arg_name == name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn integer(&self, name: &str) -> Option<i32> {
+        match self.get(name) {
+            Some(ArgumentValue::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name) {
+            Some(ArgumentValue::Float(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(ArgumentValue::String(value)) => Some(value.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn entity(&self, name: &str) -> Option<&EntitySelector> {
+        match self.get(name) {
+            Some(ArgumentValue::Entity(selector)) => Some(selector),
+            _ => None,
+        }
+    }
+
+    pub fn block_pos(&self, name: &str) -> Option<&BlockPos> {
+        match self.get(name) {
+            Some(ArgumentValue::BlockPos(pos)) => Some(pos),
+            _ => None,
+        }
+    }
+
+    pub fn identifier(&self, name: &str) -> Option<&Identifier> {
+        match self.get(name) {
+            Some(ArgumentValue::Identifier(identifier)) => Some(identifier),
+            _ => None,
+        }
+    }
+}
+
+pub struct ParsedCommand<'a, E> {
+    pub executor: &'a E,
+    pub args: Arguments,
+}
+
+/// Completions for the last word of a command line, `start` is where the replaced text begins.
+#[derive(Debug, Default, PartialEq)]
+pub struct Suggestions {
+    pub start: usize,
+    pub matches: Vec<String>,
+}
+
+/// A Brigadier style command tree, nodes are kept in a flat list and refer to their children by
+/// index.
+pub struct CommandDispatcher<E> {
+    nodes: Vec<CommandNode<E>>,
+}
+
+impl<E> Default for CommandDispatcher<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> CommandDispatcher<E> {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![CommandNode {
+                kind: NodeKind::Root,
+                children: Vec::new(),
+                executor: None,
+                permission: 0,
+            }],
+        }
+    }
+
+    pub fn nodes(&self) -> &[CommandNode<E>] {
+        self.nodes.as_slice()
+    }
+
+    /// Adds a command, nodes with the same name as existing ones are merged into them.
+    pub fn register(&mut self, command: NodeBuilder<E>) {
+        self.insert(ROOT, command);
+    }
+
+    fn insert(&mut self, parent: usize, builder: NodeBuilder<E>) {
+        let existing = self.nodes[parent]
+            .children
+            .iter()
+            .copied()
+            .find(|child| self.nodes[*child].kind == builder.kind);
+        let node = match existing {
+            Some(node) => {
+                if builder.executor.is_some() {
+                    self.nodes[node].executor = builder.executor;
+                }
+                node
+            }
+            None => {
+                let node = self.nodes.len();
+                self.nodes.push(CommandNode {
+                    kind: builder.kind,
+                    children: Vec::new(),
+                    executor: builder.executor,
+                    permission: builder.permission,
+                });
+                self.nodes[parent].children.push(node);
+                node
+            }
+        };
+        for child in builder.children {
+            self.insert(node, child);
+        }
+    }
+
+    fn visible_children<'a, S: CommandSender>(
+        &'a self,
+        node: usize,
+        source: &'a S,
+    ) -> impl Iterator<Item = usize> + Clone + 'a {
+        self.nodes[node]
+            .children
+            .iter()
+            .copied()
+            .filter(|child| source.permission_level() >= self.nodes[*child].permission)
+    }
+
+    /// Parses a command line without the leading slash.
+    pub fn parse<S: CommandSender>(
+        &self,
+        source: &S,
+        input: &str,
+    ) -> Result<ParsedCommand<'_, E>, CommandErr> {
+        let mut args = Arguments::default();
+        let executor = self.parse_children(ROOT, source, StringReader::new(input), &mut args)?;
+        Ok(ParsedCommand { executor, args })
+    }
+
+    fn parse_children<S: CommandSender>(
+        &self,
+        node: usize,
+        source: &S,
+        reader: StringReader,
+        args: &mut Arguments,
+    ) -> Result<&E, CommandErr> {
+        let mut best: Option<CommandErr> = None;
+        let mut record = |err: CommandErr| {
+            if best
+                .as_ref()
+                .is_none_or(|best| err.cursor() > best.cursor())
+            {
+                best = Some(err);
+            }
+        };
+        let word = { reader }.read_word();
+        // a matching literal hides the arguments next to it
+        let literal = self.visible_children(node, source).find(
+            |child| matches!(&self.nodes[*child].kind, NodeKind::Literal(name) if name == word),
+        );
+        let candidates: Vec<usize> = match literal {
+            Some(literal) => vec![literal],
+            None => self
+                .visible_children(node, source)
+                .filter(|child| !matches!(self.nodes[*child].kind, NodeKind::Literal(_)))
+                .collect(),
+        };
+        for child in candidates {
+            let mut reader = reader;
+            let len = args.values.len();
+            match &self.nodes[child].kind {
+                NodeKind::Argument { name, ty } => match ty.parse(&mut reader) {
+                    Ok(value) => args.values.push((name.clone(), value)),
+                    Err(err) => {
+                        record(err);
+                        continue;
+                    }
+                },
+                _ => {
+                    reader.read_word();
+                }
+            }
+            let result = if !reader.can_read() {
+                self.nodes[child]
+                    .executor
+                    .as_ref()
+                    .ok_or(CommandErr::IncompleteCommand(reader.cursor()))
+            } else if reader.peek() != Some(' ') {
+                Err(CommandErr::ExpectedSeparator(reader.cursor()))
+            } else {
+                reader.skip();
+                self.parse_children(child, source, reader, args)
+            };
+            match result {
+                Ok(executor) => return Ok(executor),
+                Err(err) => {
+                    args.values.truncate(len);
+                    record(err);
+                }
+            }
+        }
+        Err(best.unwrap_or(if node == ROOT {
+            CommandErr::UnknownCommand(reader.cursor())
+        } else {
+            CommandErr::IncorrectArgument(reader.cursor())
+        }))
+    }
+
+    /// Completes the last word of a command line without the leading slash.
+    pub fn suggest<S: CommandSender>(&self, source: &S, input: &str) -> Suggestions {
+        let mut suggestions = Suggestions::default();
+        self.suggest_children(ROOT, source, StringReader::new(input), &mut suggestions);
+        suggestions.matches.sort();
+        suggestions.matches.dedup();
+        suggestions
+    }
+
+    fn suggest_children<S: CommandSender>(
+        &self,
+        node: usize,
+        source: &S,
+        reader: StringReader,
+        suggestions: &mut Suggestions,
+    ) {
+        for child in self.visible_children(node, source) {
+            let mut child_reader = reader;
+            let parsed = match &self.nodes[child].kind {
+                NodeKind::Argument { ty, .. } => ty.parse(&mut child_reader).is_ok(),
+                NodeKind::Literal(name) => child_reader.read_word() == name,
+                NodeKind::Root => false,
+            };
+            if parsed && child_reader.peek() == Some(' ') {
+                child_reader.skip();
+                self.suggest_children(child, source, child_reader, suggestions);
+                continue;
+            }
+            if parsed && child_reader.can_read() {
+                continue;
+            }
+            // the child is what is being typed, candidates that do not fit are filtered out
+            let remaining = reader.remaining();
+            let matches = match &self.nodes[child].kind {
+                NodeKind::Literal(name) if name.starts_with(remaining) => vec![name.clone()],
+                NodeKind::Argument { ty, .. } => ty.suggest(source, remaining),
+                _ => Vec::new(),
+            };
+            if matches.is_empty() || reader.cursor() < suggestions.start {
+                continue;
+            }
+            if reader.cursor() > suggestions.start {
+                suggestions.start = reader.cursor();
+                suggestions.matches.clear();
+            }
+            suggestions.matches.extend(matches);
+        }
+    }
+
+    /// The command graph sent to a client, nodes the sender cannot see are left out.
+    pub fn graph<S: CommandSender>(&self, source: &S) -> Commands {
+        let mut nodes = Vec::new();
+        self.add_graph_node(ROOT, source, &mut nodes);
+        Commands {
+            nodes,
+            root_index: 0,
+        }
+    }
+
+    fn add_graph_node<S: CommandSender>(
+        &self,
+        node: usize,
+        source: &S,
+        nodes: &mut Vec<GraphNode>,
+    ) -> i32 {
+        let index = nodes.len();
+        let kind = match &self.nodes[node].kind {
+            NodeKind::Root => CommandNodeKind::Root,
+            NodeKind::Literal(name) => CommandNodeKind::Literal { name: name.clone() },
+            NodeKind::Argument { name, ty } => CommandNodeKind::Argument {
+                name: name.clone(),
+                parser: ty.parser(),
+                suggestions: ty.suggestions(),
+            },
+        };
+        nodes.push(GraphNode {
+            kind,
+            executable: self.nodes[node].executor.is_some(),
+            children: Vec::new(),
+            redirect: None,
+        });
+        let children: Vec<usize> = self.visible_children(node, source).collect();
+        let children = children
+            .into_iter()
+            .map(|child| self.add_graph_node(child, source, nodes))
+            .collect();
+        nodes[index].children = children;
+        index as i32
+    }
+
+    /// The usage of every command the sender can see, like `/kick <targets> [<reason>]`.
+    pub fn usage<S: CommandSender>(&self, source: &S) -> Vec<String> {
+        self.visible_children(ROOT, source)
+            .map(|child| format!("/{}", self.node_usage(child, source)))
+            .collect()
+    }
+
+    fn node_name(&self, node: usize) -> String {
+        match &self.nodes[node].kind {
+            NodeKind::Literal(name) => name.clone(),
+            NodeKind::Argument { name, .. } => format!("<{}>", name),
+            NodeKind::Root => String::new(),
+        }
+    }
+
+    fn node_usage<S: CommandSender>(&self, node: usize, source: &S) -> String {
+        let name = self.node_name(node);
+        let children: Vec<usize> = self.visible_children(node, source).collect();
+        let child_usage = match children.as_slice() {
+            [] => return name,
+            [child] => self.node_usage(*child, source),
+            children => format!(
+                "({})",
+                children
+                    .iter()
+                    .map(|child| self.node_name(*child))
+                    .collect::<Vec<String>>()
+                    .join("|")
+            ),
+        };
+        if self.nodes[node].executor.is_some() {
+            format!("{} [{}]", name, child_usage)
+        } else {
+            format!("{} {}", name, child_usage)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serverx_protocol::v765::types::{CommandNodeKind, CommandParser};
+
+    use crate::command::{
+        argument::ArgumentType,
+        dispatcher::{
+            argument, literal, Arguments, CommandDispatcher, CommandErr, CommandSender, Suggestions,
+        },
+    };
+
+    struct Sender(u8);
+
+    impl CommandSender for Sender {
+        fn permission_level(&self) -> u8 {
+            self.0
+        }
+
+        fn player_names(&self) -> Vec<String> {
+            vec!["Notch".to_string(), "jeb_".to_string()]
+        }
+    }
+
+    type Executor = fn(&Arguments) -> i32;
+
+    fn dispatcher() -> CommandDispatcher<Executor> {
+        let mut dispatcher = CommandDispatcher::<Executor>::new();
+        dispatcher.register(
+            literal("kick").requires(3).then(
+                argument::<Executor>("targets", ArgumentType::Entity {
+                    single: false,
+                    players_only: true,
+                })
+                .executes(|_| 1)
+                .then(argument::<Executor>("reason", ArgumentType::GreedyString).executes(|_| 2)),
+            ),
+        );
+        dispatcher.register(
+            literal("time").then(
+                literal("add").then(
+                    argument::<Executor>("time", ArgumentType::Integer { min: 0, max: 100 })
+                        .executes(|args| args.integer("time").unwrap()),
+                ),
+            ),
+        );
+        dispatcher.register(
+            literal("time")
+                .then(literal("query").then(literal::<Executor>("daytime").executes(|_| 3))),
+        );
+        dispatcher.register(
+            literal("tp")
+                .then(argument::<Executor>("destination", ArgumentType::BlockPos).executes(|_| 4)),
+        );
+        dispatcher
+    }
+
+    fn run(input: &str) -> Result<i32, CommandErr> {
+        let dispatcher = dispatcher();
+        let parsed = dispatcher.parse(&Sender(4), input)?;
+        Ok((parsed.executor)(&parsed.args))
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(run("kick Notch"), Ok(1));
+        assert_eq!(run("kick Notch being mean"), Ok(2));
+        assert_eq!(run("time add 42"), Ok(42));
+        assert_eq!(run("time query daytime"), Ok(3));
+        assert_eq!(run("tp 1 ~2 3"), Ok(4));
+        assert_eq!(run("foo"), Err(CommandErr::UnknownCommand(0)));
+        assert_eq!(run("time add"), Err(CommandErr::IncompleteCommand(8)));
+        assert!(matches!(
+            run("time add 101"),
+            Err(CommandErr::InvalidArgument(9, _))
+        ));
+        assert_eq!(
+            run("time query daytime x"),
+            Err(CommandErr::IncorrectArgument(19))
+        );
+        assert_eq!(run("kick"), Err(CommandErr::IncompleteCommand(4)));
+    }
+
+    #[test]
+    fn test_permission() {
+        let dispatcher = dispatcher();
+        assert_eq!(
+            dispatcher.parse(&Sender(0), "kick Notch").err(),
+            Some(CommandErr::UnknownCommand(0))
+        );
+        assert_eq!(dispatcher.usage(&Sender(0)), vec![
+            "/time (add|query)",
+            "/tp <destination>"
+        ]);
+        assert_eq!(
+            dispatcher.usage(&Sender(4))[0],
+            "/kick <targets> [<reason>]"
+        );
+    }
+
+    #[test]
+    fn test_suggest() {
+        let dispatcher = dispatcher();
+        let suggest = |input: &str| dispatcher.suggest(&Sender(4), input);
+        assert_eq!(suggest("ti"), Suggestions {
+            start: 0,
+            matches: vec!["time".to_string()],
+        });
+        assert_eq!(suggest("time "), Suggestions {
+            start: 5,
+            matches: vec!["add".to_string(), "query".to_string()],
+        });
+        assert_eq!(suggest("kick j").matches, vec!["jeb_"]);
+        assert_eq!(suggest("kick @").matches.len(), 5);
+        assert_eq!(suggest("tp 1 ").matches, vec!["1 ~ ~"]);
+        assert_eq!(suggest("tp 1 ").start, 3);
+        assert!(dispatcher.suggest(&Sender(0), "ki").matches.is_empty());
+    }
+
+    #[test]
+    fn test_graph() {
+        let graph = dispatcher().graph(&Sender(0));
+        assert_eq!(graph.root_index, 0);
+        // kick needs a higher permission level and is left out
+        let children: Vec<Vec<i32>> = graph
+            .nodes
+            .iter()
+            .map(|node| node.children.clone())
+            .collect();
+        assert_eq!(children, vec![
+            vec![1, 6],
+            vec![2, 4],
+            vec![3],
+            vec![],
+            vec![5],
+            vec![],
+            vec![7],
+            vec![],
+        ]);
+        assert!(matches!(&graph.nodes[3].kind, CommandNodeKind::Argument {
+            parser: CommandParser::Integer {
+                min: Some(0),
+                max: Some(100),
+            },
+            suggestions: None,
+            ..
+        }));
+        assert!(graph.nodes[3].executable);
+        assert!(!graph.nodes[4].executable);
+        assert!(matches!(&graph.nodes[7].kind, CommandNodeKind::Argument {
+            parser: CommandParser::BlockPos,
+            ..
+        }));
+
+        let graph = dispatcher().graph(&Sender(4));
+        assert!(matches!(&graph.nodes[2].kind, CommandNodeKind::Argument {
+            parser: CommandParser::Entity {
+                single: false,
+                players_only: true,
+            },
+            suggestions: Some(_),
+            ..
+        }));
+    }
+
+    #[test]
+    fn test_describe() {
+        let err = run("time add 101").unwrap_err();
+        assert_eq!(
+            err.describe("time add 101"),
+            "Integer must not be more than 100, found 101\ntime add <--[HERE]"
+        );
+        let err = run("time query daytime extra").unwrap_err();
+        assert_eq!(err, CommandErr::IncorrectArgument(19));
+        assert_eq!(
+            err.describe("time query daytime extra"),
+            "Incorrect argument for command\n...y daytime <--[HERE]"
+        );
+    }
+}
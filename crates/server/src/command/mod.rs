@@ -0,0 +1,27 @@
+use std::net::SocketAddr;
+
+use flume::Sender;
+
+use crate::client::ClientHandle;
+
+pub mod argument;
+pub mod builtin;
+pub mod console;
+pub mod dispatcher;
+pub mod reader;
+
+/// Who sent a command, decides how its output is delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandSource {
+    Console,
+    Rcon(SocketAddr),
+    Player(ClientHandle),
+}
+
+/// A command sent to the tick loop from outside the game, the output is sent back on `reply`.
+#[derive(Debug)]
+pub struct CommandRequest {
+    pub source: CommandSource,
+    pub command: String,
+    pub reply: Sender<String>,
+}
@@ -0,0 +1,64 @@
+/// A cursor over a command line, positions are byte offsets into the input.
+#[derive(Debug, Clone, Copy)]
+pub struct StringReader<'a> {
+    input: &'a str,
+    cursor: usize,
+}
+
+impl<'a> StringReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, cursor: 0 }
+    }
+
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn remaining(&self) -> &'a str {
+        &self.input[self.cursor..]
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.cursor < self.input.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
+
+    pub fn skip(&mut self) {
+        if let Some(c) = self.peek() {
+            self.cursor += c.len_utf8();
+        }
+    }
+
+    /// Reads up to the next space or the end of the input.
+    pub fn read_word(&mut self) -> &'a str {
+        let remaining = self.remaining();
+        let len = remaining.find(' ').unwrap_or(remaining.len());
+        self.cursor += len;
+        &remaining[..len]
+    }
+
+    /// Reads while `accept` holds for the next character.
+    pub fn read_while(&mut self, mut accept: impl FnMut(char) -> bool) -> &'a str {
+        let remaining = self.remaining();
+        let len = remaining
+            .char_indices()
+            .find(|(_, c)| !accept(*c))
+            .map(|(i, _)| i)
+            .unwrap_or(remaining.len());
+        self.cursor += len;
+        &remaining[..len]
+    }
+
+    pub fn read_to_end(&mut self) -> &'a str {
+        let remaining = self.remaining();
+        self.cursor = self.input.len();
+        remaining
+    }
+}
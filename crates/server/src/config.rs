@@ -10,10 +10,63 @@ use serde_derive::{Deserialize, Serialize};
 use toml::de::Error;
 use tracing::instrument;
 
+use crate::network::{
+    forwarding::ForwardingMode,
+    session::{MOJANG_SERVICES_SERVER, MOJANG_SESSION_SERVER},
+};
+
 #[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct ServerConfig {
     pub ip: String,
     pub port: u16,
+    pub online_mode: bool,
+    pub session_server: String,
+    /// Packets at least this many bytes long are compressed, a negative value disables
+    /// compression.
+    pub compression_threshold: i32,
+    pub motd: String,
+    /// Shown above and below the player list.
+    pub tab_list_header: String,
+    pub tab_list_footer: String,
+    pub max_players: i32,
+    /// Whether the GameSpy4 query protocol is answered over UDP.
+    pub enable_query: bool,
+    pub query_port: u16,
+    /// Whether remote consoles may connect, requires a password.
+    pub enable_rcon: bool,
+    pub rcon_port: u16,
+    pub rcon_password: String,
+    /// Bytes queued for a client above which low priority packets are dropped.
+    pub outgoing_soft_limit: usize,
+    /// Bytes queued for a client above which it is disconnected.
+    pub outgoing_hard_limit: usize,
+    /// Seconds a client may stay over the soft limit before it is disconnected.
+    pub outgoing_backlog_timeout: u64,
+    /// Connections a single address may open per connection window, zero disables the limit. Not
+    /// applied with forwarding as every connection then comes from the proxy.
+    pub connections_per_ip: u32,
+    /// Length of the connection window in seconds.
+    pub connection_window: u64,
+    /// Connections that may be in the handshake, status or login states at once.
+    pub max_pending_connections: usize,
+    /// Seconds a client has to send its handshake.
+    pub handshake_timeout: u64,
+    /// Seconds a client has to finish a status request or login.
+    pub login_timeout: u64,
+    /// How players connecting through a proxy are identified, "none", "bungeecord" or
+    /// "velocity".
+    pub forwarding: ForwardingMode,
+    /// The secret shared with Velocity.
+    pub forwarding_secret: String,
+    /// Whether players have to sign their chat messages.
+    pub enforce_secure_profile: bool,
+    /// The public key profile keys are verified against, PEM or DER encoded. When empty the keys
+    /// are fetched from the services server in online mode. Chat sessions are ignored without
+    /// one, which leaves chat unsigned.
+    pub profile_key_trust_root: String,
+    /// Where the keys profile keys are signed with are fetched from.
+    pub services_server: String,
 }
 
 impl Default for ServerConfig {
@@ -21,6 +74,31 @@ impl Default for ServerConfig {
         Self {
             ip: "127.0.0.1".to_string(),
             port: 25565,
+            online_mode: false,
+            session_server: MOJANG_SESSION_SERVER.to_string(),
+            compression_threshold: 256,
+            motd: "A Minecraft Server".to_string(),
+            tab_list_header: String::new(),
+            tab_list_footer: String::new(),
+            max_players: 20,
+            enable_query: false,
+            query_port: 25565,
+            enable_rcon: false,
+            rcon_port: 25575,
+            rcon_password: String::new(),
+            outgoing_soft_limit: 8 * 1024 * 1024,
+            outgoing_hard_limit: 32 * 1024 * 1024,
+            outgoing_backlog_timeout: 10,
+            connections_per_ip: 8,
+            connection_window: 10,
+            max_pending_connections: 256,
+            handshake_timeout: 5,
+            login_timeout: 30,
+            forwarding: ForwardingMode::None,
+            forwarding_secret: String::new(),
+            enforce_secure_profile: false,
+            profile_key_trust_root: String::new(),
+            services_server: MOJANG_SERVICES_SERVER.to_string(),
         }
     }
 }
@@ -1 +1,51 @@
-pub struct Game {}
+use flume::Receiver;
+use tracing::instrument;
+
+use crate::client::{
+    chat::{ChatEvent, ChatHook},
+    event::ClientEvent,
+};
+
+pub struct Game {
+    pub client_events: Receiver<ClientEvent>,
+    chat_hooks: Vec<Box<dyn ChatHook>>,
+}
+
+impl Game {
+    pub fn new(client_events: Receiver<ClientEvent>) -> Self {
+        Self {
+            client_events,
+            chat_hooks: Vec::new(),
+        }
+    }
+
+    /// Hooks run in the order they were added, a cancelled message is not passed on.
+    pub fn add_chat_hook(&mut self, hook: impl ChatHook + 'static) {
+        self.chat_hooks.push(Box::new(hook));
+    }
+
+    pub fn on_chat(&mut self, event: &mut ChatEvent) {
+        for hook in self.chat_hooks.iter_mut() {
+            hook.on_chat(event);
+            if event.cancelled {
+                tracing::debug!(name = event.sender_name, "chat message was cancelled");
+                return;
+            }
+        }
+        tracing::info!("[chat] <{}> {}", event.sender_name, event.message);
+    }
+
+    #[instrument(skip_all)]
+    pub fn process_events(&mut self) {
+        while let Ok(event) = self.client_events.try_recv() {
+            match event {
+                ClientEvent::Disconnected {
+                    profile, reason, ..
+                } => {
+                    let reason = reason.map(|reason| reason.to_plain());
+                    tracing::info!(name = profile.name, ?reason, "player has left the game");
+                }
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@ use tracing_subscriber::FmtSubscriber;
 use crate::server::Server;
 
 pub mod client;
+pub mod command;
 pub mod config;
 mod game;
 pub mod network;
@@ -18,7 +19,9 @@ fn main() {
         .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
     if let Some(config) = config::load("run/config.toml") {
-        if let Ok(resources) = resources::load(&Path::new("run/resources")) {
+        if let Ok(resources) =
+            resources::load(Path::new("run/resources"), Path::new("run/server-icon.png"))
+        {
             let mut server = Server::new(config, resources);
             server.start();
         } else {
@@ -1,94 +1,117 @@
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
-use flume::{Receiver, Sender};
+use flume::Sender;
 use serverx_protocol::{
-    io::{AsyncPacketReader, AsyncPacketWriter, PacketReadErr},
-    packet::{
-        ConnectionState, Packet,
-        PacketDirection::{ClientBound, ServerBound},
-    },
-    v765::{
-        clientbound::{ServerFinishConfiguration, StartConfiguration},
-        serverbound::{ClientFinishConfiguration, ConfigurationAck},
-        types::HandshakeNextState,
-        PacketDecoderImpl, PacketEncoderImpl,
-    },
+    dispatch::{ServerboundConfiguration, ServerboundPlay},
+    io::{AsyncPacketReader, AsyncPacketWriter, EncodedPacket, PacketReadErr},
+    packet::ConnectionState,
+    v765::types::HandshakeNextState,
+    version::ProtocolVersion,
 };
-use tokio::net::{
-    tcp::{OwnedReadHalf, OwnedWriteHalf},
-    TcpStream,
+use tokio::{
+    io::AsyncWriteExt,
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream,
+    },
+    time::{timeout, timeout_at, Instant},
 };
 
 use crate::network::{
-    event::NetworkEvent,
-    handlers::{handshake::handle_handshake, login::handle_login, status::handle_status},
+    event::{IncomingPacket, NetworkEvent},
+    forwarding::Forwarding,
+    handlers::{
+        handshake::handle_handshake,
+        legacy_ping::{handle_legacy_ping, is_legacy_ping},
+        login::{handle_login, LoginConfig},
+        status::{handle_status, StatusSnapshot},
+    },
+    queue::{outgoing_queue, OutgoingPacket, OutgoingReceiver, QueueLimits, INCOMING_QUEUE_LEN},
+    throttle::{ListenerLimits, Rejection},
 };
 
+/// Caps how many bytes of packets a write loop frames into a single write.
+const MAX_BATCH_LEN: usize = 1 << 20;
+
 pub fn spawn_write_loop(
-    mut state: ConnectionState,
     mut sock: OwnedWriteHalf,
     mut writer: AsyncPacketWriter,
-    packets: Receiver<Box<dyn Packet>>,
+    packets: OutgoingReceiver,
 ) {
     tokio::spawn(async move {
-        'outer: loop {
-            match packets.recv_async().await {
-                Ok(packet) => {
-                    let write_result = writer
-                        .write::<OwnedWriteHalf, PacketEncoderImpl>(
-                            &mut sock,
-                            ClientBound,
-                            state,
-                            packet.as_ref(),
-                        )
-                        .await;
-                    match write_result {
-                        Ok(()) => {
-                            if state == ConnectionState::Configuration
-                                && packet.id() == ServerFinishConfiguration::ID
-                            {
-                                state = ConnectionState::Play;
-                            } else if state == ConnectionState::Play
-                                && packet.id() == StartConfiguration::ID
-                            {
-                                state = ConnectionState::Configuration;
-                            }
-                        }
-                        Err(err) => {
-                            println!("{:?}", err);
-                            break 'outer;
-                        }
+        let mut batch: Vec<EncodedPacket> = Vec::new();
+        while let Some(mut packet) = packets.recv().await {
+            // drain what the tick loop queued so it goes out in one write
+            let mut batch_len = 0;
+            let mut close = false;
+            loop {
+                match packet {
+                    OutgoingPacket::Encoded(packet) => {
+                        batch_len += packet.encoded_len();
+                        batch.push(packet);
                     }
+                    OutgoingPacket::Close => {
+                        close = true;
+                        break;
+                    }
+                }
+                if batch_len >= MAX_BATCH_LEN {
+                    break;
+                }
+                match packets.try_recv() {
+                    Some(next) => packet = next,
+                    None => break,
                 }
-                Err(_) => break,
+            }
+            if let Err(err) = writer
+                .write_encoded_batch(&mut sock, batch.as_slice())
+                .await
+            {
+                tracing::debug!(?err, "error while writing packets");
+                break;
+            }
+            packets.written(batch_len);
+            batch.clear();
+            if close {
+                let _ = sock.shutdown().await;
+                break;
             }
         }
     });
 }
 
 pub fn spawn_read_loop(
+    version: ProtocolVersion,
     mut state: ConnectionState,
     mut sock: OwnedReadHalf,
     mut reader: AsyncPacketReader,
-    packets: Sender<Box<dyn Packet>>,
+    packets: Sender<IncomingPacket>,
 ) {
     tokio::spawn(async move {
         loop {
-            let read_result = reader
-                .read::<OwnedReadHalf, PacketDecoderImpl>(&mut sock, ServerBound, state)
-                .await;
+            let read_result = match state {
+                ConnectionState::Configuration => reader
+                    .read_versioned_typed::<_, ServerboundConfiguration>(&mut sock, version)
+                    .await
+                    .map(IncomingPacket::Configuration),
+                _ => reader
+                    .read_versioned_typed::<_, ServerboundPlay>(&mut sock, version)
+                    .await
+                    .map(IncomingPacket::Play),
+            };
             match read_result {
                 Ok(packet) => {
                     tracing::trace!(?packet, "read packet");
-                    if state == ConnectionState::Configuration
-                        && packet.id() == ClientFinishConfiguration::ID
-                    {
-                        state = ConnectionState::Play;
-                    } else if state == ConnectionState::Play && packet.id() == ConfigurationAck::ID
-                    {
-                        state = ConnectionState::Configuration;
+                    match packet {
+                        IncomingPacket::Configuration(
+                            ServerboundConfiguration::ClientFinishConfiguration(_),
+                        ) => state = ConnectionState::Play,
+                        IncomingPacket::Play(ServerboundPlay::ConfigurationAck(_)) => {
+                            state = ConnectionState::Configuration
+                        }
+                        _ => {}
                     }
-                    if packets.send(packet).is_err() {
+                    if packets.send_async(packet).await.is_err() {
                         break;
                     }
                 }
@@ -104,20 +127,62 @@ pub fn spawn_read_loop(
     });
 }
 
-pub async fn accept_client(mut socket: TcpStream, addr: SocketAddr, events: Sender<NetworkEvent>) {
+pub async fn accept_client(
+    mut socket: TcpStream,
+    addr: SocketAddr,
+    events: Sender<NetworkEvent>,
+    login_config: Arc<LoginConfig>,
+    server_status: StatusSnapshot,
+    queue_limits: QueueLimits,
+    limits: ListenerLimits,
+) -> Result<(), Rejection> {
     let _ = socket.set_nodelay(true);
+    let handshake_deadline = Instant::now() + limits.handshake_timeout;
+    match timeout_at(handshake_deadline, is_legacy_ping(&socket)).await {
+        Ok(Ok(true)) => {
+            match handle_legacy_ping(&mut socket, server_status.load().as_ref()).await {
+                Ok(()) => tracing::trace!("successfully handled legacy ping"),
+                Err(err) => tracing::debug!(?err, "unable to handle legacy ping"),
+            }
+            return Ok(());
+        }
+        Ok(_) => {}
+        Err(_) => return Err(Rejection::HandshakeTimeout),
+    }
     let mut reader = AsyncPacketReader::new();
     let mut writer = AsyncPacketWriter::new();
-    let handshake_result = match handle_handshake(&mut socket, &mut reader).await {
-        Ok(result) => result,
-        Err(error) => {
+    let handshake_result = match timeout_at(
+        handshake_deadline,
+        handle_handshake(
+            &mut socket,
+            &mut reader,
+            !matches!(login_config.forwarding, Forwarding::None),
+        ),
+    )
+    .await
+    {
+        Ok(Ok(result)) => result,
+        Ok(Err(error)) => {
             tracing::debug!(?error, "unable to process handshake request");
-            return;
+            return Ok(());
         }
+        Err(_) => return Err(Rejection::HandshakeTimeout),
     };
     match handshake_result.next {
         HandshakeNextState::Status => {
-            match handle_status(&mut socket, &mut reader, &mut writer).await {
+            let status_result = timeout(
+                limits.login_timeout,
+                handle_status(
+                    &mut socket,
+                    &mut reader,
+                    &mut writer,
+                    handshake_result.version,
+                    server_status.load().as_ref(),
+                ),
+            )
+            .await
+            .map_err(|_| Rejection::LoginTimeout)?;
+            match status_result {
                 Ok(()) => {
                     tracing::trace!("successfully handled status request");
                 }
@@ -127,26 +192,46 @@ pub async fn accept_client(mut socket: TcpStream, addr: SocketAddr, events: Send
             }
         }
         HandshakeNextState::Login => {
-            match handle_login(&mut socket, &mut reader, &mut writer).await {
+            let login_result = timeout(
+                limits.login_timeout,
+                handle_login(
+                    &mut socket,
+                    &mut reader,
+                    &mut writer,
+                    handshake_result.version,
+                    handshake_result.server_addr.as_str(),
+                    login_config.as_ref(),
+                ),
+            )
+            .await
+            .map_err(|_| Rejection::LoginTimeout)?;
+            match login_result {
                 Ok(login_result) => {
                     tracing::trace!("successfully handled connect request");
-                    let (outgoing_tx, outgoing_rx) = flume::unbounded::<Box<dyn Packet>>();
-                    let (incoming_tx, incoming_rx) = flume::unbounded::<Box<dyn Packet>>();
+                    let (outgoing_tx, outgoing_rx) = outgoing_queue(
+                        handshake_result.version,
+                        writer.compression(),
+                        queue_limits,
+                    );
+                    let (incoming_tx, incoming_rx) =
+                        flume::bounded::<IncomingPacket>(INCOMING_QUEUE_LEN);
                     let (sock_read, sock_write) = socket.into_split();
                     spawn_read_loop(
+                        handshake_result.version,
                         ConnectionState::Configuration,
                         sock_read,
                         reader,
                         incoming_tx,
                     );
-                    spawn_write_loop(
-                        ConnectionState::Configuration,
-                        sock_write,
-                        writer,
-                        outgoing_rx,
-                    );
+                    spawn_write_loop(sock_write, writer, outgoing_rx);
+                    // players behind a proxy are known by the address it forwarded
+                    let addr = match login_result.profile.forwarded_addr {
+                        Some(ip) => SocketAddr::new(ip, addr.port()),
+                        None => addr,
+                    };
                     if let Err(_) = events.send(NetworkEvent::Connected {
                         addr,
+                        version: handshake_result.version,
                         outgoing: outgoing_tx,
                         incoming: incoming_rx,
                         profile: login_result.profile,
@@ -160,4 +245,451 @@ pub async fn accept_client(mut socket: TcpStream, addr: SocketAddr, events: Send
             }
         }
     }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Cursor, Read},
+        sync::Arc,
+        time::Duration,
+    };
+
+    use flate2::read::ZlibDecoder;
+    use serverx_common::identifier;
+    use serverx_macros::identifier;
+    use serverx_protocol::{
+        decode::{BasicAllocTracker, ProtoDecode},
+        dispatch::ServerboundConfiguration,
+        encode::ProtoEncode,
+        io::{AsyncPacketReader, AsyncPacketWriter},
+        packet::{
+            ConnectionState::{Configuration, Handshake, Login, Status},
+            PacketDirection::{ClientBound, ServerBound},
+        },
+        text::TextComponent,
+        types::VarInt,
+        v765::{
+            clientbound::{
+                ConfigClientBoundPluginMessage, Disconnect, LoginSuccess,
+                ServerFinishConfiguration, SetCompression, StatusResponse,
+            },
+            serverbound::{
+                ConfigClientInformation, HandshakeRequest, LoginAck, LoginStart, StatusRequest,
+            },
+            types::{ChatMode, HandshakeNextState, MainHand},
+            PacketDecoderImpl, PacketEncoderImpl, PROTO_VER,
+        },
+        v766,
+        version::ProtocolVersion,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+    use uuid::Uuid;
+
+    use crate::network::{
+        accept::accept_client,
+        event::{IncomingPacket, NetworkEvent},
+        forwarding::Forwarding,
+        handlers::{
+            login::LoginConfig,
+            status::{ServerStatus, StatusSnapshot},
+        },
+        queue::QueueLimits,
+        session::OfflineSessionVerifier,
+        throttle::{ListenerLimits, Rejection},
+    };
+
+    const THRESHOLD: usize = 256;
+
+    async fn read_raw_frame(socket: &mut TcpStream) -> Vec<u8> {
+        let mut len: usize = 0;
+        for i in 0..VarInt::MAX_BYTES {
+            let b = socket.read_u8().await.unwrap();
+            len |= ((b & 0x7f) as usize) << (i * 7);
+            if (b & 0x80) == 0 {
+                break;
+            }
+        }
+        let mut frame = vec![0u8; len];
+        socket.read_exact(frame.as_mut_slice()).await.unwrap();
+        frame
+    }
+
+    fn read_var_int(cursor: &mut Cursor<&[u8]>) -> i32 {
+        VarInt::decode(cursor, &mut BasicAllocTracker::new(1024)).unwrap()
+    }
+
+    /// Splits a compressed frame into its data length header and its uncompressed packet.
+    fn unpack_compressed(frame: &[u8]) -> (usize, Vec<u8>) {
+        let mut cursor = Cursor::new(frame);
+        let data_len = read_var_int(&mut cursor) as usize;
+        let rest = &frame[cursor.position() as usize..];
+        if data_len == 0 {
+            (0, rest.to_vec())
+        } else {
+            let mut packet = Vec::new();
+            ZlibDecoder::new(rest).read_to_end(&mut packet).unwrap();
+            assert_eq!(packet.len(), data_len);
+            (data_len, packet)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_and_configuration_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (events_tx, events_rx) = flume::unbounded::<NetworkEvent>();
+        let login_config = Arc::new(LoginConfig {
+            key: None,
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: Some(THRESHOLD),
+            forwarding: Forwarding::None,
+        });
+        tokio::spawn(async move {
+            let (socket, addr) = listener.accept().await.unwrap();
+            accept_client(
+                socket,
+                addr,
+                events_tx,
+                login_config,
+                StatusSnapshot::default(),
+                QueueLimits::default(),
+                ListenerLimits::default(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut writer = AsyncPacketWriter::new();
+        let handshake = HandshakeRequest {
+            version: PROTO_VER,
+            server_addr: "localhost".to_string(),
+            server_port: addr.port(),
+            next_state: HandshakeNextState::Login,
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Handshake, &handshake)
+            .await
+            .unwrap();
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+
+        // set compression is sent before compression is enabled
+        let frame = read_raw_frame(&mut socket).await;
+        let mut cursor = Cursor::new(frame.as_slice());
+        assert_eq!(read_var_int(&mut cursor), SetCompression::ID);
+        assert_eq!(read_var_int(&mut cursor), THRESHOLD as i32);
+        writer.set_compression(Some(THRESHOLD));
+
+        // login success is below the threshold and carries a zero data length
+        let frame = read_raw_frame(&mut socket).await;
+        let (data_len, packet) = unpack_compressed(frame.as_slice());
+        assert_eq!(data_len, 0);
+        let mut cursor = Cursor::new(packet.as_slice());
+        assert_eq!(read_var_int(&mut cursor), LoginSuccess::ID);
+        let login_success =
+            LoginSuccess::decode(&mut cursor, &mut BasicAllocTracker::new(1024)).unwrap();
+        assert_eq!(login_success.username, "Notch");
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &LoginAck)
+            .await
+            .unwrap();
+
+        let (outgoing, incoming) = match events_rx.recv_async().await.unwrap() {
+            NetworkEvent::Connected {
+                outgoing,
+                incoming,
+                profile,
+                ..
+            } => {
+                assert_eq!(profile.name, "Notch");
+                (outgoing, incoming)
+            }
+        };
+
+        // packets sent by the tick loop keep using the negotiated compression
+        let plugin_message = ConfigClientBoundPluginMessage {
+            channel: identifier!("test"),
+            data: vec![7u8; THRESHOLD * 4],
+        };
+        outgoing.send(&plugin_message).unwrap();
+        outgoing.send(&ServerFinishConfiguration).unwrap();
+        let frame = read_raw_frame(&mut socket).await;
+        let (data_len, packet) = unpack_compressed(frame.as_slice());
+        assert_eq!(data_len, packet.len());
+        assert!(frame.len() < packet.len());
+        let mut expected = Cursor::new(Vec::new());
+        VarInt::encode(&ConfigClientBoundPluginMessage::ID, &mut expected).unwrap();
+        ConfigClientBoundPluginMessage::encode(&plugin_message, &mut expected).unwrap();
+        assert_eq!(packet, expected.into_inner());
+        let frame = read_raw_frame(&mut socket).await;
+        let (data_len, packet) = unpack_compressed(frame.as_slice());
+        assert_eq!(data_len, 0);
+        assert_eq!(packet, vec![ServerFinishConfiguration::ID as u8]);
+
+        let client_information = ConfigClientInformation {
+            locale: "en_us".to_string(),
+            view_distance: 8,
+            chat_mode: ChatMode::Enabled,
+            chat_colors: true,
+            skin_parts: 0x7f,
+            main_hand: MainHand::Right,
+            text_filtering: 0,
+            server_listings: 1,
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(
+                &mut socket,
+                ServerBound,
+                Configuration,
+                &client_information,
+            )
+            .await
+            .unwrap();
+        let packet = incoming.recv_async().await.unwrap();
+        let IncomingPacket::Configuration(ServerboundConfiguration::ConfigClientInformation(
+            decoded,
+        )) = packet
+        else {
+            panic!("unexpected packet {:?}", packet);
+        };
+        assert_eq!(decoded.locale, "en_us");
+        assert_eq!(decoded.view_distance, 8);
+
+        // the write loop closes the connection after a disconnect
+        outgoing
+            .send(&Disconnect {
+                reason: TextComponent::text("bye"),
+            })
+            .unwrap();
+        outgoing.close();
+        let frame = read_raw_frame(&mut socket).await;
+        let (_, packet) = unpack_compressed(frame.as_slice());
+        let mut cursor = Cursor::new(packet.as_slice());
+        assert_eq!(read_var_int(&mut cursor), Disconnect::ID);
+        let mut rest = Vec::new();
+        assert_eq!(socket.read_to_end(&mut rest).await.unwrap(), 0);
+    }
+
+    async fn connect(next_state: HandshakeNextState, version: i32, server_addr: &str) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (events_tx, events_rx) = flume::unbounded::<NetworkEvent>();
+        let login_config = Arc::new(LoginConfig {
+            key: None,
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: None,
+            forwarding: Forwarding::None,
+        });
+        tokio::spawn(async move {
+            let (socket, addr) = listener.accept().await.unwrap();
+            accept_client(
+                socket,
+                addr,
+                events_tx,
+                login_config,
+                StatusSnapshot::default(),
+                QueueLimits::default(),
+                ListenerLimits::default(),
+            )
+            .await
+            .unwrap();
+            drop(events_rx);
+        });
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let handshake = HandshakeRequest {
+            version,
+            server_addr: server_addr.to_string(),
+            server_port: addr.port(),
+            next_state,
+        };
+        AsyncPacketWriter::new()
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Handshake, &handshake)
+            .await
+            .unwrap();
+        socket
+    }
+
+    async fn status_version(version: i32) -> serde_json::Value {
+        let mut socket = connect(HandshakeNextState::Status, version, "localhost").await;
+        let mut reader = AsyncPacketReader::new();
+        AsyncPacketWriter::new()
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Status, &StatusRequest)
+            .await
+            .unwrap();
+        let response = reader
+            .read::<_, PacketDecoderImpl>(&mut socket, ClientBound, Status)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<StatusResponse>()
+            .unwrap();
+        response.response["version"].clone()
+    }
+
+    #[tokio::test]
+    async fn test_status_neighbouring_versions() {
+        for version in ProtocolVersion::SUPPORTED {
+            let reported = status_version(version.protocol()).await;
+            assert_eq!(reported["protocol"], version.protocol());
+            assert_eq!(reported["name"], version.name());
+        }
+        // unsupported clients are told the latest version
+        let reported = status_version(4).await;
+        assert_eq!(reported["protocol"], ProtocolVersion::LATEST.protocol());
+    }
+
+    #[tokio::test]
+    async fn test_login_neighbouring_version() {
+        let mut socket = connect(HandshakeNextState::Login, v766::PROTO_VER, "localhost").await;
+        let mut reader = AsyncPacketReader::new();
+        let mut writer = AsyncPacketWriter::new();
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        writer
+            .write::<_, v766::PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+        let login_success = reader
+            .read::<_, v766::PacketDecoderImpl>(&mut socket, ClientBound, Login)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<v766::clientbound::LoginSuccess>()
+            .unwrap();
+        assert_eq!(login_success.username, "Notch");
+        assert!(!login_success.strict_error_handling);
+    }
+
+    #[tokio::test]
+    async fn test_login_unsupported_version() {
+        let mut socket = connect(HandshakeNextState::Login, 4, "localhost").await;
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        let _ = AsyncPacketWriter::new()
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await;
+        // the connection is closed without a reply
+        let mut buf = Vec::new();
+        assert!(!matches!(socket.read_to_end(&mut buf).await, Ok(len) if len > 0));
+    }
+
+    #[tokio::test]
+    async fn test_login_long_address() {
+        // only proxies forwarding player info may send more than vanilla allows
+        let server_addr = "a".repeat(256);
+        let mut socket = connect(HandshakeNextState::Login, PROTO_VER, &server_addr).await;
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        let _ = AsyncPacketWriter::new()
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await;
+        let mut buf = Vec::new();
+        assert!(!matches!(socket.read_to_end(&mut buf).await, Ok(len) if len > 0));
+    }
+
+    #[tokio::test]
+    async fn test_handshake_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (events_tx, _events_rx) = flume::unbounded::<NetworkEvent>();
+        let login_config = Arc::new(LoginConfig {
+            key: None,
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: None,
+            forwarding: Forwarding::None,
+        });
+        let limits = ListenerLimits {
+            handshake_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let accept = tokio::spawn(async move {
+            let (socket, addr) = listener.accept().await.unwrap();
+            accept_client(
+                socket,
+                addr,
+                events_tx,
+                login_config,
+                StatusSnapshot::default(),
+                QueueLimits::default(),
+                limits,
+            )
+            .await
+        });
+        // a client that connects and never sends its handshake is dropped
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        assert!(matches!(
+            accept.await.unwrap(),
+            Err(Rejection::HandshakeTimeout)
+        ));
+        let mut buf = Vec::new();
+        assert!(!matches!(socket.read_to_end(&mut buf).await, Ok(len) if len > 0));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_ping_detected() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (events_tx, _events_rx) = flume::unbounded::<NetworkEvent>();
+        let login_config = Arc::new(LoginConfig {
+            key: None,
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: None,
+            forwarding: Forwarding::None,
+        });
+        let server_status = StatusSnapshot::new(ServerStatus {
+            max_players: 20,
+            online_players: 3,
+            ..Default::default()
+        });
+        tokio::spawn(async move {
+            let (socket, addr) = listener.accept().await.unwrap();
+            accept_client(
+                socket,
+                addr,
+                events_tx,
+                login_config,
+                server_status,
+                QueueLimits::default(),
+                ListenerLimits::default(),
+            )
+            .await
+            .unwrap();
+        });
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        socket.write_all(&[0xFE, 0x01]).await.unwrap();
+        let mut response = Vec::new();
+        socket.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response[0], 0xFF);
+        let units: Vec<u16> = response[3..]
+            .chunks(2)
+            .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+            .collect();
+        let fields: Vec<String> = String::from_utf16(units.as_slice())
+            .unwrap()
+            .split('\0')
+            .map(|field| field.to_string())
+            .collect();
+        assert_eq!(fields[0], "§1");
+        assert_eq!(fields[3], "Hello world!");
+        assert_eq!(fields[4], "3");
+        assert_eq!(fields[5], "20");
+    }
 }
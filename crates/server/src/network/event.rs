@@ -1,15 +1,27 @@
 use std::net::SocketAddr;
 
-use flume::{Receiver, Sender};
-use serverx_protocol::packet::Packet;
+use flume::Receiver;
+use serverx_protocol::{
+    dispatch::{ServerboundConfiguration, ServerboundPlay},
+    version::ProtocolVersion,
+};
 
-use crate::client::profile::Profile;
+use crate::{client::profile::Profile, network::queue::OutgoingQueue};
 
 pub enum NetworkEvent {
     Connected {
         addr: SocketAddr,
-        outgoing: Sender<Box<dyn Packet>>,
-        incoming: Receiver<Box<dyn Packet>>,
+        version: ProtocolVersion,
+        outgoing: OutgoingQueue,
+        incoming: Receiver<IncomingPacket>,
         profile: Profile,
     },
 }
+
+/// A packet read from a client that logged in, in the set of the state it was read in.
+#[derive(Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum IncomingPacket {
+    Configuration(ServerboundConfiguration),
+    Play(ServerboundPlay),
+}
@@ -0,0 +1,246 @@
+use std::{
+    fmt::{Debug, Formatter},
+    io::Cursor,
+    net::IpAddr,
+    str::FromStr,
+};
+
+use hmac::{Hmac, Mac};
+use serde_derive::{Deserialize, Serialize};
+use serverx_protocol::{
+    decode::{BasicAllocTracker, ProtoDecode, ProtoDecodeErr},
+    types::VarInt,
+    v765::types::LoginProperty,
+};
+use sha2::Sha256;
+use uuid::Uuid;
+
+/// The login plugin channel Velocity answers with the forwarded player info.
+pub const VELOCITY_CHANNEL: &str = "velocity:player_info";
+/// The only forwarding version requested from Velocity, it carries the address, profile and
+/// properties.
+pub const VELOCITY_FORWARDING_VERSION: u8 = 1;
+const VELOCITY_SIGNATURE_LEN: usize = 32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardingMode {
+    #[default]
+    None,
+    BungeeCord,
+    Velocity,
+}
+
+/// How players connecting through a proxy are identified.
+#[derive(Clone)]
+pub enum Forwarding {
+    None,
+    /// The proxy appends the player address, UUID and properties to the handshake address.
+    BungeeCord,
+    /// The proxy answers a login plugin request with player info signed with a shared secret.
+    Velocity {
+        secret: Vec<u8>,
+    },
+}
+
+impl Debug for Forwarding {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Forwarding::None => write!(f, "none"),
+            Forwarding::BungeeCord => write!(f, "bungeecord"),
+            Forwarding::Velocity { .. } => write!(f, "velocity"),
+        }
+    }
+}
+
+/// The player info a proxy forwarded.
+#[derive(Debug, Clone)]
+pub struct ForwardedPlayer {
+    pub addr: IpAddr,
+    pub uuid: Uuid,
+    /// The username, only Velocity forwards it.
+    pub name: Option<String>,
+    pub properties: Vec<LoginProperty>,
+}
+
+pub enum ForwardingErr {
+    MissingData,
+    InvalidAddress(String),
+    InvalidUuid(String),
+    InvalidProperties(serde_json::Error),
+    NotForwarded,
+    InvalidSignature,
+    UnsupportedVersion(i32),
+    DecodeErr(ProtoDecodeErr),
+}
+
+impl Debug for ForwardingErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ForwardingErr::MissingData => write!(f, "no forwarded player info"),
+            ForwardingErr::InvalidAddress(addr) => write!(f, "invalid address \"{}\"", addr),
+            ForwardingErr::InvalidUuid(uuid) => write!(f, "invalid uuid \"{}\"", uuid),
+            ForwardingErr::InvalidProperties(err) => write!(f, "invalid properties: {}", err),
+            ForwardingErr::NotForwarded => write!(f, "the proxy did not forward player info"),
+            ForwardingErr::InvalidSignature => write!(f, "invalid forwarding signature"),
+            ForwardingErr::UnsupportedVersion(version) => {
+                write!(f, "unsupported forwarding version {}", version)
+            }
+            ForwardingErr::DecodeErr(err) => write!(f, "decode error: {}", err),
+        }
+    }
+}
+
+/// Parses the handshake address of a BungeeCord connection, the host, player address, UUID
+/// without dashes and optionally a JSON array of properties separated by null characters.
+pub fn parse_bungeecord(server_addr: &str) -> Result<ForwardedPlayer, ForwardingErr> {
+    let mut parts = server_addr.split('\0');
+    let _host = parts.next();
+    let (Some(addr), Some(uuid)) = (parts.next(), parts.next()) else {
+        return Err(ForwardingErr::MissingData);
+    };
+    let addr =
+        IpAddr::from_str(addr).map_err(|_| ForwardingErr::InvalidAddress(addr.to_string()))?;
+    let uuid = Uuid::parse_str(uuid).map_err(|_| ForwardingErr::InvalidUuid(uuid.to_string()))?;
+    let properties = match parts.next() {
+        Some(properties) => serde_json::from_str::<Vec<BungeeCordProperty>>(properties)
+            .map_err(ForwardingErr::InvalidProperties)?
+            .into_iter()
+            .map(|p| LoginProperty {
+                name: p.name,
+                value: p.value,
+                signature: p.signature,
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    Ok(ForwardedPlayer {
+        addr,
+        uuid,
+        name: None,
+        properties,
+    })
+}
+
+#[derive(Deserialize)]
+struct BungeeCordProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+/// Verifies and decodes the data of a Velocity player info response, an HMAC-SHA256 signature
+/// of the rest of the data followed by the forwarding version, address, UUID, username and
+/// properties.
+pub fn parse_velocity(secret: &[u8], data: &[u8]) -> Result<ForwardedPlayer, ForwardingErr> {
+    if data.len() < VELOCITY_SIGNATURE_LEN {
+        return Err(ForwardingErr::MissingData);
+    }
+    let (signature, payload) = data.split_at(VELOCITY_SIGNATURE_LEN);
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret).map_err(|_| ForwardingErr::InvalidSignature)?;
+    mac.update(payload);
+    mac.verify_slice(signature)
+        .map_err(|_| ForwardingErr::InvalidSignature)?;
+
+    let mut reader = Cursor::new(payload);
+    let mut alloc_tracker = BasicAllocTracker::new(1 << 20);
+    let version =
+        VarInt::decode(&mut reader, &mut alloc_tracker).map_err(ForwardingErr::DecodeErr)?;
+    if version < 1 || version > VELOCITY_FORWARDING_VERSION as i32 {
+        return Err(ForwardingErr::UnsupportedVersion(version));
+    }
+    let addr = String::decode(&mut reader, &mut alloc_tracker).map_err(ForwardingErr::DecodeErr)?;
+    let addr = IpAddr::from_str(addr.as_str()).map_err(|_| ForwardingErr::InvalidAddress(addr))?;
+    let uuid = Uuid::decode(&mut reader, &mut alloc_tracker).map_err(ForwardingErr::DecodeErr)?;
+    let name = String::decode(&mut reader, &mut alloc_tracker).map_err(ForwardingErr::DecodeErr)?;
+    let properties = Vec::<LoginProperty>::decode(&mut reader, &mut alloc_tracker)
+        .map_err(ForwardingErr::DecodeErr)?;
+    Ok(ForwardedPlayer {
+        addr,
+        uuid,
+        name: Some(name),
+        properties,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        net::{IpAddr, Ipv4Addr},
+    };
+
+    use hmac::{Hmac, Mac};
+    use serverx_protocol::{encode::ProtoEncode, types::VarInt, v765::types::LoginProperty};
+    use sha2::Sha256;
+    use uuid::Uuid;
+
+    use crate::network::forwarding::{parse_bungeecord, parse_velocity, ForwardingErr};
+
+    const UUID: u128 = 0x069a79f444e94726a5befca90e38aaf5;
+
+    #[test]
+    fn test_parse_bungeecord() {
+        let player = parse_bungeecord(
+            "localhost\u{0}10.0.0.7\u{0}069a79f444e94726a5befca90e38aaf5\u{0}[{\"name\":\"\
+             textures\",\"value\":\"e30=\",\"signature\":\"c2ln\"}]",
+        )
+        .unwrap();
+        assert_eq!(player.addr, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)));
+        assert_eq!(player.uuid.as_u128(), UUID);
+        assert_eq!(player.properties.len(), 1);
+        assert_eq!(player.properties[0].name, "textures");
+        assert_eq!(player.properties[0].signature.as_deref(), Some("c2ln"));
+
+        let player =
+            parse_bungeecord("localhost\u{0}::1\u{0}069a79f444e94726a5befca90e38aaf5").unwrap();
+        assert!(player.addr.is_loopback());
+        assert!(player.properties.is_empty());
+
+        assert!(matches!(
+            parse_bungeecord("localhost"),
+            Err(ForwardingErr::MissingData)
+        ));
+    }
+
+    fn velocity_data(secret: &[u8], version: i32) -> Vec<u8> {
+        let mut payload = Cursor::new(Vec::new());
+        VarInt::encode(&version, &mut payload).unwrap();
+        String::encode(&"10.0.0.7".to_string(), &mut payload).unwrap();
+        Uuid::encode(&Uuid::from_u128(UUID), &mut payload).unwrap();
+        String::encode(&"Notch".to_string(), &mut payload).unwrap();
+        let properties = vec![LoginProperty {
+            name: "textures".to_string(),
+            value: "e30=".to_string(),
+            signature: None,
+        }];
+        Vec::<LoginProperty>::encode(&properties, &mut payload).unwrap();
+        let payload = payload.into_inner();
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(payload.as_slice());
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(payload.as_slice());
+        data
+    }
+
+    #[test]
+    fn test_parse_velocity() {
+        let data = velocity_data(b"secret", 1);
+        let player = parse_velocity(b"secret", data.as_slice()).unwrap();
+        assert_eq!(player.addr, IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)));
+        assert_eq!(player.uuid.as_u128(), UUID);
+        assert_eq!(player.name.as_deref(), Some("Notch"));
+        assert_eq!(player.properties[0].value, "e30=");
+
+        assert!(matches!(
+            parse_velocity(b"other", data.as_slice()),
+            Err(ForwardingErr::InvalidSignature)
+        ));
+        let data = velocity_data(b"secret", 4);
+        assert!(matches!(
+            parse_velocity(b"secret", data.as_slice()),
+            Err(ForwardingErr::UnsupportedVersion(4))
+        ));
+    }
+}
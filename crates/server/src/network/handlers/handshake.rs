@@ -1,36 +1,43 @@
 use std::fmt::{Debug, Display, Formatter};
 
 use serverx_protocol::{
+    dispatch::ServerboundHandshake,
     io::{AsyncPacketReader, PacketReadErr},
-    packet::{ConnectionState::Handshake, PacketDirection::ServerBound},
-    v765::{
-        serverbound::HandshakeRequest, types::HandshakeNextState, PacketDecoderImpl, PROTO_VER,
-    },
+    v765::{types::HandshakeNextState, PacketDecoderImpl},
+    version::ProtocolVersion,
 };
 use tokio::net::TcpStream;
 use tracing::instrument;
 
+/// The longest server address vanilla accepts, forwarded player info is only allowed past it.
+const MAX_SERVER_ADDR_LEN: usize = 255;
+
 #[derive(Debug, Clone)]
 pub struct HandshakeResult {
     pub next: HandshakeNextState,
+    /// The version packets are exchanged with from now on, status requests from unsupported
+    /// versions are answered with the latest one.
+    pub version: ProtocolVersion,
+    /// The address the client connected to, proxies may append player info to it.
+    pub server_addr: String,
 }
 
 pub enum HandshakeErr {
     ReadErr(PacketReadErr),
-    MismatchedVersion(i32, i32),
-    UnexpectedPacket,
+    UnsupportedVersion(i32),
+    AddressTooLong(usize),
 }
 
 impl Debug for HandshakeErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             HandshakeErr::ReadErr(err) => write!(f, "read error: {:?}", err),
-            HandshakeErr::MismatchedVersion(client_ver, server_ver) => write!(
-                f,
-                "client protocol version {} does not match server version {}",
-                client_ver, server_ver
-            ),
-            HandshakeErr::UnexpectedPacket => write!(f, "unexpected packet"),
+            HandshakeErr::UnsupportedVersion(client_ver) => {
+                write!(f, "client protocol version {} is not supported", client_ver)
+            }
+            HandshakeErr::AddressTooLong(len) => {
+                write!(f, "server address of {} characters is too long", len)
+            }
         }
     }
 }
@@ -39,27 +46,37 @@ impl Debug for HandshakeErr {
 pub async fn handle_handshake(
     socket: &mut TcpStream,
     reader: &mut AsyncPacketReader,
+    forwarding: bool,
 ) -> Result<HandshakeResult, HandshakeErr> {
-    let packet = reader
-        .read::<TcpStream, PacketDecoderImpl>(socket, ServerBound, Handshake)
+    // the handshake layout is the same in every supported version
+    let ServerboundHandshake::HandshakeRequest(handshake) = reader
+        .read_typed::<TcpStream, PacketDecoderImpl, ServerboundHandshake>(socket)
         .await
         .map_err(|err| HandshakeErr::ReadErr(err))?;
-    if let Ok(handshake) = packet.into_any().downcast::<HandshakeRequest>() {
-        return if handshake.version != PROTO_VER {
+    let addr_len = handshake.server_addr.chars().count();
+    if !forwarding && addr_len > MAX_SERVER_ADDR_LEN {
+        return Err(HandshakeErr::AddressTooLong(addr_len));
+    }
+    let version = ProtocolVersion::from_protocol(handshake.version);
+    match (version, handshake.next_state) {
+        (Some(version), next) => Ok(HandshakeResult {
+            next,
+            version,
+            server_addr: handshake.server_addr,
+        }),
+        // the status exchange is the same in every version, answering it lets the server list
+        // show the client which version to use
+        (None, HandshakeNextState::Status) => Ok(HandshakeResult {
+            next: HandshakeNextState::Status,
+            version: ProtocolVersion::LATEST,
+            server_addr: handshake.server_addr,
+        }),
+        (None, HandshakeNextState::Login) => {
             tracing::debug!(
                 client_ver = handshake.version,
-                server_vec = PROTO_VER,
-                "protocol version mismatch"
+                "unsupported protocol version"
             );
-            Err(HandshakeErr::MismatchedVersion(
-                handshake.version,
-                PROTO_VER,
-            ))
-        } else {
-            Ok(HandshakeResult {
-                next: handshake.next_state,
-            })
-        };
+            Err(HandshakeErr::UnsupportedVersion(handshake.version))
+        }
     }
-    Err(HandshakeErr::UnexpectedPacket)
 }
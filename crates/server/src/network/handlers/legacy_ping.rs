@@ -0,0 +1,245 @@
+use std::{
+    fmt::{Debug, Formatter},
+    io,
+    time::Duration,
+};
+
+use serverx_protocol::version::ProtocolVersion;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    time,
+};
+use tracing::instrument;
+
+use crate::network::handlers::status::ServerStatus;
+
+/// The first byte sent by pre-netty clients.
+pub const LEGACY_PING: u8 = 0xFE;
+const LEGACY_PING_PAYLOAD: u8 = 0x01;
+const LEGACY_PLUGIN_MESSAGE: u8 = 0xFA;
+const LEGACY_KICK: u8 = 0xFF;
+/// Newer than every legacy version, so old clients show the server as incompatible.
+const LEGACY_PROTOCOL: i32 = 127;
+const MAX_PLUGIN_MESSAGE_LEN: usize = 512;
+/// Beta clients send the ping byte alone, later ones follow it up immediately.
+const PAYLOAD_TIMEOUT: Duration = Duration::from_millis(100);
+/// How often the socket is peeked while waiting for the payload byte.
+const PEEK_INTERVAL: Duration = Duration::from_millis(10);
+
+pub enum LegacyPingErr {
+    IoErr(io::Error),
+    MalformedRequest,
+    ResponseTooLong(usize),
+}
+
+impl Debug for LegacyPingErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LegacyPingErr::IoErr(err) => write!(f, "io error: {}", err),
+            LegacyPingErr::MalformedRequest => write!(f, "malformed legacy ping request"),
+            LegacyPingErr::ResponseTooLong(len) => {
+                write!(
+                    f,
+                    "legacy ping response with {} characters is too long",
+                    len
+                )
+            }
+        }
+    }
+}
+
+/// Whether a connection starts with a legacy ping. Varint framed handshakes longer than 253 bytes
+/// start with the ping byte as well and one of exactly 254 bytes with the payload byte too, so
+/// like vanilla it only counts if the plugin message follows or if nothing follows at all.
+pub async fn is_legacy_ping(socket: &TcpStream) -> io::Result<bool> {
+    let deadline = time::Instant::now() + PAYLOAD_TIMEOUT;
+    let mut start = [0u8; 3];
+    loop {
+        let len = socket.peek(&mut start).await?;
+        if len == 0 || start[0] != LEGACY_PING {
+            return Ok(false);
+        }
+        if len >= 2 && start[1] != LEGACY_PING_PAYLOAD {
+            return Ok(false);
+        }
+        if len == 3 {
+            return Ok(start[2] == LEGACY_PLUGIN_MESSAGE);
+        }
+        if time::Instant::now() >= deadline {
+            return Ok(true);
+        }
+        time::sleep(PEEK_INTERVAL).await;
+    }
+}
+
+/// Answers the server list ping of clients older than 1.7 with a kick packet carrying the
+/// status, in the 1.4+ format if the client sent the payload byte and the beta one otherwise.
+#[instrument(skip_all)]
+pub async fn handle_legacy_ping<S: AsyncRead + AsyncWrite + Unpin>(
+    socket: &mut S,
+    server_status: &ServerStatus,
+) -> Result<(), LegacyPingErr> {
+    if socket.read_u8().await.map_err(LegacyPingErr::IoErr)? != LEGACY_PING {
+        return Err(LegacyPingErr::MalformedRequest);
+    }
+    let payload = time::timeout(PAYLOAD_TIMEOUT, socket.read_u8()).await;
+    let response = if let Ok(Ok(LEGACY_PING_PAYLOAD)) = payload {
+        read_plugin_message(socket).await?;
+        format!(
+            "§1\0{}\0{}\0{}\0{}\0{}",
+            LEGACY_PROTOCOL,
+            ProtocolVersion::LATEST.name(),
+            server_status.motd.to_legacy(),
+            server_status.online_players,
+            server_status.max_players
+        )
+    } else {
+        // the beta format uses § as its separator
+        format!(
+            "{}§{}§{}",
+            server_status.motd.to_plain().replace('§', ""),
+            server_status.online_players,
+            server_status.max_players
+        )
+    };
+    tracing::trace!(response, "writing legacy ping response");
+    let units: Vec<u16> = response.encode_utf16().collect();
+    let len =
+        u16::try_from(units.len()).map_err(|_| LegacyPingErr::ResponseTooLong(units.len()))?;
+    let mut buf = Vec::with_capacity(3 + units.len() * 2);
+    buf.push(LEGACY_KICK);
+    buf.extend_from_slice(len.to_be_bytes().as_slice());
+    for unit in units {
+        buf.extend_from_slice(unit.to_be_bytes().as_slice());
+    }
+    socket
+        .write_all(buf.as_slice())
+        .await
+        .map_err(LegacyPingErr::IoErr)?;
+    socket.shutdown().await.map_err(LegacyPingErr::IoErr)
+}
+
+/// 1.6 clients append a `MC|PingHost` plugin message, which is read so the socket isn't closed
+/// with unread data.
+async fn read_plugin_message<S: AsyncRead + Unpin>(socket: &mut S) -> Result<(), LegacyPingErr> {
+    match time::timeout(PAYLOAD_TIMEOUT, socket.read_u8()).await {
+        Ok(Ok(LEGACY_PLUGIN_MESSAGE)) => {}
+        Ok(Ok(_)) => return Err(LegacyPingErr::MalformedRequest),
+        Ok(Err(_)) | Err(_) => return Ok(()),
+    }
+    let channel_len = socket.read_u16().await.map_err(LegacyPingErr::IoErr)? as usize;
+    skip(socket, channel_len * 2).await?;
+    let data_len = socket.read_u16().await.map_err(LegacyPingErr::IoErr)? as usize;
+    skip(socket, data_len).await
+}
+
+async fn skip<S: AsyncRead + Unpin>(socket: &mut S, len: usize) -> Result<(), LegacyPingErr> {
+    if len > MAX_PLUGIN_MESSAGE_LEN {
+        return Err(LegacyPingErr::MalformedRequest);
+    }
+    let mut buf = vec![0u8; len];
+    socket
+        .read_exact(buf.as_mut_slice())
+        .await
+        .map_err(LegacyPingErr::IoErr)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serverx_protocol::{
+        text::{TextColor, TextComponent},
+        version::ProtocolVersion,
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+        net::{TcpListener, TcpStream},
+    };
+
+    use crate::network::handlers::{
+        legacy_ping::{handle_legacy_ping, is_legacy_ping},
+        status::ServerStatus,
+    };
+
+    fn server_status() -> ServerStatus {
+        ServerStatus {
+            motd: TextComponent::text("A Minecraft Server").color(TextColor::Gold),
+            max_players: 20,
+            online_players: 3,
+            ..ServerStatus::default()
+        }
+    }
+
+    async fn ping(request: &[u8]) -> String {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client.write_all(request).await.unwrap();
+        let status = server_status();
+        handle_legacy_ping(&mut server, &status).await.unwrap();
+        read_kick(&mut client).await
+    }
+
+    async fn read_kick(client: &mut DuplexStream) -> String {
+        assert_eq!(client.read_u8().await.unwrap(), 0xFF);
+        let len = client.read_u16().await.unwrap() as usize;
+        let mut units = Vec::with_capacity(len);
+        for _ in 0..len {
+            units.push(client.read_u16().await.unwrap());
+        }
+        String::from_utf16(units.as_slice()).unwrap()
+    }
+
+    /// Whether a connection starting with the given bytes is taken for a legacy ping.
+    async fn detect(start: &[u8]) -> bool {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        client.write_all(start).await.unwrap();
+        is_legacy_ping(&server).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_detect_legacy_ping() {
+        assert!(detect(&[0xFE]).await);
+        assert!(detect(&[0xFE, 0x01]).await);
+        assert!(detect(&[0xFE, 0x01, 0xFA]).await);
+        // the length of a 254 byte handshake followed by its packet id
+        assert!(!detect(&[0xFE, 0x01, 0x00]).await);
+        // the length of a 382 byte handshake
+        assert!(!detect(&[0xFE, 0x02, 0x00]).await);
+        assert!(!detect(&[0x10, 0x00]).await);
+    }
+
+    #[tokio::test]
+    async fn test_beta_ping() {
+        assert_eq!(ping(&[0xFE]).await, "A Minecraft Server§3§20");
+    }
+
+    #[tokio::test]
+    async fn test_legacy_ping() {
+        let expected = format!(
+            "§1\u{0}127\u{0}{}\u{0}§6A Minecraft Server\u{0}3\u{0}20",
+            ProtocolVersion::LATEST.name()
+        );
+        assert_eq!(ping(&[0xFE, 0x01]).await, expected);
+
+        let mut request = vec![0xFE, 0x01, 0xFA];
+        let channel: Vec<u16> = "MC|PingHost".encode_utf16().collect();
+        request.extend_from_slice((channel.len() as u16).to_be_bytes().as_slice());
+        for unit in channel {
+            request.extend_from_slice(unit.to_be_bytes().as_slice());
+        }
+        let host: Vec<u16> = "localhost".encode_utf16().collect();
+        let mut data = vec![74];
+        data.extend_from_slice((host.len() as u16).to_be_bytes().as_slice());
+        for unit in host {
+            data.extend_from_slice(unit.to_be_bytes().as_slice());
+        }
+        data.extend_from_slice(25565i32.to_be_bytes().as_slice());
+        request.extend_from_slice((data.len() as u16).to_be_bytes().as_slice());
+        request.extend_from_slice(data.as_slice());
+        assert_eq!(ping(request.as_slice()).await, expected);
+    }
+}
@@ -1,22 +1,43 @@
-use std::fmt::{Debug, Formatter};
+use std::{
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
 
 use serverx_protocol::{
-    io::{AsyncPacketReader, AsyncPacketWriter, PacketReadErr, PacketWriteErr},
-    packet::{
-        ConnectionState::Login,
-        PacketDirection::{ClientBound, ServerBound},
-    },
-    v765::{
-        clientbound::LoginSuccess,
-        serverbound::{LoginAck, LoginStart},
-        PacketDecoderImpl, PacketEncoderImpl,
-    },
+    dispatch::ServerboundLogin,
+    io::{AsyncPacketReader, AsyncPacketWriter, PacketReadErr, PacketWriteErr, CRYPT_KEY_LEN},
+    packet::{ConnectionState::Login, Packet, PacketDirection::ClientBound},
+    text::TextComponent,
+    v765,
+    v765::clientbound::{LoginDisconnect, LoginPluginRequest, SetCompression},
+    v766,
+    version::ProtocolVersion,
 };
+use subtle::ConstantTimeEq;
 use tokio::net::TcpStream;
 use tracing::instrument;
-use uuid::Uuid;
 
-use crate::client::profile::{Profile, ProfileErr};
+use crate::{
+    client::profile::{Profile, ProfileErr},
+    network::{
+        forwarding::{
+            parse_bungeecord, parse_velocity, ForwardedPlayer, Forwarding, ForwardingErr,
+            VELOCITY_CHANNEL, VELOCITY_FORWARDING_VERSION,
+        },
+        session::{server_hash, ServerKey, SessionErr, SessionVerifier},
+    },
+};
+
+pub const VERIFY_TOKEN_LEN: usize = 4;
+
+pub struct LoginConfig {
+    /// The server keypair, present only when the server runs in online mode.
+    pub key: Option<Arc<ServerKey>>,
+    pub verifier: Arc<dyn SessionVerifier>,
+    pub compression_threshold: Option<usize>,
+    /// When set players are identified by the proxy instead of the session server.
+    pub forwarding: Forwarding,
+}
 
 #[derive(Debug, Clone)]
 pub struct LoginResult {
@@ -27,6 +48,11 @@ pub enum LoginErr {
     WriteErr(PacketWriteErr),
     ReadErr(PacketReadErr),
     InvalidProfile(ProfileErr),
+    DecryptionErr(SessionErr),
+    InvalidVerifyToken,
+    InvalidSharedSecret,
+    AuthenticationFailed(SessionErr),
+    ForwardingErr(ForwardingErr),
     UnexpectedPacket,
 }
 
@@ -36,44 +62,526 @@ impl Debug for LoginErr {
             LoginErr::WriteErr(err) => write!(f, "write error: {}", err),
             LoginErr::ReadErr(err) => write!(f, "write error: {}", err),
             LoginErr::InvalidProfile(err) => write!(f, "invalid user profile: {}", err),
+            LoginErr::DecryptionErr(err) => write!(f, "decryption error: {}", err),
+            LoginErr::InvalidVerifyToken => write!(f, "invalid verify token"),
+            LoginErr::InvalidSharedSecret => write!(f, "invalid shared secret"),
+            LoginErr::AuthenticationFailed(err) => write!(f, "authentication failed: {}", err),
+            LoginErr::ForwardingErr(err) => write!(f, "forwarding error: {:?}", err),
             LoginErr::UnexpectedPacket => write!(f, "unexpected packet"),
         }
     }
 }
 
+#[instrument(skip_all)]
+async fn enable_encryption(
+    socket: &mut TcpStream,
+    reader: &mut AsyncPacketReader,
+    writer: &mut AsyncPacketWriter,
+    version: ProtocolVersion,
+    key: &ServerKey,
+) -> Result<String, LoginErr> {
+    let verify_token: [u8; VERIFY_TOKEN_LEN] = rand::random();
+    let server_id = String::new();
+    let encryption_request: Box<dyn Packet> = match version {
+        ProtocolVersion::V765 => Box::new(v765::clientbound::EncryptionRequest {
+            server_id: server_id.clone(),
+            public_key: key.public_key_der().to_vec(),
+            verify_token: verify_token.to_vec(),
+        }),
+        ProtocolVersion::V766 => Box::new(v766::clientbound::EncryptionRequest {
+            server_id: server_id.clone(),
+            public_key: key.public_key_der().to_vec(),
+            verify_token: verify_token.to_vec(),
+            should_authenticate: true,
+        }),
+    };
+    tracing::trace!("sending encryption request packet");
+    writer
+        .write_versioned(
+            socket,
+            version,
+            ClientBound,
+            Login,
+            encryption_request.as_ref(),
+        )
+        .await
+        .map_err(|err| LoginErr::WriteErr(err))?;
+    let ServerboundLogin::EncryptionResponse(encryption_response) = reader
+        .read_versioned_typed::<TcpStream, ServerboundLogin>(socket, version)
+        .await
+        .map_err(|err| LoginErr::ReadErr(err))?
+    else {
+        return Err(LoginErr::UnexpectedPacket);
+    };
+    tracing::trace!("received encryption response packet");
+    let token = key
+        .decrypt(encryption_response.verify_token.as_slice())
+        .map_err(|err| LoginErr::DecryptionErr(err))?;
+    // compared in constant time so the token cannot be guessed byte by byte
+    if !bool::from(token.as_slice().ct_eq(verify_token.as_slice())) {
+        return Err(LoginErr::InvalidVerifyToken);
+    }
+    let shared_secret: [u8; CRYPT_KEY_LEN] = key
+        .decrypt(encryption_response.shared_secret.as_slice())
+        .map_err(|err| LoginErr::DecryptionErr(err))?
+        .try_into()
+        .map_err(|_| LoginErr::InvalidSharedSecret)?;
+    reader.enable_encryption(&shared_secret);
+    writer.enable_encryption(&shared_secret);
+    Ok(server_hash(
+        server_id.as_str(),
+        shared_secret.as_slice(),
+        key.public_key_der(),
+    ))
+}
+
+/// Asks Velocity for the info of the player it is connecting.
+#[instrument(skip_all)]
+async fn velocity_player_info(
+    socket: &mut TcpStream,
+    reader: &mut AsyncPacketReader,
+    writer: &mut AsyncPacketWriter,
+    version: ProtocolVersion,
+    secret: &[u8],
+) -> Result<ForwardedPlayer, LoginErr> {
+    let message_id: i32 = rand::random();
+    let plugin_request = LoginPluginRequest {
+        message_id,
+        channel: VELOCITY_CHANNEL.to_string(),
+        data: vec![VELOCITY_FORWARDING_VERSION],
+    };
+    tracing::trace!(?plugin_request, "sending login plugin request packet");
+    writer
+        .write_versioned(socket, version, ClientBound, Login, &plugin_request)
+        .await
+        .map_err(LoginErr::WriteErr)?;
+    let ServerboundLogin::LoginPluginResponse(plugin_response) = reader
+        .read_versioned_typed::<TcpStream, ServerboundLogin>(socket, version)
+        .await
+        .map_err(LoginErr::ReadErr)?
+    else {
+        return Err(LoginErr::UnexpectedPacket);
+    };
+    if plugin_response.message_id != message_id {
+        return Err(LoginErr::UnexpectedPacket);
+    }
+    if !plugin_response.successful {
+        return Err(LoginErr::ForwardingErr(ForwardingErr::NotForwarded));
+    }
+    parse_velocity(secret, plugin_response.data.as_slice()).map_err(LoginErr::ForwardingErr)
+}
+
+async fn disconnect(
+    socket: &mut TcpStream,
+    writer: &mut AsyncPacketWriter,
+    version: ProtocolVersion,
+    reason: TextComponent,
+) {
+    let login_disconnect = LoginDisconnect { reason };
+    let _ = writer
+        .write_versioned(socket, version, ClientBound, Login, &login_disconnect)
+        .await;
+}
+
 #[instrument(skip_all)]
 pub async fn handle_login(
     socket: &mut TcpStream,
     reader: &mut AsyncPacketReader,
     writer: &mut AsyncPacketWriter,
+    version: ProtocolVersion,
+    server_addr: &str,
+    config: &LoginConfig,
 ) -> Result<LoginResult, LoginErr> {
-    let login_start = reader
-        .read::<TcpStream, PacketDecoderImpl>(socket, ServerBound, Login)
+    let ServerboundLogin::LoginStart(login_start) = reader
+        .read_versioned_typed::<TcpStream, ServerboundLogin>(socket, version)
         .await
         .map_err(|err| LoginErr::ReadErr(err))?
-        .into_any()
-        .downcast::<LoginStart>()
-        .map_err(|_| LoginErr::UnexpectedPacket)?;
+    else {
+        return Err(LoginErr::UnexpectedPacket);
+    };
     tracing::trace!(?login_start, "received connect start packet");
-    let profile = Profile::try_from((login_start.name, login_start.uuid))
-        .map_err(|err| LoginErr::InvalidProfile(err))?;
+    let forwarded = match &config.forwarding {
+        Forwarding::None => None,
+        Forwarding::BungeeCord => Some(parse_bungeecord(server_addr).map_err(|err| {
+            (
+                LoginErr::ForwardingErr(err),
+                "If you wish to use IP forwarding, please enable it in your BungeeCord config as \
+                 well!",
+            )
+        })),
+        Forwarding::Velocity { secret } => Some(
+            velocity_player_info(socket, reader, writer, version, secret.as_slice())
+                .await
+                .map_err(|err| (err, "This server requires you to connect with Velocity.")),
+        ),
+    };
+    let profile = match forwarded {
+        // the proxy already authenticated the player
+        Some(Ok(forwarded)) => {
+            let name = forwarded.name.unwrap_or(login_start.name);
+            let mut profile =
+                Profile::try_from((name, forwarded.uuid)).map_err(LoginErr::InvalidProfile)?;
+            profile.properties = forwarded.properties;
+            profile.forwarded_addr = Some(forwarded.addr);
+            profile
+        }
+        Some(Err((err, reason))) => {
+            disconnect(socket, writer, version, TextComponent::text(reason)).await;
+            return Err(err);
+        }
+        None => {
+            let server_hash = if let Some(key) = config.key.as_ref() {
+                enable_encryption(socket, reader, writer, version, key.as_ref()).await?
+            } else {
+                String::new()
+            };
+            match config
+                .verifier
+                .verify(login_start.name.as_str(), server_hash.as_str())
+                .await
+            {
+                Ok(profile) => profile,
+                Err(err) => {
+                    let reason = TextComponent::translate(
+                        "multiplayer.disconnect.unverified_username",
+                        Vec::new(),
+                    );
+                    disconnect(socket, writer, version, reason).await;
+                    return Err(LoginErr::AuthenticationFailed(err));
+                }
+            }
+        }
+    };
     tracing::debug!(?profile, "loaded user profile");
-    let login_success = LoginSuccess {
-        uuid: profile.uuid,
-        username: profile.name.clone(),
-        properties: vec![],
+    if let Some(threshold) = config.compression_threshold {
+        let set_compression = SetCompression {
+            threshold: i32::try_from(threshold).unwrap_or(i32::MAX),
+        };
+        tracing::trace!(?set_compression, "sending set compression packet");
+        writer
+            .write_versioned(socket, version, ClientBound, Login, &set_compression)
+            .await
+            .map_err(|err| LoginErr::WriteErr(err))?;
+        // both halves switch together, the client compresses everything after set compression
+        writer.set_compression(Some(threshold));
+        reader.set_compression(Some(threshold));
+    }
+    let login_success: Box<dyn Packet> = match version {
+        ProtocolVersion::V765 => Box::new(v765::clientbound::LoginSuccess {
+            uuid: profile.uuid,
+            username: profile.name.clone(),
+            properties: profile.properties.clone(),
+        }),
+        ProtocolVersion::V766 => Box::new(v766::clientbound::LoginSuccess {
+            uuid: profile.uuid,
+            username: profile.name.clone(),
+            properties: profile.properties.clone(),
+            strict_error_handling: false,
+        }),
     };
     tracing::trace!(?login_success, "sending connect success packet");
     writer
-        .write::<TcpStream, PacketEncoderImpl>(socket, ClientBound, Login, &login_success)
+        .write_versioned(socket, version, ClientBound, Login, login_success.as_ref())
         .await
         .map_err(|err| LoginErr::WriteErr(err))?;
-    let _ = reader
-        .read::<TcpStream, PacketDecoderImpl>(socket, ServerBound, Login)
+    let ServerboundLogin::LoginAck(_) = reader
+        .read_versioned_typed::<TcpStream, ServerboundLogin>(socket, version)
         .await
         .map_err(|err| LoginErr::ReadErr(err))?
-        .into_any()
-        .downcast::<LoginAck>()
-        .map_err(|_| LoginErr::UnexpectedPacket)?;
+    else {
+        return Err(LoginErr::UnexpectedPacket);
+    };
     Ok(LoginResult { profile })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Cursor,
+        net::{IpAddr, Ipv4Addr},
+        sync::Arc,
+    };
+
+    use hmac::{Hmac, Mac};
+    use rsa::{pkcs8::DecodePublicKey, Pkcs1v15Encrypt, RsaPublicKey};
+    use serverx_protocol::{
+        encode::ProtoEncode,
+        io::{AsyncPacketReader, AsyncPacketWriter},
+        packet::{
+            ConnectionState::Login,
+            PacketDirection::{ClientBound, ServerBound},
+        },
+        types::VarInt,
+        v765::{
+            clientbound::{EncryptionRequest, LoginDisconnect, LoginPluginRequest, LoginSuccess},
+            serverbound::{EncryptionResponse, LoginAck, LoginPluginResponse, LoginStart},
+            types::LoginProperty,
+            PacketDecoderImpl, PacketEncoderImpl,
+        },
+        version::ProtocolVersion,
+    };
+    use sha2::Sha256;
+    use tokio::net::{TcpListener, TcpStream};
+    use uuid::Uuid;
+
+    use crate::{
+        client::profile::offline_uuid,
+        network::{
+            forwarding::{Forwarding, ForwardingErr, VELOCITY_CHANNEL},
+            handlers::login::{handle_login, LoginConfig, LoginErr, LoginResult},
+            session::{OfflineSessionVerifier, ServerKey},
+        },
+    };
+
+    async fn login_online(
+        tamper_token: bool,
+    ) -> (Result<LoginResult, LoginErr>, Option<LoginSuccess>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = LoginConfig {
+            key: Some(Arc::new(ServerKey::generate().unwrap())),
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: None,
+            forwarding: Forwarding::None,
+        };
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut reader = AsyncPacketReader::new();
+            let mut writer = AsyncPacketWriter::new();
+            handle_login(
+                &mut socket,
+                &mut reader,
+                &mut writer,
+                ProtocolVersion::V765,
+                "localhost",
+                &config,
+            )
+            .await
+        });
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut reader = AsyncPacketReader::new();
+        let mut writer = AsyncPacketWriter::new();
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+        let request = reader
+            .read::<_, PacketDecoderImpl>(&mut socket, ClientBound, Login)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<EncryptionRequest>()
+            .unwrap();
+        let public_key = RsaPublicKey::from_public_key_der(request.public_key.as_slice()).unwrap();
+        let shared_secret: [u8; 16] = rand::random();
+        let mut verify_token = request.verify_token.clone();
+        if tamper_token {
+            verify_token[0] ^= 0xff;
+        }
+        let mut rng = rand::thread_rng();
+        let response = EncryptionResponse {
+            shared_secret: public_key
+                .encrypt(&mut rng, Pkcs1v15Encrypt, shared_secret.as_slice())
+                .unwrap(),
+            verify_token: public_key
+                .encrypt(&mut rng, Pkcs1v15Encrypt, verify_token.as_slice())
+                .unwrap(),
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &response)
+            .await
+            .unwrap();
+        if tamper_token {
+            return (server.await.unwrap(), None);
+        }
+        reader.enable_encryption(&shared_secret);
+        writer.enable_encryption(&shared_secret);
+        let login_success = reader
+            .read::<_, PacketDecoderImpl>(&mut socket, ClientBound, Login)
+            .await
+            .unwrap()
+            .into_any()
+            .downcast::<LoginSuccess>()
+            .unwrap();
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &LoginAck)
+            .await
+            .unwrap();
+        (server.await.unwrap(), Some(*login_success))
+    }
+
+    #[tokio::test]
+    async fn test_online_login() {
+        let (result, login_success) = login_online(false).await;
+        let profile = result.unwrap().profile;
+        let login_success = login_success.unwrap();
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(profile.uuid, offline_uuid("Notch"));
+        assert_eq!(login_success.uuid, profile.uuid);
+        assert_eq!(login_success.username, profile.name);
+    }
+
+    #[tokio::test]
+    async fn test_online_login_invalid_token() {
+        let (result, _) = login_online(true).await;
+        assert!(matches!(result, Err(LoginErr::InvalidVerifyToken)));
+    }
+
+    const FORWARDED_UUID: u128 = 0x069a79f444e94726a5befca90e38aaf5;
+
+    fn velocity_data(secret: &[u8]) -> Vec<u8> {
+        let mut payload = Cursor::new(Vec::new());
+        VarInt::encode(&1, &mut payload).unwrap();
+        String::encode(&"10.0.0.7".to_string(), &mut payload).unwrap();
+        Uuid::encode(&Uuid::from_u128(FORWARDED_UUID), &mut payload).unwrap();
+        String::encode(&"Notch".to_string(), &mut payload).unwrap();
+        let properties = vec![LoginProperty {
+            name: "textures".to_string(),
+            value: "e30=".to_string(),
+            signature: Some("c2ln".to_string()),
+        }];
+        Vec::<LoginProperty>::encode(&properties, &mut payload).unwrap();
+        let payload = payload.into_inner();
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(payload.as_slice());
+        let mut data = mac.finalize().into_bytes().to_vec();
+        data.extend_from_slice(payload.as_slice());
+        data
+    }
+
+    /// Logs in through a proxy, answering a Velocity player info request with data signed with
+    /// `velocity_secret`.
+    async fn login_forwarded(
+        forwarding: Forwarding,
+        server_addr: &'static str,
+        velocity_secret: Option<&'static [u8]>,
+    ) -> (Result<LoginResult, LoginErr>, Option<LoginSuccess>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // the proxy authenticates players, no encryption request is sent in online mode
+        let config = LoginConfig {
+            key: Some(Arc::new(ServerKey::generate().unwrap())),
+            verifier: Arc::new(OfflineSessionVerifier),
+            compression_threshold: None,
+            forwarding,
+        };
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut reader = AsyncPacketReader::new();
+            let mut writer = AsyncPacketWriter::new();
+            handle_login(
+                &mut socket,
+                &mut reader,
+                &mut writer,
+                ProtocolVersion::V765,
+                server_addr,
+                &config,
+            )
+            .await
+        });
+
+        let mut socket = TcpStream::connect(addr).await.unwrap();
+        let mut reader = AsyncPacketReader::new();
+        let mut writer = AsyncPacketWriter::new();
+        let login_start = LoginStart {
+            name: "Notch".to_string(),
+            uuid: Uuid::nil(),
+        };
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &login_start)
+            .await
+            .unwrap();
+        if let Some(secret) = velocity_secret {
+            let request = reader
+                .read::<_, PacketDecoderImpl>(&mut socket, ClientBound, Login)
+                .await
+                .unwrap()
+                .into_any()
+                .downcast::<LoginPluginRequest>()
+                .unwrap();
+            assert_eq!(request.channel, VELOCITY_CHANNEL);
+            let response = LoginPluginResponse {
+                message_id: request.message_id,
+                successful: true,
+                data: velocity_data(secret),
+            };
+            writer
+                .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &response)
+                .await
+                .unwrap();
+        }
+        let packet = reader
+            .read::<_, PacketDecoderImpl>(&mut socket, ClientBound, Login)
+            .await
+            .unwrap()
+            .into_any();
+        if packet.is::<LoginDisconnect>() {
+            return (server.await.unwrap(), None);
+        }
+        let login_success = packet.downcast::<LoginSuccess>().unwrap();
+        writer
+            .write::<_, PacketEncoderImpl>(&mut socket, ServerBound, Login, &LoginAck)
+            .await
+            .unwrap();
+        (server.await.unwrap(), Some(*login_success))
+    }
+
+    #[tokio::test]
+    async fn test_bungeecord_login() {
+        let (result, login_success) = login_forwarded(
+            Forwarding::BungeeCord,
+            "localhost\u{0}10.0.0.7\u{0}069a79f444e94726a5befca90e38aaf5\u{0}[{\"name\":\"\
+             textures\",\"value\":\"e30=\"}]",
+            None,
+        )
+        .await;
+        let profile = result.unwrap().profile;
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(profile.uuid.as_u128(), FORWARDED_UUID);
+        assert_eq!(
+            profile.forwarded_addr,
+            Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)))
+        );
+        let login_success = login_success.unwrap();
+        assert_eq!(login_success.uuid, profile.uuid);
+        assert_eq!(login_success.properties[0].value, "e30=");
+
+        let (result, login_success) =
+            login_forwarded(Forwarding::BungeeCord, "localhost", None).await;
+        assert!(matches!(
+            result,
+            Err(LoginErr::ForwardingErr(ForwardingErr::MissingData))
+        ));
+        assert!(login_success.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_velocity_login() {
+        let forwarding = Forwarding::Velocity {
+            secret: b"secret".to_vec(),
+        };
+        let (result, login_success) =
+            login_forwarded(forwarding.clone(), "localhost", Some(b"secret")).await;
+        let profile = result.unwrap().profile;
+        assert_eq!(profile.uuid.as_u128(), FORWARDED_UUID);
+        assert_eq!(
+            profile.forwarded_addr,
+            Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)))
+        );
+        assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+        assert_eq!(login_success.unwrap().properties.len(), 1);
+
+        let (result, login_success) =
+            login_forwarded(forwarding, "localhost", Some(b"other")).await;
+        assert!(matches!(
+            result,
+            Err(LoginErr::ForwardingErr(ForwardingErr::InvalidSignature))
+        ));
+        assert!(login_success.is_none());
+    }
+}
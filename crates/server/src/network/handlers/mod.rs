@@ -1,3 +1,4 @@
 pub mod handshake;
+pub mod legacy_ping;
 pub mod login;
 pub mod status;
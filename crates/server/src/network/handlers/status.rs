@@ -1,24 +1,114 @@
-use std::fmt::{Debug, Formatter};
+use std::{
+    fmt::{Debug, Formatter},
+    sync::Arc,
+};
 
+use parking_lot::RwLock;
 use serde_json::json;
 use serverx_protocol::{
+    dispatch::ServerboundStatus,
     io::{AsyncPacketReader, AsyncPacketWriter, PacketReadErr, PacketWriteErr},
-    packet::{
-        ConnectionState::Status,
-        PacketDirection::{ClientBound, ServerBound},
-    },
-    v765::{
-        clientbound::{StatusPingResponse, StatusResponse},
-        serverbound::{StatusPingRequest, StatusRequest},
-        PacketDecoderImpl, PacketEncoderImpl, PROTO_NAME, PROTO_VER,
-    },
+    packet::{ConnectionState::Status, PacketDirection::ClientBound},
+    text::TextComponent,
+    v765::clientbound::{StatusPingResponse, StatusResponse},
+    version::ProtocolVersion,
 };
 use tokio::net::TcpStream;
 use tracing::instrument;
+use uuid::Uuid;
+
+/// The most players listed in the hover text of the player count.
+pub const MAX_PLAYER_SAMPLE: usize = 12;
+
+/// What the server list shows, shared by the modern and the legacy status response.
+#[derive(Debug, Clone)]
+pub struct ServerStatus {
+    pub motd: TextComponent,
+    pub max_players: i32,
+    pub online_players: i32,
+    pub sample: Vec<PlayerSample>,
+    /// Names of every online player, listed by the query protocol.
+    pub players: Vec<String>,
+    /// A `data:image/png;base64,` URI of a 64x64 icon.
+    pub favicon: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: Uuid,
+}
+
+impl Default for ServerStatus {
+    fn default() -> Self {
+        Self {
+            motd: TextComponent::text("Hello world!"),
+            max_players: 0,
+            online_players: 0,
+            sample: Vec::new(),
+            players: Vec::new(),
+            favicon: None,
+        }
+    }
+}
+
+impl ServerStatus {
+    pub fn to_json(&self, version: ProtocolVersion) -> serde_json::Value {
+        let sample: Vec<serde_json::Value> = self
+            .sample
+            .iter()
+            .map(|player| {
+                json!({
+                    "name": player.name,
+                    "id": player.id.hyphenated().to_string()
+                })
+            })
+            .collect();
+        let mut response = json!({
+            "version": {
+                "name": version.name(),
+                "protocol": version.protocol()
+            },
+            "players": {
+                "max": self.max_players,
+                "online": self.online_players,
+                "sample": sample
+            },
+            "description": self.motd.to_json()
+        });
+        if let Some(favicon) = self.favicon.as_ref() {
+            response["favicon"] = favicon.as_ref().into();
+        }
+        response
+    }
+}
+
+/// The latest server status, published by the tick loop and read by the network tasks. Clones
+/// share the same status.
+#[derive(Clone, Default)]
+pub struct StatusSnapshot {
+    status: Arc<RwLock<Arc<ServerStatus>>>,
+}
+
+impl StatusSnapshot {
+    pub fn new(status: ServerStatus) -> Self {
+        Self {
+            status: Arc::new(RwLock::new(Arc::new(status))),
+        }
+    }
+
+    pub fn load(&self) -> Arc<ServerStatus> {
+        self.status.read().clone()
+    }
+
+    pub fn store(&self, status: ServerStatus) {
+        *self.status.write() = Arc::new(status);
+    }
+}
+
 pub enum StatusErr {
     WriteErr(PacketWriteErr),
     ReadErr(PacketReadErr),
-    UnexpectedPacket,
 }
 
 impl Debug for StatusErr {
@@ -26,7 +116,6 @@ impl Debug for StatusErr {
         match self {
             StatusErr::WriteErr(err) => write!(f, "write error: {}", err),
             StatusErr::ReadErr(err) => write!(f, "read error: {}", err),
-            StatusErr::UnexpectedPacket => write!(f, "unexpected packet"),
         }
     }
 }
@@ -36,57 +125,91 @@ pub async fn handle_status(
     socket: &mut TcpStream,
     reader: &mut AsyncPacketReader,
     writer: &mut AsyncPacketWriter,
+    version: ProtocolVersion,
+    server_status: &ServerStatus,
 ) -> Result<(), StatusErr> {
     loop {
         let packet = reader
-            .read::<TcpStream, PacketDecoderImpl>(socket, ServerBound, Status)
+            .read_versioned_typed::<TcpStream, ServerboundStatus>(socket, version)
             .await
             .map_err(|err| StatusErr::ReadErr(err))?;
-        match packet.id() {
-            StatusRequest::ID => {
-                let status = packet
-                    .as_any()
-                    .downcast_ref::<StatusRequest>()
-                    .ok_or_else(|| StatusErr::UnexpectedPacket)?;
+        match packet {
+            ServerboundStatus::StatusRequest(status) => {
                 tracing::trace!(packet = ?status, "received status request packet");
                 let response = StatusResponse {
-                    response: json!({
-                        "version": {
-                            "name": PROTO_NAME,
-                            "protocol": PROTO_VER
-                        },
-                        "players": {
-                            "max": 0,
-                            "online": 0
-                        },
-                        "description": {
-                            "text": "Hello world!"
-                        }
-                    }),
+                    response: server_status.to_json(version),
                 };
                 tracing::trace!(packet = ?response, "writing status response packet");
                 writer
-                    .write::<TcpStream, PacketEncoderImpl>(socket, ClientBound, Status, &response)
+                    .write_versioned(socket, version, ClientBound, Status, &response)
                     .await
                     .map_err(|err| StatusErr::WriteErr(err))?;
             }
-            StatusPingRequest::ID => {
-                let ping = packet
-                    .as_any()
-                    .downcast_ref::<StatusPingRequest>()
-                    .ok_or_else(|| StatusErr::UnexpectedPacket)?;
+            ServerboundStatus::StatusPingRequest(ping) => {
                 tracing::trace!(packet = ?ping, "received ping request packet");
                 let pong = StatusPingResponse {
                     payload: ping.payload,
                 };
                 tracing::trace!(packet = ?pong, "writing pong packet");
                 writer
-                    .write::<TcpStream, PacketEncoderImpl>(socket, ClientBound, Status, &pong)
+                    .write_versioned(socket, version, ClientBound, Status, &pong)
                     .await
                     .map_err(|err| StatusErr::WriteErr(err))?;
                 return Ok(());
             }
-            _ => return Err(StatusErr::UnexpectedPacket),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use serverx_protocol::{text::TextComponent, version::ProtocolVersion};
+    use uuid::Uuid;
+
+    use crate::network::handlers::status::{PlayerSample, ServerStatus, StatusSnapshot};
+
+    #[test]
+    fn test_status_json() {
+        let status = ServerStatus {
+            motd: TextComponent::text("A Minecraft Server"),
+            max_players: 20,
+            online_players: 1,
+            sample: vec![PlayerSample {
+                name: "Notch".to_string(),
+                id: Uuid::from_u128(0x069a79f444e94726a5befca90e38aaf5),
+            }],
+            players: vec!["Notch".to_string()],
+            favicon: Some(Arc::from("data:image/png;base64,AAAA")),
+        };
+        let json = status.to_json(ProtocolVersion::V765);
+        assert_eq!(json["version"]["protocol"], 765);
+        assert_eq!(json["players"]["max"], 20);
+        assert_eq!(json["players"]["online"], 1);
+        assert_eq!(json["players"]["sample"][0]["name"], "Notch");
+        assert_eq!(
+            json["players"]["sample"][0]["id"],
+            "069a79f4-44e9-4726-a5be-fca90e38aaf5"
+        );
+        assert_eq!(json["description"], "A Minecraft Server");
+        assert_eq!(json["favicon"], "data:image/png;base64,AAAA");
+        assert!(ServerStatus::default()
+            .to_json(ProtocolVersion::V765)
+            .get("favicon")
+            .is_none());
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let snapshot = StatusSnapshot::new(ServerStatus::default());
+        let published = snapshot.clone();
+        let before = snapshot.load();
+        published.store(ServerStatus {
+            online_players: 5,
+            ..ServerStatus::default()
+        });
+        assert_eq!(before.online_players, 0);
+        assert_eq!(snapshot.load().online_players, 5);
+    }
+}
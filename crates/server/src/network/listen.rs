@@ -2,20 +2,61 @@ use std::{
     io,
     net::{IpAddr, SocketAddr},
     str::FromStr,
+    sync::Arc,
+    time::Instant,
 };
 
 use flume::Sender;
 use tokio::net::TcpListener;
 
-use crate::network::{accept::accept_client, event::NetworkEvent};
+use crate::network::{
+    accept::accept_client,
+    event::NetworkEvent,
+    handlers::{login::LoginConfig, status::StatusSnapshot},
+    queue::QueueLimits,
+    throttle::{ConnectionThrottle, ListenerLimits, Rejections},
+};
 
-pub async fn listen(addr: SocketAddr, events: Sender<NetworkEvent>) {
+pub async fn listen(
+    addr: SocketAddr,
+    events: Sender<NetworkEvent>,
+    login_config: Arc<LoginConfig>,
+    server_status: StatusSnapshot,
+    queue_limits: QueueLimits,
+    limits: ListenerLimits,
+) {
     if let Ok(listener) = TcpListener::bind(addr).await {
+        let mut throttle = ConnectionThrottle::new(limits, Instant::now());
+        let rejections = Arc::new(Rejections::default());
         loop {
             if let Ok((mut socket, addr)) = listener.accept().await {
+                // dropping the socket closes the connection before anything is read
+                let pending = match throttle.admit(addr.ip(), Instant::now()) {
+                    Ok(pending) => pending,
+                    Err(reason) => {
+                        rejections.record(addr, reason);
+                        continue;
+                    }
+                };
                 let events_clone = events.clone();
+                let login_config_clone = login_config.clone();
+                let server_status_clone = server_status.clone();
+                let rejections_clone = rejections.clone();
                 tokio::spawn(async move {
-                    accept_client(socket, addr, events_clone).await;
+                    if let Err(reason) = accept_client(
+                        socket,
+                        addr,
+                        events_clone,
+                        login_config_clone,
+                        server_status_clone,
+                        queue_limits,
+                        limits,
+                    )
+                    .await
+                    {
+                        rejections_clone.record(addr, reason);
+                    }
+                    drop(pending);
                 });
             }
         }
@@ -1,5 +1,11 @@
 pub mod accept;
 pub mod brand;
 pub mod event;
+pub mod forwarding;
 pub mod handlers;
 pub mod listen;
+pub mod query;
+pub mod queue;
+pub mod rcon;
+pub mod session;
+pub mod throttle;
@@ -0,0 +1,369 @@
+use std::{
+    fmt::{Debug, Formatter},
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+use hashbrown::HashMap;
+use serverx_protocol::version::ProtocolVersion;
+use tokio::net::UdpSocket;
+
+use crate::network::handlers::status::{ServerStatus, StatusSnapshot};
+
+const QUERY_MAGIC: [u8; 2] = [0xFE, 0xFD];
+const TYPE_HANDSHAKE: u8 = 9;
+const TYPE_STAT: u8 = 0;
+/// Session ids are masked like vanilla does it, the client ignores the high nibbles.
+const SESSION_ID_MASK: i32 = 0x0F0F0F0F;
+const BASIC_STAT_LEN: usize = 11;
+const FULL_STAT_LEN: usize = 15;
+const FULL_STAT_PADDING: &[u8] = b"splitnum\0\x80\0";
+const PLAYERS_PADDING: &[u8] = b"\x01player_\0\0";
+/// How long a challenge token stays valid, the same as vanilla.
+pub const CHALLENGE_LIFETIME: Duration = Duration::from_secs(30);
+/// Large enough for any request, anything longer is not a query packet.
+const MAX_REQUEST_LEN: usize = 1460;
+
+/// What the query protocol reports besides the status snapshot.
+#[derive(Debug, Clone)]
+pub struct QueryInfo {
+    pub game_type: String,
+    pub map: String,
+    pub host_ip: String,
+    pub host_port: u16,
+    pub plugins: String,
+}
+
+pub enum QueryErr {
+    InvalidMagic,
+    InvalidLength(usize),
+    UnknownType(u8),
+    InvalidChallenge(SocketAddr),
+}
+
+impl Debug for QueryErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryErr::InvalidMagic => write!(f, "invalid magic"),
+            QueryErr::InvalidLength(len) => write!(f, "invalid request length {}", len),
+            QueryErr::UnknownType(ty) => write!(f, "unknown request type {}", ty),
+            QueryErr::InvalidChallenge(addr) => write!(f, "invalid challenge token from {}", addr),
+        }
+    }
+}
+
+/// Answers GameSpy4 query requests, tokens are handed out per address and expire after
+/// [`CHALLENGE_LIFETIME`].
+pub struct QueryHandler {
+    info: QueryInfo,
+    challenges: HashMap<SocketAddr, (i32, Instant)>,
+    last_prune: Instant,
+}
+
+impl QueryHandler {
+    pub fn new(info: QueryInfo, now: Instant) -> Self {
+        Self {
+            info,
+            challenges: HashMap::new(),
+            last_prune: now,
+        }
+    }
+
+    /// Handles a single datagram and returns the response to send back.
+    pub fn handle(
+        &mut self,
+        addr: SocketAddr,
+        request: &[u8],
+        status: &ServerStatus,
+        now: Instant,
+    ) -> Result<Vec<u8>, QueryErr> {
+        if now.saturating_duration_since(self.last_prune) >= CHALLENGE_LIFETIME {
+            self.challenges.retain(|_, (_, issued)| {
+                now.saturating_duration_since(*issued) < CHALLENGE_LIFETIME
+            });
+            self.last_prune = now;
+        }
+        if request.len() < 7 {
+            return Err(QueryErr::InvalidLength(request.len()));
+        }
+        if request[..2] != QUERY_MAGIC {
+            return Err(QueryErr::InvalidMagic);
+        }
+        let ty = request[2];
+        let session_id =
+            i32::from_be_bytes([request[3], request[4], request[5], request[6]]) & SESSION_ID_MASK;
+        match ty {
+            TYPE_HANDSHAKE => {
+                let token = rand::random::<i32>() & 0x7FFFFFFF;
+                self.challenges.insert(addr, (token, now));
+                let mut response = Self::header(TYPE_HANDSHAKE, session_id);
+                response.extend_from_slice(token.to_string().as_bytes());
+                response.push(0);
+                Ok(response)
+            }
+            TYPE_STAT => {
+                if request.len() != BASIC_STAT_LEN && request.len() != FULL_STAT_LEN {
+                    return Err(QueryErr::InvalidLength(request.len()));
+                }
+                let token = i32::from_be_bytes([request[7], request[8], request[9], request[10]]);
+                match self.challenges.get(&addr) {
+                    Some((expected, issued))
+                        if *expected == token
+                            && now.saturating_duration_since(*issued) < CHALLENGE_LIFETIME => {}
+                    _ => return Err(QueryErr::InvalidChallenge(addr)),
+                }
+                if request.len() == BASIC_STAT_LEN {
+                    Ok(self.basic_stat(session_id, status))
+                } else {
+                    Ok(self.full_stat(session_id, status))
+                }
+            }
+            ty => Err(QueryErr::UnknownType(ty)),
+        }
+    }
+
+    fn header(ty: u8, session_id: i32) -> Vec<u8> {
+        let mut response = vec![ty];
+        response.extend_from_slice(session_id.to_be_bytes().as_slice());
+        response
+    }
+
+    fn push_str(response: &mut Vec<u8>, value: &str) {
+        // the query protocol has no escaping, null characters would end the field early
+        response.extend(value.bytes().filter(|b| *b != 0));
+        response.push(0);
+    }
+
+    fn basic_stat(&self, session_id: i32, status: &ServerStatus) -> Vec<u8> {
+        let mut response = Self::header(TYPE_STAT, session_id);
+        Self::push_str(&mut response, status.motd.to_plain().as_str());
+        Self::push_str(&mut response, self.info.game_type.as_str());
+        Self::push_str(&mut response, self.info.map.as_str());
+        Self::push_str(&mut response, status.online_players.to_string().as_str());
+        Self::push_str(&mut response, status.max_players.to_string().as_str());
+        // the only little endian field of the protocol
+        response.extend_from_slice(self.info.host_port.to_le_bytes().as_slice());
+        Self::push_str(&mut response, self.info.host_ip.as_str());
+        response
+    }
+
+    fn full_stat(&self, session_id: i32, status: &ServerStatus) -> Vec<u8> {
+        let mut response = Self::header(TYPE_STAT, session_id);
+        response.extend_from_slice(FULL_STAT_PADDING);
+        let values = [
+            ("hostname", status.motd.to_plain()),
+            ("gametype", self.info.game_type.clone()),
+            ("game_id", "MINECRAFT".to_string()),
+            ("version", ProtocolVersion::LATEST.name().to_string()),
+            ("plugins", self.info.plugins.clone()),
+            ("map", self.info.map.clone()),
+            ("numplayers", status.online_players.to_string()),
+            ("maxplayers", status.max_players.to_string()),
+            ("hostport", self.info.host_port.to_string()),
+            ("hostip", self.info.host_ip.clone()),
+        ];
+        for (key, value) in values.iter() {
+            Self::push_str(&mut response, key);
+            Self::push_str(&mut response, value.as_str());
+        }
+        response.push(0);
+        response.extend_from_slice(PLAYERS_PADDING);
+        for name in status.players.iter() {
+            Self::push_str(&mut response, name.as_str());
+        }
+        response.push(0);
+        response
+    }
+}
+
+pub async fn listen_query(addr: SocketAddr, info: QueryInfo, server_status: StatusSnapshot) {
+    let Ok(socket) = UdpSocket::bind(addr).await else {
+        tracing::error!(?addr, "unable to bind query socket to address");
+        return;
+    };
+    let mut handler = QueryHandler::new(info, Instant::now());
+    let mut buf = [0u8; MAX_REQUEST_LEN];
+    loop {
+        let (len, peer) = match socket.recv_from(&mut buf).await {
+            Ok(received) => received,
+            Err(err) => {
+                tracing::debug!(?err, "error while receiving query request");
+                continue;
+            }
+        };
+        let status = server_status.load();
+        match handler.handle(peer, &buf[..len], status.as_ref(), Instant::now()) {
+            Ok(response) => {
+                if let Err(err) = socket.send_to(response.as_slice(), peer).await {
+                    tracing::debug!(?err, ?peer, "unable to send query response");
+                }
+            }
+            Err(err) => tracing::trace!(?err, ?peer, "ignored query request"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{Ipv4Addr, SocketAddr},
+        time::Instant,
+    };
+
+    use serverx_protocol::text::TextComponent;
+
+    use crate::network::{
+        handlers::status::ServerStatus,
+        query::{QueryErr, QueryHandler, QueryInfo, CHALLENGE_LIFETIME},
+    };
+
+    fn handler(now: Instant) -> QueryHandler {
+        QueryHandler::new(
+            QueryInfo {
+                game_type: "SMP".to_string(),
+                map: "world".to_string(),
+                host_ip: "127.0.0.1".to_string(),
+                host_port: 25565,
+                plugins: String::new(),
+            },
+            now,
+        )
+    }
+
+    fn status() -> ServerStatus {
+        ServerStatus {
+            motd: TextComponent::text("A Minecraft Server"),
+            max_players: 20,
+            online_players: 2,
+            players: vec!["Notch".to_string(), "jeb_".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn request(ty: u8, token: Option<i32>, full: bool) -> Vec<u8> {
+        let mut request = vec![0xFE, 0xFD, ty, 0x01, 0x02, 0x03, 0x04];
+        if let Some(token) = token {
+            request.extend_from_slice(token.to_be_bytes().as_slice());
+        }
+        if full {
+            request.extend_from_slice(&[0, 0, 0, 0]);
+        }
+        request
+    }
+
+    /// Splits a response into its null terminated fields.
+    fn fields(response: &[u8]) -> Vec<String> {
+        response
+            .split(|b| *b == 0)
+            .map(|field| String::from_utf8_lossy(field).to_string())
+            .collect()
+    }
+
+    fn challenge(handler: &mut QueryHandler, addr: SocketAddr, now: Instant) -> i32 {
+        let response = handler
+            .handle(addr, request(9, None, false).as_slice(), &status(), now)
+            .unwrap();
+        assert_eq!(response[0], 9);
+        assert_eq!(&response[1..5], &[0x01, 0x02, 0x03, 0x04]);
+        fields(&response[5..])[0].parse().unwrap()
+    }
+
+    #[test]
+    fn test_basic_stat() {
+        let now = Instant::now();
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40000);
+        let mut handler = handler(now);
+        let token = challenge(&mut handler, addr, now);
+        let response = handler
+            .handle(
+                addr,
+                request(0, Some(token), false).as_slice(),
+                &status(),
+                now,
+            )
+            .unwrap();
+        assert_eq!(&response[..5], &[0x00, 0x01, 0x02, 0x03, 0x04]);
+        let body = &response[5..];
+        let fields = fields(body);
+        assert_eq!(fields[..5], [
+            "A Minecraft Server",
+            "SMP",
+            "world",
+            "2",
+            "20"
+        ]);
+        let port_at = body.len() - "127.0.0.1".len() - 3;
+        assert_eq!(
+            &body[port_at..port_at + 2],
+            25565u16.to_le_bytes().as_slice()
+        );
+        assert!(body.ends_with(b"127.0.0.1\0"));
+    }
+
+    #[test]
+    fn test_full_stat() {
+        let now = Instant::now();
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40000);
+        let mut handler = handler(now);
+        let token = challenge(&mut handler, addr, now);
+        let response = handler
+            .handle(
+                addr,
+                request(0, Some(token), true).as_slice(),
+                &status(),
+                now,
+            )
+            .unwrap();
+        let body = &response[5..];
+        assert!(body.starts_with(b"splitnum\0\x80\0"));
+        let (values, players) = body[11..].split_at(
+            body[11..]
+                .windows(10)
+                .position(|w| w == b"\x01player_\0\0")
+                .unwrap(),
+        );
+        let values = fields(values);
+        let pairs: Vec<(&str, &str)> = values
+            .chunks(2)
+            .take_while(|pair| !pair[0].is_empty())
+            .map(|pair| (pair[0].as_str(), pair[1].as_str()))
+            .collect();
+        assert!(pairs.contains(&("hostname", "A Minecraft Server")));
+        assert!(pairs.contains(&("game_id", "MINECRAFT")));
+        assert!(pairs.contains(&("numplayers", "2")));
+        assert!(pairs.contains(&("hostport", "25565")));
+        assert_eq!(&players[10..], b"Notch\0jeb_\0\0");
+    }
+
+    #[test]
+    fn test_challenge() {
+        let now = Instant::now();
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40000);
+        let other = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40001);
+        let mut handler = handler(now);
+        let token = challenge(&mut handler, addr, now);
+        // tokens are bound to the address they were issued to
+        assert!(matches!(
+            handler.handle(
+                other,
+                request(0, Some(token), false).as_slice(),
+                &status(),
+                now
+            ),
+            Err(QueryErr::InvalidChallenge(_))
+        ));
+        assert!(matches!(
+            handler.handle(
+                addr,
+                request(0, Some(token), false).as_slice(),
+                &status(),
+                now + CHALLENGE_LIFETIME
+            ),
+            Err(QueryErr::InvalidChallenge(_))
+        ));
+        assert!(matches!(
+            handler.handle(addr, &[0xFE, 0xFD, 9], &status(), now),
+            Err(QueryErr::InvalidLength(3))
+        ));
+    }
+}
@@ -0,0 +1,408 @@
+use std::{
+    fmt::{Debug, Formatter},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use flume::{Receiver, Sender};
+use serverx_protocol::{
+    encode::ProtoEncodeErr,
+    io::{encode_versioned, EncodedPacket, PreEncodedPacket},
+    packet::{Packet, PacketDirection::ClientBound},
+    version::ProtocolVersion,
+};
+
+/// How many packets a read loop buffers before it stops reading from the socket.
+pub const INCOMING_QUEUE_LEN: usize = 1024;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum PacketPriority {
+    Normal,
+    /// Packets the client can do without, dropped once the queue is over its soft limit.
+    Low,
+}
+
+#[derive(Debug)]
+pub enum OutgoingPacket {
+    Encoded(EncodedPacket),
+    /// Closes the connection once everything queued before it is written.
+    Close,
+}
+
+/// Byte limits of an outgoing queue, measured on encoded packets.
+#[derive(Debug, Copy, Clone)]
+pub struct QueueLimits {
+    pub soft_limit: usize,
+    pub hard_limit: usize,
+    /// How long the queue may stay over its soft limit before the client is disconnected.
+    pub backlog_timeout: Duration,
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self {
+            soft_limit: 8 * 1024 * 1024,
+            hard_limit: 32 * 1024 * 1024,
+            backlog_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// A snapshot of the counters of an outgoing queue.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct QueueMetrics {
+    pub queued_bytes: usize,
+    pub queued_packets: usize,
+    pub peak_bytes: usize,
+    pub dropped_packets: u64,
+    pub written_bytes: u64,
+}
+
+pub enum QueueErr {
+    EncodeErr(ProtoEncodeErr),
+    Full(usize),
+    Backlogged(Duration),
+    /// A pre-encoded packet was made for another protocol version or compression threshold.
+    Mismatch,
+    Closed,
+}
+
+impl Debug for QueueErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueueErr::EncodeErr(err) => write!(f, "encode error: {}", err),
+            QueueErr::Full(len) => write!(f, "queue is full with {} bytes", len),
+            QueueErr::Backlogged(duration) => {
+                write!(f, "queue has been over its soft limit for {:?}", duration)
+            }
+            QueueErr::Mismatch => write!(f, "pre-encoded packet does not match the connection"),
+            QueueErr::Closed => write!(f, "queue is closed"),
+        }
+    }
+}
+
+#[derive(Default)]
+struct QueueState {
+    queued_bytes: AtomicUsize,
+    queued_packets: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    dropped_packets: AtomicU64,
+    written_bytes: AtomicU64,
+    overflowed: AtomicBool,
+    closed: AtomicBool,
+}
+
+impl QueueState {
+    fn dequeued(&self, len: usize) {
+        self.queued_bytes.fetch_sub(len, Ordering::Relaxed);
+        self.queued_packets.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Creates the outgoing queue of a client, the tick loop sends to the queue and the write loop of
+/// the connection drains the receiver.
+pub fn outgoing_queue(
+    version: ProtocolVersion,
+    compression: Option<usize>,
+    limits: QueueLimits,
+) -> (OutgoingQueue, OutgoingReceiver) {
+    let (sender, receiver) = flume::unbounded::<OutgoingPacket>();
+    let state = Arc::new(QueueState::default());
+    (
+        OutgoingQueue {
+            version,
+            compression,
+            limits,
+            sender,
+            receiver: receiver.clone(),
+            state: state.clone(),
+            backlog_since: None,
+        },
+        OutgoingReceiver { receiver, state },
+    )
+}
+
+/// The sending half of an outgoing queue. Packets are encoded when they are queued so the queue
+/// knows exactly how many bytes it holds.
+pub struct OutgoingQueue {
+    version: ProtocolVersion,
+    compression: Option<usize>,
+    limits: QueueLimits,
+    sender: Sender<OutgoingPacket>,
+    /// Kept to drop the backlog of a client that is disconnected for falling behind.
+    receiver: Receiver<OutgoingPacket>,
+    state: Arc<QueueState>,
+    backlog_since: Option<Instant>,
+}
+
+impl OutgoingQueue {
+    pub fn send(&self, packet: &dyn Packet) -> Result<(), QueueErr> {
+        self.send_with_priority(packet, PacketPriority::Normal)
+    }
+
+    pub fn send_with_priority(
+        &self,
+        packet: &dyn Packet,
+        priority: PacketPriority,
+    ) -> Result<(), QueueErr> {
+        if self.is_closed() {
+            return Err(QueueErr::Closed);
+        }
+        let mut body = Vec::new();
+        encode_versioned(&mut body, self.version, ClientBound, packet)
+            .map_err(QueueErr::EncodeErr)?;
+        self.push(EncodedPacket::Body(body), priority)
+    }
+
+    /// Queues a packet encoded once for every client sharing this connection's protocol version
+    /// and compression threshold.
+    pub fn send_pre_encoded(
+        &self,
+        packet: PreEncodedPacket,
+        priority: PacketPriority,
+    ) -> Result<(), QueueErr> {
+        if self.is_closed() {
+            return Err(QueueErr::Closed);
+        }
+        if packet.version() != self.version || packet.compression() != self.compression {
+            return Err(QueueErr::Mismatch);
+        }
+        self.push(EncodedPacket::PreEncoded(packet), priority)
+    }
+
+    fn push(&self, packet: EncodedPacket, priority: PacketPriority) -> Result<(), QueueErr> {
+        let queued = self.state.queued_bytes.load(Ordering::Relaxed);
+        let len = packet.encoded_len();
+        if priority == PacketPriority::Low && queued + len > self.limits.soft_limit {
+            self.state.dropped_packets.fetch_add(1, Ordering::Relaxed);
+            tracing::trace!(len, queued, "dropping low priority packet");
+            return Ok(());
+        }
+        if queued + len > self.limits.hard_limit {
+            self.state.overflowed.store(true, Ordering::Relaxed);
+            return Err(QueueErr::Full(queued));
+        }
+        self.state.queued_bytes.fetch_add(len, Ordering::Relaxed);
+        self.state.queued_packets.fetch_add(1, Ordering::Relaxed);
+        self.state
+            .peak_bytes
+            .fetch_max(queued + len, Ordering::Relaxed);
+        self.sender
+            .send(OutgoingPacket::Encoded(packet))
+            .map_err(|_| QueueErr::Closed)
+    }
+
+    /// Asks the write loop to close the connection after writing everything queued so far.
+    pub fn close(&self) {
+        let _ = self.sender.send(OutgoingPacket::Close);
+    }
+
+    /// Drops every packet that was not written yet.
+    pub fn clear(&self) {
+        for packet in self.receiver.drain() {
+            if let OutgoingPacket::Encoded(packet) = packet {
+                self.state.dequeued(packet.encoded_len());
+            }
+        }
+    }
+
+    /// Fails if a packet was refused for going over the hard limit, or once the queue has stayed
+    /// over its soft limit for longer than the backlog timeout.
+    pub fn check_backlog(&mut self, now: Instant) -> Result<(), QueueErr> {
+        let queued = self.state.queued_bytes.load(Ordering::Relaxed);
+        if self.state.overflowed.load(Ordering::Relaxed) {
+            return Err(QueueErr::Full(queued));
+        }
+        if queued <= self.limits.soft_limit {
+            self.backlog_since = None;
+            return Ok(());
+        }
+        let since = *self.backlog_since.get_or_insert(now);
+        let duration = now.saturating_duration_since(since);
+        if duration >= self.limits.backlog_timeout {
+            Err(QueueErr::Backlogged(duration))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn version(&self) -> ProtocolVersion {
+        self.version
+    }
+
+    pub fn compression(&self) -> Option<usize> {
+        self.compression
+    }
+
+    /// Whether the write loop has stopped, after an io error or a close.
+    pub fn is_closed(&self) -> bool {
+        self.state.closed.load(Ordering::Relaxed)
+    }
+
+    pub fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            queued_bytes: self.state.queued_bytes.load(Ordering::Relaxed),
+            queued_packets: self.state.queued_packets.load(Ordering::Relaxed),
+            peak_bytes: self.state.peak_bytes.load(Ordering::Relaxed),
+            dropped_packets: self.state.dropped_packets.load(Ordering::Relaxed),
+            written_bytes: self.state.written_bytes.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The receiving half of an outgoing queue, owned by the write loop.
+pub struct OutgoingReceiver {
+    receiver: Receiver<OutgoingPacket>,
+    state: Arc<QueueState>,
+}
+
+impl OutgoingReceiver {
+    /// Waits for the next packet, `None` once the queue is dropped.
+    pub async fn recv(&self) -> Option<OutgoingPacket> {
+        let packet = self.receiver.recv_async().await.ok()?;
+        Some(self.dequeued(packet))
+    }
+
+    /// Returns the next packet if one is queued.
+    pub fn try_recv(&self) -> Option<OutgoingPacket> {
+        let packet = self.receiver.try_recv().ok()?;
+        Some(self.dequeued(packet))
+    }
+
+    fn dequeued(&self, packet: OutgoingPacket) -> OutgoingPacket {
+        if let OutgoingPacket::Encoded(packet) = &packet {
+            self.state.dequeued(packet.encoded_len());
+        }
+        packet
+    }
+
+    pub fn written(&self, len: usize) {
+        self.state
+            .written_bytes
+            .fetch_add(len as u64, Ordering::Relaxed);
+    }
+}
+
+impl Drop for OutgoingReceiver {
+    fn drop(&mut self) {
+        self.state.closed.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serverx_protocol::{
+        io::{EncodedPacket, PreEncodedPacket},
+        packet::PacketDirection::ClientBound,
+        v765::clientbound::{ChunkBatchFinish, KeepAlive},
+        version::ProtocolVersion,
+    };
+
+    use crate::network::queue::{
+        outgoing_queue, OutgoingPacket, PacketPriority, QueueErr, QueueLimits,
+    };
+
+    #[test]
+    fn test_pre_encoded() {
+        let (queue, _receiver) = outgoing_queue(ProtocolVersion::V765, Some(256), LIMITS);
+        let packet = KeepAlive { id: 1 };
+        let pre_encoded =
+            PreEncodedPacket::new(ProtocolVersion::V765, ClientBound, &packet, Some(256)).unwrap();
+        // a zero data length precedes the uncompressed body
+        assert_eq!(pre_encoded.frame().len(), 11);
+        queue
+            .send_pre_encoded(pre_encoded, PacketPriority::Normal)
+            .unwrap();
+        assert_eq!(queue.metrics().queued_bytes, 11);
+        for (version, compression) in [
+            (ProtocolVersion::V766, Some(256)),
+            (ProtocolVersion::V765, None),
+        ] {
+            let pre_encoded =
+                PreEncodedPacket::new(version, ClientBound, &packet, compression).unwrap();
+            assert!(matches!(
+                queue.send_pre_encoded(pre_encoded, PacketPriority::Normal),
+                Err(QueueErr::Mismatch)
+            ));
+        }
+    }
+
+    const LIMITS: QueueLimits = QueueLimits {
+        soft_limit: 20,
+        hard_limit: 40,
+        backlog_timeout: Duration::from_secs(1),
+    };
+
+    #[tokio::test]
+    async fn test_outgoing_queue() {
+        let (mut queue, receiver) = outgoing_queue(ProtocolVersion::V765, None, LIMITS);
+        // a keep alive is a one byte id and an eight byte long
+        for id in 0..2 {
+            queue.send(&KeepAlive { id }).unwrap();
+        }
+        assert_eq!(queue.metrics().queued_bytes, 18);
+        assert_eq!(queue.metrics().queued_packets, 2);
+        queue
+            .send_with_priority(&KeepAlive { id: 2 }, PacketPriority::Low)
+            .unwrap();
+        assert_eq!(queue.metrics().queued_packets, 2);
+        assert_eq!(queue.metrics().dropped_packets, 1);
+        queue.send(&KeepAlive { id: 3 }).unwrap();
+        queue.send(&KeepAlive { id: 4 }).unwrap();
+        assert!(matches!(
+            queue.send(&KeepAlive { id: 5 }),
+            Err(QueueErr::Full(36))
+        ));
+
+        assert!(matches!(
+            queue.check_backlog(Instant::now()),
+            Err(QueueErr::Full(36))
+        ));
+
+        let Some(OutgoingPacket::Encoded(EncodedPacket::Body(body))) = receiver.recv().await else {
+            panic!("expected an encoded packet");
+        };
+        receiver.written(body.len());
+        assert_eq!(body[0], 0x24);
+        queue.clear();
+        assert_eq!(queue.metrics().queued_bytes, 0);
+        assert_eq!(queue.metrics().peak_bytes, 36);
+        assert_eq!(queue.metrics().written_bytes, 9);
+    }
+
+    #[test]
+    fn test_backlog_timeout() {
+        let (mut queue, _receiver) = outgoing_queue(ProtocolVersion::V765, None, LIMITS);
+        let now = Instant::now();
+        for id in 0..3 {
+            queue.send(&KeepAlive { id }).unwrap();
+        }
+        queue.check_backlog(now).unwrap();
+        assert!(matches!(
+            queue.check_backlog(now + LIMITS.backlog_timeout),
+            Err(QueueErr::Backlogged(_))
+        ));
+        queue.clear();
+        queue.check_backlog(now + LIMITS.backlog_timeout).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_close() {
+        let (queue, receiver) = outgoing_queue(ProtocolVersion::V765, None, LIMITS);
+
+        queue.send(&ChunkBatchFinish { size: 1 }).unwrap();
+        queue.close();
+        assert!(matches!(
+            receiver.recv().await,
+            Some(OutgoingPacket::Encoded(_))
+        ));
+        assert!(matches!(receiver.recv().await, Some(OutgoingPacket::Close)));
+        drop(receiver);
+        assert!(queue.is_closed());
+    }
+}
@@ -0,0 +1,275 @@
+use std::{
+    fmt::{Debug, Formatter},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+};
+
+use flume::Sender;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpListener,
+};
+
+use crate::command::{CommandRequest, CommandSource};
+
+pub const RCON_RESPONSE: i32 = 0;
+/// Shared by command requests and authentication responses.
+pub const RCON_EXEC_COMMAND: i32 = 2;
+pub const RCON_AUTH: i32 = 3;
+/// The request id of an authentication response when the password was wrong.
+pub const RCON_AUTH_FAILED: i32 = -1;
+/// Vanilla rejects requests longer than this.
+const MAX_REQUEST_LEN: usize = 1460;
+/// Longer responses are split over several packets with the same request id.
+pub const MAX_RESPONSE_BODY_LEN: usize = 4096;
+/// The request id, type and the two null bytes ending the body.
+const HEADER_LEN: usize = 10;
+pub const MAX_RESPONSE_LEN: usize = HEADER_LEN + MAX_RESPONSE_BODY_LEN;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RconPacket {
+    pub id: i32,
+    pub ty: i32,
+    pub body: String,
+}
+
+pub enum RconErr {
+    IoErr(io::Error),
+    InvalidLength(usize),
+    InvalidBody,
+    ServerStopped,
+}
+
+impl Debug for RconErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RconErr::IoErr(err) => write!(f, "io error: {}", err),
+            RconErr::InvalidLength(len) => write!(f, "invalid packet length {}", len),
+            RconErr::InvalidBody => write!(f, "packet body is not null terminated utf-8"),
+            RconErr::ServerStopped => write!(f, "the server stopped handling commands"),
+        }
+    }
+}
+
+/// Reads a packet of at most `max_len` bytes, every field is little endian.
+pub async fn read_packet<R: AsyncRead + Unpin>(
+    reader: &mut R,
+    max_len: usize,
+) -> Result<RconPacket, RconErr> {
+    let len = reader.read_i32_le().await.map_err(RconErr::IoErr)?;
+    let len = usize::try_from(len).map_err(|_| RconErr::InvalidLength(0))?;
+    if !(HEADER_LEN..=max_len).contains(&len) {
+        return Err(RconErr::InvalidLength(len));
+    }
+    let mut buf = vec![0u8; len];
+    reader
+        .read_exact(buf.as_mut_slice())
+        .await
+        .map_err(RconErr::IoErr)?;
+    let id = i32::from_le_bytes([buf[0], buf[1], buf[2], buf[3]]);
+    let ty = i32::from_le_bytes([buf[4], buf[5], buf[6], buf[7]]);
+    let body = &buf[8..];
+    let Some(body) = body.strip_suffix(&[0, 0]) else {
+        return Err(RconErr::InvalidBody);
+    };
+    let body = String::from_utf8(body.to_vec()).map_err(|_| RconErr::InvalidBody)?;
+    Ok(RconPacket { id, ty, body })
+}
+
+pub async fn write_packet<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    packet: &RconPacket,
+) -> Result<(), RconErr> {
+    let len = HEADER_LEN + packet.body.len();
+    let mut buf = Vec::with_capacity(4 + len);
+    buf.extend_from_slice((len as i32).to_le_bytes().as_slice());
+    buf.extend_from_slice(packet.id.to_le_bytes().as_slice());
+    buf.extend_from_slice(packet.ty.to_le_bytes().as_slice());
+    buf.extend_from_slice(packet.body.as_bytes());
+    buf.extend_from_slice(&[0, 0]);
+    writer
+        .write_all(buf.as_slice())
+        .await
+        .map_err(RconErr::IoErr)
+}
+
+/// Sends a command response, split on character boundaries when it is too long for one packet.
+async fn write_response<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    id: i32,
+    mut body: &str,
+) -> Result<(), RconErr> {
+    loop {
+        let mut split = body.len().min(MAX_RESPONSE_BODY_LEN);
+        while !body.is_char_boundary(split) {
+            split -= 1;
+        }
+        let (chunk, rest) = body.split_at(split);
+        let response = RconPacket {
+            id,
+            ty: RCON_RESPONSE,
+            body: chunk.to_string(),
+        };
+        write_packet(writer, &response).await?;
+        if rest.is_empty() {
+            return Ok(());
+        }
+        body = rest;
+    }
+}
+
+/// Serves a single RCON connection until it is closed.
+pub async fn handle_connection<S: AsyncRead + AsyncWrite + Unpin>(
+    socket: &mut S,
+    addr: SocketAddr,
+    password: &str,
+    commands: &Sender<CommandRequest>,
+) -> Result<(), RconErr> {
+    let mut authenticated = false;
+    loop {
+        let request = read_packet(socket, MAX_REQUEST_LEN).await?;
+        match request.ty {
+            RCON_AUTH => {
+                authenticated = request.body == password;
+                let id = if authenticated {
+                    request.id
+                } else {
+                    tracing::info!(?addr, "rcon authentication failed");
+                    RCON_AUTH_FAILED
+                };
+                let response = RconPacket {
+                    id,
+                    ty: RCON_EXEC_COMMAND,
+                    body: String::new(),
+                };
+                write_packet(socket, &response).await?;
+            }
+            RCON_EXEC_COMMAND if authenticated => {
+                tracing::info!(?addr, command = request.body, "rcon command");
+                let (reply, output) = flume::bounded::<String>(1);
+                commands
+                    .send_async(CommandRequest {
+                        source: CommandSource::Rcon(addr),
+                        command: request.body,
+                        reply,
+                    })
+                    .await
+                    .map_err(|_| RconErr::ServerStopped)?;
+                let output = output
+                    .recv_async()
+                    .await
+                    .map_err(|_| RconErr::ServerStopped)?;
+                write_response(socket, request.id, output.as_str()).await?;
+            }
+            RCON_EXEC_COMMAND => {
+                let response = RconPacket {
+                    id: RCON_AUTH_FAILED,
+                    ty: RCON_EXEC_COMMAND,
+                    body: String::new(),
+                };
+                write_packet(socket, &response).await?;
+            }
+            // clients send an empty response after a command to find where its output ends
+            RCON_RESPONSE => write_response(socket, request.id, "").await?,
+            ty => {
+                let body = format!("Unknown request {:x}", ty);
+                write_response(socket, request.id, body.as_str()).await?;
+            }
+        }
+    }
+}
+
+pub async fn listen_rcon(addr: SocketAddr, password: Arc<str>, commands: Sender<CommandRequest>) {
+    let Ok(listener) = TcpListener::bind(addr).await else {
+        tracing::error!(?addr, "unable to bind rcon listener to address");
+        return;
+    };
+    loop {
+        if let Ok((mut socket, addr)) = listener.accept().await {
+            let password = password.clone();
+            let commands = commands.clone();
+            tokio::spawn(async move {
+                if let Err(err) =
+                    handle_connection(&mut socket, addr, password.as_ref(), &commands).await
+                {
+                    tracing::debug!(?err, ?addr, "rcon connection closed");
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    use tokio::io::DuplexStream;
+
+    use crate::{
+        command::CommandSource,
+        network::rcon::{
+            handle_connection, read_packet, write_packet, RconPacket, MAX_RESPONSE_BODY_LEN,
+            MAX_RESPONSE_LEN, RCON_AUTH, RCON_AUTH_FAILED, RCON_EXEC_COMMAND, RCON_RESPONSE,
+        },
+    };
+
+    async fn request(socket: &mut DuplexStream, id: i32, ty: i32, body: &str) -> RconPacket {
+        let packet = RconPacket {
+            id,
+            ty,
+            body: body.to_string(),
+        };
+        write_packet(socket, &packet).await.unwrap();
+        read_packet(socket, MAX_RESPONSE_LEN).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rcon() {
+        let (mut client, mut server) = tokio::io::duplex(1 << 16);
+        let (commands, requests) = flume::unbounded();
+        let addr = SocketAddr::new(Ipv4Addr::LOCALHOST.into(), 40000);
+        tokio::spawn(async move {
+            let _ = handle_connection(&mut server, addr, "hunter2", &commands).await;
+        });
+        tokio::spawn(async move {
+            while let Ok(request) = requests.recv_async().await {
+                assert_eq!(request.source, CommandSource::Rcon(addr));
+                let output = match request.command.as_str() {
+                    "long" => "é".repeat(MAX_RESPONSE_BODY_LEN),
+                    command => format!("ran {}", command),
+                };
+                let _ = request.reply.send(output);
+            }
+        });
+
+        // commands are refused before authenticating
+        let response = request(&mut client, 1, RCON_EXEC_COMMAND, "list").await;
+        assert_eq!(response.id, RCON_AUTH_FAILED);
+        let response = request(&mut client, 2, RCON_AUTH, "hunter3").await;
+        assert_eq!(response.id, RCON_AUTH_FAILED);
+        let response = request(&mut client, 3, RCON_AUTH, "hunter2").await;
+        assert_eq!(response.id, 3);
+        assert_eq!(response.ty, RCON_EXEC_COMMAND);
+
+        let response = request(&mut client, 4, RCON_EXEC_COMMAND, "list").await;
+        assert_eq!(response, RconPacket {
+            id: 4,
+            ty: RCON_RESPONSE,
+            body: "ran list".to_string(),
+        });
+
+        // long output is split without cutting characters in half
+        let first = request(&mut client, 5, RCON_EXEC_COMMAND, "long").await;
+        let second = read_packet(&mut client, MAX_RESPONSE_LEN).await.unwrap();
+        assert_eq!((first.id, second.id), (5, 5));
+        assert_eq!(first.body.len(), MAX_RESPONSE_BODY_LEN);
+        assert_eq!(
+            first.body.len() + second.body.len(),
+            MAX_RESPONSE_BODY_LEN * 2
+        );
+
+        let response = request(&mut client, 6, RCON_RESPONSE, "").await;
+        assert_eq!((response.id, response.body.as_str()), (6, ""));
+    }
+}
@@ -0,0 +1,350 @@
+use std::{
+    fmt::{Debug, Display, Formatter},
+    future::Future,
+    pin::Pin,
+};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::{
+    pkcs8::{DecodePublicKey, EncodePublicKey},
+    Pkcs1v15Encrypt, RsaPrivateKey, RsaPublicKey,
+};
+use serde_derive::Deserialize;
+use serverx_protocol::v765::types::LoginProperty;
+use sha1::{Digest, Sha1};
+use uuid::Uuid;
+
+use crate::client::profile::{offline_uuid, Profile, ProfileErr};
+
+pub const SERVER_KEY_BITS: usize = 1024;
+pub const MOJANG_SESSION_SERVER: &'static str = "https://sessionserver.mojang.com";
+pub const MOJANG_SERVICES_SERVER: &str = "https://api.minecraftservices.com";
+
+pub struct ServerKey {
+    private_key: RsaPrivateKey,
+    public_key_der: Vec<u8>,
+}
+
+impl ServerKey {
+    pub fn generate() -> Result<Self, SessionErr> {
+        let private_key = RsaPrivateKey::new(&mut rand::thread_rng(), SERVER_KEY_BITS)
+            .map_err(|_| SessionErr::KeyErr)?;
+        let public_key_der = RsaPublicKey::from(&private_key)
+            .to_public_key_der()
+            .map_err(|_| SessionErr::KeyErr)?
+            .into_vec();
+        Ok(Self {
+            private_key,
+            public_key_der,
+        })
+    }
+
+    pub fn public_key_der(&self) -> &[u8] {
+        self.public_key_der.as_slice()
+    }
+
+    pub fn decrypt(&self, data: &[u8]) -> Result<Vec<u8>, SessionErr> {
+        self.private_key
+            .decrypt(Pkcs1v15Encrypt, data)
+            .map_err(|_| SessionErr::KeyErr)
+    }
+}
+
+/// Computes the hash sent to the session server, a SHA-1 digest of the server id, shared secret
+/// and public key formatted as a signed (two's complement) hexadecimal number.
+pub fn server_hash(server_id: &str, shared_secret: &[u8], public_key_der: &[u8]) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(server_id.as_bytes());
+    hasher.update(shared_secret);
+    hasher.update(public_key_der);
+    let mut digest: [u8; 20] = hasher.finalize().into();
+    let negative = (digest[0] & 0x80) != 0;
+    if negative {
+        let mut carry = true;
+        for b in digest.iter_mut().rev() {
+            *b = !*b;
+            if carry {
+                let (value, overflow) = b.overflowing_add(1);
+                *b = value;
+                carry = overflow;
+            }
+        }
+    }
+    let hex: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+    let trimmed = hex.trim_start_matches('0');
+    if negative {
+        format!("-{}", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub enum SessionErr {
+    KeyErr,
+    RequestErr(String),
+    NotAuthenticated,
+    MalformedResponse,
+    InvalidProfile(ProfileErr),
+}
+
+impl Debug for SessionErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionErr::KeyErr => write!(f, "rsa key error"),
+            SessionErr::RequestErr(err) => write!(f, "session request error: {}", err),
+            SessionErr::NotAuthenticated => write!(f, "player is not authenticated"),
+            SessionErr::MalformedResponse => write!(f, "malformed session server response"),
+            SessionErr::InvalidProfile(err) => write!(f, "invalid user profile: {}", err),
+        }
+    }
+}
+
+impl Display for SessionErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        <dyn Debug>::fmt(self, f)
+    }
+}
+
+pub type SessionFuture<'a> = Pin<Box<dyn Future<Output = Result<Profile, SessionErr>> + Send + 'a>>;
+
+/// Resolves the profile of a player that is logging in. `server_hash` is empty when the connection
+/// is not encrypted.
+pub trait SessionVerifier: Send + Sync {
+    fn verify<'a>(&'a self, username: &'a str, server_hash: &'a str) -> SessionFuture<'a>;
+}
+
+/// Accepts every player using the UUID vanilla assigns in offline mode.
+pub struct OfflineSessionVerifier;
+
+impl SessionVerifier for OfflineSessionVerifier {
+    fn verify<'a>(&'a self, username: &'a str, _server_hash: &'a str) -> SessionFuture<'a> {
+        Box::pin(async move {
+            Profile::try_from((username.to_string(), offline_uuid(username)))
+                .map_err(|err| SessionErr::InvalidProfile(err))
+        })
+    }
+}
+
+/// Queries the `hasJoined` endpoint of a session server, by default the one hosted by Mojang.
+pub struct HttpSessionVerifier {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl HttpSessionVerifier {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct HasJoinedResponse {
+    id: Uuid,
+    name: String,
+    #[serde(default)]
+    properties: Vec<HasJoinedProperty>,
+}
+
+#[derive(Deserialize)]
+struct HasJoinedProperty {
+    name: String,
+    value: String,
+    signature: Option<String>,
+}
+
+impl SessionVerifier for HttpSessionVerifier {
+    fn verify<'a>(&'a self, username: &'a str, server_hash: &'a str) -> SessionFuture<'a> {
+        Box::pin(async move {
+            let url = format!(
+                "{}/session/minecraft/hasJoined",
+                self.base_url.trim_end_matches('/')
+            );
+            let response = self
+                .client
+                .get(url)
+                .query(&[("username", username), ("serverId", server_hash)])
+                .send()
+                .await
+                .map_err(|err| SessionErr::RequestErr(err.to_string()))?;
+            if response.status() != reqwest::StatusCode::OK {
+                return Err(SessionErr::NotAuthenticated);
+            }
+            let body = response
+                .bytes()
+                .await
+                .map_err(|err| SessionErr::RequestErr(err.to_string()))?;
+            let has_joined = serde_json::from_slice::<HasJoinedResponse>(body.as_ref())
+                .map_err(|_| SessionErr::MalformedResponse)?;
+            let mut profile = Profile::try_from((has_joined.name, has_joined.id))
+                .map_err(|err| SessionErr::InvalidProfile(err))?;
+            profile.properties = has_joined
+                .properties
+                .into_iter()
+                .map(|p| LoginProperty {
+                    name: p.name,
+                    value: p.value,
+                    signature: p.signature,
+                })
+                .collect();
+            Ok(profile)
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeysResponse {
+    player_certificate_keys: Vec<PublicKeyEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublicKeyEntry {
+    public_key: String,
+}
+
+/// Fetches the keys the services server signs profile keys with, the ones vanilla servers trust.
+pub async fn fetch_profile_key_roots(base_url: &str) -> Result<Vec<RsaPublicKey>, SessionErr> {
+    let url = format!("{}/publickeys", base_url.trim_end_matches('/'));
+    let response = reqwest::get(url)
+        .await
+        .map_err(|err| SessionErr::RequestErr(err.to_string()))?;
+    if response.status() != reqwest::StatusCode::OK {
+        return Err(SessionErr::MalformedResponse);
+    }
+    let body = response
+        .bytes()
+        .await
+        .map_err(|err| SessionErr::RequestErr(err.to_string()))?;
+    let public_keys = serde_json::from_slice::<PublicKeysResponse>(body.as_ref())
+        .map_err(|_| SessionErr::MalformedResponse)?;
+    let keys = public_keys
+        .player_certificate_keys
+        .iter()
+        .map(|entry| {
+            STANDARD
+                .decode(entry.public_key.as_str())
+                .ok()
+                .and_then(|der| RsaPublicKey::from_public_key_der(der.as_slice()).ok())
+                .ok_or(SessionErr::KeyErr)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if keys.is_empty() {
+        return Err(SessionErr::MalformedResponse);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use rsa::{pkcs8::EncodePublicKey, RsaPrivateKey, RsaPublicKey};
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use crate::network::session::{
+        fetch_profile_key_roots, server_hash, HttpSessionVerifier, SessionErr, SessionVerifier,
+    };
+
+    fn sha1_hex(name: &str) -> String {
+        server_hash(name, &[], &[])
+    }
+
+    #[test]
+    fn test_server_hash() {
+        assert_eq!(
+            sha1_hex("Notch"),
+            "4ed1f46bbe04bc756bcb17c0c7ce3e4632f06a48"
+        );
+        assert_eq!(
+            sha1_hex("jeb_"),
+            "-7c9d5b0044c130109a5d7b5fb5c317c02b4e28c1"
+        );
+        assert_eq!(sha1_hex("simon"), "88e16a1019277b15d58faf0541e11910eb756f6");
+    }
+
+    async fn serve_once(
+        status: &'static str,
+        body: impl Into<String>,
+    ) -> (String, tokio::task::JoinHandle<String>) {
+        let body = body.into();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response = format!(
+                "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: \
+                 {}\r\nconnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (format!("http://{}", addr), handle)
+    }
+
+    #[tokio::test]
+    async fn test_http_session_verifier() {
+        let (url, handle) = serve_once(
+            "200 OK",
+            r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","properties":[{"name":"textures","value":"e30=","signature":"c2ln"}]}"#,
+        )
+        .await;
+        let verifier = HttpSessionVerifier::new(url);
+        let profile = verifier.verify("Notch", "-1a2b").await.unwrap();
+        let request = handle.await.unwrap();
+        assert!(request.starts_with(
+            "GET /session/minecraft/hasJoined?username=Notch&serverId=-1a2b HTTP/1.1"
+        ));
+        assert_eq!(profile.name, "Notch");
+        assert_eq!(
+            profile.uuid.as_u128(),
+            0x069a79f444e94726a5befca90e38aaf5u128
+        );
+        assert_eq!(profile.properties.len(), 1);
+        assert_eq!(profile.properties[0].name, "textures");
+        assert_eq!(profile.properties[0].value, "e30=");
+        assert_eq!(profile.properties[0].signature.as_deref(), Some("c2ln"));
+    }
+
+    #[tokio::test]
+    async fn test_http_session_verifier_rejected() {
+        let (url, handle) = serve_once("204 No Content", "").await;
+        let verifier = HttpSessionVerifier::new(url);
+        let result = verifier.verify("Notch", "1a2b").await;
+        let _ = handle.await;
+        assert!(matches!(result, Err(SessionErr::NotAuthenticated)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_profile_key_roots() {
+        let key = RsaPublicKey::from(&RsaPrivateKey::new(&mut rand::thread_rng(), 512).unwrap());
+        let body = format!(
+            r#"{{"profilePropertyKeys":[],"playerCertificateKeys":[{{"publicKey":"{}"}}]}}"#,
+            STANDARD.encode(key.to_public_key_der().unwrap().as_bytes())
+        );
+        let (url, handle) = serve_once("200 OK", body).await;
+        let keys = fetch_profile_key_roots(url.as_str()).await.unwrap();
+        let request = handle.await.unwrap();
+        assert!(request.starts_with("GET /publickeys HTTP/1.1"));
+        assert_eq!(keys, vec![key]);
+
+        let (url, handle) = serve_once("200 OK", r#"{"playerCertificateKeys":[]}"#).await;
+        let result = fetch_profile_key_roots(url.as_str()).await;
+        let _ = handle.await;
+        assert!(matches!(result, Err(SessionErr::MalformedResponse)));
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    fmt::{Debug, Formatter},
+    net::{IpAddr, SocketAddr},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use hashbrown::HashMap;
+
+/// Limits the listener puts on connections before they reach the play state.
+#[derive(Debug, Copy, Clone)]
+pub struct ListenerLimits {
+    /// How many connections a single address may open per window, zero disables the limit.
+    pub connections_per_ip: u32,
+    pub connection_window: Duration,
+    /// How many connections may be in the handshake, status or login states at once.
+    pub max_pending: usize,
+    /// How long a client has to send its handshake.
+    pub handshake_timeout: Duration,
+    /// How long a client has to finish a status request or login after its handshake.
+    pub login_timeout: Duration,
+}
+
+impl Default for ListenerLimits {
+    fn default() -> Self {
+        Self {
+            connections_per_ip: 8,
+            connection_window: Duration::from_secs(10),
+            max_pending: 256,
+            handshake_timeout: Duration::from_secs(5),
+            login_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    RateLimited,
+    TooManyPending,
+    HandshakeTimeout,
+    LoginTimeout,
+}
+
+impl Debug for Rejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::RateLimited => write!(f, "too many connections from the address"),
+            Rejection::TooManyPending => write!(f, "too many pending connections"),
+            Rejection::HandshakeTimeout => write!(f, "handshake timed out"),
+            Rejection::LoginTimeout => write!(f, "login timed out"),
+        }
+    }
+}
+
+/// Counts rejected connections by reason.
+#[derive(Debug, Default)]
+pub struct Rejections {
+    rate_limited: AtomicU64,
+    too_many_pending: AtomicU64,
+    handshake_timeout: AtomicU64,
+    login_timeout: AtomicU64,
+}
+
+impl Rejections {
+    fn counter(&self, reason: Rejection) -> &AtomicU64 {
+        match reason {
+            Rejection::RateLimited => &self.rate_limited,
+            Rejection::TooManyPending => &self.too_many_pending,
+            Rejection::HandshakeTimeout => &self.handshake_timeout,
+            Rejection::LoginTimeout => &self.login_timeout,
+        }
+    }
+
+    /// Counts and logs a rejected connection.
+    pub fn record(&self, addr: SocketAddr, reason: Rejection) {
+        let total = self.counter(reason).fetch_add(1, Ordering::Relaxed) + 1;
+        tracing::info!(?addr, ?reason, total, "rejected connection");
+    }
+
+    pub fn count(&self, reason: Rejection) -> u64 {
+        self.counter(reason).load(Ordering::Relaxed)
+    }
+}
+
+/// Decides which accepted connections the listener hands on, owned by the listener task.
+pub struct ConnectionThrottle {
+    limits: ListenerLimits,
+    attempts: HashMap<IpAddr, (Instant, u32)>,
+    last_prune: Instant,
+    pending: Arc<AtomicUsize>,
+}
+
+impl ConnectionThrottle {
+    pub fn new(limits: ListenerLimits, now: Instant) -> Self {
+        Self {
+            limits,
+            attempts: HashMap::new(),
+            last_prune: now,
+            pending: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Admits a connection from the address, the returned guard counts it as pending until it is
+    /// dropped.
+    pub fn admit(&mut self, ip: IpAddr, now: Instant) -> Result<PendingGuard, Rejection> {
+        let window = self.limits.connection_window;
+        if now.saturating_duration_since(self.last_prune) >= window {
+            self.attempts
+                .retain(|_, (start, _)| now.saturating_duration_since(*start) < window);
+            self.last_prune = now;
+        }
+        if self.limits.connections_per_ip > 0 {
+            let (start, count) = self.attempts.entry(ip).or_insert((now, 0));
+            if now.saturating_duration_since(*start) >= window {
+                *start = now;
+                *count = 0;
+            }
+            if *count >= self.limits.connections_per_ip {
+                return Err(Rejection::RateLimited);
+            }
+            *count += 1;
+        }
+        if self.pending.fetch_add(1, Ordering::AcqRel) >= self.limits.max_pending {
+            self.pending.fetch_sub(1, Ordering::AcqRel);
+            return Err(Rejection::TooManyPending);
+        }
+        Ok(PendingGuard {
+            pending: self.pending.clone(),
+        })
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::Acquire)
+    }
+}
+
+/// Marks a connection that has not reached the play state yet.
+pub struct PendingGuard {
+    pending: Arc<AtomicUsize>,
+}
+
+impl Drop for PendingGuard {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        net::{IpAddr, Ipv4Addr},
+        time::{Duration, Instant},
+    };
+
+    use crate::network::throttle::{ConnectionThrottle, ListenerLimits, Rejection};
+
+    #[test]
+    fn test_rate_limit() {
+        let limits = ListenerLimits {
+            connections_per_ip: 2,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let mut throttle = ConnectionThrottle::new(limits, start);
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let other = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let _first = throttle.admit(ip, start).unwrap();
+        let _second = throttle.admit(ip, start).unwrap();
+        assert!(matches!(
+            throttle.admit(ip, start),
+            Err(Rejection::RateLimited)
+        ));
+        let _other = throttle.admit(other, start).unwrap();
+        // the count starts over with the next window
+        let next = start + limits.connection_window;
+        let _third = throttle.admit(ip, next).unwrap();
+    }
+
+    #[test]
+    fn test_max_pending() {
+        let limits = ListenerLimits {
+            connections_per_ip: 0,
+            max_pending: 2,
+            ..Default::default()
+        };
+        let now = Instant::now();
+        let mut throttle = ConnectionThrottle::new(limits, now);
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let first = throttle.admit(ip, now).unwrap();
+        let _second = throttle.admit(ip, now).unwrap();
+        assert!(matches!(
+            throttle.admit(ip, now),
+            Err(Rejection::TooManyPending)
+        ));
+        assert_eq!(throttle.pending(), 2);
+        drop(first);
+        let _third = throttle.admit(ip, now + Duration::from_millis(1)).unwrap();
+        assert_eq!(throttle.pending(), 2);
+    }
+}
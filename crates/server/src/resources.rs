@@ -1,29 +1,340 @@
-use std::{fs::File, io, path::Path, sync::Arc};
+use std::{
+    fmt::{Debug, Formatter},
+    fs,
+    fs::File,
+    io,
+    path::Path,
+    sync::Arc,
+};
 
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rsa::{pkcs8::DecodePublicKey, RsaPublicKey};
+use serverx_common::identifier::Identifier;
+use serverx_macros::nbt;
 use serverx_nbt as nbt;
 use serverx_nbt::decode::NbtDecodeErr;
+use serverx_protocol::v766::{clientbound::RegistryData, types::RegistryDataEntry};
 use tracing::instrument;
 
 pub struct Resources {
     pub registry_data: Arc<nbt::Tag>,
+    /// The registry data as 1.20.5 sends it, a packet for each registry.
+    pub registry_packets: Vec<RegistryData>,
+    /// The ids of the chat types in the registry data, keyed by name.
+    pub chat_types: hashbrown::HashMap<String, i32>,
+    /// The ids of the dimension types, 1.20.5 refers to them by id when joining.
+    pub dimension_types: hashbrown::HashMap<String, i32>,
+    /// The server icon as a data URI, absent if there is no valid icon.
+    pub favicon: Option<Arc<str>>,
 }
 
 pub enum LoadResourcesErr {
     IoErr(io::Error),
     NbtErr(NbtDecodeErr),
+    InvalidFavicon,
+    InvalidPublicKey,
 }
 
+impl Debug for LoadResourcesErr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LoadResourcesErr::IoErr(err) => write!(f, "io error: {}", err),
+            LoadResourcesErr::NbtErr(err) => write!(f, "nbt error: {}", err),
+            LoadResourcesErr::InvalidFavicon => write!(f, "invalid favicon"),
+            LoadResourcesErr::InvalidPublicKey => write!(f, "invalid public key"),
+        }
+    }
+}
+
+pub const FAVICON_SIZE: u32 = 64;
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
 pub fn load_registry_data(resource_path: &Path) -> Result<nbt::Tag, LoadResourcesErr> {
     let registry_path = resource_path.join("registries.nbt");
     let mut file = File::open(registry_path).map_err(|err| LoadResourcesErr::IoErr(err))?;
     nbt::io::read_tag(&mut file).map_err(|err| LoadResourcesErr::NbtErr(err))
 }
 
+/// Reads a 64x64 PNG and encodes it as the data URI sent in status responses.
+pub fn load_favicon(favicon_path: &Path) -> Result<String, LoadResourcesErr> {
+    let png = fs::read(favicon_path).map_err(LoadResourcesErr::IoErr)?;
+    // the IHDR chunk always comes first, its width and height follow the chunk header
+    if png.len() < 24 || png[..8] != PNG_SIGNATURE || &png[12..16] != b"IHDR" {
+        return Err(LoadResourcesErr::InvalidFavicon);
+    }
+    let width = u32::from_be_bytes([png[16], png[17], png[18], png[19]]);
+    let height = u32::from_be_bytes([png[20], png[21], png[22], png[23]]);
+    if width != FAVICON_SIZE || height != FAVICON_SIZE {
+        return Err(LoadResourcesErr::InvalidFavicon);
+    }
+    Ok(format!("data:image/png;base64,{}", STANDARD.encode(png)))
+}
+
+/// Reads an RSA public key, either PEM encoded or the raw DER Mojang distributes.
+pub fn load_public_key(key_path: &Path) -> Result<RsaPublicKey, LoadResourcesErr> {
+    let key = fs::read(key_path).map_err(LoadResourcesErr::IoErr)?;
+    match std::str::from_utf8(key.as_slice()) {
+        Ok(pem) if pem.trim_start().starts_with("-----BEGIN") => {
+            RsaPublicKey::from_public_key_pem(pem.trim())
+        }
+        _ => RsaPublicKey::from_public_key_der(key.as_slice()),
+    }
+    .map_err(|_| LoadResourcesErr::InvalidPublicKey)
+}
+
+/// The ids of the entries of a registry in the registry data, keyed by name.
+pub fn registry_ids(registry_data: &nbt::Tag, registry: &str) -> hashbrown::HashMap<String, i32> {
+    let mut ids = hashbrown::HashMap::new();
+    let Some(nbt::Tag::List(entries)) = compound_field(registry_data, registry)
+        .and_then(|registry| compound_field(registry, "value"))
+    else {
+        return ids;
+    };
+    for entry in entries {
+        if let (Some(nbt::Tag::String(name)), Some(nbt::Tag::Int(id))) =
+            (compound_field(entry, "name"), compound_field(entry, "id"))
+        {
+            ids.insert(name.clone(), *id);
+        }
+    }
+    ids
+}
+
+/// The wolf variants 1.20.5 added to the synced registries, by name, texture and biome.
+const WOLF_VARIANTS: [(&str, &str, &str); 9] = [
+    ("ashen", "wolf_ashen", "snowy_taiga"),
+    ("black", "wolf_black", "old_growth_pine_taiga"),
+    ("chestnut", "wolf_chestnut", "old_growth_spruce_taiga"),
+    ("pale", "wolf", "taiga"),
+    ("rusty", "wolf_rusty", "sparse_jungle"),
+    ("snowy", "wolf_snowy", "grove"),
+    ("spotted", "wolf_spotted", "savanna_plateau"),
+    ("striped", "wolf_striped", "wooded_badlands"),
+    ("woods", "wolf_woods", "forest"),
+];
+
+/// The banner patterns, synced since 1.20.5.
+const BANNER_PATTERNS: [&str; 41] = [
+    "base",
+    "square_bottom_left",
+    "square_bottom_right",
+    "square_top_left",
+    "square_top_right",
+    "stripe_bottom",
+    "stripe_top",
+    "stripe_left",
+    "stripe_right",
+    "stripe_center",
+    "stripe_middle",
+    "stripe_downright",
+    "stripe_downleft",
+    "small_stripes",
+    "cross",
+    "straight_cross",
+    "triangle_bottom",
+    "triangle_top",
+    "triangles_bottom",
+    "triangles_top",
+    "diagonal_left",
+    "diagonal_up_right",
+    "diagonal_up_left",
+    "diagonal_right",
+    "circle",
+    "rhombus",
+    "half_vertical",
+    "half_horizontal",
+    "half_vertical_right",
+    "half_horizontal_bottom",
+    "border",
+    "curly_border",
+    "gradient",
+    "gradient_up",
+    "bricks",
+    "globe",
+    "creeper",
+    "skull",
+    "flower",
+    "mojang",
+    "piglin",
+];
+
+/// Splits the registry data into the packets 1.20.5 sends for each registry and adds the
+/// registries it introduced. Every entry is sent with its data, so clients do not need to share
+/// a data pack with the server.
+pub fn registry_packets(registry_data: &nbt::Tag) -> Vec<RegistryData> {
+    let mut packets = Vec::new();
+    if let nbt::Tag::Compound(registries) = registry_data {
+        for registry in registries {
+            let Ok(name) = Identifier::try_from(registry.name.as_str()) else {
+                continue;
+            };
+            let Some(nbt::Tag::List(values)) = compound_field(&registry.payload, "value") else {
+                continue;
+            };
+            let mut entries: Vec<(i32, RegistryDataEntry)> = values
+                .iter()
+                .filter_map(|value| {
+                    let (Some(nbt::Tag::String(id)), Some(nbt::Tag::Int(index))) =
+                        (compound_field(value, "name"), compound_field(value, "id"))
+                    else {
+                        return None;
+                    };
+                    let entry = RegistryDataEntry {
+                        id: Identifier::try_from(id.as_str()).ok()?,
+                        data: compound_field(value, "element").cloned(),
+                    };
+                    Some((*index, entry))
+                })
+                .collect();
+            // clients number the entries in the order they are sent
+            entries.sort_by_key(|(index, _)| *index);
+            packets.push(RegistryData {
+                registry: name,
+                entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+            });
+        }
+    }
+    packets.push(RegistryData {
+        registry: Identifier::try_from("minecraft:wolf_variant").unwrap(),
+        entries: WOLF_VARIANTS
+            .iter()
+            .map(|(name, texture, biome)| RegistryDataEntry {
+                id: Identifier::try_from(format!("minecraft:{}", name).as_str()).unwrap(),
+                data: Some(nbt!({
+                    "wild_texture": format!("minecraft:entity/wolf/{}", texture),
+                    "tame_texture": format!("minecraft:entity/wolf/{}_tame", texture),
+                    "angry_texture": format!("minecraft:entity/wolf/{}_angry", texture),
+                    "biomes": format!("minecraft:{}", biome)
+                })),
+            })
+            .collect(),
+    });
+    packets.push(RegistryData {
+        registry: Identifier::try_from("minecraft:banner_pattern").unwrap(),
+        entries: BANNER_PATTERNS
+            .iter()
+            .map(|name| RegistryDataEntry {
+                id: Identifier::try_from(format!("minecraft:{}", name).as_str()).unwrap(),
+                data: Some(nbt!({
+                    "asset_id": format!("minecraft:{}", name),
+                    "translation_key": format!("block.minecraft.banner.{}", name)
+                })),
+            })
+            .collect(),
+    });
+    packets
+}
+
+fn compound_field<'a>(tag: &'a nbt::Tag, name: &str) -> Option<&'a nbt::Tag> {
+    match tag {
+        nbt::Tag::Compound(fields) => fields
+            .iter()
+            .find(|field| field.name == name)
+            .map(|field| &field.payload),
+        _ => None,
+    }
+}
+
 #[instrument]
-pub fn load(resource_path: &Path) -> Result<Resources, LoadResourcesErr> {
+pub fn load(resource_path: &Path, favicon_path: &Path) -> Result<Resources, LoadResourcesErr> {
     tracing::debug!("loading resources");
     let registry_data = load_registry_data(resource_path)?;
+    let favicon = if favicon_path.exists() {
+        match load_favicon(favicon_path) {
+            Ok(favicon) => Some(Arc::from(favicon)),
+            Err(err) => {
+                tracing::warn!(?err, "ignoring server icon, it must be a 64x64 png");
+                None
+            }
+        }
+    } else {
+        None
+    };
+    let chat_types = registry_ids(&registry_data, "minecraft:chat_type");
+    let dimension_types = registry_ids(&registry_data, "minecraft:dimension_type");
     Ok(Resources {
+        registry_packets: registry_packets(&registry_data),
         registry_data: Arc::new(registry_data),
+        chat_types,
+        dimension_types,
+        favicon,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::resources::{
+        load_favicon, load_registry_data, registry_ids, registry_packets, LoadResourcesErr,
+        PNG_SIGNATURE,
+    };
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(13u32.to_be_bytes().as_slice());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(width.to_be_bytes().as_slice());
+        png.extend_from_slice(height.to_be_bytes().as_slice());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        png
+    }
+
+    #[test]
+    fn test_load_favicon() {
+        let dir = std::env::temp_dir().join(format!("serverx-favicon-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let icon = dir.join("server-icon.png");
+        fs::write(&icon, png_header(64, 64)).unwrap();
+        let favicon = load_favicon(&icon).unwrap();
+        assert_eq!(
+            favicon,
+            "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAEAAAABACAYAAAA="
+        );
+        fs::write(&icon, png_header(32, 64)).unwrap();
+        assert!(matches!(
+            load_favicon(&icon),
+            Err(LoadResourcesErr::InvalidFavicon)
+        ));
+        fs::write(&icon, b"GIF89a").unwrap();
+        assert!(matches!(
+            load_favicon(&icon),
+            Err(LoadResourcesErr::InvalidFavicon)
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_registry_ids() {
+        let resource_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../run/resources");
+        let registry_data = load_registry_data(&resource_path).unwrap();
+        let chat_types = registry_ids(&registry_data, "minecraft:chat_type");
+        assert_eq!(chat_types.get("minecraft:chat"), Some(&0));
+        assert_eq!(chat_types.get("minecraft:emote_command"), Some(&1));
+        assert!(registry_ids(&registry_data, "minecraft:missing").is_empty());
+    }
+
+    #[test]
+    fn test_registry_packets() {
+        let resource_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../run/resources");
+        let registry_data = load_registry_data(&resource_path).unwrap();
+        let packets = registry_packets(&registry_data);
+        let dimension_types = packets
+            .iter()
+            .find(|packet| packet.registry.as_str() == "minecraft:dimension_type")
+            .unwrap();
+        let ids = registry_ids(&registry_data, "minecraft:dimension_type");
+        for (index, entry) in dimension_types.entries.iter().enumerate() {
+            assert_eq!(ids.get(entry.id.as_str()), Some(&(index as i32)));
+            assert!(entry.data.is_some());
+        }
+        // registries 1.20.4 does not sync come last
+        assert!(packets
+            .iter()
+            .any(|packet| packet.registry.as_str() == "minecraft:wolf_variant"));
+        assert_eq!(
+            packets.last().unwrap().registry.as_str(),
+            "minecraft:banner_pattern"
+        );
+    }
+}
@@ -1,14 +1,29 @@
 use std::{
     net::{IpAddr, SocketAddr},
+    path::Path,
     str::FromStr,
+    sync::Arc,
     time::Duration,
 };
 
 use flume::{Receiver, Sender};
+use rand::seq::IteratorRandom;
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use serverx_protocol::{
+    io::PreEncodedPacket,
+    text::TextComponent,
+    v765::{
+        clientbound::{
+            CommandSuggestionsResponse, PlayerChatMessage, PlayerInfoUpdate, SystemChatMessage,
+        },
+        serverbound::CommandSuggestionsRequest,
+        types::{CommandSuggestion, FilterType, MessageSignatureRef, PlayerInfoEntry},
+    },
+    version::ProtocolVersion,
+};
 use smallvec::SmallVec;
 use tokio::{
-    runtime::Runtime,
+    runtime::{Builder, Runtime},
     time,
     time::{Instant, MissedTickBehavior},
 };
@@ -16,10 +31,32 @@ use tracing::instrument;
 
 use crate::{
     client,
-    client::{status::ClientStatus, ClientHandle, Clients},
+    client::{
+        chat::ChatEvent, chat_session::ProfileKeyVerifier, event::ClientEvent,
+        status::ClientStatus, tab_list::TabList, ClientHandle, Clients,
+    },
+    command::{
+        builtin::{register_builtin, CommandExecution, ServerExecutor},
+        console::spawn_console,
+        dispatcher::CommandDispatcher,
+        CommandRequest, CommandSource,
+    },
     config::ServerConfig,
+    game::Game,
     network,
-    network::event::NetworkEvent,
+    network::{
+        event::NetworkEvent,
+        forwarding::{Forwarding, ForwardingMode},
+        handlers::{
+            login::LoginConfig,
+            status::{PlayerSample, ServerStatus, StatusSnapshot, MAX_PLAYER_SAMPLE},
+        },
+        query::QueryInfo,
+        queue::{PacketPriority, QueueLimits},
+        session::{self, HttpSessionVerifier, OfflineSessionVerifier, ServerKey},
+        throttle::ListenerLimits,
+    },
+    resources,
     resources::Resources,
 };
 
@@ -29,20 +66,105 @@ pub struct Server {
     pub resources: Resources,
     pub net_send: Sender<NetworkEvent>,
     pub net_recv: Receiver<NetworkEvent>,
+    pub command_send: Sender<CommandRequest>,
+    pub command_recv: Receiver<CommandRequest>,
+    pub commands: Arc<CommandDispatcher<ServerExecutor>>,
+    /// Completion requests of players, answered once every client was updated.
+    pub suggestion_requests: Vec<(ClientHandle, CommandSuggestionsRequest)>,
+    /// Chat messages and commands of players, handled once every client was updated.
+    pub pending_chat: Vec<ChatEvent>,
+    pub pending_commands: Vec<(ClientHandle, String)>,
+    /// Chat sessions players started, sent to everyone so they can verify their messages.
+    pub chat_sessions: Vec<PlayerInfoEntry>,
+    /// Absent if no trust root is configured, chat sessions are ignored then.
+    pub profile_keys: Option<ProfileKeyVerifier>,
+    /// Set by the stop command, the tick loop disconnects everyone and returns.
+    pub stopping: bool,
+    pub tab_list: TabList,
+    pub client_events: Sender<ClientEvent>,
+    pub game: Game,
+    pub status: StatusSnapshot,
+    pub ticks: u64,
+    spawn_chunks: hashbrown::HashMap<(ProtocolVersion, Option<usize>), Arc<[PreEncodedPacket]>>,
+}
+
+/// How many ticks pass between two status snapshots, one per second.
+const STATUS_INTERVAL: u64 = 20;
+/// How many ticks pass between two latency updates of the tab list, like vanilla.
+const LATENCY_INTERVAL: u64 = 600;
+/// The chat type of messages players send.
+const CHAT_TYPE: &str = "minecraft:chat";
+/// How many ticks clients get to leave once the server is stopping.
+const SHUTDOWN_TICKS: u32 = 20;
+
+/// Loads the configured trust root or fetches Mojang's, secure profiles can not be enforced
+/// without one.
+fn profile_key_verifier(config: &ServerConfig) -> Option<ProfileKeyVerifier> {
+    let keys = if !config.profile_key_trust_root.is_empty() {
+        resources::load_public_key(Path::new(config.profile_key_trust_root.as_str()))
+            .map(|key| vec![key])
+            .map_err(|err| tracing::error!(?err, "unable to load the profile key trust root"))
+            .ok()
+    } else if config.online_mode {
+        Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("unable to create runtime")
+            .block_on(session::fetch_profile_key_roots(
+                config.services_server.as_str(),
+            ))
+            .map_err(|err| tracing::error!(?err, "unable to fetch the profile key trust root"))
+            .ok()
+    } else {
+        None
+    };
+    if config.enforce_secure_profile && keys.is_none() {
+        panic!("secure profiles are enforced without a profile key trust root");
+    }
+    keys.map(ProfileKeyVerifier::new)
 }
 
 impl Server {
     pub fn new(config: ServerConfig, resources: Resources) -> Self {
         let (net_send, net_recv) = flume::unbounded::<NetworkEvent>();
+        let (client_events, client_events_recv) = flume::unbounded::<ClientEvent>();
+        let (command_send, command_recv) = flume::unbounded::<CommandRequest>();
+        let mut commands = CommandDispatcher::new();
+        register_builtin(&mut commands);
+        let profile_keys = profile_key_verifier(&config);
+        let tab_list = TabList::new(
+            TextComponent::text(config.tab_list_header.clone()),
+            TextComponent::text(config.tab_list_footer.clone()),
+        );
         Self {
             thread_pool: ThreadPoolBuilder::new()
                 .num_threads(8)
                 .build()
                 .expect("unable to create rayon threadpool"),
+            status: StatusSnapshot::new(ServerStatus {
+                motd: TextComponent::text(config.motd.clone()),
+                max_players: config.max_players,
+                favicon: resources.favicon.clone(),
+                ..ServerStatus::default()
+            }),
             config,
             resources,
             net_send,
             net_recv,
+            command_send,
+            command_recv,
+            commands: Arc::new(commands),
+            suggestion_requests: Vec::new(),
+            pending_chat: Vec::new(),
+            pending_commands: Vec::new(),
+            chat_sessions: Vec::new(),
+            profile_keys,
+            stopping: false,
+            tab_list,
+            client_events,
+            game: Game::new(client_events_recv),
+            ticks: 0,
+            spawn_chunks: hashbrown::HashMap::new(),
         }
     }
 
@@ -52,17 +174,62 @@ impl Server {
             match event {
                 NetworkEvent::Connected {
                     addr,
+                    version,
                     outgoing,
                     incoming,
                     profile,
                 } => {
-                    tracing::debug!(?profile, "player has joined the game");
-                    clients.add(addr, outgoing, incoming, profile);
+                    tracing::debug!(?profile, ?version, "player has joined the game");
+                    clients.add(addr, version, outgoing, incoming, profile);
                 }
             }
         }
     }
 
+    #[instrument(skip_all)]
+    pub fn process_commands(&mut self, clients: &mut Clients) {
+        while let Ok(request) = self.command_recv.try_recv() {
+            let output = self.execute_command(clients, request.source, request.command.as_str());
+            let _ = request.reply.send(output);
+        }
+    }
+
+    pub fn execute_command(
+        &mut self,
+        clients: &mut Clients,
+        source: CommandSource,
+        command: &str,
+    ) -> String {
+        tracing::debug!(?source, command, "executing command");
+        let input = command.trim();
+        let input = input.strip_prefix('/').unwrap_or(input);
+        let commands = self.commands.clone();
+        let mut execution = CommandExecution {
+            server: self,
+            clients,
+            source,
+            output: Vec::new(),
+        };
+        let result = commands
+            .parse(&execution, input)
+            .and_then(|parsed| (parsed.executor)(&mut execution, &parsed.args));
+        if let Err(err) = result {
+            execution.output.push(err.describe(input));
+        }
+        execution.output.join("\n")
+    }
+
+    /// Disconnects every client, they are removed by the following ticks.
+    pub fn shutdown(&mut self, clients: &mut Clients) {
+        tracing::info!("stopping the server");
+        for (_, client) in clients.clients.iter_mut() {
+            client.disconnect(TextComponent::translate(
+                "multiplayer.disconnect.server_shutdown",
+                Vec::new(),
+            ));
+        }
+    }
+
     #[instrument(skip_all)]
     pub fn update_clients(&mut self, clients: &mut Clients) {
         let mut disconnected: SmallVec<[ClientHandle; 4]> = SmallVec::new();
@@ -76,6 +243,144 @@ impl Server {
         for handle in disconnected {
             let _ = clients.remove(handle);
         }
+        self.answer_suggestions(clients);
+        self.process_player_commands(clients);
+    }
+
+    /// Runs the commands players sent, their output is sent back as system chat.
+    pub fn process_player_commands(&mut self, clients: &mut Clients) {
+        for (handle, command) in std::mem::take(&mut self.pending_commands) {
+            if let Some(client) = clients.get(handle) {
+                tracing::info!(name = client.profile.name, command, "player issued command");
+            }
+            let output = self.execute_command(clients, CommandSource::Player(handle), &command);
+            if output.is_empty() {
+                continue;
+            }
+            if let Some(client) = clients.get(handle) {
+                let _ = client.outgoing.send(&SystemChatMessage {
+                    content: TextComponent::text(output),
+                    overlay: false,
+                });
+            }
+        }
+    }
+
+    /// Passes chat messages through the game layer and broadcasts the ones it let through.
+    pub fn process_chat(&mut self, clients: &mut Clients) {
+        if !self.chat_sessions.is_empty() {
+            clients.broadcast(
+                &PlayerInfoUpdate {
+                    actions: PlayerInfoUpdate::INITIALIZE_CHAT,
+                    players: std::mem::take(&mut self.chat_sessions),
+                },
+                PacketPriority::Normal,
+            );
+        }
+        for mut event in std::mem::take(&mut self.pending_chat) {
+            self.game.on_chat(&mut event);
+            if event.cancelled {
+                continue;
+            }
+            if let Some(system) = event.system.as_ref() {
+                clients.broadcast(
+                    &SystemChatMessage {
+                        content: system.clone(),
+                        overlay: false,
+                    },
+                    PacketPriority::Normal,
+                );
+                continue;
+            }
+            let signed = event.signed.as_ref();
+            let player_chat = PlayerChatMessage {
+                sender: event.sender_uuid,
+                index: signed.map_or(0, |signed| signed.index),
+                signature: signed.map(|signed| signed.signature),
+                message: event.message.clone(),
+                timestamp: event.timestamp,
+                salt: event.salt,
+                // the signatures are sent in full as the signature cache of clients is not
+                // mirrored
+                previous_messages: signed
+                    .map(|signed| {
+                        signed
+                            .last_seen
+                            .iter()
+                            .map(|signature| MessageSignatureRef::Signature(Box::new(*signature)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                unsigned_content: event.decorated.clone(),
+                filter: FilterType::PassThrough,
+                chat_type: self
+                    .resources
+                    .chat_types
+                    .get(CHAT_TYPE)
+                    .copied()
+                    .unwrap_or_default(),
+                sender_name: TextComponent::text(event.sender_name.clone()),
+                target_name: None,
+            };
+            // 1.20.5 changed the layout of player chat, its clients get the message as system chat
+            let system_chat = SystemChatMessage {
+                content: event.content(),
+                overlay: false,
+            };
+            for (_, client) in clients.clients.iter_mut() {
+                if client.status != ClientStatus::Connected {
+                    continue;
+                }
+                if client.version != ProtocolVersion::V765 {
+                    let _ = client.outgoing.send(&system_chat);
+                    continue;
+                }
+                let _ = client.outgoing.send(&player_chat);
+                let Some(signed) = signed else {
+                    continue;
+                };
+                if let Err(err) = client.chat.last_seen.add_pending(&signed.signature) {
+                    tracing::debug!(?err, profile = ?client.profile, "client does not acknowledge chat");
+                    client.disconnect(err.reason());
+                }
+            }
+        }
+    }
+
+    /// Completes the commands players are typing, offsets in the response count UTF-16 code units
+    /// of the whole text like the client does.
+    pub fn answer_suggestions(&mut self, clients: &mut Clients) {
+        let requests = std::mem::take(&mut self.suggestion_requests);
+        let commands = self.commands.clone();
+        for (handle, request) in requests {
+            let input = request.text.strip_prefix('/').unwrap_or(&request.text);
+            let suggestions = {
+                let execution = CommandExecution {
+                    server: self,
+                    clients,
+                    source: CommandSource::Player(handle),
+                    output: Vec::new(),
+                };
+                commands.suggest(&execution, input)
+            };
+            let start = request.text.len() - input.len() + suggestions.start;
+            let response = CommandSuggestionsResponse {
+                transaction_id: request.transaction_id,
+                start: request.text[..start].encode_utf16().count() as i32,
+                length: request.text[start..].encode_utf16().count() as i32,
+                matches: suggestions
+                    .matches
+                    .into_iter()
+                    .map(|text| CommandSuggestion {
+                        text,
+                        tooltip: None,
+                    })
+                    .collect(),
+            };
+            if let Some(client) = clients.get(handle) {
+                let _ = client.outgoing.send(&response);
+            }
+        }
     }
 
     #[instrument(skip_all)]
@@ -89,12 +394,136 @@ impl Server {
     pub async fn tick(&mut self, clients: &mut Clients) {
         let start = Instant::now();
         self.process_events(clients);
+        self.process_commands(clients);
         self.update_clients(clients);
+        self.game.process_events();
+        self.tab_list
+            .sync(clients, self.ticks.is_multiple_of(LATENCY_INTERVAL));
+        // relayed after the sync, clients disconnect on chat from players missing in their tab list
+        self.process_chat(clients);
         self.sync_clients(clients);
+        if self.ticks.is_multiple_of(STATUS_INTERVAL) {
+            self.update_status(clients);
+        }
+        self.ticks = self.ticks.wrapping_add(1);
         let elapsed = start.elapsed();
         tracing::info!(?elapsed, "finished tick");
     }
 
+    /// The chunks sent to joining clients, encoded the first time a protocol version and
+    /// compression threshold is seen.
+    pub fn spawn_chunks(
+        &mut self,
+        version: ProtocolVersion,
+        compression: Option<usize>,
+    ) -> Arc<[PreEncodedPacket]> {
+        self.spawn_chunks
+            .entry((version, compression))
+            .or_insert_with(|| Arc::from(client::update::encode_spawn_chunks(version, compression)))
+            .clone()
+    }
+
+    /// Publishes what the server list shows, read by the network tasks answering status requests.
+    #[instrument(skip_all)]
+    pub fn update_status(&self, clients: &Clients) {
+        let sample = clients
+            .clients
+            .iter()
+            .map(|(_, client)| PlayerSample {
+                name: client.profile.name.clone(),
+                id: client.profile.uuid,
+            })
+            .choose_multiple(&mut rand::thread_rng(), MAX_PLAYER_SAMPLE);
+        self.status.store(ServerStatus {
+            motd: TextComponent::text(self.config.motd.clone()),
+            max_players: self.config.max_players,
+            online_players: i32::try_from(clients.clients.len()).unwrap_or(i32::MAX),
+            sample,
+            players: clients
+                .clients
+                .iter()
+                .map(|(_, client)| client.profile.name.clone())
+                .collect(),
+            favicon: self.resources.favicon.clone(),
+        });
+    }
+
+    pub fn forwarding(&self) -> Forwarding {
+        match self.config.forwarding {
+            ForwardingMode::None => Forwarding::None,
+            ForwardingMode::BungeeCord => {
+                tracing::warn!(
+                    "bungeecord forwarding trusts every connection, firewall the server"
+                );
+                Forwarding::BungeeCord
+            }
+            ForwardingMode::Velocity => {
+                if self.config.forwarding_secret.is_empty() {
+                    panic!("velocity forwarding requires a forwarding secret");
+                }
+                Forwarding::Velocity {
+                    secret: self.config.forwarding_secret.as_bytes().to_vec(),
+                }
+            }
+        }
+    }
+
+    pub fn login_config(&self) -> LoginConfig {
+        let compression_threshold = usize::try_from(self.config.compression_threshold).ok();
+        let forwarding = self.forwarding();
+        if self.config.online_mode {
+            tracing::debug!("generating server keypair");
+            LoginConfig {
+                key: Some(Arc::new(
+                    ServerKey::generate().expect("unable to generate server keypair"),
+                )),
+                verifier: Arc::new(HttpSessionVerifier::new(self.config.session_server.clone())),
+                compression_threshold,
+                forwarding,
+            }
+        } else {
+            LoginConfig {
+                key: None,
+                verifier: Arc::new(OfflineSessionVerifier),
+                compression_threshold,
+                forwarding,
+            }
+        }
+    }
+
+    pub fn queue_limits(&self) -> QueueLimits {
+        QueueLimits {
+            soft_limit: self.config.outgoing_soft_limit,
+            hard_limit: self.config.outgoing_hard_limit,
+            backlog_timeout: Duration::from_secs(self.config.outgoing_backlog_timeout),
+        }
+    }
+
+    pub fn query_info(&self) -> QueryInfo {
+        QueryInfo {
+            game_type: "SMP".to_string(),
+            map: "world".to_string(),
+            host_ip: self.config.ip.clone(),
+            host_port: self.config.port,
+            plugins: String::new(),
+        }
+    }
+
+    pub fn listener_limits(&self) -> ListenerLimits {
+        // behind a proxy every connection comes from its address
+        let connections_per_ip = match self.config.forwarding {
+            ForwardingMode::None => self.config.connections_per_ip,
+            ForwardingMode::BungeeCord | ForwardingMode::Velocity => 0,
+        };
+        ListenerLimits {
+            connections_per_ip,
+            connection_window: Duration::from_secs(self.config.connection_window),
+            max_pending: self.config.max_pending_connections,
+            handshake_timeout: Duration::from_secs(self.config.handshake_timeout),
+            login_timeout: Duration::from_secs(self.config.login_timeout),
+        }
+    }
+
     #[instrument(skip_all)]
     pub fn start(mut self) {
         let rt = Runtime::new().unwrap();
@@ -104,18 +533,58 @@ impl Server {
             self.config.port,
         );
         let net_send_clone = self.net_send.clone();
+        let login_config = Arc::new(self.login_config());
+        let server_status = self.status.clone();
+        let queue_limits = self.queue_limits();
+        let listener_limits = self.listener_limits();
         tokio::spawn(async move {
-            network::listen::listen(listener_addr, net_send_clone).await;
+            network::listen::listen(
+                listener_addr,
+                net_send_clone,
+                login_config,
+                server_status,
+                queue_limits,
+                listener_limits,
+            )
+            .await;
         });
+        if self.config.enable_query {
+            let query_addr = SocketAddr::new(listener_addr.ip(), self.config.query_port);
+            let query_info = self.query_info();
+            let server_status = self.status.clone();
+            tokio::spawn(async move {
+                network::query::listen_query(query_addr, query_info, server_status).await;
+            });
+        }
+        if self.config.enable_rcon {
+            if self.config.rcon_password.is_empty() {
+                tracing::error!("rcon is enabled without a password, not starting it");
+            } else {
+                let rcon_addr = SocketAddr::new(listener_addr.ip(), self.config.rcon_port);
+                let password: Arc<str> = Arc::from(self.config.rcon_password.as_str());
+                let command_send = self.command_send.clone();
+                tokio::spawn(async move {
+                    network::rcon::listen_rcon(rcon_addr, password, command_send).await;
+                });
+            }
+        }
+        spawn_console(self.command_send.clone());
         let mut clients = Clients::new();
         rt.block_on(async move {
-            loop {
-                let mut interval = time::interval(Duration::from_millis(50));
-                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
-                loop {
-                    self.tick(&mut clients).await;
-                    interval.tick().await;
+            let mut interval = time::interval(Duration::from_millis(50));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            while !self.stopping {
+                self.tick(&mut clients).await;
+                interval.tick().await;
+            }
+            self.shutdown(&mut clients);
+            // give the disconnect packets a moment to be flushed
+            for _ in 0..SHUTDOWN_TICKS {
+                if clients.clients.is_empty() {
+                    break;
                 }
+                self.tick(&mut clients).await;
+                interval.tick().await;
             }
         })
     }